@@ -0,0 +1,250 @@
+//! Snapshots of files taken before `fix` or `deploy` change them.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use std::{
+    collections::BTreeSet,
+    fs,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    history::{format_age, state_path},
+    output::{Color, info, success},
+    paths::is_link,
+    types::{Config, ExitCode},
+};
+
+// Snapshots of deployed files can be large; keep enough to undo the last few
+// fixes and deploys
+pub const BACKUP_LIMIT: usize = 20;
+
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum SavedState {
+    File { stored: String },
+    Symlink { target: PathBuf },
+    // Restoring removes whatever was created at the path afterwards
+    Missing,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BackupEntry {
+    pub path: PathBuf,
+    #[serde(flatten)]
+    pub state: SavedState,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub id: String,
+    pub timestamp: u64,
+    pub reason: String,
+    pub entries: Vec<BackupEntry>,
+}
+
+pub fn backups_dir(config: &Config) -> Result<PathBuf> {
+    state_path(config, "backups")
+}
+
+/// Saves the current state of `paths` (absolute) under a new snapshot.
+pub fn create_snapshot(config: &Config, reason: &str, paths: &[PathBuf]) -> Result<Snapshot> {
+    let backups = backups_dir(config)?;
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+
+    // Two snapshots in the same second (fix, then deploy) get a suffix
+    let mut id = timestamp.to_string();
+    let mut suffix = 1;
+    while backups.join(&id).exists() {
+        id = format!("{}-{}", timestamp, suffix);
+        suffix += 1;
+    }
+    let dir = backups.join(&id);
+    let files_dir = dir.join("files");
+    fs::create_dir_all(&files_dir)
+        .with_context(|| format!("Failed to create {}", files_dir.display()))?;
+
+    let mut entries = Vec::new();
+    let unique: BTreeSet<_> = paths.iter().collect();
+    for (idx, path) in unique.into_iter().enumerate() {
+        let state = match fs::symlink_metadata(path) {
+            Ok(metadata) if is_link(&metadata) => SavedState::Symlink {
+                target: fs::read_link(path)
+                    .with_context(|| format!("Failed to read link {}", path.display()))?,
+            },
+            Ok(metadata) if metadata.is_file() => {
+                let stored = idx.to_string();
+                fs::copy(path, files_dir.join(&stored))
+                    .with_context(|| format!("Failed to back up {}", path.display()))?;
+                SavedState::File { stored }
+            }
+            // Directories are never mutated as a whole
+            Ok(_) => continue,
+            Err(_) => SavedState::Missing,
+        };
+        entries.push(BackupEntry {
+            path: path.clone(),
+            state,
+        });
+    }
+
+    let snapshot = Snapshot {
+        id,
+        timestamp,
+        reason: reason.to_string(),
+        entries,
+    };
+    let manifest = dir.join(MANIFEST_FILE);
+    fs::write(&manifest, serde_json::to_string_pretty(&snapshot)?)
+        .with_context(|| format!("Failed to write {}", manifest.display()))?;
+
+    prune_snapshots(config)?;
+    Ok(snapshot)
+}
+
+/// All readable snapshots, oldest first.
+pub fn list_snapshots(config: &Config) -> Result<Vec<Snapshot>> {
+    let backups = backups_dir(config)?;
+    let Ok(dirs) = fs::read_dir(&backups) else {
+        return Ok(Vec::new());
+    };
+    let mut snapshots: Vec<Snapshot> = dirs
+        .flatten()
+        .filter_map(|e| fs::read_to_string(e.path().join(MANIFEST_FILE)).ok())
+        .filter_map(|content| serde_json::from_str(&content).ok())
+        .collect();
+    snapshots.sort_by(|a, b| (a.timestamp, &a.id).cmp(&(b.timestamp, &b.id)));
+    Ok(snapshots)
+}
+
+pub fn prune_snapshots(config: &Config) -> Result<()> {
+    let backups = backups_dir(config)?;
+    let snapshots = list_snapshots(config)?;
+    let overflow = snapshots.len().saturating_sub(BACKUP_LIMIT);
+    for snapshot in &snapshots[..overflow] {
+        let dir = backups.join(&snapshot.id);
+        fs::remove_dir_all(&dir).with_context(|| format!("Failed to remove {}", dir.display()))?;
+    }
+    Ok(())
+}
+
+fn remove_existing(path: &Path) -> Result<()> {
+    match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.is_dir() && !is_link(&metadata) => {
+            anyhow::bail!("{} is now a directory; not replacing it", path.display())
+        }
+        Ok(_) => {
+            fs::remove_file(path).with_context(|| format!("Failed to remove {}", path.display()))
+        }
+        Err(_) => Ok(()),
+    }
+}
+
+#[cfg(unix)]
+fn create_symlink(target: &Path, link: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(target, link)
+}
+
+#[cfg(windows)]
+fn create_symlink(target: &Path, link: &Path) -> std::io::Result<()> {
+    std::os::windows::fs::symlink_file(target, link)
+}
+
+/// Puts every path in the snapshot back the way it was.
+pub fn restore_snapshot(config: &Config, snapshot: &Snapshot) -> Result<usize> {
+    let dir = backups_dir(config)?.join(&snapshot.id);
+    for entry in &snapshot.entries {
+        let path = &entry.path;
+        remove_existing(path)?;
+        match &entry.state {
+            SavedState::File { stored } => {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)
+                        .with_context(|| format!("Failed to create {}", parent.display()))?;
+                }
+                fs::copy(dir.join("files").join(stored), path)
+                    .with_context(|| format!("Failed to restore {}", path.display()))?;
+            }
+            SavedState::Symlink { target } => {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)
+                        .with_context(|| format!("Failed to create {}", parent.display()))?;
+                }
+                create_symlink(target, path)
+                    .with_context(|| format!("Failed to restore link {}", path.display()))?;
+            }
+            SavedState::Missing => {}
+        }
+    }
+    Ok(snapshot.entries.len())
+}
+
+// The `restore` command: list snapshots, or revert to one of them
+pub fn restore(config: &Config, id: Option<&str>) -> Result<i32> {
+    let snapshots = list_snapshots(config)?;
+
+    let Some(id) = id else {
+        if snapshots.is_empty() {
+            info("No backup snapshots recorded");
+            return Ok(ExitCode::CLEAN);
+        }
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        println!("{}Backup snapshots:{}", Color::bold(), Color::reset());
+        for snapshot in snapshots.iter().rev() {
+            println!(
+                "  {}  {:>4} ago  {:<8} {} file(s)",
+                snapshot.id,
+                format_age(now.saturating_sub(snapshot.timestamp)),
+                snapshot.reason,
+                snapshot.entries.len()
+            );
+        }
+        return Ok(ExitCode::CLEAN);
+    };
+
+    let snapshot = snapshots
+        .iter()
+        .find(|s| s.id == id)
+        .with_context(|| format!("No backup snapshot '{}'", id))?;
+    let restored = restore_snapshot(config, snapshot)?;
+    success(&format!(
+        "Restored {} path(s) from snapshot {} ({})",
+        restored, snapshot.id, snapshot.reason
+    ));
+    Ok(ExitCode::CLEAN)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::FixtureRepo;
+
+    #[test]
+    fn restoring_a_snapshot_reverts_changes_and_removes_new_files() {
+        let repo = FixtureRepo::new();
+        repo.write(".gitignore", "*.log\n");
+        let config = repo.config();
+        let gitignore = repo.path().join(".gitignore");
+        let created = repo.path().join("created");
+
+        let snapshot =
+            create_snapshot(&config, "fix", &[gitignore.clone(), created.clone()]).unwrap();
+        fs::write(&gitignore, "*.log\n!keep.log\n").unwrap();
+        fs::write(&created, "new\n").unwrap();
+
+        assert_eq!(list_snapshots(&config).unwrap().len(), 1);
+        restore_snapshot(&config, &snapshot).unwrap();
+        assert_eq!(fs::read_to_string(&gitignore).unwrap(), "*.log\n");
+        assert!(!created.exists());
+    }
+}
@@ -0,0 +1,164 @@
+//! The `bootstrap` command: everything a new machine needs, in one go.
+
+use anyhow::{Context, Result};
+
+use std::{
+    fs,
+    io::{self, BufRead, IsTerminal, Write},
+    process::Command,
+};
+
+use crate::{
+    deploy::{DeployOptions, deploy},
+    output::{Color, failure, info, success},
+    repo::find_executable,
+    types::{Config, ExitCode, GitMissing},
+};
+
+pub struct BootstrapOptions {
+    pub yes: bool,
+    pub dry_run: bool,
+}
+
+fn step(number: usize, title: &str) {
+    println!(
+        "\n{}[{}/4] {}{}",
+        Color::bold(),
+        number,
+        title,
+        Color::reset()
+    );
+}
+
+fn confirm(question: &str, yes: bool) -> Result<bool> {
+    if yes {
+        return Ok(true);
+    }
+    // Nobody to answer in CI or a piped install script
+    if !io::stdin().is_terminal() {
+        return Ok(false);
+    }
+    print!("{} [y/N] ", question);
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
+
+// Homebrew ships a prebuilt binary, so it's preferred over compiling
+pub fn dotter_install_command() -> Option<Vec<&'static str>> {
+    if find_executable("brew").is_some() {
+        Some(vec!["brew", "install", "dotter"])
+    } else if find_executable("cargo").is_some() {
+        Some(vec!["cargo", "install", "--locked", "dotter"])
+    } else {
+        None
+    }
+}
+
+fn ensure_dotter(options: &BootstrapOptions) -> Result<bool> {
+    if let Some(path) = find_executable("dotter") {
+        success(&format!("dotter: {}", path.display()));
+        return Ok(true);
+    }
+
+    let Some(install) = dotter_install_command() else {
+        failure(
+            "dotter is not installed, and neither Homebrew nor cargo is available to install it",
+        );
+        info("  See https://github.com/SuperCuber/dotter#installation");
+        return Ok(false);
+    };
+    let install_line = install.join(" ");
+    if !confirm(
+        &format!(
+            "dotter is not installed. Install it with `{}`?",
+            install_line
+        ),
+        options.yes,
+    )? {
+        failure("dotter is not installed");
+        info(&format!("  Run: {} (or pass --yes)", install_line));
+        return Ok(false);
+    }
+
+    let status = Command::new(install[0])
+        .args(&install[1..])
+        .status()
+        .with_context(|| format!("Failed to run {}", install_line))?;
+    if !status.success() {
+        failure(&format!("`{}` failed ({})", install_line, status));
+        return Ok(false);
+    }
+    if find_executable("dotter").is_none() {
+        failure("dotter was installed but isn't on PATH");
+        info("  Add the install location (e.g. ~/.cargo/bin) to PATH and re-run bootstrap");
+        return Ok(false);
+    }
+    success("Installed dotter");
+    Ok(true)
+}
+
+fn check_local_config(config: &Config) -> Result<bool> {
+    let local = config.dotfiles_dir.join(".dotter/local.toml");
+    if local.exists() {
+        success(&format!("Using {}", local.display()));
+        return Ok(true);
+    }
+
+    failure(".dotter/local.toml not found; dotter doesn't know which packages to deploy here");
+    let global = config.dotfiles_dir.join(".dotter/global.toml");
+    if let Ok(content) = fs::read_to_string(&global)
+        && let Ok(doc) = toml::from_str::<toml::Table>(&content)
+    {
+        let packages: Vec<_> = doc
+            .iter()
+            .filter(|(_, v)| v.get("files").is_some())
+            .map(|(k, _)| format!("\"{}\"", k))
+            .collect();
+        info(&format!(
+            "  Create it with the packages this machine needs, e.g.: packages = [{}]",
+            packages.join(", ")
+        ));
+    }
+    Ok(false)
+}
+
+pub fn bootstrap(config: Config, options: &BootstrapOptions) -> Result<i32> {
+    step(1, "Checking required tools");
+    match find_executable("git") {
+        Some(path) => success(&format!("git: {}", path.display())),
+        None => return Err(GitMissing.into()),
+    }
+    if !ensure_dotter(options)? {
+        return Ok(ExitCode::ERRORS);
+    }
+
+    step(2, "Checking dotter configuration");
+    if !check_local_config(&config)? {
+        return Ok(ExitCode::ERRORS);
+    }
+
+    // deploy() validates first and refuses to continue on errors
+    step(3, "Validating and deploying");
+    let code = deploy(
+        config,
+        &DeployOptions {
+            dry_run: options.dry_run,
+            force: false,
+            validate: true,
+            dotter_args: Vec::new(),
+        },
+    )?;
+    if code == ExitCode::ERRORS {
+        return Ok(code);
+    }
+
+    step(4, "Done");
+    success(if options.dry_run {
+        "Bootstrap dry run complete; re-run without --dry-run to deploy"
+    } else {
+        "This machine is set up"
+    });
+    Ok(ExitCode::CLEAN)
+}
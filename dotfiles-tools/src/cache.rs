@@ -0,0 +1,186 @@
+//! Per-file rule results cached by content hash, for `--incremental` runs.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use std::{
+    collections::{HashMap, HashSet, hash_map::DefaultHasher},
+    env, fs,
+    hash::{Hash, Hasher},
+    path::PathBuf,
+    sync::Mutex,
+};
+
+use crate::{
+    history::state_path,
+    settings::SETTINGS_FILE,
+    types::{Config, Issue},
+};
+
+pub const CACHE_FILE: &str = "cache.json";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CacheData {
+    // Everything besides file content that affects results; a different key
+    // discards the whole cache
+    key: u64,
+    // rule id -> file -> result
+    rules: HashMap<String, HashMap<String, CacheEntry>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    hash: u64,
+    issues: Vec<Issue>,
+}
+
+#[derive(Debug)]
+pub struct ResultCache {
+    path: PathBuf,
+    data: Mutex<CacheData>,
+    // (rule, file) pairs looked up this run; the rest are pruned on save
+    used: Mutex<HashSet<(String, String)>>,
+}
+
+pub fn content_hash(content: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
+}
+
+// A rebuilt binary or edited settings can change what any rule reports
+fn cache_key(config: &Config) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    env!("CARGO_PKG_VERSION").hash(&mut hasher);
+    env::current_exe()
+        .and_then(fs::metadata)
+        .and_then(|m| m.modified())
+        .ok()
+        .hash(&mut hasher);
+    fs::read(config.dotfiles_dir.join(SETTINGS_FILE))
+        .ok()
+        .hash(&mut hasher);
+    hasher.finish()
+}
+
+impl ResultCache {
+    pub fn load(config: &Config) -> Result<Self> {
+        let path = state_path(config, CACHE_FILE)?;
+        let key = cache_key(config);
+        // A corrupt or stale cache just means a full run
+        let data = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<CacheData>(&content).ok())
+            .filter(|data| data.key == key)
+            .unwrap_or_else(|| CacheData {
+                key,
+                rules: HashMap::new(),
+            });
+        Ok(Self {
+            path,
+            data: Mutex::new(data),
+            used: Mutex::new(HashSet::new()),
+        })
+    }
+
+    pub fn get(&self, rule: &str, file: &str, hash: u64) -> Option<Vec<Issue>> {
+        self.used
+            .lock()
+            .unwrap()
+            .insert((rule.to_string(), file.to_string()));
+        let data = self.data.lock().unwrap();
+        let entry = data.rules.get(rule)?.get(file)?;
+        (entry.hash == hash).then(|| entry.issues.clone())
+    }
+
+    pub fn insert(&self, rule: &str, file: &str, hash: u64, issues: &[Issue]) {
+        self.used
+            .lock()
+            .unwrap()
+            .insert((rule.to_string(), file.to_string()));
+        self.data
+            .lock()
+            .unwrap()
+            .rules
+            .entry(rule.to_string())
+            .or_default()
+            .insert(
+                file.to_string(),
+                CacheEntry {
+                    hash,
+                    issues: issues.to_vec(),
+                },
+            );
+    }
+
+    /// Writes the cache back. With `prune`, entries for files the rules
+    /// that ran didn't look at (deleted or renamed files) are dropped, which
+    /// is only safe after a run that covered every file.
+    pub fn save(&self, prune: bool) -> Result<()> {
+        let mut data = self.data.lock().unwrap();
+        if prune {
+            let used = self.used.lock().unwrap();
+            let rules_run: HashSet<_> = used.iter().map(|(rule, _)| rule.as_str()).collect();
+            for (rule, files) in data.rules.iter_mut() {
+                if rules_run.contains(rule.as_str()) {
+                    files.retain(|file, _| used.contains(&(rule.clone(), file.clone())));
+                }
+            }
+        }
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        fs::write(&self.path, serde_json::to_string(&*data)?)
+            .with_context(|| format!("Failed to write {}", self.path.display()))
+    }
+}
+
+/// Runs a per-file check, or reuses its issues from the last run when the
+/// file's content hasn't changed since.
+pub fn file_issues(
+    config: &Config,
+    rule: &str,
+    file: &str,
+    content: &str,
+    check: impl FnOnce() -> Result<Vec<Issue>>,
+) -> Result<Vec<Issue>> {
+    let Some(cache) = &config.cache else {
+        return check();
+    };
+    let hash = content_hash(content);
+    if let Some(issues) = cache.get(rule, file, hash) {
+        debug!(rule, file, "cache hit");
+        return Ok(issues);
+    }
+    let issues = check()?;
+    cache.insert(rule, file, hash, &issues);
+    Ok(issues)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{rules::syntax::toml_files_valid, test_support::FixtureRepo};
+    use std::sync::Arc;
+
+    #[test]
+    fn unchanged_files_reuse_cached_issues() {
+        let repo = FixtureRepo::new();
+        repo.write("a.toml", "a = \n").commit("fixture");
+        let mut config = repo.config();
+        config.cache = Some(Arc::new(ResultCache::load(&config).unwrap()));
+
+        let first = toml_files_valid(&config).unwrap();
+        assert_eq!(first.issues.len(), 1);
+        config.cache.as_ref().unwrap().save(true).unwrap();
+
+        // A reloaded cache answers without re-checking
+        let cache = ResultCache::load(&config).unwrap();
+        let hash = content_hash("a = \n");
+        let cached = cache.get("toml-syntax", "a.toml", hash).unwrap();
+        assert_eq!(cached[0].message, first.issues[0].message);
+        assert!(cache.get("toml-syntax", "a.toml", hash + 1).is_none());
+    }
+}
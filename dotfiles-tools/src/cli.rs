@@ -0,0 +1,453 @@
+//! Command-line interface definitions.
+
+use clap::{ArgAction, Args, Parser, Subcommand};
+
+use std::{path::PathBuf, time::Duration};
+
+use dotfiles_tools::{
+    bootstrap::BootstrapOptions,
+    deploy::DeployOptions,
+    manage::{AddOptions, AdoptOptions},
+    output::ColorChoice,
+    schedule::{ScheduleOptions, parse_time},
+    types::{Config, DEFAULT_RULE_TIMEOUT, GroupBy, OutputFormat, OutputMode, Severity},
+};
+
+#[derive(Parser)]
+#[command(name = "dotfiles-tools")]
+#[command(about = "Validate, fix, and deploy the dotfiles repository")]
+#[command(
+    after_help = "Exit codes: 0 clean, 1 warnings (with --strict or over --max-warnings), \
+                  2 errors, 3 internal failure, 4 git missing, 64 usage error"
+)]
+pub struct Cli {
+    /// Dotfiles repository to operate on; repeat to validate several repos
+    /// together (defaults to $DOTFILES_DIR or the current directory)
+    #[arg(
+        long = "dir",
+        visible_alias = "dotfiles-dir",
+        global = true,
+        value_name = "DIR"
+    )]
+    pub dirs: Vec<PathBuf>,
+
+    /// Log diagnostics to stderr (-v for debug, -vv for trace)
+    #[arg(short, long, global = true, action = ArgAction::Count)]
+    pub verbose: u8,
+
+    /// Emit diagnostic logs as JSON lines
+    #[arg(long, global = true)]
+    pub log_json: bool,
+
+    /// When to use colored output
+    #[arg(long, global = true, value_enum, value_name = "WHEN", default_value_t = ColorChoice::Auto)]
+    pub color: ColorChoice,
+
+    /// Mark results with [OK], [FAIL], [WARN] and [INFO] instead of symbols
+    /// (the default when the locale isn't UTF-8)
+    #[arg(long, global = true)]
+    pub ascii: bool,
+
+    /// Print reports straight to the terminal instead of through $PAGER
+    #[arg(long, global = true)]
+    pub no_pager: bool,
+
+    #[command(subcommand)]
+    pub command: Commands,
+}
+
+#[derive(Subcommand)]
+pub enum Commands {
+    /// Validate repository structure and configuration
+    Validate(ValidateArgs),
+
+    /// Validate, then apply the fixes that can be made automatically
+    Fix(ValidateArgs),
+
+    /// Report external tools referenced by configs that are missing from PATH
+    Doctor,
+
+    /// Compare deployed files in $HOME against the repository
+    Drift,
+
+    /// Copy edits made to deployed copies and templates back into the repo
+    /// and stage them
+    SyncBack {
+        /// Show what would be copied without changing the repo
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Move a file from $HOME into the repo, map it in dotter, and stage it
+    Adopt(AdoptArgs),
+
+    /// Create a repo file, map it to a target in dotter, and stage it
+    Add(AddArgs),
+
+    /// Move a repo file or directory with git, updating dotter mappings and
+    /// the configs that source it
+    Mv {
+        /// Repo path to move
+        old: String,
+
+        /// Repo path to move it to
+        new: String,
+
+        /// Show what would change without moving anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Remove a repo file or directory with git, along with its dotter
+    /// mappings and the symlinks deployed from it
+    Rm {
+        /// Repo path to remove
+        path: String,
+
+        /// Show what would be removed without removing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Remove symlinks in $HOME that point at files no longer in the repo
+    CleanLinks {
+        /// List the links without removing them
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Validate, then deploy with dotter if there are no errors
+    Deploy(DeployArgs),
+
+    /// Check each profile declared in .validate-dotfiles.toml (a package
+    /// combination, as in local.toml) and show which are deployable
+    Profiles {
+        /// Only check this profile (repeatable)
+        #[arg(long = "profile", value_name = "NAME")]
+        profiles: Vec<String>,
+    },
+
+    /// Report tracked files by extension, dotter packages, and the largest files
+    Stats,
+
+    /// Set up a new machine: install missing tools, validate, and deploy
+    Bootstrap(BootstrapArgs),
+
+    /// List the backups taken before fixes and deploys, or restore one
+    Restore {
+        /// Snapshot ID to restore (omit to list snapshots)
+        snapshot: Option<String>,
+    },
+
+    /// Describe an issue code (e.g. DOT003), or list every code
+    Explain {
+        /// Issue code to describe (omit to list all codes)
+        code: Option<String>,
+    },
+
+    /// Install a LaunchAgent that validates the repo daily (macOS)
+    Schedule(ScheduleArgs),
+
+    /// Install a git pre-commit hook that runs the validator
+    InstallHooks {
+        /// Write the hook into this repo-relative directory and point
+        /// core.hooksPath at it, instead of using .git/hooks
+        #[arg(long, value_name = "DIR")]
+        hooks_path: Option<PathBuf>,
+
+        /// Overwrite an existing pre-commit hook not installed by this tool
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+impl Commands {
+    // Reports worth paging; not the interactive or long-running commands
+    pub fn pages_output(&self) -> bool {
+        match self {
+            Commands::Validate(args) | Commands::Fix(args) => !args.watch && !args.daemon,
+            Commands::Doctor
+            | Commands::Drift
+            | Commands::Profiles { .. }
+            | Commands::Stats
+            | Commands::Explain { .. } => true,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Args)]
+pub struct ValidateArgs {
+    /// Show fix suggestions
+    #[arg(short, long)]
+    pub fix: bool,
+
+    /// Only check content of files staged for commit, as they appear in the index
+    #[arg(long)]
+    pub staged: bool,
+
+    /// Only check content of files changed since the given git ref
+    #[arg(long, value_name = "REF", conflicts_with = "staged")]
+    pub changed_since: Option<String>,
+
+    /// Enable an opt-in rule by ID (repeatable)
+    #[arg(long, value_name = "RULE")]
+    pub enable: Vec<String>,
+
+    /// Only run rules with one of these tags (comma-separated; e.g. syntax,git).
+    /// Opt-in rules with a listed tag run too
+    #[arg(long, value_name = "TAGS", value_delimiter = ',')]
+    pub tags: Vec<String>,
+
+    /// Skip rules with any of these tags (comma-separated; e.g. slow)
+    #[arg(long, value_name = "TAGS", value_delimiter = ',')]
+    pub exclude_tags: Vec<String>,
+
+    /// Print nothing; report the result through the exit code only
+    #[arg(short, long, conflicts_with_all = ["summary_only", "verbose"])]
+    pub quiet: bool,
+
+    /// Print only the final summary line
+    #[arg(long)]
+    pub summary_only: bool,
+
+    /// Report format: human-readable, one `file:line:col: severity:
+    /// message [rule-id]` line per issue for editor quickfix lists, or
+    /// Prometheus metrics for node_exporter's textfile collector
+    #[arg(long, value_enum, value_name = "FORMAT", default_value_t = OutputFormat::Human)]
+    pub format: OutputFormat,
+
+    /// Group reported issues by rule, or by the file they're about
+    #[arg(long, value_enum, value_name = "BY", default_value_t = GroupBy::Rule)]
+    pub group_by: GroupBy,
+
+    /// Only display issues at or above this severity (all are still counted)
+    #[arg(long, value_enum, value_name = "LEVEL", default_value_t = Severity::Hint)]
+    pub min_severity: Severity,
+
+    /// Exit with a non-zero code when there are warnings
+    #[arg(long)]
+    pub strict: bool,
+
+    /// Report issues introduced since the previous recorded run
+    #[arg(long)]
+    pub compare: bool,
+
+    /// Exit with a non-zero code when there are more than N warnings
+    #[arg(long, value_name = "N")]
+    pub max_warnings: Option<usize>,
+
+    /// Keep running and re-validate whenever the repository changes,
+    /// with a desktop notification when a passing repo starts failing
+    #[arg(long)]
+    pub watch: bool,
+
+    /// Stay running and answer check-file, check-all, and status requests
+    /// as JSON lines on a Unix socket
+    #[arg(long, conflicts_with = "watch")]
+    pub daemon: bool,
+
+    /// Socket for --daemon (defaults to validate-dotfiles/daemon.sock in the git dir)
+    #[arg(long, value_name = "PATH", requires = "daemon")]
+    pub socket: Option<PathBuf>,
+
+    /// Write the fix suggestions to FILE as a shell script to review and run
+    #[arg(long, value_name = "FILE")]
+    pub emit_fix_script: Option<PathBuf>,
+
+    /// Also write a JSON summary (issue counts by rule and severity,
+    /// duration, git HEAD) to FILE, whatever the report format
+    #[arg(long, value_name = "FILE")]
+    pub summary_out: Option<PathBuf>,
+
+    /// Reuse per-file results from the previous run for files whose content
+    /// hasn't changed
+    #[arg(long)]
+    pub incremental: bool,
+
+    /// Abandon a rule or plugin that runs longer than SECS, reporting it as
+    /// an error (0 waits indefinitely)
+    #[arg(long, value_name = "SECS", default_value_t = DEFAULT_RULE_TIMEOUT.as_secs())]
+    pub rule_timeout: u64,
+
+    /// Stop running rules at the first error
+    #[arg(long)]
+    pub fail_fast: bool,
+
+    /// Send a desktop notification when validation reports errors, and post
+    /// to the webhook in [notify] if one is set
+    #[arg(long)]
+    pub notify: bool,
+
+    /// Only check dotter files for one platform config (e.g. linux for .dotter/linux.toml)
+    #[arg(long, value_name = "NAME")]
+    pub platform: Option<String>,
+}
+
+impl ValidateArgs {
+    pub fn apply(&self, config: &mut Config) {
+        config.fix_mode = self.fix;
+        config.staged = self.staged;
+        config.changed_since = self.changed_since.clone();
+        config.enabled_rules = self.enable.clone();
+        config.tags = self.tags.clone();
+        config.exclude_tags = self.exclude_tags.clone();
+        config.output = if self.quiet {
+            OutputMode::Quiet
+        } else if self.summary_only {
+            OutputMode::SummaryOnly
+        } else {
+            OutputMode::Normal
+        };
+        config.format = self.format;
+        config.group_by = self.group_by;
+        config.min_severity = self.min_severity;
+        config.strict = self.strict;
+        config.max_warnings = self.max_warnings;
+        config.compare = self.compare;
+        config.platform = self.platform.clone();
+        config.notify = self.notify;
+        config.fail_fast = self.fail_fast;
+        config.incremental = self.incremental;
+        config.rule_timeout =
+            (self.rule_timeout > 0).then(|| Duration::from_secs(self.rule_timeout));
+        config.emit_fix_script = self.emit_fix_script.clone();
+        config.summary_out = self.summary_out.clone();
+    }
+}
+
+#[derive(Args)]
+pub struct DeployArgs {
+    /// Show what dotter would change without touching any files
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Overwrite target files that were modified outside of dotter
+    #[arg(long)]
+    pub force: bool,
+
+    /// Deploy even when validation reports errors
+    #[arg(long)]
+    pub no_validate: bool,
+
+    /// Extra arguments passed through to `dotter deploy`
+    #[arg(last = true, value_name = "DOTTER_ARGS")]
+    pub dotter_args: Vec<String>,
+}
+
+impl DeployArgs {
+    pub fn into_options(self) -> DeployOptions {
+        DeployOptions {
+            dry_run: self.dry_run,
+            force: self.force,
+            validate: !self.no_validate,
+            dotter_args: self.dotter_args,
+        }
+    }
+}
+
+#[derive(Args)]
+pub struct AdoptArgs {
+    /// File under $HOME to adopt
+    pub path: PathBuf,
+
+    /// Dotter package to add it to (defaults to the app's name, e.g. kitty
+    /// for ~/.config/kitty/kitty.conf or zsh for ~/.zshrc)
+    #[arg(short, long)]
+    pub package: Option<String>,
+
+    /// Repo path to move it to (defaults to its path under .config)
+    #[arg(long = "as", value_name = "SOURCE")]
+    pub source: Option<String>,
+
+    /// Deploy afterwards so the file is linked back into place
+    #[arg(long)]
+    pub deploy: bool,
+
+    /// Show where the file would go without moving it
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+impl AdoptArgs {
+    pub fn into_options(self) -> AdoptOptions {
+        AdoptOptions {
+            package: self.package,
+            source: self.source,
+            deploy: self.deploy,
+            dry_run: self.dry_run,
+        }
+    }
+}
+
+#[derive(Args)]
+pub struct AddArgs {
+    /// Repo path of the file (created empty if it doesn't exist)
+    pub source: String,
+
+    /// Where dotter deploys it, e.g. ~/.config/kitty/kitty.conf
+    pub target: String,
+
+    /// Dotter package to add it to (defaults to the app's name, as for adopt)
+    #[arg(short, long)]
+    pub package: Option<String>,
+
+    /// Show the mapping that would be added without changing anything
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+impl AddArgs {
+    pub fn into_options(self) -> AddOptions {
+        AddOptions {
+            package: self.package,
+            dry_run: self.dry_run,
+        }
+    }
+}
+
+#[derive(Args)]
+pub struct BootstrapArgs {
+    /// Install missing tools without asking
+    #[arg(short, long)]
+    pub yes: bool,
+
+    /// Validate and show what dotter would change, without deploying
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+impl BootstrapArgs {
+    pub fn into_options(self) -> BootstrapOptions {
+        BootstrapOptions {
+            yes: self.yes,
+            dry_run: self.dry_run,
+        }
+    }
+}
+
+#[derive(Args)]
+pub struct ScheduleArgs {
+    /// Time of day to run, as HH:MM
+    #[arg(long, value_name = "HH:MM", value_parser = parse_time, default_value = "09:00")]
+    pub at: (u8, u8),
+
+    /// Print the LaunchAgent plist instead of installing it
+    #[arg(long)]
+    pub print: bool,
+
+    /// Unload and delete the LaunchAgent
+    #[arg(long, conflicts_with_all = ["at", "print"])]
+    pub remove: bool,
+}
+
+impl ScheduleArgs {
+    pub fn into_options(self) -> ScheduleOptions {
+        ScheduleOptions {
+            hour: self.at.0,
+            minute: self.at.1,
+            print: self.print,
+            remove: self.remove,
+        }
+    }
+}
@@ -0,0 +1,1306 @@
+//! Stable issue codes, and the longer explanations behind `explain`.
+
+use anyhow::Result;
+
+use crate::{
+    output::Color,
+    types::{ExitCode, Severity},
+};
+
+pub struct IssueCode {
+    pub code: &'static str,
+    // The rule id that reports it, as accepted by --enable
+    pub rule: &'static str,
+    pub severity: Severity,
+    pub title: &'static str,
+    pub description: &'static str,
+    pub rationale: &'static str,
+    pub fix: &'static str,
+}
+
+// Codes are never renumbered or reused once released; retired ones stay here
+// so old reports and suppressions still resolve
+pub const CODES: &[IssueCode] = &[
+    IssueCode {
+        code: "DOT001",
+        rule: "dotter-configs",
+        severity: Severity::Error,
+        title: "Dotter global.toml not found",
+        description: "The repository has no .dotter/global.toml, which is where dotter reads \
+                      the packages and file mappings to deploy.",
+        rationale: "Without it dotter has nothing to deploy, and every rule that checks the \
+                    mappings is skipped.",
+        fix: "Create .dotter/global.toml (see `dotter init`), or point --dir at the repository \
+              root.",
+    },
+    IssueCode {
+        code: "DOT002",
+        rule: "dotter-files",
+        severity: Severity::Error,
+        title: "Referenced file missing",
+        description: "A file mapped in the dotter configuration doesn't exist in the repository.",
+        rationale: "dotter fails the whole deploy when a source is missing, so one stale mapping \
+                    blocks every other file.",
+        fix: "Restore the file, or remove its mapping from .dotter/global.toml.",
+    },
+    IssueCode {
+        code: "DOT003",
+        rule: "dotter-files",
+        severity: Severity::Error,
+        title: "Referenced file ignored by git",
+        description: "A file mapped in the dotter configuration matches a .gitignore pattern.",
+        rationale: "It deploys from this checkout but is never committed, so a fresh clone on \
+                    another machine is missing it and the deploy fails there.",
+        fix: "Add a negated pattern (`!path`) to .gitignore, then commit the file. `fix` can \
+              add the pattern for you.",
+    },
+    IssueCode {
+        code: "DOT004",
+        rule: "dotter-files",
+        severity: Severity::Warning,
+        title: "Referenced file not tracked",
+        description: "A file mapped in the dotter configuration exists but hasn't been added \
+                      to git.",
+        rationale: "Like an ignored file, it only exists on this machine until it's committed.",
+        fix: "Run `git add` on the file and commit it.",
+    },
+    IssueCode {
+        code: "DOT005",
+        rule: "dotter-local",
+        severity: Severity::Error,
+        title: "Machine-local dotter file tracked",
+        description: "One of dotter's per-machine files (.dotter/local.toml, .dotter/cache.toml \
+                      or .dotter/cache/) is committed.",
+        rationale: "local.toml picks this machine's packages and the cache records what was \
+                    deployed here; on another machine they select the wrong packages and make \
+                    dotter think it owns files it never wrote.",
+        fix: "Run `git rm --cached` on the file and add it to .gitignore.",
+    },
+    IssueCode {
+        code: "DOT006",
+        rule: "dotter-local",
+        severity: Severity::Warning,
+        title: "Machine-local dotter file not ignored",
+        description: "One of dotter's per-machine files isn't covered by .gitignore.",
+        rationale: "A `git add -A` away from being committed.",
+        fix: "Add the path to .gitignore.",
+    },
+    IssueCode {
+        code: "DOT007",
+        rule: "deploy-targets",
+        severity: Severity::Error,
+        title: "Deploy target outside the home directory",
+        description: "A dotter target is an absolute path outside $HOME, such as /etc or \
+                      /usr/local.",
+        rationale: "A dotfiles deploy that writes system files can break the machine for every \
+                    user, and usually only works under sudo.",
+        fix: "Deploy under ~, or list the target in [deploy] allowed-targets in \
+              .validate-dotfiles.toml if it's deliberate.",
+    },
+    IssueCode {
+        code: "DOT008",
+        rule: "deploy-targets",
+        severity: Severity::Error,
+        title: "Deploy target needs elevated permissions",
+        description: "A dotter target inside $HOME is in a directory owned by another user \
+                      (usually root), so writing it needs sudo.",
+        rationale: "The deploy fails partway through, or gets run as root and leaves \
+                    root-owned files in the home directory.",
+        fix: "Fix the directory's ownership, or list the target in [deploy] allowed-targets.",
+    },
+    IssueCode {
+        code: "DOT009",
+        rule: "dotter-files",
+        severity: Severity::Error,
+        title: "Glob source matches nothing",
+        description: "A dotter source with glob characters matches no file in the repository.",
+        rationale: "Like a missing file, it deploys nothing, usually because files were moved \
+                    or renamed.",
+        fix: "Fix the pattern, or remove the mapping.",
+    },
+    IssueCode {
+        code: "DOT010",
+        rule: "dotter-files",
+        severity: Severity::Error,
+        title: "Broken symlink in a directory source",
+        description: "A directory that dotter deploys as a whole contains a symlink whose \
+                      target doesn't exist.",
+        rationale: "dotter deploys the dangling link along with everything else, and whatever \
+                    reads the directory trips over it.",
+        fix: "Remove the link or point it at an existing file.",
+    },
+    IssueCode {
+        code: "DOT011",
+        rule: "dotter-files",
+        severity: Severity::Warning,
+        title: "Empty directory in a directory source",
+        description: "A directory that dotter deploys as a whole contains an empty directory.",
+        rationale: "git doesn't store empty directories, so it exists on this machine only.",
+        fix: "Remove it, or commit a placeholder file such as .gitkeep inside it.",
+    },
+    IssueCode {
+        code: "LNK001",
+        rule: "symlinks",
+        severity: Severity::Error,
+        title: "Broken symlink",
+        description: "A symlink in the repository points at something that doesn't exist.",
+        rationale: "Deploying a dangling link gives the tool that reads it a confusing \
+                    \"file not found\".",
+        fix: "Recreate the link with the right target, or remove it.",
+    },
+    IssueCode {
+        code: "LNK002",
+        rule: "symlinks",
+        severity: Severity::Error,
+        title: "Symlink cycle",
+        description: "Following a symlink leads back to a link already visited.",
+        rationale: "Anything that resolves the link loops until the OS gives up with \
+                    \"too many levels of symbolic links\".",
+        fix: "Point one of the links in the cycle at a real file.",
+    },
+    IssueCode {
+        code: "LNK003",
+        rule: "symlinks",
+        severity: Severity::Warning,
+        title: "Symlink chain too deep",
+        description: "A symlink resolves through more links than the validator follows.",
+        rationale: "Long chains are almost always accidental and are fragile to rename.",
+        fix: "Point the link directly at its final target.",
+    },
+    IssueCode {
+        code: "LNK004",
+        rule: "symlinks",
+        severity: Severity::Warning,
+        title: "Symlink escapes the repository",
+        description: "A relative symlink resolves to a path outside the repository.",
+        rationale: "The target only exists on machines laid out exactly like this one.",
+        fix: "Move the target into the repository, or map it with dotter instead of a link.",
+    },
+    IssueCode {
+        code: "LNK005",
+        rule: "relative-symlinks",
+        severity: Severity::Error,
+        title: "Absolute symlink target",
+        description: "A symlink in the repository uses an absolute target.",
+        rationale: "Absolute targets break as soon as the repository is cloned somewhere else \
+                    or the home directory differs.",
+        fix: "Recreate the link with a relative target.",
+    },
+    IssueCode {
+        code: "LNK006",
+        rule: "stale-links",
+        severity: Severity::Warning,
+        title: "Stale deployed symlink",
+        description: "A symlink in a directory dotter deploys into points into the repository \
+                      at a file that no longer exists.",
+        rationale: "Files deleted or renamed in the repo leave their old links behind, and \
+                    tools that read them fail or silently skip their config.",
+        fix: "Run dotfiles-tools clean-links, or remove the link by hand.",
+    },
+    IssueCode {
+        code: "SYN001",
+        rule: "toml-syntax",
+        severity: Severity::Error,
+        title: "Invalid TOML",
+        description: "A TOML file doesn't parse.",
+        rationale: "Tools usually refuse to start, or silently fall back to defaults, when their \
+                    config doesn't parse.",
+        fix: "Correct the syntax at the reported position.",
+    },
+    IssueCode {
+        code: "SYN002",
+        rule: "json-syntax",
+        severity: Severity::Error,
+        title: "Invalid JSON",
+        description: "A JSON or JSON5 file doesn't parse. Comments and trailing commas are \
+                      accepted in files whose tools allow them; .json5 files are held to the \
+                      JSON5 grammar.",
+        rationale: "Tools usually refuse to start, or silently fall back to defaults, when their \
+                    config doesn't parse.",
+        fix: "Correct the syntax at the reported position.",
+    },
+    IssueCode {
+        code: "SYN003",
+        rule: "nushell-syntax",
+        severity: Severity::Error,
+        title: "Nushell parse error",
+        description: "`nu-check` reported a parse error in a Nushell script.",
+        rationale: "Nushell parses the whole file before running any of it, so one error \
+                    disables the entire config.",
+        fix: "Correct the reported error; `nu-check --debug <file>` shows the full diagnostic.",
+    },
+    IssueCode {
+        code: "SYN004",
+        rule: "conflict-markers",
+        severity: Severity::Error,
+        title: "Merge conflict markers",
+        description: "A file still contains <<<<<<< / ======= / >>>>>>> markers from a merge.",
+        rationale: "The markers are syntax errors in nearly every config format.",
+        fix: "Resolve the conflict and remove the markers.",
+    },
+    IssueCode {
+        code: "SYN005",
+        rule: "confusable-chars",
+        severity: Severity::Warning,
+        title: "Invisible or look-alike character",
+        description: "A config has a zero-width or non-breaking space, a smart quote, a dash \
+                      standing in for a hyphen, a bidi control or a stray byte order mark. \
+                      Prose files (Markdown, plain text) aren't checked.",
+        rationale: "These arrive by copy-paste from web pages and break shell parsing in ways \
+                    that are invisible in an editor: a smart-quoted alias, or an NBSP that \
+                    glues two words into one.",
+        fix: "Delete the character or retype it as plain ASCII.",
+    },
+    IssueCode {
+        code: "SYN006",
+        rule: "utf8",
+        severity: Severity::Warning,
+        title: "Text file isn't valid UTF-8",
+        description: "A tracked file with no NUL bytes (so not binary) contains bytes that \
+                      aren't valid UTF-8, usually from a Latin-1 or Windows-1252 editor.",
+        rationale: "Every content rule reads files as UTF-8 and silently skips this one, so \
+                    nothing else in it is checked.",
+        fix: "Re-save it as UTF-8, e.g. with iconv.",
+    },
+    IssueCode {
+        code: "FMT001",
+        rule: "toml-format",
+        severity: Severity::Warning,
+        title: "TOML file not formatted",
+        description: "A TOML file differs from taplo's default layout: entries and headers \
+                      unindented, one space around `=`, no trailing whitespace, at most two \
+                      blank lines in a row, and a single final newline.",
+        rationale: "Consistent formatting keeps diffs to the lines that actually changed.",
+        fix: "Run `dotfiles-tools fix --enable toml-format` to rewrite the file.",
+    },
+    IssueCode {
+        code: "FMT002",
+        rule: "json-format",
+        severity: Severity::Warning,
+        title: "JSON file not formatted",
+        description: "A JSON file isn't pretty-printed with two-space indentation (and sorted \
+                      keys, when `sort-json-keys` is set under [formatting]). Files with \
+                      comments are skipped.",
+        rationale: "Tools that rewrite their own JSON settings use this layout, so matching it \
+                    keeps their edits from reformatting the whole file.",
+        fix: "Run `dotfiles-tools fix --enable json-format` to rewrite the file.",
+    },
+    IssueCode {
+        code: "FMT003",
+        rule: "indentation",
+        severity: Severity::Warning,
+        title: "Mixed tab and space indentation",
+        description: "A TOML, YAML or Lua file (or another type listed under \
+                      [formatting.indent]) indents some lines with tabs and others with spaces, \
+                      or uses the kind its [formatting.indent] entry rules out. YAML files may \
+                      never indent with tabs.",
+        rationale: "YAML rejects tab indentation outright, and elsewhere a pasted block with \
+                    the other kind looks aligned in one editor and ragged in the next.",
+        fix: "Re-indent the listed lines, e.g. with your editor's retab command.",
+    },
+    IssueCode {
+        code: "FMT004",
+        rule: "indentation",
+        severity: Severity::Warning,
+        title: "Inconsistent indentation width",
+        description: "Some space-indented lines aren't at a multiple of the file's indentation \
+                      width: the one set under [formatting.indent], or else the step the file \
+                      uses most. Multi-line strings and YAML block scalars are ignored.",
+        rationale: "In YAML an off-by-one indent silently moves a key to a different parent; \
+                    elsewhere it's the usual sign of a sloppy paste.",
+        fix: "Re-indent the listed lines to the file's width.",
+    },
+    IssueCode {
+        code: "TOOL001",
+        rule: "tool-versions",
+        severity: Severity::Error,
+        title: "Tool pin without a version",
+        description: "A .tool-versions line names a tool but no version.",
+        rationale: "asdf and mise ignore the line, so the tool isn't pinned at all.",
+        fix: "Add the version after the tool name.",
+    },
+    IssueCode {
+        code: "TOOL002",
+        rule: "tool-versions",
+        severity: Severity::Error,
+        title: "[tools] is not a table",
+        description: "The [tools] key in a mise config isn't a table.",
+        rationale: "mise rejects the file, so none of its pins apply.",
+        fix: "Write it as a table: `[tools]` followed by `name = \"version\"` lines.",
+    },
+    IssueCode {
+        code: "TOOL003",
+        rule: "tool-versions",
+        severity: Severity::Error,
+        title: "Tool pin has no usable version",
+        description: "A mise tool entry has no version string mise can use.",
+        rationale: "mise skips the entry, so the tool isn't pinned.",
+        fix: "Give the tool a version string, or `{ version = \"...\" }`.",
+    },
+    IssueCode {
+        code: "TOOL004",
+        rule: "tool-versions",
+        severity: Severity::Warning,
+        title: "Conflicting tool versions",
+        description: "The same tool is pinned to different versions in different files.",
+        rationale: "Which version wins depends on the directory you're in, which is rarely \
+                    what's intended.",
+        fix: "Pin the tool in one place, or make the versions agree.",
+    },
+    IssueCode {
+        code: "TOOL005",
+        rule: "tool-versions-installed",
+        severity: Severity::Warning,
+        title: "Pinned tool not installed",
+        description: "A pinned tool version isn't installed on this machine.",
+        rationale: "Shims fail with \"no version installed\" the first time the tool runs.",
+        fix: "Run `mise install` (or `asdf install`).",
+    },
+    IssueCode {
+        code: "TOOL006",
+        rule: "brew-bundle",
+        severity: Severity::Warning,
+        title: "Brewfile entry not installed",
+        description: "A package listed in the Brewfile isn't installed.",
+        rationale: "Configs that expect the package will fail on this machine.",
+        fix: "Run `brew bundle install`.",
+    },
+    IssueCode {
+        code: "TOOL007",
+        rule: "brew-bundle",
+        severity: Severity::Warning,
+        title: "Installed package missing from Brewfile",
+        description: "A package is installed with Homebrew but not listed in the Brewfile.",
+        rationale: "A new machine set up from the Brewfile won't get it.",
+        fix: "Add it with `brew bundle dump --force`, or uninstall it.",
+    },
+    IssueCode {
+        code: "TOOL008",
+        rule: "vscode-extensions",
+        severity: Severity::Warning,
+        title: "Tracked VS Code extension not installed",
+        description: "An extension in the tracked extension list isn't installed in VS Code.",
+        rationale: "The list is how a new machine gets its extensions; one that's listed but \
+                    missing here was either dropped on purpose or never installed.",
+        fix: "Install it with `code --install-extension`, or remove it from the list.",
+    },
+    IssueCode {
+        code: "TOOL009",
+        rule: "vscode-extensions",
+        severity: Severity::Warning,
+        title: "Installed VS Code extension missing from list",
+        description: "An extension is installed in VS Code but not in the tracked extension list.",
+        rationale: "A new machine set up from the list won't get it.",
+        fix: "Add it to the list (`code --list-extensions` prints the ids), or uninstall it.",
+    },
+    IssueCode {
+        code: "SH001",
+        rule: "shell-definitions",
+        severity: Severity::Warning,
+        title: "Conflicting shell definitions",
+        description: "An alias, function or exported variable is defined differently in more \
+                      than one sourced shell file.",
+        rationale: "The one sourced last silently wins, so edits to the other have no effect.",
+        fix: "Keep a single definition.",
+    },
+    IssueCode {
+        code: "SH002",
+        rule: "path-entries",
+        severity: Severity::Warning,
+        title: "Relative PATH entry",
+        description: "A PATH modification adds a relative directory.",
+        rationale: "Relative entries resolve against the current directory, so commands change \
+                    as you cd around, which is also a security risk.",
+        fix: "Use an absolute path, e.g. starting with $HOME.",
+    },
+    IssueCode {
+        code: "SH003",
+        rule: "path-entries",
+        severity: Severity::Warning,
+        title: "PATH entry doesn't exist",
+        description: "A PATH modification adds a directory that doesn't exist on this machine.",
+        rationale: "Harmless on its own, but usually a leftover from a tool that's been removed \
+                    or a path that differs between machines.",
+        fix: "Remove the entry, or guard it with an existence check.",
+    },
+    IssueCode {
+        code: "SH004",
+        rule: "path-entries",
+        severity: Severity::Warning,
+        title: "Duplicate PATH entry",
+        description: "The same directory is added to PATH more than once.",
+        rationale: "Duplicates make PATH harder to read and can reorder lookups unexpectedly.",
+        fix: "Remove all but one of the additions.",
+    },
+    IssueCode {
+        code: "SH005",
+        rule: "fish-autoload",
+        severity: Severity::Warning,
+        title: "Fish function file doesn't define its function",
+        description: "A file in fish/functions/ doesn't define a function with the same name \
+                      as the file.",
+        rationale: "fish only autoloads functions/NAME.fish when NAME is called, so a function \
+                    with any other name is never defined, and fish doesn't say why.",
+        fix: "Rename the file or the function so they match, or move other code to conf.d/.",
+    },
+    IssueCode {
+        code: "SH006",
+        rule: "fish-autoload",
+        severity: Severity::Warning,
+        title: "Fish completion file doesn't complete its command",
+        description: "A file in fish/completions/ has no `complete` statement for the command \
+                      it's named after.",
+        rationale: "fish loads completions/NAME.fish only when completing NAME, so completions \
+                    for any other command never load.",
+        fix: "Rename the file after the command it completes, or fix the -c argument.",
+    },
+    IssueCode {
+        code: "RS001",
+        rule: "rust-script-headers",
+        severity: Severity::Error,
+        title: "Invalid rust-script manifest",
+        description: "The embedded Cargo manifest in a rust-script's doc comment doesn't parse.",
+        rationale: "rust-script refuses to build the script.",
+        fix: "Correct the ```cargo block in the script's header.",
+    },
+    IssueCode {
+        code: "RS002",
+        rule: "rust-script-headers",
+        severity: Severity::Error,
+        title: "Dependency without a version",
+        description: "A rust-script dependency has no version requirement.",
+        rationale: "The script picks up whatever is newest the next time it's compiled, which \
+                    can break it without any change to the file.",
+        fix: "Add a version requirement to the dependency.",
+    },
+    IssueCode {
+        code: "RS003",
+        rule: "rust-script-check",
+        severity: Severity::Error,
+        title: "rust-script doesn't compile",
+        description: "A rust-script failed `cargo check`.",
+        rationale: "The script fails the first time it's run, usually when it's needed.",
+        fix: "Run the script directly to see the compiler output, and fix the errors.",
+    },
+    IssueCode {
+        code: "XDG001",
+        rule: "xdg-paths",
+        severity: Severity::Info,
+        title: "Legacy config location",
+        description: "A file is deployed to a tool's legacy home-directory location although \
+                      the tool also reads an XDG location.",
+        rationale: "XDG locations keep $HOME tidy and group everything under ~/.config.",
+        fix: "Change the dotter target to the suggested XDG path.",
+    },
+    IssueCode {
+        code: "KAR001",
+        rule: "karabiner",
+        severity: Severity::Error,
+        title: "Invalid Karabiner configuration",
+        description: "karabiner.json doesn't match the structure Karabiner-Elements expects: a \
+                      value has the wrong type, a profile or rule is missing required fields, \
+                      or a manipulator has no key to match or nothing to send.",
+        rationale: "Karabiner rejects or silently skips malformed parts of its config, so a \
+                    remapping can stop working without any visible error.",
+        fix: "Correct the reported value; Karabiner's complex modification reference lists the \
+              expected fields.",
+    },
+    IssueCode {
+        code: "KAR002",
+        rule: "karabiner",
+        severity: Severity::Warning,
+        title: "Ambiguous Karabiner profiles",
+        description: "Profiles share a name, or not exactly one profile is marked `selected`.",
+        rationale: "Karabiner picks a profile by name and selection state; with duplicates it's \
+                    unclear which profile's settings are active.",
+        fix: "Give each profile a unique name and mark exactly one as selected.",
+    },
+    IssueCode {
+        code: "KAR003",
+        rule: "karabiner",
+        severity: Severity::Warning,
+        title: "Key combination mapped by several rules",
+        description: "Two complex modification rules in the same profile map the same `from` \
+                      key and modifiers under the same conditions.",
+        rationale: "Karabiner applies whichever rule comes first and silently ignores the other, \
+                    so reordering rules changes what a key does.",
+        fix: "Remove one of the mappings, or add conditions so they apply in different contexts.",
+    },
+    IssueCode {
+        code: "YAB001",
+        rule: "yabai",
+        severity: Severity::Error,
+        title: "yabai config has a shell syntax error",
+        description: "The yabairc doesn't parse as a shell script (checked with `sh -n`, or the \
+                      shell named in its shebang).",
+        rationale: "yabai runs the file as a script at startup; a syntax error stops it partway, \
+                    leaving every later setting, rule, and signal unapplied.",
+        fix: "Fix the reported line; `sh -n .yabairc` shows the same error.",
+    },
+    IssueCode {
+        code: "YAB002",
+        rule: "yabai",
+        severity: Severity::Warning,
+        title: "Invalid yabai command",
+        description: "A `yabai -m` command uses an unknown domain, setting, rule property, or \
+                      signal event, or gives a setting a value it doesn't accept.",
+        rationale: "yabai prints an error for the one command and carries on, so the mistake \
+                    only shows up as a setting that silently never took effect.",
+        fix: "Check the name against `man yabai` for the installed version; settings removed \
+              in yabai 6 and 7 (window borders, window_topmost) can simply be deleted.",
+    },
+    IssueCode {
+        code: "SKHD001",
+        rule: "skhd",
+        severity: Severity::Error,
+        title: "Invalid skhd hotkey",
+        description: "An skhdrc line isn't a valid hotkey, mode declaration, or directive: an \
+                      unknown modifier, a missing key or action, or a mode that's never \
+                      declared with `::`.",
+        rationale: "skhd refuses to load a config it can't parse, which disables every \
+                    binding, not just the broken one.",
+        fix: "Fix the reported line; `skhd --verbose` reports the same parse error.",
+    },
+    IssueCode {
+        code: "SKHD002",
+        rule: "skhd",
+        severity: Severity::Warning,
+        title: "Hotkey bound twice",
+        description: "The same modifiers and key are bound more than once in the same mode.",
+        rationale: "Only one of the bindings can fire, so the other command is dead code that \
+                    looks like it works.",
+        fix: "Remove one binding or move it to another key or mode.",
+    },
+    IssueCode {
+        code: "SKHD003",
+        rule: "skhd",
+        severity: Severity::Warning,
+        title: "Hotkey runs a missing script",
+        description: "A binding runs a script under the home directory that neither the repo \
+                      nor the dotter mappings provide, or a script name that isn't on PATH.",
+        rationale: "The hotkey does nothing when pressed, and skhd only logs the failure.",
+        fix: "Add the script to the repo and map it with dotter, or fix the path in the binding.",
+    },
+    IssueCode {
+        code: "AERO001",
+        rule: "aerospace",
+        severity: Severity::Warning,
+        title: "Unknown AeroSpace setting",
+        description: "aerospace.toml has a top-level key AeroSpace doesn't recognize.",
+        rationale: "Usually a typo or a setting renamed between releases; AeroSpace refuses \
+                    to load a config with unknown keys, keeping the previous one instead.",
+        fix: "Check the key against AeroSpace's default config for the installed version.",
+    },
+    IssueCode {
+        code: "AERO002",
+        rule: "aerospace",
+        severity: Severity::Error,
+        title: "Undefined AeroSpace binding mode",
+        description: "A binding switches to a mode with no [mode.<name>.binding] table, or the \
+                      config has no main mode at all.",
+        rationale: "Switching to a mode that doesn't exist fails at the moment the key is \
+                    pressed, which can leave you without a way back to your normal bindings.",
+        fix: "Define the mode's binding table, or fix the mode name in the binding.",
+    },
+    IssueCode {
+        code: "AERO003",
+        rule: "aerospace",
+        severity: Severity::Warning,
+        title: "Binding uses an unconfigured workspace",
+        description: "A binding focuses or moves windows to a workspace that isn't listed in \
+                      persistent-workspaces or workspace-to-monitor-force-assignment, in a \
+                      config that lists its workspaces.",
+        rationale: "The workspace is created on demand instead of where the config places the \
+                    others, which is usually a typo in its name.",
+        fix: "Add the workspace to persistent-workspaces, or fix the name in the binding.",
+    },
+    IssueCode {
+        code: "HELIX001",
+        rule: "helix",
+        severity: Severity::Warning,
+        title: "Unknown Helix key",
+        description: "config.toml or languages.toml sets a key Helix doesn't recognize.",
+        rationale: "Helix refuses to load a config with unknown keys and falls back to its \
+                    defaults, so one typo silently drops every other setting in the file.",
+        fix: "Check the key against the Helix documentation for the installed version.",
+    },
+    IssueCode {
+        code: "HELIX002",
+        rule: "helix",
+        severity: Severity::Warning,
+        title: "Helix language tool not installed",
+        description: "A language server or formatter command in languages.toml isn't on PATH.",
+        rationale: "Helix only reports a missing language server in its log, so completions \
+                    and formatting just stop working for that language.",
+        fix: "Install the tool, or drop the entry; `dotfiles-tools doctor` lists every missing tool.",
+    },
+    IssueCode {
+        code: "HELIX003",
+        rule: "helix",
+        severity: Severity::Warning,
+        title: "Unknown Helix theme",
+        description: "The theme in config.toml isn't in the Helix runtime, ~/.config/helix/themes, \
+                      or a themes directory next to the config in the repo.",
+        rationale: "Helix falls back to the default theme with an error at startup.",
+        fix: "Fix the theme name, or add the theme file under themes/ next to config.toml.",
+    },
+    IssueCode {
+        code: "CARGO001",
+        rule: "cargo-config",
+        severity: Severity::Warning,
+        title: "Unknown Cargo config table",
+        description: "A .cargo/config.toml has a top-level table Cargo doesn't define.",
+        rationale: "Cargo ignores unknown tables without a word, so a misspelled [buidl] \
+                    quietly drops every setting in it.",
+        fix: "Check the table name against the Cargo configuration reference.",
+    },
+    IssueCode {
+        code: "CARGO002",
+        rule: "cargo-config",
+        severity: Severity::Warning,
+        title: "Deprecated Cargo config key",
+        description: "A Cargo config sets a key Cargo no longer reads (build.pipelining, \
+                      cargo-new.name and .email, registry.index, target.<triple>.ar), or is \
+                      named .cargo/config rather than .cargo/config.toml.",
+        rationale: "Deprecated keys do nothing, or stop working in a later release, while \
+                    the config still reads as if they apply.",
+        fix: "Follow the fix on the issue; most of these keys can simply be removed.",
+    },
+    IssueCode {
+        code: "CARGO003",
+        rule: "cargo-config",
+        severity: Severity::Warning,
+        title: "Cargo alias or registry collides with a built-in",
+        description: "An [alias] entry has the name of a built-in cargo command, or \
+                      [registries.crates-io] sets an index.",
+        rationale: "Cargo always runs the built-in command over an alias of the same name, and \
+                    crates.io's index can only be swapped through source replacement.",
+        fix: "Rename the alias, or move the mirror to a [source.crates-io] replace-with entry.",
+    },
+    IssueCode {
+        code: "CARGO004",
+        rule: "cargo-config",
+        severity: Severity::Warning,
+        title: "Cargo linker not found",
+        description: "A [target] table's linker isn't on PATH or doesn't exist at its path. \
+                      Relative paths are resolved against the home directory, where the \
+                      deployed .cargo lives.",
+        rationale: "Every build for that target fails at the link step, usually long after \
+                    the config was changed.",
+        fix: "Install the linker, or keep the [target] table out of this machine's profile; \
+              `dotfiles-tools doctor` lists every missing tool.",
+    },
+    IssueCode {
+        code: "LAZYGIT001",
+        rule: "lazygit",
+        severity: Severity::Error,
+        title: "Invalid lazygit YAML",
+        description: "lazygit's config.yml doesn't parse as YAML.",
+        rationale: "lazygit refuses to start until the config is fixed.",
+        fix: "Correct the syntax at the reported position.",
+    },
+    IssueCode {
+        code: "LAZYGIT002",
+        rule: "lazygit",
+        severity: Severity::Warning,
+        title: "Unknown lazygit setting",
+        description: "config.yml has a top-level key, or a view under keybinding:, that \
+                      lazygit doesn't define.",
+        rationale: "lazygit ignores what it doesn't recognize, so a misspelled section \
+                    quietly drops everything in it.",
+        fix: "Check the key against lazygit's Config.md for the installed version.",
+    },
+    IssueCode {
+        code: "LAZYGIT003",
+        rule: "lazygit",
+        severity: Severity::Warning,
+        title: "lazygit key bound twice",
+        description: "Two actions in the same keybinding view, or two custom commands in the \
+                      same context, use the same key. <disabled> entries are ignored.",
+        rationale: "Only one of them ever fires, and which one isn't obvious from the config.",
+        fix: "Move one of them to a free key, or set it to <disabled>.",
+    },
+    IssueCode {
+        code: "RG001",
+        rule: "ripgrep",
+        severity: Severity::Error,
+        title: "More than one argument on a ripgrep config line",
+        description: "A line in .ripgreprc holds a flag followed by a space, such as \
+                      `--hidden --follow` or `--max-columns 150`. ripgrep reads every line as \
+                      exactly one argument.",
+        rationale: "rg rejects the whole line as an unknown flag, and with it every search \
+                    until the config is fixed.",
+        fix: "Put each flag on its own line, and join values with `=` (--max-columns=150).",
+    },
+    IssueCode {
+        code: "RG002",
+        rule: "ripgrep",
+        severity: Severity::Error,
+        title: "Unknown ripgrep flag",
+        description: "A flag in .ripgreprc isn't one the installed rg accepts, going by \
+                      `rg --help`. Only checked when rg is on PATH.",
+        rationale: "rg refuses to run at all when its config has a flag it doesn't know.",
+        fix: "Fix the spelling, or drop flags from newer or older ripgrep releases.",
+    },
+    IssueCode {
+        code: "FD001",
+        rule: "fd-ignore",
+        severity: Severity::Warning,
+        title: "Invalid glob in an fd ignore file",
+        description: "A pattern in .fdignore or fd/ignore has an unclosed [ or {, a reversed \
+                      range, nested {} groups, or a trailing backslash.",
+        rationale: "fd skips patterns it can't parse, so the files they were meant to hide \
+                    turn up in every search.",
+        fix: "Close the bracket or brace, or escape it with a backslash to match it literally.",
+    },
+    IssueCode {
+        code: "BAT001",
+        rule: "bat",
+        severity: Severity::Warning,
+        title: "Unknown bat theme",
+        description: "--theme, --theme-dark or --theme-light in bat's config names a theme \
+                      that `bat --list-themes` doesn't know and that isn't tracked under \
+                      themes/ next to the config. Only checked when bat is on PATH.",
+        rationale: "bat warns on every run and falls back to its default theme.",
+        fix: "Pick a name from `bat --list-themes`, or add the theme and run \
+              `bat cache --build`.",
+    },
+    IssueCode {
+        code: "ENVRC001",
+        rule: "envrc",
+        severity: Severity::Error,
+        title: "Invalid .envrc syntax",
+        description: "A tracked .envrc doesn't parse with `bash -n`, which is how direnv \
+                      evaluates it.",
+        rationale: "direnv reports the error and loads none of the file's environment.",
+        fix: "Correct the syntax at the reported line.",
+    },
+    IssueCode {
+        code: "ENVRC002",
+        rule: "envrc",
+        severity: Severity::Error,
+        title: "Secret exported from a tracked .envrc",
+        description: "An .envrc exports a variable named like a credential (TOKEN, SECRET, \
+                      PASSWORD, API_KEY, ...) with a literal value, or a value shaped like a \
+                      known token (ghp_, github_pat_, sk-, xoxb-, AKIA...). Values read from a \
+                      command, another variable, an op:// reference or a file path are fine.",
+        rationale: "Anything committed to the repo is in its history for good, and on every \
+                    machine and remote it's pushed to.",
+        fix: "Rotate the secret, then read it at load time, e.g. \
+              export TOKEN=\"$(op read op://vault/item/field)\".",
+    },
+    IssueCode {
+        code: "ENVRC003",
+        rule: "envrc",
+        severity: Severity::Warning,
+        title: "direnv not listed for a deployed .envrc",
+        description: "dotter deploys an .envrc, but no Brewfile has brew \"direnv\" and no \
+                      tool version file pins direnv.",
+        rationale: "A machine set up from the repo gets the .envrc without anything to load \
+                    it, and the environment it sets silently never appears.",
+        fix: "Add brew \"direnv\" to the Brewfile; `dotfiles-tools doctor` shows whether \
+              direnv is installed here.",
+    },
+    IssueCode {
+        code: "ZED001",
+        rule: "zed-keymap",
+        severity: Severity::Error,
+        title: "Invalid Zed keymap context",
+        description: "A keymap block's context selector doesn't parse, or keymap.json isn't an \
+                      array of binding blocks.",
+        rationale: "Zed skips the whole block when its context doesn't parse, so none of its \
+                    bindings apply.",
+        fix: "Fix the selector; it combines identifiers and `key == value` with !, &&, ||, > \
+              and parentheses.",
+    },
+    IssueCode {
+        code: "ZED002",
+        rule: "zed-keymap",
+        severity: Severity::Warning,
+        title: "Duplicate Zed binding",
+        description: "The same keystrokes are bound twice in blocks with the same context.",
+        rationale: "Only the later binding takes effect, which is easy to miss when the blocks \
+                    are far apart.",
+        fix: "Remove one of the bindings, or merge the blocks.",
+    },
+    IssueCode {
+        code: "ZED003",
+        rule: "zed-keymap",
+        severity: Severity::Warning,
+        title: "Misspelled Zed action",
+        description: "A binding's action isn't a known Zed action but is a near miss of one.",
+        rationale: "Zed reports unknown actions only in its log and the key silently does \
+                    nothing.",
+        fix: "Use the suggested action name.",
+    },
+    IssueCode {
+        code: "TMUX001",
+        rule: "tmux",
+        severity: Severity::Error,
+        title: "Invalid tmux config",
+        description: "A tmux config has an unknown command or directive, an unclosed quote or \
+                      block, or a set, bind-key, unbind-key or source-file missing its \
+                      arguments.",
+        rationale: "tmux stops reading a config at the first error, so everything after the \
+                    bad line is silently dropped.",
+        fix: "Fix the line; `tmux source-file` on the file shows the same error.",
+    },
+    IssueCode {
+        code: "TMUX002",
+        rule: "tmux",
+        severity: Severity::Warning,
+        title: "Duplicate tmux key binding",
+        description: "The same key is bound twice in the same key table without an unbind-key \
+                      in between. Bindings inside %if blocks aren't compared.",
+        rationale: "Only the last binding takes effect, so the earlier one is dead config.",
+        fix: "Remove one of the bindings or move one to another key.",
+    },
+    IssueCode {
+        code: "TMUX003",
+        rule: "tmux",
+        severity: Severity::Error,
+        title: "Sourced tmux file missing",
+        description: "A source-file path doesn't resolve to a file in the repo, through a \
+                      dotter target, or on this machine.",
+        rationale: "tmux reports the missing file and stops loading the rest of the config.",
+        fix: "Track and deploy the file, or use `source-file -q` for optional local files.",
+    },
+    IssueCode {
+        code: "TMUX004",
+        rule: "tmux",
+        severity: Severity::Warning,
+        title: "TPM plugins without a deployed TPM",
+        description: "A tmux config declares @plugin entries but never runs TPM, or runs it \
+                      from a directory the dotter config doesn't deploy.",
+        rationale: "On a fresh machine the plugins are never installed, and the bindings and \
+                    options they provide are missing.",
+        fix: "Track TPM (as a submodule, say), map it in dotter, and run it at the end of \
+              tmux.conf.",
+    },
+    IssueCode {
+        code: "REF001",
+        rule: "bin-references",
+        severity: Severity::Error,
+        title: "Reference to a missing repo script",
+        description: "A shell config, skhd/yabai config or LaunchAgent points at a path under \
+                      the repo's bin/ or scripts/ that doesn't exist.",
+        rationale: "The reference fails when it runs, often silently: a LaunchAgent just \
+                    exits and a hotkey does nothing.",
+        fix: "Fix the path, restore the script, or remove the reference.",
+    },
+    IssueCode {
+        code: "REF002",
+        rule: "bin-references",
+        severity: Severity::Warning,
+        title: "Reference to an untracked repo script",
+        description: "A config points at a script under bin/ or scripts/ that exists here but \
+                      isn't tracked by git.",
+        rationale: "It works on this machine and breaks on every other one.",
+        fix: "Track the script with `git add`.",
+    },
+    IssueCode {
+        code: "REF003",
+        rule: "sourced-files",
+        severity: Severity::Error,
+        title: "Sourced file missing",
+        description: "A shell or vim `source`, or a Lua `require` of one of the config's own \
+                      modules, points into the repo (directly or through a dotter target) at \
+                      a file that doesn't exist.",
+        rationale: "The shell or editor errors at startup, and everything the file defined is \
+                    missing.",
+        fix: "Restore the file, or fix the path or module name.",
+    },
+    IssueCode {
+        code: "REF004",
+        rule: "sourced-files",
+        severity: Severity::Warning,
+        title: "Sourced file not tracked",
+        description: "A file pulled in by `source`, `.`, tmux source-file, a gitconfig \
+                      include or a Lua `require` exists in the checkout but isn't tracked.",
+        rationale: "It works here, but a fresh clone is missing it.",
+        fix: "Run `git add` on the file and commit it.",
+    },
+    IssueCode {
+        code: "GIT001",
+        rule: "gitconfig-includes",
+        severity: Severity::Warning,
+        title: "Missing included git config",
+        description: "An include.path or includeIf path points at a file that isn't in the repo, \
+                      isn't deployed by dotter, and doesn't exist on this machine.",
+        rationale: "Git ignores includes it can't find, so the settings in it are silently \
+                    missing.",
+        fix: "Fix the path, or add the file to the repo and deploy it.",
+    },
+    IssueCode {
+        code: "GIT002",
+        rule: "gitconfig-includes",
+        severity: Severity::Warning,
+        title: "Directory tree without user.email",
+        description: "A git config splits user.email across includeIf \"gitdir:...\" sections \
+                      but some directories end up without one.",
+        rationale: "Git then guesses an address from the hostname, or refuses to commit with \
+                    user.useConfigOnly, in repos outside the listed directories.",
+        fix: "Set a default user.email in the main config, or in every included file.",
+    },
+    IssueCode {
+        code: "GIT003",
+        rule: "git-aliases",
+        severity: Severity::Warning,
+        title: "Alias shadows a git command",
+        description: "An [alias] entry has the same name as one of git's own commands.",
+        rationale: "Git always runs its own command and ignores the alias, so the alias is \
+                    dead configuration that looks like it works.",
+        fix: "Rename the alias.",
+    },
+    IssueCode {
+        code: "GIT004",
+        rule: "git-aliases",
+        severity: Severity::Warning,
+        title: "Alias runs a missing command",
+        description: "A `!cmd` alias runs a program that isn't on PATH.",
+        rationale: "The alias fails with a shell error when used; `dotfiles-tools doctor` lists \
+                    it with the other missing tools.",
+        fix: "Install the program, or remove the alias.",
+    },
+    IssueCode {
+        code: "GIT005",
+        rule: "git-aliases",
+        severity: Severity::Warning,
+        title: "Alias defined twice",
+        description: "The same alias is defined more than once across a git config and the \
+                      files it includes.",
+        rationale: "Git keeps the last definition it reads, so the earlier one silently stops \
+                    working.",
+        fix: "Remove one of the definitions.",
+    },
+    IssueCode {
+        code: "GIT006",
+        rule: "git-tools",
+        severity: Severity::Warning,
+        title: "Git pager or diff tool not installed",
+        description: "core.pager, pager.*, interactive.diffFilter, diff.external, or a diff or \
+                      merge tool command runs a program that isn't on PATH.",
+        rationale: "A missing pager breaks every paged git command, and a missing diff tool \
+                    fails only when it's finally needed.",
+        fix: "Install the program (`dotfiles-tools doctor` lists everything missing), or \
+              change the setting.",
+    },
+    IssueCode {
+        code: "GIT007",
+        rule: "git-tools",
+        severity: Severity::Warning,
+        title: "Unknown git diff or merge tool",
+        description: "diff.tool or merge.tool names a tool git doesn't know, and no tracked \
+                      config has a [difftool \"name\"] or [mergetool \"name\"] section for it.",
+        rationale: "`git difftool` and `git mergetool` fail with \"unknown tool\" instead of \
+                    opening anything.",
+        fix: "Add a section with a cmd for the tool, or use a tool git knows.",
+    },
+    IssueCode {
+        code: "GIT008",
+        rule: "git-excludes",
+        severity: Severity::Warning,
+        title: "Global gitignore not deployed",
+        description: "core.excludesFile points at a file that doesn't exist, or at one the repo \
+                      doesn't track and deploy.",
+        rationale: "Git silently skips a missing excludesFile, so every global ignore stops \
+                    applying without any error.",
+        fix: "Track the ignore file and deploy it with dotter to the path excludesFile names.",
+    },
+    IssueCode {
+        code: "GIT009",
+        rule: "git-excludes",
+        severity: Severity::Warning,
+        title: "Ineffective global gitignore pattern",
+        description: "A pattern in the global gitignore doesn't do what it looks like: trailing \
+                      whitespace, a lone backslash, an unclosed '[', a misplaced '**', or a \
+                      negation inside an excluded directory.",
+        rationale: "Git accepts these without complaint and quietly ignores the wrong files.",
+        fix: "Rewrite the pattern; see gitignore(5).",
+    },
+    IssueCode {
+        code: "JUNK001",
+        rule: "junk-files",
+        severity: Severity::Warning,
+        title: "Cache or history file tracked",
+        description: "A tracked file is machine state rather than configuration: Finder \
+                      metadata, a log, fish_variables, a history, compiled zsh, an editor swap \
+                      file or dotter's cache.",
+        rationale: "These change on every use, leak what was typed or opened, and overwrite \
+                    another machine's state when deployed there.",
+        fix: "Untrack it with `git rm --cached` and add its pattern to .gitignore.",
+    },
+    IssueCode {
+        code: "SEC001",
+        rule: "credential-files",
+        severity: Severity::Error,
+        title: "Credential file tracked",
+        description: "A tracked file is named like a credential store: .env, an ssh private key, \
+                      a .pem or other key bundle, .netrc, .git-credentials, AWS credentials, or \
+                      a gh hosts.yml holding an oauth_token.",
+        rationale: "These are flagged by name, even when empty, so a secret written into one \
+                    later can't be committed without notice.",
+        fix: "Untrack it with `git rm --cached`, add it to .gitignore, and rotate any secret it \
+              ever held.",
+    },
+    IssueCode {
+        code: "OP001",
+        rule: "op-references",
+        severity: Severity::Error,
+        title: "Malformed 1Password reference",
+        description: "An op:// reference isn't vault/item/field or vault/item/section/field, \
+                      has an empty segment, or uses a query parameter `op read` doesn't know.",
+        rationale: "A template renders whatever `op` returns for it, which for a malformed \
+                    reference is nothing.",
+        fix: "Copy the reference from 1Password with \"Copy Secret Reference\".",
+    },
+    IssueCode {
+        code: "OP002",
+        rule: "op-references",
+        severity: Severity::Error,
+        title: "1Password reference doesn't resolve",
+        description: "`op read` failed for a reference: the vault, item or field was renamed, \
+                      deleted, or isn't shared with this account. Only checked when `op` is \
+                      installed and signed in.",
+        rationale: "The template would otherwise render an empty secret at deploy time.",
+        fix: "Run `op read` on the reference and update it to the item's current path.",
+    },
+    IssueCode {
+        code: "JUNK002",
+        rule: "untracked-files",
+        severity: Severity::Warning,
+        title: "File neither tracked nor ignored",
+        description: "A file or directory in the working tree isn't tracked and doesn't match \
+                      .gitignore. Untracked directories are reported once.",
+        rationale: "These are usually configs edited in place and never committed; no content \
+                    rule checks them, and a fresh clone doesn't have them.",
+        fix: "Commit it, or add it to .gitignore if it's meant to stay local.",
+    },
+    IssueCode {
+        code: "JUNK003",
+        rule: "large-files",
+        severity: Severity::Warning,
+        title: "File too large to check",
+        description: "A content file is over the 2 MiB limit, so content rules skip it rather \
+                      than reading it into memory.",
+        rationale: "Config files are small; something this size is usually a dump, cache or \
+                    binary committed by accident, and nothing else validates it.",
+        fix: "Remove it with git rm --cached and ignore it, or move it out of the repo.",
+    },
+    IssueCode {
+        code: "DRIFT001",
+        rule: "drift",
+        severity: Severity::Warning,
+        title: "File not deployed",
+        description: "A mapped file has no deployed copy or link at its target.",
+        rationale: "The tool is running without this configuration.",
+        fix: "Run `dotfiles-tools deploy`.",
+    },
+    IssueCode {
+        code: "DRIFT002",
+        rule: "drift",
+        severity: Severity::Error,
+        title: "Deployed symlink points elsewhere",
+        description: "The target is a symlink, but not to the file in this repository.",
+        rationale: "Edits in the repository don't reach the deployed config.",
+        fix: "Remove the link and redeploy.",
+    },
+    IssueCode {
+        code: "DRIFT003",
+        rule: "drift",
+        severity: Severity::Warning,
+        title: "Copy deployed instead of a symlink",
+        description: "A file dotter should symlink is a regular file at its target.",
+        rationale: "The copy stops tracking the repository; later edits on either side diverge.",
+        fix: "Move any local changes into the repository, then redeploy.",
+    },
+    IssueCode {
+        code: "DRIFT004",
+        rule: "drift",
+        severity: Severity::Warning,
+        title: "Deployed file replaced and differs",
+        description: "A deployed file was replaced by something with different contents.",
+        rationale: "Usually a tool rewrote its own config; those changes are lost on the next \
+                    deploy.",
+        fix: "Copy the changes worth keeping into the repository, then redeploy.",
+    },
+    IssueCode {
+        code: "DRIFT005",
+        rule: "drift",
+        severity: Severity::Warning,
+        title: "Rendered template edited",
+        description: "A template's rendered output was edited after deployment.",
+        rationale: "dotter overwrites the edits on the next deploy.",
+        fix: "Make the change in the template instead.",
+    },
+    IssueCode {
+        code: "DRIFT006",
+        rule: "drift",
+        severity: Severity::Warning,
+        title: "Deployed copy's permissions drifted",
+        description: "A templated or copied file at its target grants access the repo source \
+                      doesn't, or has lost or gained the executable bit.",
+        rationale: "A script that lost its executable bit stops running, and a widened mode \
+                    exposes a config meant to be private.",
+        fix: "chmod the deployed file to match the source, or redeploy.",
+    },
+    IssueCode {
+        code: "DRIFT007",
+        rule: "drift",
+        severity: Severity::Error,
+        title: "ssh or gnupg file not private",
+        description: "A deployed file in ~/.ssh or ~/.gnupg, or the directory itself, can be \
+                      read or written by group or others. Files should be 0600 and the \
+                      directories 0700.",
+        rationale: "ssh refuses keys and configs with loose permissions, and gpg warns about an \
+                    unsafe home directory on every command.",
+        fix: "chmod 600 the file and 700 the directory.",
+    },
+    IssueCode {
+        code: "PROF001",
+        rule: "profiles",
+        severity: Severity::Error,
+        title: "Profile deploys two files to one target",
+        description: "Two packages in a profile declared in .validate-dotfiles.toml map different \
+                      sources to the same target.",
+        rationale: "dotter refuses to deploy the combination, or one file silently wins.",
+        fix: "Drop one of the packages from the profile, or give the files different targets.",
+    },
+    IssueCode {
+        code: "PROF002",
+        rule: "profiles",
+        severity: Severity::Error,
+        title: "Profile needs a missing file",
+        description: "A profile includes a dotter config, or deploys a source, that doesn't exist \
+                      in the repository.",
+        rationale: "A machine set up with the profile fails to deploy.",
+        fix: "Restore the file, or remove its mapping or include.",
+    },
+    IssueCode {
+        code: "PROF003",
+        rule: "profiles",
+        severity: Severity::Error,
+        title: "Template variable undefined in profile",
+        description: "A template deployed by a profile outputs or iterates over a variable that \
+                      none of the profile's packages, nor the profile itself, defines.",
+        rationale: "dotter fails to render the template on machines using the profile.",
+        fix: "Define the variable in a package's [<package>.variables] table or in the \
+              profile's variables.",
+    },
+    IssueCode {
+        code: "PROF004",
+        rule: "profiles",
+        severity: Severity::Error,
+        title: "Profile selects unknown package",
+        description: "A profile lists a package that global.toml and the profile's includes \
+                      don't define.",
+        rationale: "Usually a typo or a renamed package; its files are never deployed.",
+        fix: "Correct the package name, or add the platform config that defines it to includes.",
+    },
+    IssueCode {
+        code: "HOST001",
+        rule: "host-templates",
+        severity: Severity::Error,
+        title: "Template fails to render on some hosts",
+        description: "A template outputs a variable that isn't defined for one or more of the \
+                      hosts declared in .validate-dotfiles.toml, usually inside a branch on \
+                      dotter.hostname that only those hosts take.",
+        rationale: "dotter fails to deploy on exactly the machines you aren't sitting at, and \
+                    nothing on this one shows it.",
+        fix: "Define the variable for those hosts, in a package, profile or the host's \
+              variables, or guard its use with #if.",
+    },
+    IssueCode {
+        code: "HOST002",
+        rule: "host-templates",
+        severity: Severity::Error,
+        title: "Host uses undeclared profile",
+        description: "A [hosts.<name>] table names a profile that .validate-dotfiles.toml \
+                      doesn't declare.",
+        rationale: "Without the profile there's no way to tell what the host deploys, so its \
+                    templates go unchecked.",
+        fix: "Correct the profile name, or declare it under [profiles.<name>].",
+    },
+];
+
+pub fn lookup(code: &str) -> Option<&'static IssueCode> {
+    CODES.iter().find(|c| c.code.eq_ignore_ascii_case(code))
+}
+
+// The `explain` command: one code in detail, or an index of all of them
+pub fn explain(code: Option<&str>) -> Result<i32> {
+    let Some(code) = code else {
+        for entry in CODES {
+            println!(
+                "{}{:<9}{} {:<24} {}",
+                Color::bold(),
+                entry.code,
+                Color::reset(),
+                entry.rule,
+                entry.title
+            );
+        }
+        return Ok(ExitCode::CLEAN);
+    };
+
+    // shellcheck's own codes are documented upstream
+    if let Some(number) = code.to_ascii_uppercase().strip_prefix("SC")
+        && number.parse::<u32>().is_ok()
+    {
+        println!("SC{} is reported by the shellcheck rule", number);
+        println!("See https://www.shellcheck.net/wiki/SC{}", number);
+        return Ok(ExitCode::CLEAN);
+    }
+
+    let Some(entry) = lookup(code) else {
+        anyhow::bail!(
+            "Unknown issue code '{}' (run `dotfiles-tools explain` to list them)",
+            code
+        );
+    };
+    println!(
+        "{}{}: {}{}",
+        Color::bold(),
+        entry.code,
+        entry.title,
+        Color::reset()
+    );
+    println!(
+        "Reported by the {} rule as {}\n",
+        entry.rule,
+        format!("{:?}", entry.severity).to_lowercase()
+    );
+    println!("{}\n", entry.description);
+    println!(
+        "{}Why it matters:{} {}\n",
+        Color::bold(),
+        Color::reset(),
+        entry.rationale
+    );
+    println!("{}Fix:{} {}", Color::bold(), Color::reset(), entry.fix);
+    Ok(ExitCode::CLEAN)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules;
+    use std::collections::HashSet;
+
+    #[test]
+    fn codes_are_unique_and_name_real_rules() {
+        let rule_ids: HashSet<_> = rules::all().iter().map(|r| r.id).collect();
+        let mut seen = HashSet::new();
+        for entry in CODES {
+            assert!(seen.insert(entry.code), "{} is listed twice", entry.code);
+            assert!(
+                // Commands that report issues outside of a rule
+                matches!(entry.rule, "drift" | "profiles") || rule_ids.contains(entry.rule),
+                "{} names unknown rule {}",
+                entry.code,
+                entry.rule
+            );
+        }
+        assert_eq!(lookup("dot003").map(|c| c.rule), Some("dotter-files"));
+    }
+}
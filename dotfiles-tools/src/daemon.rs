@@ -0,0 +1,327 @@
+//! Daemon mode: a resident validator answering JSON requests on a Unix socket.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use std::{
+    fs,
+    io::{BufRead, BufReader, Write},
+    os::unix::net::{UnixListener, UnixStream},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    thread,
+    time::Instant,
+};
+
+use crate::{
+    cache::ResultCache,
+    history::state_path,
+    output::info,
+    repo::check_environment,
+    types::{Config, ExitCode, OutputMode, Severity, ValidationResult},
+    validator::Validator,
+    watch::repo_fingerprint,
+};
+
+pub const SOCKET_FILE: &str = "daemon.sock";
+
+/// One request per line, e.g. `{"command": "check-file", "file": "zshrc"}`.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "kebab-case")]
+pub enum Request {
+    /// Run the per-file rules against one file, as it is on disk
+    CheckFile {
+        file: String,
+    },
+    /// Run every rule, reusing the last results while the repo is unchanged
+    CheckAll,
+    Status,
+}
+
+/// One response line per request; `ok` is false only when the request
+/// itself failed, not when validation found errors.
+#[derive(Debug, Default, Serialize)]
+pub struct Response {
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub passed: Option<bool>,
+    // Whether check-all answered from the previous run
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cached: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub results: Option<Vec<ValidationResult>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<Status>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Status {
+    pub root: PathBuf,
+    pub pid: u32,
+    pub uptime_ms: u64,
+    pub requests: u64,
+    // Counts from the last check-all, if there's been one
+    pub errors: Option<usize>,
+    pub warnings: Option<usize>,
+}
+
+impl Response {
+    fn results(results: Vec<ValidationResult>) -> Self {
+        Self {
+            ok: true,
+            passed: Some(count(&results, Severity::Error) == 0),
+            results: Some(results),
+            ..Self::default()
+        }
+    }
+
+    fn error(err: anyhow::Error) -> Self {
+        Self {
+            error: Some(format!("{:#}", err)),
+            ..Self::default()
+        }
+    }
+}
+
+pub struct Daemon {
+    config: Config,
+    started: Instant,
+    requests: u64,
+    // Repo fingerprint when check-all last ran, and what it found
+    last_run: Option<(u64, Vec<ValidationResult>)>,
+}
+
+fn count(results: &[ValidationResult], severity: Severity) -> usize {
+    results
+        .iter()
+        .flat_map(|r| &r.issues)
+        .filter(|i| i.severity == severity)
+        .count()
+}
+
+impl Daemon {
+    // The per-file result cache stays loaded for the daemon's lifetime, so
+    // only files that changed since the last request are re-checked
+    pub fn new(mut config: Config) -> Result<Self> {
+        config.output = OutputMode::Quiet;
+        config.incremental = true;
+        config.cache = Some(Arc::new(ResultCache::load(&config)?));
+        Ok(Self {
+            config,
+            started: Instant::now(),
+            requests: 0,
+            last_run: None,
+        })
+    }
+
+    // Editors send absolute paths; rules want them relative to the repo
+    fn repo_relative(&self, file: &str) -> Result<String> {
+        let path = Path::new(file);
+        let relative = if path.is_absolute() {
+            path.strip_prefix(&self.config.dotfiles_dir)
+                .with_context(|| format!("{} is outside the repository", file))?
+        } else {
+            path
+        };
+        anyhow::ensure!(
+            self.config.dotfiles_dir.join(relative).is_file(),
+            "No such file: {}",
+            file
+        );
+        Ok(relative.to_string_lossy().into_owned())
+    }
+
+    pub fn handle(&mut self, request: Request) -> Result<Response> {
+        self.requests += 1;
+        match request {
+            Request::CheckFile { file } => {
+                let mut config = self.config.clone();
+                config.only_files = Some(vec![self.repo_relative(&file)?]);
+                Ok(Response::results(Validator::new(config).run_rules()?))
+            }
+            Request::CheckAll => {
+                let fingerprint = repo_fingerprint(&self.config)?;
+                if let Some((last, results)) = &self.last_run
+                    && *last == fingerprint
+                {
+                    return Ok(Response {
+                        cached: Some(true),
+                        ..Response::results(results.clone())
+                    });
+                }
+                let results = Validator::new(self.config.clone()).run_rules()?;
+                if let Some(cache) = &self.config.cache {
+                    cache.save(true)?;
+                }
+                self.last_run = Some((fingerprint, results.clone()));
+                Ok(Response {
+                    cached: Some(false),
+                    ..Response::results(results)
+                })
+            }
+            Request::Status => {
+                let last = self.last_run.as_ref().map(|(_, results)| results);
+                Ok(Response {
+                    ok: true,
+                    status: Some(Status {
+                        root: self.config.dotfiles_dir.clone(),
+                        pid: std::process::id(),
+                        uptime_ms: self.started.elapsed().as_millis() as u64,
+                        requests: self.requests,
+                        errors: last.map(|r| count(r, Severity::Error)),
+                        warnings: last.map(|r| count(r, Severity::Warning)),
+                    }),
+                    ..Response::default()
+                })
+            }
+        }
+    }
+}
+
+// A connection can send any number of requests, one per line, so an editor
+// can keep one open for the whole session
+fn serve_connection(daemon: &Mutex<Daemon>, stream: UnixStream) -> Result<()> {
+    let reader = BufReader::new(stream.try_clone()?);
+    let mut writer = stream;
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str::<Request>(&line) {
+            Ok(request) => {
+                // A rule that panicked shouldn't take the daemon down with it
+                let mut daemon = daemon.lock().unwrap_or_else(|e| e.into_inner());
+                daemon.handle(request).unwrap_or_else(Response::error)
+            }
+            Err(err) => Response::error(anyhow::anyhow!("Invalid request: {}", err)),
+        };
+        serde_json::to_writer(&mut writer, &response)?;
+        writer.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+pub fn accept_loop(listener: UnixListener, daemon: Daemon) -> Result<()> {
+    let daemon = Arc::new(Mutex::new(daemon));
+    for stream in listener.incoming() {
+        let stream = stream.context("Failed to accept a connection")?;
+        let daemon = Arc::clone(&daemon);
+        thread::spawn(move || {
+            if let Err(err) = serve_connection(&daemon, stream) {
+                debug!(error = %err, "connection closed");
+            }
+        });
+    }
+    Ok(())
+}
+
+// A socket file nobody is listening on is left over from a daemon that
+// didn't shut down cleanly
+fn bind(socket: &Path) -> Result<UnixListener> {
+    if socket.exists() {
+        if UnixStream::connect(socket).is_ok() {
+            anyhow::bail!("A daemon is already listening on {}", socket.display());
+        }
+        fs::remove_file(socket)
+            .with_context(|| format!("Failed to remove stale socket {}", socket.display()))?;
+    }
+    if let Some(parent) = socket.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    UnixListener::bind(socket).with_context(|| format!("Failed to listen on {}", socket.display()))
+}
+
+pub fn serve(config: Config, socket: Option<&Path>) -> Result<i32> {
+    check_environment(&config)?;
+    let socket = match socket {
+        Some(path) => path.to_path_buf(),
+        None => state_path(&config, SOCKET_FILE)?,
+    };
+    let chatty = config.output != OutputMode::Quiet;
+    let daemon = Daemon::new(config)?;
+    let listener = bind(&socket)?;
+    if chatty {
+        info(&format!("Listening on {}", socket.display()));
+    }
+    accept_loop(listener, daemon)?;
+    Ok(ExitCode::CLEAN)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::FixtureRepo;
+
+    fn send(stream: &mut UnixStream, request: &str) -> serde_json::Value {
+        stream
+            .write_all(format!("{}\n", request).as_bytes())
+            .unwrap();
+        let mut line = String::new();
+        BufReader::new(stream.try_clone().unwrap())
+            .read_line(&mut line)
+            .unwrap();
+        serde_json::from_str(&line).unwrap()
+    }
+
+    #[test]
+    fn answers_requests_over_the_socket() {
+        let repo = FixtureRepo::new();
+        repo.write("good.toml", "a = 1\n")
+            .write("bad.toml", "a = \n")
+            .commit("fixture");
+        let socket = repo.path().join(".git/test.sock");
+        let listener = bind(&socket).unwrap();
+        let daemon = Daemon::new(repo.config()).unwrap();
+        thread::spawn(move || accept_loop(listener, daemon));
+        let mut stream = UnixStream::connect(&socket).unwrap();
+
+        let response = send(
+            &mut stream,
+            r#"{"command": "check-file", "file": "bad.toml"}"#,
+        );
+        assert_eq!(response["passed"], false);
+        let rules: Vec<_> = response["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["rule_id"].as_str().unwrap())
+            .collect();
+        assert!(rules.contains(&"toml-syntax"));
+        assert!(!rules.contains(&"dotter-files"));
+
+        let response = send(
+            &mut stream,
+            r#"{"command": "check-file", "file": "good.toml"}"#,
+        );
+        assert_eq!(response["passed"], true);
+
+        assert_eq!(
+            send(&mut stream, r#"{"command": "check-all"}"#)["cached"],
+            false
+        );
+        assert_eq!(
+            send(&mut stream, r#"{"command": "check-all"}"#)["cached"],
+            true
+        );
+
+        let response = send(&mut stream, r#"{"command": "status"}"#);
+        assert_eq!(response["status"]["requests"], 5);
+        // bad.toml, plus the fixture's missing dotter config
+        assert_eq!(response["status"]["errors"], 2);
+
+        let response = send(
+            &mut stream,
+            r#"{"command": "check-file", "file": "missing"}"#,
+        );
+        assert_eq!(response["ok"], false);
+        assert!(
+            bind(&socket).is_err(),
+            "a second daemon should refuse to start"
+        );
+    }
+}
@@ -0,0 +1,73 @@
+//! Deployment through dotter, gated on a clean validation run.
+
+use anyhow::{Context, Result};
+
+use std::process::Command;
+
+use crate::{
+    backup::create_snapshot,
+    dotter::deployed_files,
+    output::{failure, info, success},
+    repo::{expand_home, find_executable},
+    types::{Config, ExitCode},
+    validator::validate,
+};
+
+pub struct DeployOptions {
+    pub dry_run: bool,
+    pub force: bool,
+    pub validate: bool,
+    pub dotter_args: Vec<String>,
+}
+
+pub fn deploy(config: Config, options: &DeployOptions) -> Result<i32> {
+    if find_executable("dotter").is_none() {
+        anyhow::bail!("dotter was not found on PATH");
+    }
+    if options.validate {
+        let code = validate(config.clone())?;
+        if code == ExitCode::ERRORS {
+            failure(
+                "Not deploying while validation reports errors (use --no-validate to override)",
+            );
+            return Ok(code);
+        }
+    }
+
+    if !options.dry_run {
+        let targets: Vec<_> = deployed_files(&config)?
+            .iter()
+            .map(|f| expand_home(&f.target))
+            .collect();
+        let snapshot = create_snapshot(&config, "deploy", &targets)?;
+        info(&format!(
+            "Backed up {} deploy target(s); undo with: dotfiles-tools restore {}",
+            snapshot.entries.len(),
+            snapshot.id
+        ));
+    }
+
+    let mut command = Command::new("dotter");
+    command.args(["deploy", "--noconfirm"]);
+    if options.dry_run {
+        command.arg("--dry-run");
+    }
+    if options.force {
+        command.arg("--force");
+    }
+    let status = command
+        .args(&options.dotter_args)
+        .current_dir(&config.dotfiles_dir)
+        .status()
+        .context("Failed to run dotter deploy")?;
+    if !status.success() {
+        anyhow::bail!("dotter deploy failed ({})", status);
+    }
+
+    success(if options.dry_run {
+        "Dry run complete"
+    } else {
+        "Dotfiles deployed"
+    });
+    Ok(ExitCode::CLEAN)
+}
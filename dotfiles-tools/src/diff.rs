@@ -0,0 +1,160 @@
+//! Line-based unified diffs, for showing how a deployed file differs from
+//! what the repo would put there.
+
+use crate::output::Color;
+
+// Lines of unchanged context around each hunk, as in `diff -u`
+const CONTEXT: usize = 3;
+
+// Beyond this many line pairs the LCS table gets too big to be worth it
+const MAX_CELLS: usize = 4_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Same(usize, usize),
+    Removed(usize),
+    Added(usize),
+}
+
+// Longest-common-subsequence edit script from `old` to `new`
+fn edit_script(old: &[&str], new: &[&str]) -> Vec<Op> {
+    let (n, m) = (old.len(), new.len());
+    let mut lcs = vec![0u32; (n + 1) * (m + 1)];
+    let at = |i: usize, j: usize| i * (m + 1) + j;
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[at(i, j)] = if old[i] == new[j] {
+                lcs[at(i + 1, j + 1)] + 1
+            } else {
+                lcs[at(i + 1, j)].max(lcs[at(i, j + 1)])
+            };
+        }
+    }
+
+    let mut ops = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && old[i] == new[j] {
+            ops.push(Op::Same(i, j));
+            i += 1;
+            j += 1;
+        } else if i < n && (j == m || lcs[at(i + 1, j)] >= lcs[at(i, j + 1)]) {
+            ops.push(Op::Removed(i));
+            i += 1;
+        } else {
+            ops.push(Op::Added(j));
+            j += 1;
+        }
+    }
+    ops
+}
+
+/// A unified diff from `old` to `new` with `diff -u`'s headers and three
+/// lines of context, or `None` when they're the same or too large to diff.
+pub fn unified_diff(old: &str, new: &str, old_label: &str, new_label: &str) -> Option<String> {
+    let old_lines: Vec<_> = old.lines().collect();
+    let new_lines: Vec<_> = new.lines().collect();
+    if old_lines == new_lines
+        || (old_lines.len() + 1).saturating_mul(new_lines.len() + 1) > MAX_CELLS
+    {
+        return None;
+    }
+    let ops = edit_script(&old_lines, &new_lines);
+
+    // Group changes into hunks, merging those whose context would overlap
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for k in (0..ops.len()).filter(|&k| !matches!(ops[k], Op::Same(..))) {
+        let start = k.saturating_sub(CONTEXT);
+        let end = (k + CONTEXT + 1).min(ops.len());
+        match hunks.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    let mut out = format!("--- {}\n+++ {}\n", old_label, new_label);
+    for (start, end) in hunks {
+        // Lines of each side before the hunk; an empty side is numbered by
+        // the line it comes after, as `diff -u` does
+        let old_before = ops[..start]
+            .iter()
+            .filter(|op| !matches!(op, Op::Added(_)))
+            .count();
+        let new_before = ops[..start]
+            .iter()
+            .filter(|op| !matches!(op, Op::Removed(_)))
+            .count();
+        let ops = &ops[start..end];
+        let old_count = ops.iter().filter(|op| !matches!(op, Op::Added(_))).count();
+        let new_count = ops
+            .iter()
+            .filter(|op| !matches!(op, Op::Removed(_)))
+            .count();
+        let range = |before: usize, count: usize| {
+            format!("{},{}", if count == 0 { before } else { before + 1 }, count)
+        };
+        out.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            range(old_before, old_count),
+            range(new_before, new_count)
+        ));
+        for op in ops {
+            let (mark, line) = match *op {
+                Op::Same(i, _) => (' ', old_lines[i]),
+                Op::Removed(i) => ('-', old_lines[i]),
+                Op::Added(j) => ('+', new_lines[j]),
+            };
+            out.push(mark);
+            out.push_str(line);
+            out.push('\n');
+        }
+    }
+    Some(out)
+}
+
+/// A unified diff's lines with additions green, removals red, and hunk
+/// headers cyan.
+pub fn colorize(diff: &str) -> Vec<String> {
+    diff.lines()
+        .map(|line| {
+            let color = if line.starts_with("+++") || line.starts_with("---") {
+                Color::bold()
+            } else if line.starts_with('+') {
+                Color::green()
+            } else if line.starts_with('-') {
+                Color::red()
+            } else if line.starts_with("@@") {
+                Color::cyan()
+            } else {
+                return line.to_string();
+            };
+            format!("{}{}{}", color, line, Color::reset())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hunks_carry_three_lines_of_context() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\nm\n";
+        let new = "a\nB\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\nm\nn\n";
+        assert_eq!(
+            unified_diff(old, new, "repo", "deployed").unwrap(),
+            "--- repo\n+++ deployed\n\
+             @@ -1,5 +1,5 @@\n a\n-b\n+B\n c\n d\n e\n\
+             @@ -11,3 +11,4 @@\n k\n l\n m\n+n\n"
+        );
+        assert_eq!(unified_diff(old, old, "a", "b"), None);
+    }
+
+    #[test]
+    fn additions_to_an_empty_file_start_at_zero() {
+        assert_eq!(
+            unified_diff("", "x\n", "a", "b").unwrap(),
+            "--- a\n+++ b\n@@ -0,0 +1,1 @@\n+x\n"
+        );
+    }
+}
@@ -0,0 +1,451 @@
+//! The `doctor` command: external tools the configs expect to exist.
+
+use anyhow::Result;
+use regex::Regex;
+use serde::Deserialize;
+
+use std::{
+    collections::{BTreeMap, HashSet},
+    env,
+    path::{Path, PathBuf},
+    sync::LazyLock,
+};
+
+use crate::{
+    gitconfig::{GitConfigEntry, builtin_tool_program, is_gitconfig, parse_gitconfig},
+    output::{Color, failure, success, warning},
+    repo::{expand_home, find_executable, get_tracked_files, read_file_content},
+    rules::{
+        cargo::{CargoConfig, is_cargo_config},
+        direnv::is_envrc,
+        syntax::strip_json_comments,
+        tmux::is_tmux_config,
+    },
+    shell::{is_shell_config, shell_words},
+    snippet::{line_col, line_of},
+    types::{Config, ExitCode},
+};
+
+#[derive(Debug, Clone)]
+pub struct ToolReference {
+    pub command: String,
+    pub file: String,
+    pub line: Option<usize>,
+    pub kind: &'static str,
+}
+
+// Builtins and wrappers that show up in alias expansions but never need a
+// binary of their own
+pub const SHELL_BUILTINS: &[&str] = &[
+    ".", ":", "[", "alias", "bg", "builtin", "cd", "command", "echo", "eval", "exec", "exit",
+    "export", "false", "fg", "hide", "history", "jobs", "overlay", "popd", "printf", "pushd",
+    "pwd", "read", "set", "source", "test", "true", "type", "ulimit", "unset", "use", "wait",
+];
+
+pub const COMMAND_PREFIXES: &[&str] = &["sudo", "env", "noglob", "nocorrect", "command", "builtin"];
+
+// The binary an expansion like `sudo FOO=1 ^bat -p` actually runs
+pub fn expansion_command(expansion: &str) -> Option<String> {
+    shell_words(expansion)
+        .into_iter()
+        .map(|w| w.trim_start_matches('^').to_string())
+        .find(|w| !w.contains('=') && !COMMAND_PREFIXES.contains(&w.as_str()))
+        .filter(|w| !w.is_empty() && !w.contains(['$', '(', '{', '`', ';', '|']))
+}
+
+pub fn collect_shell_references(file: &str, content: &str, refs: &mut Vec<ToolReference>) {
+    static RE_POSIX_ALIAS: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"^\s*alias\s+(?:-g\s+)?[^\s=]+=(.+)$").unwrap());
+    static RE_FISH_ALIAS: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"^\s*alias\s+[^\s=]+\s+(.+)$").unwrap());
+    static RE_NU_ALIAS: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"^\s*(?:export\s+)?alias\s+\S+\s*=\s*(.+)$").unwrap());
+    static RE_GUARD: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(
+            r"(?:command\s+-[vq]|type\s+-q|which)(?:\s+-\w+)*\s+([A-Za-z0-9_.+][A-Za-z0-9_.+-]*)",
+        )
+        .unwrap()
+    });
+
+    for (idx, line) in content.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with('#') {
+            continue;
+        }
+
+        let mut push = |command: String, kind| {
+            refs.push(ToolReference {
+                command,
+                file: file.to_string(),
+                line: Some(idx + 1),
+                kind,
+            });
+        };
+
+        let alias_expansion = if file.ends_with(".nu") {
+            RE_NU_ALIAS.captures(line)
+        } else {
+            RE_POSIX_ALIAS
+                .captures(line)
+                .or_else(|| RE_FISH_ALIAS.captures(line))
+        };
+        if let Some(caps) = alias_expansion {
+            // Quoted expansions hold the whole command in one word
+            let words = shell_words(&caps[1]);
+            if let Some(command) = words.first().and_then(|w| expansion_command(w)) {
+                push(command, "alias");
+            }
+        }
+
+        if trimmed.starts_with("abbr ") {
+            let words = shell_words(trimmed);
+            if !words
+                .iter()
+                .any(|w| w == "-f" || w.starts_with("--function"))
+            {
+                let mut positional = Vec::new();
+                let mut iter = words[1..].iter();
+                while let Some(word) = iter.next() {
+                    if matches!(
+                        word.as_str(),
+                        "-p" | "--position" | "-r" | "--regex" | "-c" | "--command"
+                    ) {
+                        iter.next();
+                    } else if !word.starts_with('-') {
+                        positional.push(word);
+                    }
+                }
+                // positional[0] is the abbreviation itself
+                if let Some(command) = positional.get(1).and_then(|w| expansion_command(w)) {
+                    push(command, "abbr");
+                }
+            }
+        }
+
+        for caps in RE_GUARD.captures_iter(line) {
+            push(caps[1].to_string(), "guard");
+        }
+    }
+}
+
+// The parts of Helix's languages.toml that name a command, with where each
+// command is written
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct HelixLanguages {
+    language: Vec<HelixLanguage>,
+    #[serde(rename = "language-server")]
+    language_server: BTreeMap<String, HelixCommand>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct HelixLanguage {
+    formatter: Option<HelixCommand>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct HelixCommand {
+    command: Option<toml::Spanned<toml::Value>>,
+}
+
+pub fn collect_helix_references(file: &str, content: &str, refs: &mut Vec<ToolReference>) {
+    let Ok(doc) = toml::from_str::<HelixLanguages>(content) else {
+        return;
+    };
+    let mut push = |command: &HelixCommand, kind| {
+        let Some(spanned) = &command.command else {
+            return;
+        };
+        let Some(command) = spanned.get_ref().as_str() else {
+            return;
+        };
+        // Languages often share a formatter; one report is enough
+        if refs
+            .iter()
+            .any(|r| r.file == file && r.command == command && r.kind == kind)
+        {
+            return;
+        }
+        refs.push(ToolReference {
+            command: command.to_string(),
+            file: file.to_string(),
+            line: Some(line_col(content, spanned.span().start).0),
+            kind,
+        });
+    };
+
+    for formatter in doc.language.iter().filter_map(|l| l.formatter.as_ref()) {
+        push(formatter, "formatter");
+    }
+    for server in doc.language_server.values() {
+        push(server, "language server");
+    }
+}
+
+pub fn collect_cargo_references(file: &str, content: &str, refs: &mut Vec<ToolReference>) {
+    let Ok(cargo) = toml::from_str::<CargoConfig>(content) else {
+        return;
+    };
+    for linker in cargo.target.values().filter_map(|t| t.linker.as_deref()) {
+        // Cargo resolves relative paths against the directory holding
+        // .cargo, which is home for the deployed global config
+        let command = if linker.contains('/') && !linker.starts_with(['/', '~']) {
+            format!("~/{}", linker)
+        } else {
+            linker.to_string()
+        };
+        refs.push(ToolReference {
+            command,
+            file: file.to_string(),
+            line: line_of(content, &format!("\"{}\"", linker)),
+            kind: "cargo linker",
+        });
+    }
+}
+
+pub fn collect_zed_references(file: &str, content: &str, refs: &mut Vec<ToolReference>) {
+    let Ok(doc) = serde_json::from_str::<serde_json::Value>(&strip_json_comments(content)) else {
+        return;
+    };
+
+    pub fn walk(value: &serde_json::Value, file: &str, refs: &mut Vec<ToolReference>) {
+        match value {
+            serde_json::Value::Object(map) => {
+                for (key, child) in map {
+                    let command = match key.as_str() {
+                        "external" => child.get("command").and_then(|c| c.as_str()),
+                        "binary" => child.get("path").and_then(|c| c.as_str()),
+                        _ => None,
+                    };
+                    if let Some(command) = command {
+                        refs.push(ToolReference {
+                            command: command.to_string(),
+                            file: file.to_string(),
+                            line: None,
+                            kind: if key == "external" {
+                                "formatter"
+                            } else {
+                                "language server"
+                            },
+                        });
+                    }
+                    walk(child, file, refs);
+                }
+            }
+            serde_json::Value::Array(items) => {
+                for item in items {
+                    walk(item, file, refs);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    walk(&doc, file, refs);
+}
+
+pub fn collect_tmux_references(file: &str, content: &str, refs: &mut Vec<ToolReference>) {
+    static RE_PLUGIN: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r#"^\s*set(?:-option)?\s+-g\s+@plugin\s+['"]?([^'"\s]+)"#).unwrap()
+    });
+
+    for (idx, line) in content.lines().enumerate() {
+        if let Some(caps) = RE_PLUGIN.captures(line) {
+            refs.push(ToolReference {
+                command: caps[1].to_string(),
+                file: file.to_string(),
+                line: Some(idx + 1),
+                kind: "tmux plugin",
+            });
+        }
+    }
+}
+
+pub fn collect_gitconfig_references(
+    file: &str,
+    entries: &[GitConfigEntry],
+    refs: &mut Vec<ToolReference>,
+) {
+    for entry in entries {
+        let (command, kind) = match (entry.section.as_str(), entry.key.as_str()) {
+            // `!cmd ...` aliases run through the shell; anything else is a
+            // git subcommand
+            ("alias", _) => (
+                entry.value.strip_prefix('!').and_then(expansion_command),
+                "git alias",
+            ),
+            // pager.<cmd> can also be a boolean that turns paging on or off
+            ("core", "pager") | ("pager", _) | ("interactive", "difffilter") => (
+                Some(&entry.value)
+                    .filter(|v| !matches!(v.as_str(), "true" | "false" | "yes" | "no" | "1" | "0"))
+                    .and_then(|v| expansion_command(v)),
+                "git pager",
+            ),
+            ("diff", "external") | ("difftool" | "mergetool", "cmd" | "path") => {
+                (expansion_command(&entry.value), "git diff tool")
+            }
+            ("diff" | "merge", "tool" | "guitool") => (
+                builtin_tool_program(&entry.value).map(String::from),
+                "git diff tool",
+            ),
+            _ => continue,
+        };
+        if let Some(command) = command.filter(|c| !SHELL_BUILTINS.contains(&c.as_str())) {
+            refs.push(ToolReference {
+                command,
+                file: file.to_string(),
+                line: Some(entry.line),
+                kind,
+            });
+        }
+    }
+}
+
+// Every external command or plugin the tracked configs expect to exist on
+// this machine
+pub fn collect_tool_references(config: &Config) -> Result<Vec<ToolReference>> {
+    let tracked = get_tracked_files(config)?;
+    let mut refs = Vec::new();
+
+    for file in &tracked {
+        let name = Path::new(file)
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default();
+        let is_helix = file.contains("helix/") && name == "languages.toml";
+        let is_zed = file.contains("zed/") && name == "settings.json";
+        let is_tmux = is_tmux_config(file);
+        let is_git = is_gitconfig(file);
+        let is_cargo = is_cargo_config(file);
+        if !(is_shell_config(file) || is_helix || is_zed || is_tmux || is_git || is_cargo) {
+            continue;
+        }
+
+        let Some(content) = read_file_content(config, file) else {
+            continue;
+        };
+        if is_helix {
+            collect_helix_references(file, &content, &mut refs);
+        } else if is_zed {
+            collect_zed_references(file, &content, &mut refs);
+        } else if is_cargo {
+            collect_cargo_references(file, &content, &mut refs);
+        } else if is_git {
+            collect_gitconfig_references(file, &parse_gitconfig(&content), &mut refs);
+        } else if is_tmux {
+            collect_tmux_references(file, &content, &mut refs);
+            collect_shell_references(file, &content, &mut refs);
+        } else {
+            collect_shell_references(file, &content, &mut refs);
+            // An .envrc is inert without direnv to load it
+            if is_envrc(file) {
+                refs.push(ToolReference {
+                    command: "direnv".to_string(),
+                    file: file.to_string(),
+                    line: None,
+                    kind: "direnv",
+                });
+            }
+        }
+    }
+
+    // Aliases often point at other aliases or shell functions defined in the
+    // same configs, which aren't binaries at all
+    let defined = collect_shell_definitions(config, &tracked);
+    refs.retain(|r| !SHELL_BUILTINS.contains(&r.command.as_str()) && !defined.contains(&r.command));
+
+    Ok(refs)
+}
+
+pub fn collect_shell_definitions(config: &Config, tracked: &[String]) -> HashSet<String> {
+    static RE_DEFINITION: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(
+            r#"^\s*(?:export\s+)?(?:alias\s+(?:-g\s+)?([^\s=]+)|function\s+([^\s({]+)|def(?:\s+--\w+)*\s+['"]?([^\s'"]+)|abbr\s+(?:-\S+\s+)*([^\s=]+)|([\w-]+)\s*\(\)\s*\{)"#,
+        )
+        .unwrap()
+    });
+
+    let mut defined = HashSet::new();
+    for file in tracked.iter().filter(|f| is_shell_config(f)) {
+        let Some(content) = read_file_content(config, file) else {
+            continue;
+        };
+        for caps in content.lines().filter_map(|l| RE_DEFINITION.captures(l)) {
+            if let Some(name) = caps.iter().skip(1).flatten().next() {
+                defined.insert(name.as_str().to_string());
+            }
+        }
+    }
+    defined
+}
+
+pub fn tool_is_available(reference: &ToolReference) -> bool {
+    if reference.kind == "tmux plugin" {
+        let plugins_dir = env::var_os("TMUX_PLUGIN_MANAGER_PATH")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| expand_home("~/.tmux/plugins"));
+        let name = reference.command.rsplit('/').next().unwrap_or_default();
+        return plugins_dir.join(name).exists();
+    }
+
+    if reference.command.contains('/') {
+        return expand_home(&reference.command).exists();
+    }
+
+    find_executable(&reference.command).is_some()
+}
+
+pub fn doctor(config: &Config) -> Result<i32> {
+    let refs = collect_tool_references(config)?;
+
+    let mut missing: BTreeMap<&str, BTreeMap<&str, Vec<&ToolReference>>> = BTreeMap::new();
+    let mut checked = HashSet::new();
+    let mut missing_tools = HashSet::new();
+    for reference in &refs {
+        checked.insert(reference.command.as_str());
+        if !tool_is_available(reference) {
+            missing_tools.insert(reference.command.as_str());
+            missing
+                .entry(reference.file.as_str())
+                .or_default()
+                .entry(reference.command.as_str())
+                .or_default()
+                .push(reference);
+        }
+    }
+
+    for (file, commands) in &missing {
+        failure(file);
+        for (command, refs) in commands {
+            let lines: Vec<_> = refs
+                .iter()
+                .filter_map(|r| r.line.map(|l| l.to_string()))
+                .collect();
+            let location = if lines.is_empty() {
+                String::new()
+            } else {
+                format!(" (line {})", lines.join(", "))
+            };
+            warning(&format!("  {} [{}]{}", command, refs[0].kind, location));
+        }
+    }
+
+    println!("\n{}{}{}", Color::bold(), "=".repeat(60), Color::reset());
+    if missing_tools.is_empty() {
+        success(&format!(
+            "All {} referenced tools are available",
+            checked.len()
+        ));
+        Ok(ExitCode::CLEAN)
+    } else {
+        failure(&format!(
+            "{} of {} referenced tools missing across {} config(s)",
+            missing_tools.len(),
+            checked.len(),
+            missing.len()
+        ));
+        Ok(ExitCode::ERRORS)
+    }
+}
@@ -0,0 +1,334 @@
+//! Reading dotter's configuration: which files get deployed where.
+
+use anyhow::{Context, Result};
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{settings::ProfileSettings, types::Config};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeployKind {
+    Symbolic,
+    Template,
+}
+
+#[derive(Debug, Clone)]
+pub struct DotterFile {
+    pub package: String,
+    pub source: String,
+    pub target: String,
+    pub kind: DeployKind,
+}
+
+// local.toml selects packages for one machine and cache.toml is dotter's own
+// bookkeeping; every other TOML in .dotter/ describes a platform
+const NON_PLATFORM_CONFIGS: &[&str] = &["global", "local", "cache"];
+
+/// The platform configs in .dotter/ (macos, linux, wsl, ...), by name.
+pub fn platforms(config: &Config) -> Result<Vec<String>> {
+    let dotter_dir = config.dotfiles_dir.join(".dotter");
+    let Ok(entries) = fs::read_dir(&dotter_dir) else {
+        return Ok(Vec::new());
+    };
+    let mut platforms: Vec<_> = entries
+        .flatten()
+        .filter_map(|e| {
+            let path = e.path();
+            if path.extension()? != "toml" {
+                return None;
+            }
+            let name = path.file_stem()?.to_str()?.to_string();
+            (!NON_PLATFORM_CONFIGS.contains(&name.as_str())).then_some(name)
+        })
+        .collect();
+    platforms.sort();
+    Ok(platforms)
+}
+
+/// global.toml plus the platform configs to check: all of them, or only the
+/// one selected with --platform.
+pub fn platform_config_files(config: &Config) -> Result<Vec<PathBuf>> {
+    let dotter_dir = config.dotfiles_dir.join(".dotter");
+    let available = platforms(config)?;
+    let selected = match &config.platform {
+        Some(platform) if !available.contains(platform) => anyhow::bail!(
+            "Unknown platform '{}' (found in .dotter/: {})",
+            platform,
+            available.join(", ")
+        ),
+        Some(platform) => vec![platform.clone()],
+        None => available,
+    };
+
+    let mut files = vec![dotter_dir.join("global.toml")];
+    files.extend(
+        selected
+            .iter()
+            .map(|p| dotter_dir.join(format!("{}.toml", p))),
+    );
+    files.retain(|f| f.exists());
+    Ok(files)
+}
+
+/// The dotter config that declares a package's files: the first of
+/// global.toml and the platform configs with a `[package.files]` table, or
+/// global.toml for a package that doesn't exist yet.
+pub fn package_config_file(config: &Config, package: &str) -> Result<PathBuf> {
+    for path in platform_config_files(config)? {
+        let doc = read_toml(&path)?;
+        if doc.get(package).and_then(|p| p.get("files")).is_some() {
+            return Ok(path);
+        }
+    }
+    Ok(config.dotfiles_dir.join(".dotter/global.toml"))
+}
+
+/// The packages local.toml selects for this machine.
+pub fn selected_packages(config: &Config) -> Result<Vec<String>> {
+    let local = read_toml(&config.dotfiles_dir.join(".dotter/local.toml"))?;
+    Ok(string_list(&local, "packages"))
+}
+
+/// The `~/x` spelling of a target or reference written as `$HOME/x` or
+/// `${HOME}/x`, so the two compare as strings.
+pub fn tilde_path(path: &str) -> String {
+    let path = path.trim_matches(['"', '\'']);
+    for prefix in ["$HOME", "${HOME}"] {
+        if let Some(rest) = path.strip_prefix(prefix) {
+            return format!("~{}", rest);
+        }
+    }
+    path.to_string()
+}
+
+/// The repo source dotter deploys to `path`: a file target itself, or
+/// something under a directory target.
+pub fn source_for_target(files: &[DotterFile], path: &str) -> Option<String> {
+    let path = tilde_path(path);
+    let path = path.trim_end_matches('/');
+    files.iter().find_map(|file| {
+        let target = tilde_path(&file.target);
+        let target = target.trim_end_matches('/');
+        if path == target {
+            return Some(file.source.clone());
+        }
+        path.strip_prefix(&format!("{}/", target))
+            .map(|rest| format!("{}/{}", file.source, rest))
+    })
+}
+
+/// Where dotter deploys a repo file, including a file inside a deployed
+/// directory.
+pub fn target_for_source(files: &[DotterFile], source: &str) -> Option<String> {
+    files.iter().find_map(|file| {
+        if source == file.source {
+            return Some(tilde_path(&file.target));
+        }
+        source
+            .strip_prefix(&format!("{}/", file.source))
+            .map(|rest| format!("{}/{}", tilde_path(&file.target), rest))
+    })
+}
+
+fn read_toml(path: &Path) -> Result<toml::Table> {
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    toml::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+fn string_list(table: &toml::Table, key: &str) -> Vec<String> {
+    table
+        .get(key)
+        .and_then(|v| v.as_array())
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|v| v.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// Dotter treats a file as a template when it's marked as one, or when an
+// "automatic" file contains handlebars syntax
+fn deploy_kind(config: &Config, source: &str, declared: Option<&str>) -> DeployKind {
+    match declared {
+        Some("template") => DeployKind::Template,
+        Some("symbolic") => DeployKind::Symbolic,
+        _ => {
+            let is_template = fs::read_to_string(config.dotfiles_dir.join(source))
+                .is_ok_and(|content| content.contains("{{"));
+            if is_template {
+                DeployKind::Template
+            } else {
+                DeployKind::Symbolic
+            }
+        }
+    }
+}
+
+// Merges one package's [files] table into `files`, keyed by source
+fn merge_package_files(
+    config: &Config,
+    doc: &toml::Table,
+    package: &str,
+    files: &mut BTreeMap<String, DotterFile>,
+) {
+    let Some(entries) = doc
+        .get(package)
+        .and_then(|p| p.get("files"))
+        .and_then(|f| f.as_table())
+    else {
+        return;
+    };
+    for (source, value) in entries {
+        let (target, declared) = match value {
+            toml::Value::String(target) => (target.clone(), None),
+            toml::Value::Table(table) => (
+                table
+                    .get("target")
+                    .and_then(|t| t.as_str())
+                    .unwrap_or_default()
+                    .to_string(),
+                table.get("type").and_then(|t| t.as_str()),
+            ),
+            _ => continue,
+        };
+        // An empty target is how dotter disables an inherited file
+        if target.is_empty() {
+            files.remove(source);
+            continue;
+        }
+        files.insert(
+            source.clone(),
+            DotterFile {
+                package: package.to_string(),
+                source: source.clone(),
+                target,
+                kind: deploy_kind(config, source, declared),
+            },
+        );
+    }
+}
+
+/// This machine's selection in local.toml, shaped like a profile, or `None`
+/// when there's no local.toml.
+pub fn local_profile(config: &Config) -> Result<Option<ProfileSettings>> {
+    let local_path = config.dotfiles_dir.join(".dotter/local.toml");
+    if !local_path.exists() {
+        return Ok(None);
+    }
+    let local = read_toml(&local_path)?;
+    Ok(Some(ProfileSettings {
+        packages: string_list(&local, "packages"),
+        includes: string_list(&local, "includes"),
+        variables: local
+            .get("variables")
+            .and_then(|v| v.as_table())
+            .cloned()
+            .unwrap_or_default(),
+    }))
+}
+
+/// The files dotter deploys on this machine: the packages selected in
+/// local.toml, looked up in global.toml and anything local.toml includes.
+/// Later files override earlier ones for the same source, as in dotter.
+pub fn deployed_files(config: &Config) -> Result<Vec<DotterFile>> {
+    let Some(local) = local_profile(config)? else {
+        anyhow::bail!(
+            "{} not found; dotter doesn't know which packages to deploy",
+            config.dotfiles_dir.join(".dotter/local.toml").display()
+        );
+    };
+    selected_files(config, &local.packages, &local.includes)
+}
+
+// global.toml followed by the included configs, in override order
+fn selection_configs(config: &Config, includes: &[String]) -> Result<Vec<toml::Table>> {
+    let mut configs = vec![read_toml(&config.dotfiles_dir.join(".dotter/global.toml"))?];
+    for include in includes {
+        configs.push(read_toml(&config.dotfiles_dir.join(include))?);
+    }
+    Ok(configs)
+}
+
+/// The files a set of packages deploys, as `deployed_files` does for the
+/// selection in local.toml.
+pub fn selected_files(
+    config: &Config,
+    packages: &[String],
+    includes: &[String],
+) -> Result<Vec<DotterFile>> {
+    let mut files = BTreeMap::new();
+    for doc in &selection_configs(config, includes)? {
+        for package in packages {
+            merge_package_files(config, doc, package, &mut files);
+        }
+    }
+    Ok(files.into_values().collect())
+}
+
+/// The template variables a set of packages defines in their
+/// `[package.variables]` tables, later configs overriding earlier ones.
+pub fn selected_variables(
+    config: &Config,
+    packages: &[String],
+    includes: &[String],
+) -> Result<toml::Table> {
+    let mut variables = toml::Table::new();
+    for doc in &selection_configs(config, includes)? {
+        for package in packages {
+            if let Some(table) = doc
+                .get(package)
+                .and_then(|p| p.get("variables"))
+                .and_then(|v| v.as_table())
+            {
+                variables.extend(table.clone());
+            }
+        }
+    }
+    Ok(variables)
+}
+
+/// The packages global.toml and the included configs define, and the custom
+/// helpers global.toml registers.
+pub fn defined_packages_and_helpers(
+    config: &Config,
+    includes: &[String],
+) -> Result<(BTreeSet<String>, Vec<String>)> {
+    let configs = selection_configs(config, includes)?;
+    let helpers = configs[0]
+        .get("helpers")
+        .and_then(|h| h.as_table())
+        .map(|h| h.keys().cloned().collect())
+        .unwrap_or_default();
+    let packages = configs
+        .iter()
+        .flat_map(|doc| doc.iter())
+        .filter(|(name, value)| {
+            value.is_table() && !matches!(name.as_str(), "helpers" | "settings")
+        })
+        .map(|(name, _)| name.clone())
+        .collect();
+    Ok((packages, helpers))
+}
+
+/// Every file mapping in global.toml and the platform configs, regardless of
+/// which packages this machine selects. The same source can appear once per
+/// platform with different targets.
+pub fn mapped_files(config: &Config) -> Result<Vec<DotterFile>> {
+    let mut mapped = Vec::new();
+    for path in platform_config_files(config)? {
+        let doc = read_toml(&path)?;
+        let mut files = BTreeMap::new();
+        for package in doc.keys() {
+            merge_package_files(config, &doc, package, &mut files);
+        }
+        mapped.extend(files.into_values());
+    }
+    Ok(mapped)
+}
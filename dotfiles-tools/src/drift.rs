@@ -0,0 +1,411 @@
+//! Drift detection: deployed files that no longer match the repository.
+
+use anyhow::{Context, Result};
+use tracing::trace_span;
+
+use std::{
+    collections::BTreeSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    diff::unified_diff,
+    dotter::{DeployKind, DotterFile, deployed_files},
+    fixes::shell_quote_path,
+    output::{Color, info, success},
+    paths::{is_link, normalize_path},
+    repo::{expand_home, home_dir, looks_binary},
+    types::{Config, ExitCode, Issue, Severity, ValidationResult},
+    validator::Validator,
+};
+
+/// What was changed on the machine, as a diff from what the repo would
+/// deploy; `None` for binary files.
+pub fn content_diff(expected: &[u8], deployed: &[u8], file: &DotterFile) -> Option<String> {
+    if looks_binary(expected) || looks_binary(deployed) {
+        return None;
+    }
+    unified_diff(
+        std::str::from_utf8(expected).ok()?,
+        std::str::from_utf8(deployed).ok()?,
+        &file.source,
+        &file.target,
+    )
+}
+
+fn check_deployed_file(config: &Config, file: &DotterFile) -> Option<Issue> {
+    let source = config.dotfiles_dir.join(&file.source);
+    let target = expand_home(&file.target);
+    let describe = format!("{} -> {}", file.source, file.target);
+
+    let Ok(metadata) = fs::symlink_metadata(&target) else {
+        return Some(
+            Issue::new(Severity::Warning, format!("Not deployed: {}", describe))
+                .with_code("DRIFT001")
+                .with_file(file.source.clone())
+                .with_fix("Run: dotfiles-tools deploy"),
+        );
+    };
+
+    match file.kind {
+        DeployKind::Symbolic if is_link(&metadata) => {
+            let points_at_source = match (fs::canonicalize(&target), fs::canonicalize(&source)) {
+                (Ok(actual), Ok(expected)) => actual == expected,
+                _ => false,
+            };
+            (!points_at_source).then(|| {
+                Issue::new(
+                    Severity::Error,
+                    format!("Deployed symlink points elsewhere: {}", describe),
+                )
+                .with_code("DRIFT002")
+                .with_file(file.source.clone())
+                .with_fix("Run: dotfiles-tools deploy --force")
+            })
+        }
+        DeployKind::Symbolic => {
+            let deployed = fs::read(&target).ok();
+            let repo = fs::read(&source).ok();
+            let same = metadata.is_file() && deployed == repo;
+            Some(if same {
+                Issue::new(
+                    Severity::Warning,
+                    format!("Deployed as a copy instead of a symlink: {}", describe),
+                )
+                .with_code("DRIFT003")
+                .with_file(file.source.clone())
+            } else {
+                Issue::new(
+                    Severity::Warning,
+                    format!(
+                        "Deployed file was replaced and differs from the repo: {}",
+                        describe
+                    ),
+                )
+                .with_code("DRIFT004")
+                .with_file(file.source.clone())
+                .with_diff(
+                    repo.zip(deployed)
+                        .and_then(|(repo, deployed)| content_diff(&repo, &deployed, file)),
+                )
+                .with_fix(format!(
+                    "Copy local changes into {} or run: dotfiles-tools deploy --force",
+                    file.source
+                ))
+            })
+        }
+        // Templates can't be re-rendered here, but dotter keeps the output of
+        // the last deploy in .dotter/cache, so edits made since then show up
+        DeployKind::Template => {
+            let cached = config.dotfiles_dir.join(".dotter/cache").join(&file.source);
+            let rendered = fs::read(&cached).ok()?;
+            let deployed = fs::read(&target).ok()?;
+            (deployed != rendered).then(|| {
+                Issue::new(
+                    Severity::Warning,
+                    format!("Rendered template was edited after deploy: {}", describe),
+                )
+                .with_code("DRIFT005")
+                .with_file(file.source.clone())
+                .with_diff(content_diff(&rendered, &deployed, file))
+                .with_fix(format!("Move the edits into the template {}", file.source))
+            })
+        }
+    }
+}
+
+// The .ssh or .gnupg directory a path is in; both refuse (or warn about)
+// files that anyone but the owner can read
+fn private_dir(path: &Path) -> Option<&Path> {
+    path.ancestors().skip(1).find(|dir| {
+        matches!(
+            dir.file_name().and_then(|n| n.to_str()),
+            Some(".ssh" | ".gnupg")
+        )
+    })
+}
+
+/// Permission problems with a deployed file: a copy whose mode has drifted
+/// from the repo source, or anything in ~/.ssh or ~/.gnupg that isn't
+/// private to its owner.
+#[cfg(unix)]
+fn check_permissions(config: &Config, file: &DotterFile) -> Vec<Issue> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let source = config.dotfiles_dir.join(&file.source);
+    let target = expand_home(&file.target);
+    let describe = format!("{} -> {}", file.source, file.target);
+    let mode = |path: &Path| {
+        fs::metadata(path)
+            .ok()
+            .map(|m| m.permissions().mode() & 0o777)
+    };
+    let mut issues = Vec::new();
+
+    // A symlink has no mode of its own, and git only records the source's
+    // executable bit, so a copy drifts when it gains access the source
+    // doesn't grant or loses the executable bit
+    let is_copy = fs::symlink_metadata(&target).is_ok_and(|m| !is_link(&m));
+    if is_copy
+        && source.is_file()
+        && let (Some(expected), Some(actual)) = (mode(&source), mode(&target))
+        && (actual & !expected != 0 || (expected & 0o100) != (actual & 0o100))
+    {
+        issues.push(
+            Issue::new(
+                Severity::Warning,
+                format!(
+                    "Deployed copy is {:o} but the source is {:o}: {}",
+                    actual, expected, describe
+                ),
+            )
+            .with_code("DRIFT006")
+            .with_file(file.source.clone())
+            .with_fix(format!(
+                "Run: chmod {:o} {}",
+                expected,
+                shell_quote_path(&file.target)
+            )),
+        );
+    }
+
+    if let Some(dir) = private_dir(&target) {
+        let private = [(target.as_path(), 0o600), (dir, 0o700)];
+        for (path, wanted) in private {
+            let Some(actual) = mode(path) else {
+                continue;
+            };
+            if actual & 0o077 != 0 {
+                issues.push(
+                    Issue::new(
+                        Severity::Error,
+                        format!(
+                            "{} is {:o}; it must be private ({:o})",
+                            path.display(),
+                            actual,
+                            wanted
+                        ),
+                    )
+                    .with_code("DRIFT007")
+                    .with_file(file.source.clone())
+                    .with_fix(format!(
+                        "Run: chmod {:o} {}",
+                        wanted,
+                        shell_quote_path(&path.to_string_lossy())
+                    )),
+                );
+            }
+        }
+    }
+    issues
+}
+
+#[cfg(not(unix))]
+fn check_permissions(_config: &Config, _file: &DotterFile) -> Vec<Issue> {
+    Vec::new()
+}
+
+/// A symlink in $HOME into the repo whose repo side is gone.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StaleLink {
+    pub link: PathBuf,
+    pub target: PathBuf,
+}
+
+// Only the directories dotter deploys into (and their parents, up to home)
+// are looked at, one level deep; walking all of $HOME would take too long
+fn stale_links_under(config: &Config, files: &[DotterFile], home: &Path) -> Vec<StaleLink> {
+    let mut dirs = BTreeSet::new();
+    for file in files {
+        let target = expand_home(&file.target);
+        for dir in target.ancestors().skip(1) {
+            if !dir.starts_with(home) {
+                break;
+            }
+            dirs.insert(dir.to_path_buf());
+        }
+    }
+    let roots = [
+        Some(config.dotfiles_dir.clone()),
+        fs::canonicalize(&config.dotfiles_dir).ok(),
+    ];
+
+    let mut stale = Vec::new();
+    for dir in dirs {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let link = entry.path();
+            let Ok(target) = fs::read_link(&link) else {
+                continue;
+            };
+            let target = normalize_path(&dir.join(target));
+            let into_repo = roots.iter().flatten().any(|root| target.starts_with(root));
+            if into_repo && !target.exists() {
+                stale.push(StaleLink { link, target });
+            }
+        }
+    }
+    stale
+}
+
+/// Symlinks left in $HOME by files that were deleted or renamed in the repo
+/// since they were deployed.
+pub fn stale_links(config: &Config) -> Result<Vec<StaleLink>> {
+    let Some(home) = home_dir() else {
+        return Ok(Vec::new());
+    };
+    Ok(stale_links_under(config, &deployed_files(config)?, &home))
+}
+
+// The `clean-links` command: remove the links `stale_links` finds
+pub fn clean_links(config: &Config, dry_run: bool) -> Result<i32> {
+    let stale = stale_links(config)?;
+    if stale.is_empty() {
+        success("No stale symlinks into the repo");
+        return Ok(ExitCode::CLEAN);
+    }
+    for StaleLink { link, target } in &stale {
+        if dry_run {
+            info(&format!(
+                "Would remove {} -> {}",
+                link.display(),
+                target.display()
+            ));
+        } else {
+            fs::remove_file(link)
+                .with_context(|| format!("Failed to remove {}", link.display()))?;
+            success(&format!(
+                "Removed {} -> {}",
+                link.display(),
+                target.display()
+            ));
+        }
+    }
+    Ok(ExitCode::CLEAN)
+}
+
+pub fn drift(config: Config) -> Result<i32> {
+    println!(
+        "\n{}Checking deployed files for drift...{}\n",
+        Color::bold(),
+        Color::reset()
+    );
+
+    let files = deployed_files(&config)?;
+    let mut issues = Vec::new();
+    for file in &files {
+        let _span = trace_span!("file", package = %file.package, source = %file.source).entered();
+        issues.extend(check_deployed_file(&config, file));
+        issues.extend(check_permissions(&config, file));
+    }
+
+    let passed = issues.iter().all(|i| i.severity != Severity::Error);
+    let mut result = ValidationResult::new(
+        format!("All {} deployed files match the repo", files.len()),
+        passed,
+        issues,
+    );
+    result.rule_id = "drift".to_string();
+
+    let validator = Validator::new(config);
+    validator.print_result(&result);
+    Ok(validator.summarize(&[result]))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::test_support::FixtureRepo;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn copied_files_keep_their_mode_and_ssh_stays_private() {
+        let repo = FixtureRepo::new();
+        repo.write("bin/backup", "#!/bin/sh\n")
+            .write("ssh/config", "Host *\n")
+            .write("home/bin/backup", "#!/bin/sh\n")
+            .write("home/.ssh/config", "Host *\n");
+        let chmod = |file: &str, mode: u32| {
+            fs::set_permissions(repo.path().join(file), fs::Permissions::from_mode(mode)).unwrap();
+        };
+        chmod("bin/backup", 0o755);
+        chmod("ssh/config", 0o600);
+        chmod("home/bin/backup", 0o644);
+        chmod("home/.ssh", 0o755);
+        chmod("home/.ssh/config", 0o600);
+
+        let deployed = |source: &str, target: &str| DotterFile {
+            package: "test".to_string(),
+            source: source.to_string(),
+            target: repo.path().join(target).display().to_string(),
+            kind: DeployKind::Template,
+        };
+        let config = repo.config();
+        let codes = |file: &DotterFile| -> Vec<_> {
+            check_permissions(&config, file)
+                .into_iter()
+                .filter_map(|i| i.code)
+                .collect()
+        };
+        assert_eq!(
+            codes(&deployed("bin/backup", "home/bin/backup")),
+            ["DRIFT006"]
+        );
+        assert_eq!(
+            codes(&deployed("ssh/config", "home/.ssh/config")),
+            ["DRIFT007"]
+        );
+
+        chmod("home/bin/backup", 0o755);
+        chmod("home/.ssh", 0o700);
+        assert!(codes(&deployed("bin/backup", "home/bin/backup")).is_empty());
+        assert!(codes(&deployed("ssh/config", "home/.ssh/config")).is_empty());
+    }
+
+    #[test]
+    fn links_into_deleted_repo_files_are_stale() {
+        let repo = FixtureRepo::new();
+        repo.write("zsh/.zshrc", "\n")
+            .write("kitty/kitty.conf", "\n");
+        let home = tempfile::TempDir::new().unwrap();
+        let root = repo.path();
+        let link = |name: &str, target: &Path| {
+            let path = home.path().join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::os::unix::fs::symlink(target, path).unwrap();
+        };
+        link(".zshrc", &root.join("zsh/.zshrc"));
+        link(".zprofile", &root.join("zsh/.zprofile"));
+        link(".config/kitty/theme.conf", &root.join("kitty/theme.conf"));
+        link(".config/other.conf", Path::new("/nonexistent/other.conf"));
+        link("unrelated/.old", &root.join("old"));
+
+        let files: Vec<_> = [
+            ("zsh/.zshrc", ".zshrc"),
+            ("kitty/kitty.conf", ".config/kitty/kitty.conf"),
+        ]
+        .iter()
+        .map(|(source, target)| DotterFile {
+            package: "test".to_string(),
+            source: source.to_string(),
+            target: home.path().join(target).display().to_string(),
+            kind: DeployKind::Symbolic,
+        })
+        .collect();
+        let mut stale: Vec<_> = stale_links_under(&repo.config(), &files, home.path())
+            .into_iter()
+            .map(|s| {
+                s.link
+                    .strip_prefix(home.path())
+                    .unwrap()
+                    .display()
+                    .to_string()
+            })
+            .collect();
+        stale.sort();
+        assert_eq!(stale, [".config/kitty/theme.conf", ".zprofile"]);
+    }
+}
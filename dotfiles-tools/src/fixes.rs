@@ -0,0 +1,255 @@
+//! Automatic fixes applied by `fix`, and fix scripts for applying them by hand.
+
+use anyhow::{Context, Result};
+
+use std::{collections::BTreeSet, fs, path::Path};
+
+use crate::{
+    backup::create_snapshot,
+    rules::formatting::formatted_content,
+    types::{Config, ValidationResult},
+};
+
+pub const GITIGNORE_BLOCK_START: &str = "# >>> validate-dotfiles: dotter negations >>>";
+pub const GITIGNORE_BLOCK_END: &str = "# <<< validate-dotfiles: dotter negations <<<";
+
+pub struct GitignoreUpdate {
+    pub added: usize,
+    // Backup of .gitignore taken before it was rewritten
+    pub snapshot: Option<String>,
+}
+
+// Rewrites the managed negation block in .gitignore, merging in any new
+// files, after backing up the previous version
+pub fn apply_gitignore_negations(config: &Config, files: &[&String]) -> Result<GitignoreUpdate> {
+    let gitignore = config.dotfiles_dir.join(".gitignore");
+    let content = if gitignore.exists() {
+        fs::read_to_string(&gitignore)
+            .with_context(|| format!("Failed to read {}", gitignore.display()))?
+    } else {
+        String::new()
+    };
+
+    let mut before = Vec::new();
+    let mut existing = BTreeSet::new();
+    let mut after = Vec::new();
+    let mut section = 0;
+    for line in content.lines() {
+        match (section, line.trim()) {
+            (0, GITIGNORE_BLOCK_START) => section = 1,
+            (1, GITIGNORE_BLOCK_END) => section = 2,
+            (0, _) => before.push(line),
+            (1, entry) if !entry.is_empty() => {
+                existing.insert(entry.to_string());
+            }
+            (1, _) => {}
+            _ => after.push(line),
+        }
+    }
+
+    let mut negations = existing.clone();
+    negations.extend(files.iter().map(|f| format!("!{}", f)));
+    let added = negations.len() - existing.len();
+    if added == 0 {
+        return Ok(GitignoreUpdate {
+            added,
+            snapshot: None,
+        });
+    }
+
+    let mut lines: Vec<String> = before.iter().map(|l| l.to_string()).collect();
+    if lines.last().is_some_and(|l| !l.is_empty()) {
+        lines.push(String::new());
+    }
+    lines.push(GITIGNORE_BLOCK_START.to_string());
+    lines.extend(negations);
+    lines.push(GITIGNORE_BLOCK_END.to_string());
+    lines.extend(after.iter().map(|l| l.to_string()));
+
+    let snapshot = create_snapshot(config, "fix", std::slice::from_ref(&gitignore))?;
+    fs::write(&gitignore, lines.join("\n") + "\n")
+        .with_context(|| format!("Failed to write {}", gitignore.display()))?;
+
+    Ok(GitignoreUpdate {
+        added,
+        snapshot: Some(snapshot.id),
+    })
+}
+
+pub struct FormatUpdate {
+    pub rewritten: usize,
+    pub snapshot: Option<String>,
+}
+
+// Rewrites each file in its formatted form, after backing them all up.
+// Formatting the working-tree copy (not the index, even with --staged) keeps
+// unstaged edits
+pub fn apply_formatting(config: &Config, files: &[&String]) -> Result<FormatUpdate> {
+    let rewrites: Vec<_> = files
+        .iter()
+        .filter_map(|file| {
+            let path = config.dotfiles_dir.join(file);
+            let content = fs::read_to_string(&path).ok()?;
+            let formatted = formatted_content(config, file, &content)?;
+            (formatted != content).then_some((path, formatted))
+        })
+        .collect();
+    if rewrites.is_empty() {
+        return Ok(FormatUpdate {
+            rewritten: 0,
+            snapshot: None,
+        });
+    }
+
+    let paths: Vec<_> = rewrites.iter().map(|(path, _)| path.clone()).collect();
+    let snapshot = create_snapshot(config, "fix", &paths)?;
+    for (path, formatted) in &rewrites {
+        fs::write(path, formatted)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+    }
+    Ok(FormatUpdate {
+        rewritten: rewrites.len(),
+        snapshot: Some(snapshot.id),
+    })
+}
+
+// Single-quotes a word for sh
+pub fn shell_quote(word: &str) -> String {
+    if !word.is_empty()
+        && word
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./~+=:@%".contains(c))
+    {
+        return word.to_string();
+    }
+    format!("'{}'", word.replace('\'', r"'\''"))
+}
+
+// Quotes a path for sh, leaving a leading `~/` outside the quotes so the
+// shell still expands it
+pub fn shell_quote_path(path: &str) -> String {
+    match path.strip_prefix("~/") {
+        Some(rest) => format!("~/{}", shell_quote(rest)),
+        None => shell_quote(path),
+    }
+}
+
+// The shell command a fix suggestion spells out, if it's one we can run as-is
+fn suggested_command(fix: &str) -> Option<&str> {
+    fix.strip_prefix("Run: ")
+}
+
+/// A section of the fix script for one repository's results: a commented
+/// command per fixable issue, with .gitignore negations batched into a
+/// heredoc and anything that needs a human left as a comment.
+pub fn fix_script_section(config: &Config, results: &[ValidationResult]) -> String {
+    let mut commands = Vec::new();
+    let mut negations = Vec::new();
+    let mut manual = Vec::new();
+
+    for result in results {
+        for issue in &result.issues {
+            let Some(fix) = &issue.fix_suggestion else {
+                continue;
+            };
+            let comment = format!("# [{}] {}", result.rule_id, issue.message);
+            if let Some(negation) = fix.strip_prefix("Add to .gitignore: ") {
+                negations.push((comment, negation));
+            } else if let (Some(file), true) = (&issue.file, fix.starts_with("Run: git add ")) {
+                commands.push(format!("{}\ngit add -- {}", comment, shell_quote(file)));
+            } else if let Some(command) = suggested_command(fix) {
+                commands.push(format!("{}\n{}", comment, command));
+            } else {
+                manual.push(format!("{}\n#   {}", comment, fix));
+            }
+        }
+    }
+
+    let mut section = format!(
+        "\n# ===== {} =====\ncd {}\n",
+        config.dotfiles_dir.display(),
+        shell_quote(&config.dotfiles_dir.to_string_lossy())
+    );
+    if !negations.is_empty() {
+        section.push('\n');
+        for (comment, _) in &negations {
+            section.push_str(comment);
+            section.push('\n');
+        }
+        section.push_str("cat >> .gitignore <<'EOF'\n");
+        for (_, negation) in &negations {
+            section.push_str(negation);
+            section.push('\n');
+        }
+        section.push_str("EOF\n");
+    }
+    for command in &commands {
+        section.push_str(&format!("\n{}\n", command));
+    }
+    if !manual.is_empty() {
+        section.push_str("\n# Needs a manual fix:\n");
+        for note in &manual {
+            section.push_str(&format!("{}\n", note));
+        }
+    }
+    section
+}
+
+pub fn write_fix_script(path: &Path, sections: &[String]) -> Result<()> {
+    let mut script = String::from(
+        "#!/bin/sh\n# Fixes suggested by dotfiles-tools validate. Review before running.\nset -eu\n",
+    );
+    for section in sections {
+        script.push_str(section);
+    }
+    fs::write(path, script).with_context(|| format!("Failed to write {}", path.display()))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o755))
+            .with_context(|| format!("Failed to make {} executable", path.display()))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_support::FixtureRepo, types::Issue, types::Severity};
+
+    #[test]
+    fn fix_script_turns_suggestions_into_commands() {
+        let repo = FixtureRepo::new();
+        let mut result = ValidationResult::new(
+            "Dotter files exist and are tracked",
+            false,
+            vec![
+                Issue::new(Severity::Warning, "File not tracked: my file")
+                    .with_file("my file")
+                    .with_fix("Run: git add my file"),
+                Issue::new(Severity::Error, "File ignored by git: .env.tmpl")
+                    .with_file(".env.tmpl")
+                    .with_fix("Add to .gitignore: !.env.tmpl"),
+                Issue::new(Severity::Warning, "Pinned twice")
+                    .with_fix("Pin the tool in a single file"),
+            ],
+        );
+        result.rule_id = "dotter-files".to_string();
+
+        let section = fix_script_section(&repo.config(), &[result]);
+        assert!(section.contains("git add -- 'my file'\n"));
+        assert!(section.contains("cat >> .gitignore <<'EOF'\n!.env.tmpl\nEOF\n"));
+        assert!(section.contains("#   Pin the tool in a single file\n"));
+    }
+
+    #[test]
+    fn shell_quoting() {
+        assert_eq!(shell_quote("a/b.toml"), "a/b.toml");
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+        assert_eq!(
+            shell_quote_path("~/Library/Application Support/x"),
+            "~/'Library/Application Support/x'"
+        );
+    }
+}
@@ -0,0 +1,369 @@
+//! Native TOML and JSON formatters for the formatting rules and `fix`.
+
+// Where a multi-line string or array started on an earlier line is still open
+#[derive(Default)]
+struct TomlState {
+    string: Option<&'static str>,
+    depth: usize,
+}
+
+impl TomlState {
+    // Advances through one line, tracking strings, comments, and brackets
+    fn scan(&mut self, line: &str, count_brackets: bool) {
+        let mut rest = line;
+        while !rest.is_empty() {
+            if let Some(delimiter) = self.string {
+                match find_closing(rest, delimiter) {
+                    Some(end) => {
+                        self.string = None;
+                        rest = &rest[end..];
+                    }
+                    None => return,
+                }
+                continue;
+            }
+            if rest.starts_with('#') {
+                return;
+            }
+            if let Some(delimiter) = ["\"\"\"", "'''"].into_iter().find(|d| rest.starts_with(d)) {
+                self.string = Some(delimiter);
+                rest = &rest[3..];
+                continue;
+            }
+            if let Some(quote) = ["\"", "'"].into_iter().find(|q| rest.starts_with(q)) {
+                rest = match find_closing(&rest[1..], quote) {
+                    Some(end) => &rest[1 + end..],
+                    None => "",
+                };
+                continue;
+            }
+            match rest.as_bytes()[0] {
+                b'[' | b'{' if count_brackets => self.depth += 1,
+                b']' | b'}' if count_brackets => self.depth = self.depth.saturating_sub(1),
+                _ => {}
+            }
+            rest = &rest[rest.chars().next().map_or(1, char::len_utf8)..];
+        }
+    }
+}
+
+// Byte offset just past the closing delimiter, skipping escapes in basic strings
+fn find_closing(text: &str, delimiter: &str) -> Option<usize> {
+    let escapes = delimiter.starts_with('"');
+    let mut idx = 0;
+    while idx < text.len() {
+        if escapes && text[idx..].starts_with('\\') {
+            idx += 1 + text[idx + 1..].chars().next().map_or(0, char::len_utf8);
+            continue;
+        }
+        if text[idx..].starts_with(delimiter) {
+            // Up to two quotes may sit right before a multi-line closer
+            let mut end = idx + delimiter.len();
+            while delimiter.len() == 3 && text[end..].starts_with(&delimiter[..1]) && end - idx < 5
+            {
+                end += 1;
+            }
+            return Some(end);
+        }
+        idx += text[idx..].chars().next().map_or(1, char::len_utf8);
+    }
+    None
+}
+
+// Index of the `=` separating a key from its value, outside any quoted key
+fn key_separator(line: &str) -> Option<usize> {
+    let mut quote = None;
+    for (idx, c) in line.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (None, '=') => return Some(idx),
+            (None, '#') => return None,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Formats TOML the way taplo does by default: entries and headers
+/// unindented, one space around `=`, no trailing whitespace, at most two
+/// blank lines in a row, and a single final newline. Multi-line strings and
+/// the insides of multi-line arrays are left alone.
+pub fn format_toml(content: &str) -> String {
+    let mut state = TomlState::default();
+    let mut lines: Vec<String> = Vec::new();
+    let mut blank_run = 0;
+
+    for line in content.split('\n') {
+        if state.string.is_some() {
+            state.scan(line, true);
+            lines.push(line.to_string());
+            continue;
+        }
+        if state.depth > 0 {
+            state.scan(line, true);
+            lines.push(if state.string.is_some() {
+                line.to_string()
+            } else {
+                line.trim_end().to_string()
+            });
+            continue;
+        }
+
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            blank_run += 1;
+            if blank_run <= 2 && !lines.is_empty() {
+                lines.push(String::new());
+            }
+            continue;
+        }
+        blank_run = 0;
+
+        let formatted = if trimmed.starts_with('[') {
+            state.scan(trimmed, false);
+            format_header(trimmed)
+        } else if let Some(eq) = key_separator(trimmed).filter(|_| !trimmed.starts_with('#')) {
+            let value = trimmed[eq + 1..].trim_start();
+            state.scan(value, true);
+            format!("{} = {}", trimmed[..eq].trim_end(), value)
+        } else {
+            state.scan(trimmed, true);
+            trimmed.to_string()
+        };
+        // A value that opens a multi-line string keeps its trailing spaces,
+        // since they're part of the string
+        lines.push(if state.string.is_some() {
+            let start = line.len() - line.trim_start().len();
+            let mut kept = formatted;
+            kept.push_str(&line[start + trimmed.len()..]);
+            kept
+        } else {
+            formatted
+        });
+    }
+
+    while lines.last().is_some_and(|l| l.is_empty()) {
+        lines.pop();
+    }
+    if lines.is_empty() {
+        return String::new();
+    }
+    lines.join("\n") + "\n"
+}
+
+// `[ a.b ]` -> `[a.b]`, keeping any trailing comment
+fn format_header(line: &str) -> String {
+    let (open, close) = if line.starts_with("[[") {
+        ("[[", "]]")
+    } else {
+        ("[", "]")
+    };
+    let Some(end) = line.find(close) else {
+        return line.to_string();
+    };
+    let name = line[open.len()..end].trim();
+    let rest = line[end + close.len()..].trim_start();
+    if rest.is_empty() {
+        format!("{}{}{}", open, name, close)
+    } else {
+        format!("{}{}{} {}", open, name, close, rest)
+    }
+}
+
+enum JsonNode<'a> {
+    Scalar(&'a str),
+    Array(Vec<JsonNode<'a>>),
+    Object(Vec<(&'a str, JsonNode<'a>)>),
+}
+
+struct JsonReader<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+impl<'a> JsonReader<'a> {
+    fn skip_whitespace(&mut self) {
+        let rest = &self.src[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn eat(&mut self, byte: u8) -> bool {
+        self.skip_whitespace();
+        let matched = self.src.as_bytes().get(self.pos) == Some(&byte);
+        if matched {
+            self.pos += 1;
+        }
+        matched
+    }
+
+    fn string(&mut self) -> Option<&'a str> {
+        let start = self.pos;
+        if self.src.as_bytes().get(start) != Some(&b'"') {
+            return None;
+        }
+        let end = find_closing(&self.src[start + 1..], "\"")?;
+        self.pos = start + 1 + end;
+        Some(&self.src[start..self.pos])
+    }
+
+    fn node(&mut self) -> Option<JsonNode<'a>> {
+        self.skip_whitespace();
+        match *self.src.as_bytes().get(self.pos)? {
+            b'{' => {
+                self.pos += 1;
+                let mut entries = Vec::new();
+                if self.eat(b'}') {
+                    return Some(JsonNode::Object(entries));
+                }
+                loop {
+                    self.skip_whitespace();
+                    let key = self.string()?;
+                    if !self.eat(b':') {
+                        return None;
+                    }
+                    entries.push((key, self.node()?));
+                    if self.eat(b'}') {
+                        return Some(JsonNode::Object(entries));
+                    }
+                    if !self.eat(b',') {
+                        return None;
+                    }
+                }
+            }
+            b'[' => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                if self.eat(b']') {
+                    return Some(JsonNode::Array(items));
+                }
+                loop {
+                    items.push(self.node()?);
+                    if self.eat(b']') {
+                        return Some(JsonNode::Array(items));
+                    }
+                    if !self.eat(b',') {
+                        return None;
+                    }
+                }
+            }
+            b'"' => self.string().map(JsonNode::Scalar),
+            _ => {
+                let rest = &self.src[self.pos..];
+                let len = rest
+                    .find(|c: char| c.is_whitespace() || matches!(c, ',' | ']' | '}'))
+                    .unwrap_or(rest.len());
+                self.pos += len;
+                (len > 0).then(|| JsonNode::Scalar(&rest[..len]))
+            }
+        }
+    }
+}
+
+fn write_json(node: &mut JsonNode, indent: usize, sort_keys: bool, out: &mut String) {
+    let pad = |level: usize| "  ".repeat(level);
+    match node {
+        JsonNode::Scalar(raw) => out.push_str(raw),
+        JsonNode::Array(items) if items.is_empty() => out.push_str("[]"),
+        JsonNode::Object(entries) if entries.is_empty() => out.push_str("{}"),
+        JsonNode::Array(items) => {
+            out.push_str("[\n");
+            for (idx, item) in items.iter_mut().enumerate() {
+                if idx > 0 {
+                    out.push_str(",\n");
+                }
+                out.push_str(&pad(indent + 1));
+                write_json(item, indent + 1, sort_keys, out);
+            }
+            out.push('\n');
+            out.push_str(&pad(indent));
+            out.push(']');
+        }
+        JsonNode::Object(entries) => {
+            if sort_keys {
+                // Compare the decoded keys so escapes don't affect the order
+                entries.sort_by_cached_key(|(key, _)| {
+                    serde_json::from_str::<String>(key).unwrap_or_else(|_| key.to_string())
+                });
+            }
+            out.push_str("{\n");
+            for (idx, (key, value)) in entries.iter_mut().enumerate() {
+                if idx > 0 {
+                    out.push_str(",\n");
+                }
+                out.push_str(&pad(indent + 1));
+                out.push_str(key);
+                out.push_str(": ");
+                write_json(value, indent + 1, sort_keys, out);
+            }
+            out.push('\n');
+            out.push_str(&pad(indent));
+            out.push('}');
+        }
+    }
+}
+
+/// Pretty-prints valid JSON with two-space indentation, as
+/// `JSON.stringify(value, null, 2)` would, keeping numbers and string escapes
+/// exactly as written. `None` if the content isn't a single JSON value.
+pub fn format_json(content: &str, sort_keys: bool) -> Option<String> {
+    let mut reader = JsonReader {
+        src: content.strip_prefix('\u{feff}').unwrap_or(content),
+        pos: 0,
+    };
+    let mut node = reader.node()?;
+    reader.skip_whitespace();
+    if reader.pos != reader.src.len() {
+        return None;
+    }
+    let mut out = String::new();
+    write_json(&mut node, 0, sort_keys, &mut out);
+    out.push('\n');
+    Some(out)
+}
+
+/// 1-based number of the first line where `formatted` differs from `content`.
+pub fn first_difference(content: &str, formatted: &str) -> usize {
+    let mut original = content.lines();
+    let mut expected = formatted.lines();
+    let mut line = 1;
+    while let (Some(a), Some(b)) = (original.next(), expected.next()) {
+        if a != b {
+            return line;
+        }
+        line += 1;
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toml_spacing_is_normalized_outside_strings_and_arrays() {
+        let content = "\n  [ server ]\nport=8080   \nname =  \"a=b\"\n\n\n\n\nlist = [\n    1,  \n    2,\n]\ntext = \"\"\"\nkeep   \n  this\"\"\"\n\n";
+        assert_eq!(
+            format_toml(content),
+            "[server]\nport = 8080\nname = \"a=b\"\n\n\nlist = [\n    1,\n    2,\n]\ntext = \"\"\"\nkeep   \n  this\"\"\"\n"
+        );
+        let formatted = format_toml(content);
+        assert_eq!(format_toml(&formatted), formatted);
+    }
+
+    #[test]
+    fn json_is_pretty_printed_with_optional_key_sorting() {
+        let content = r#"{"b": [1, 2.50, {}], "a": {"x": "\u00e9"}}"#;
+        assert_eq!(
+            format_json(content, false).unwrap(),
+            "{\n  \"b\": [\n    1,\n    2.50,\n    {}\n  ],\n  \"a\": {\n    \"x\": \"\\u00e9\"\n  }\n}\n"
+        );
+        assert!(
+            format_json(content, true)
+                .unwrap()
+                .starts_with("{\n  \"a\"")
+        );
+        assert!(format_json("{\"a\": 1} x", false).is_none());
+        assert_eq!(first_difference("a\nb\n", "a\nc\n"), 2);
+    }
+}
@@ -0,0 +1,297 @@
+//! Lightweight parsing of git config files, and resolving the files they
+//! include.
+
+use std::path::{Path, PathBuf};
+
+use crate::{
+    dotter::{DotterFile, source_for_target, target_for_source, tilde_path},
+    paths::normalize_path,
+    repo::{expand_home, home_dir},
+    types::Config,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GitConfigEntry {
+    // Section and key names are case-insensitive, so both are lowercased;
+    // subsections are case-sensitive and kept as written
+    pub section: String,
+    pub subsection: Option<String>,
+    pub key: String,
+    pub value: String,
+    pub line: usize,
+}
+
+impl GitConfigEntry {
+    pub fn is(&self, section: &str, key: &str) -> bool {
+        self.section == section && self.key == key
+    }
+}
+
+pub fn is_gitconfig(file: &str) -> bool {
+    let name = Path::new(file)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default();
+    // .gitconfig, gitconfig-work, git/config, git/work.config, ...
+    name.starts_with(".gitconfig")
+        || name.starts_with("gitconfig")
+        || name.ends_with(".gitconfig")
+        || (Path::new(file)
+            .parent()
+            .is_some_and(|p| p.ends_with("git") || p.ends_with(".git"))
+            && (name == "config" || name.ends_with(".config") || name.ends_with(".inc")))
+}
+
+// A value after `=`: quotes group, backslash escapes, and `;` or `#` outside
+// quotes starts a comment
+fn parse_value(raw: &str) -> String {
+    let mut value = String::new();
+    let mut quoted = false;
+    // Whitespace is only kept once something follows it
+    let mut pending = String::new();
+    let mut chars = raw.trim_start().chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                value.push_str(&std::mem::take(&mut pending));
+                quoted = !quoted;
+            }
+            '\\' => {
+                value.push_str(&std::mem::take(&mut pending));
+                match chars.next() {
+                    Some('n') => value.push('\n'),
+                    Some('t') => value.push('\t'),
+                    Some(other) => value.push(other),
+                    None => {}
+                }
+            }
+            ';' | '#' if !quoted => break,
+            c if c.is_whitespace() && !quoted => pending.push(c),
+            c => {
+                value.push_str(&std::mem::take(&mut pending));
+                value.push(c);
+            }
+        }
+    }
+    value
+}
+
+// `[section "subsection"]` or the deprecated `[section.subsection]`
+fn parse_header(header: &str) -> (String, Option<String>) {
+    match header.split_once(char::is_whitespace) {
+        Some((section, subsection)) => (
+            section.to_lowercase(),
+            Some(subsection.trim().trim_matches('"').replace("\\\"", "\"")),
+        ),
+        None => match header.split_once('.') {
+            Some((section, subsection)) => (section.to_lowercase(), Some(subsection.to_string())),
+            None => (header.to_lowercase(), None),
+        },
+    }
+}
+
+pub fn parse_gitconfig(content: &str) -> Vec<GitConfigEntry> {
+    let mut entries = Vec::new();
+    let mut section = String::new();
+    let mut subsection = None;
+
+    let lines: Vec<_> = content.lines().collect();
+    let mut idx = 0;
+    while idx < lines.len() {
+        let line_number = idx + 1;
+        let mut line = lines[idx].trim().to_string();
+        idx += 1;
+        // A trailing backslash continues the value on the next line
+        while line.ends_with('\\') && idx < lines.len() {
+            line.pop();
+            line.push_str(lines[idx].trim());
+            idx += 1;
+        }
+
+        let mut rest = line.as_str();
+        if let Some(header) = rest.strip_prefix('[') {
+            let Some((header, after)) = header.split_once(']') else {
+                continue;
+            };
+            (section, subsection) = parse_header(header.trim());
+            rest = after.trim();
+        }
+        if rest.is_empty() || rest.starts_with(['#', ';']) || section.is_empty() {
+            continue;
+        }
+
+        // A key without `=` is a boolean set to true
+        let (key, value) = match rest.split_once('=') {
+            Some((key, value)) => (key.trim(), parse_value(value)),
+            None => (rest.trim(), "true".to_string()),
+        };
+        entries.push(GitConfigEntry {
+            section: section.clone(),
+            subsection: subsection.clone(),
+            key: key.to_lowercase(),
+            value,
+            line: line_number,
+        });
+    }
+    entries
+}
+
+// Tools git's difftool and mergetool know without a [difftool "x"] section,
+// and the program each one runs
+const BUILTIN_TOOLS: &[(&str, &str)] = &[
+    ("araxis", "compare"),
+    ("bc", "bcompare"),
+    ("bc3", "bcompare"),
+    ("bc4", "bcompare"),
+    ("codecompare", "CodeMerge"),
+    ("deltawalker", "DeltaWalker"),
+    ("diffmerge", "diffmerge"),
+    ("diffuse", "diffuse"),
+    ("ecmerge", "ecmerge"),
+    ("emerge", "emacs"),
+    ("examdiff", "ExamDiff"),
+    ("guiffy", "guiffy"),
+    ("gvimdiff", "gvim"),
+    ("kdiff3", "kdiff3"),
+    ("kompare", "kompare"),
+    ("meld", "meld"),
+    ("nvimdiff", "nvim"),
+    ("opendiff", "opendiff"),
+    ("p4merge", "p4merge"),
+    ("smerge", "smerge"),
+    ("tkdiff", "tkdiff"),
+    ("tortoisemerge", "TortoiseGitMerge"),
+    ("vimdiff", "vim"),
+    ("winmerge", "WinMergeU"),
+    ("xxdiff", "xxdiff"),
+];
+
+/// The program a diff or merge tool git knows by name runs; `vimdiff1`
+/// through `vimdiff3` are layouts of `vimdiff`.
+pub fn builtin_tool_program(name: &str) -> Option<&'static str> {
+    let name = name.trim_end_matches(['1', '2', '3']);
+    BUILTIN_TOOLS
+        .iter()
+        .find(|(tool, _)| *tool == name)
+        .map(|(_, program)| *program)
+}
+
+/// Where an `include.path` or `includeIf.*.path` points.
+#[derive(Debug, Clone, PartialEq)]
+pub enum IncludeTarget {
+    /// A file in the repo, directly or through a dotter deployment.
+    Repo(String),
+    /// A path on this machine that the repo doesn't manage.
+    Machine(PathBuf),
+}
+
+impl IncludeTarget {
+    pub fn exists(&self, config: &Config) -> bool {
+        match self {
+            IncludeTarget::Repo(source) => config.dotfiles_dir.join(source).exists(),
+            IncludeTarget::Machine(path) => path.exists(),
+        }
+    }
+
+    pub fn display(&self) -> String {
+        match self {
+            IncludeTarget::Repo(source) => source.clone(),
+            IncludeTarget::Machine(path) => path.display().to_string(),
+        }
+    }
+}
+
+/// Resolves a path from a tracked git config the way git would once the
+/// config is deployed: `~/` is the home directory and relative paths are
+/// relative to the deployed config's directory.
+pub fn resolve_config_path(
+    config: &Config,
+    files: &[DotterFile],
+    including: &str,
+    path: &str,
+) -> IncludeTarget {
+    let path = tilde_path(path);
+    let repo_root = config.dotfiles_dir.display().to_string();
+    let repo_under_home = home_dir().and_then(|home| {
+        config
+            .dotfiles_dir
+            .strip_prefix(home)
+            .ok()
+            .map(|r| format!("~/{}", r.display()))
+    });
+
+    let absolute = if path.starts_with("~/") || Path::new(&path).is_absolute() {
+        path
+    } else {
+        match target_for_source(files, including) {
+            Some(target) => {
+                let dir = Path::new(&target).parent().unwrap_or(Path::new("~"));
+                normalize_path(&dir.join(&path)).display().to_string()
+            }
+            // An undeployed config is read from the repo itself
+            None => {
+                let dir = Path::new(including).parent().unwrap_or(Path::new(""));
+                let source = normalize_path(&dir.join(&path));
+                return IncludeTarget::Repo(source.display().to_string());
+            }
+        }
+    };
+
+    let in_repo = [Some(repo_root), repo_under_home]
+        .into_iter()
+        .flatten()
+        .find_map(|root| {
+            absolute
+                .strip_prefix(&format!("{}/", root))
+                .map(String::from)
+        });
+    match in_repo.or_else(|| source_for_target(files, &absolute)) {
+        Some(source) => IncludeTarget::Repo(source),
+        None => IncludeTarget::Machine(expand_home(&absolute)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_follow_git_config_syntax() {
+        let content = "[core]\n\tpager = delta --dark ; comment\n\
+                       [includeIf \"gitdir:~/work/\"]\n\tpath = ~/.config/git/work\n\
+                       [alias]\n\tlg = \"log --graph # not a comment\"\n\
+                       \tst = status \\\n  -sb\n\
+                       [Diff.Tool] Color = \"a\\\"b\"\n\
+                       [push]\n\tautoSetupRemote\n";
+        let entries = parse_gitconfig(content);
+        let found: Vec<_> = entries
+            .iter()
+            .map(|e| {
+                (
+                    e.section.as_str(),
+                    e.subsection.as_deref(),
+                    e.key.as_str(),
+                    e.value.as_str(),
+                    e.line,
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("core", None, "pager", "delta --dark", 2),
+                (
+                    "includeif",
+                    Some("gitdir:~/work/"),
+                    "path",
+                    "~/.config/git/work",
+                    4
+                ),
+                ("alias", None, "lg", "log --graph # not a comment", 6),
+                ("alias", None, "st", "status -sb", 7),
+                ("diff", Some("Tool"), "color", "a\"b", 9),
+                ("push", None, "autosetupremote", "true", 11),
+            ]
+        );
+    }
+}
@@ -0,0 +1,176 @@
+//! Run history, used to compare a run against the previous one.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::PathBuf,
+    process::Command,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    output::{Color, failure, info, success, warning},
+    types::{Config, Issue, Severity, ValidationResult},
+};
+
+// Enough runs to compare against recent history without the state file
+// growing forever
+pub const HISTORY_LIMIT: usize = 50;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RunRecord {
+    pub timestamp: u64,
+    pub head: Option<String>,
+    pub errors: usize,
+    pub warnings: usize,
+    // rule id -> issue fingerprints
+    pub rules: BTreeMap<String, Vec<String>>,
+}
+
+impl RunRecord {
+    pub fn from_results(config: &Config, results: &[ValidationResult]) -> Self {
+        let issues = || results.iter().flat_map(|r| &r.issues);
+        Self {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+            head: git_head(config),
+            errors: issues().filter(|i| i.severity == Severity::Error).count(),
+            warnings: issues().filter(|i| i.severity == Severity::Warning).count(),
+            rules: results
+                .iter()
+                .map(|r| {
+                    (
+                        r.rule_id.clone(),
+                        r.issues.iter().map(issue_fingerprint).collect(),
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    pub fn fingerprints(&self) -> BTreeSet<(&str, &str)> {
+        self.rules
+            .iter()
+            .flat_map(|(rule, prints)| prints.iter().map(move |p| (rule.as_str(), p.as_str())))
+            .collect()
+    }
+}
+
+pub fn issue_fingerprint(issue: &Issue) -> String {
+    format!(
+        "{}|{}",
+        issue.file.as_deref().unwrap_or_default(),
+        issue.message
+    )
+}
+
+pub fn git_head(config: &Config) -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .current_dir(&config.dotfiles_dir)
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+// State lives inside the git dir so it's never committed and follows the clone
+pub fn state_path(config: &Config, name: &str) -> Result<PathBuf> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--git-path"])
+        .arg(format!("validate-dotfiles/{}", name))
+        .current_dir(&config.dotfiles_dir)
+        .output()
+        .context("Failed to run git rev-parse")?;
+    let path = String::from_utf8(output.stdout).context("Invalid UTF-8 in git output")?;
+    Ok(config.dotfiles_dir.join(path.trim()))
+}
+
+pub fn history_path(config: &Config) -> Result<PathBuf> {
+    state_path(config, "history.json")
+}
+
+pub fn load_history(config: &Config) -> Result<Vec<RunRecord>> {
+    let path = history_path(config)?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content =
+        fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    // A corrupt or outdated history file shouldn't break validation
+    Ok(serde_json::from_str(&content).unwrap_or_default())
+}
+
+pub fn save_history(config: &Config, mut history: Vec<RunRecord>, record: RunRecord) -> Result<()> {
+    let path = history_path(config)?;
+    history.push(record);
+    let overflow = history.len().saturating_sub(HISTORY_LIMIT);
+    history.drain(..overflow);
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    fs::write(&path, serde_json::to_string_pretty(&history)?)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+pub fn print_comparison(previous: Option<&RunRecord>, current: &RunRecord) {
+    println!(
+        "\n{}Compared with previous run:{}",
+        Color::bold(),
+        Color::reset()
+    );
+
+    let Some(previous) = previous else {
+        info("No previous run recorded; this run is the new baseline");
+        return;
+    };
+
+    let before = previous.fingerprints();
+    let after = current.fingerprints();
+    let new: Vec<_> = after.difference(&before).collect();
+    let resolved = before.difference(&after).count();
+    let existing = after.intersection(&before).count();
+
+    let age = current.timestamp.saturating_sub(previous.timestamp);
+    info(&format!(
+        "Previous run {} ago{}",
+        format_age(age),
+        previous
+            .head
+            .as_deref()
+            .map(|h| format!(" at {}", &h[..h.len().min(12)]))
+            .unwrap_or_default()
+    ));
+
+    if new.is_empty() {
+        success("No new issues");
+    } else {
+        failure(&format!("{} new issue(s):", new.len()));
+        for (rule, print) in new {
+            let message = print.split_once('|').map(|(_, m)| m).unwrap_or(print);
+            warning(&format!("  [{}] {}", rule, message));
+        }
+    }
+    info(&format!(
+        "{} pre-existing issue(s), {} resolved",
+        existing, resolved
+    ));
+}
+
+pub fn format_age(seconds: u64) -> String {
+    match seconds {
+        s if s < 60 => format!("{}s", s),
+        s if s < 3600 => format!("{}m", s / 60),
+        s if s < 86400 => format!("{}h", s / 3600),
+        s => format!("{}d", s / 86400),
+    }
+}
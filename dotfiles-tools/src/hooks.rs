@@ -0,0 +1,95 @@
+//! Git hook installation.
+
+use anyhow::{Context, Result};
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use crate::{output::success, types::Config};
+
+// Kept from when the validator was a standalone script, so hooks installed
+// back then are still recognized as ours
+pub const HOOK_MARKER: &str = "# managed by validate-dotfiles install-hooks";
+
+pub fn pre_commit_hook_script() -> String {
+    format!(
+        r#"#!/bin/sh
+{}
+repo_root="$(git rev-parse --show-toplevel)" || exit 1
+if command -v dotfiles-tools >/dev/null 2>&1; then
+    exec dotfiles-tools validate --staged --fail-fast
+fi
+exec cargo run --quiet --release --manifest-path "$repo_root/Cargo.toml" -p dotfiles-tools -- validate --staged --fail-fast
+"#,
+        HOOK_MARKER
+    )
+}
+
+pub fn git_hooks_dir(config: &Config) -> Result<PathBuf> {
+    // --git-path respects worktrees and any existing core.hooksPath
+    let output = Command::new("git")
+        .args(["rev-parse", "--git-path", "hooks"])
+        .current_dir(&config.dotfiles_dir)
+        .output()
+        .context("Failed to run git rev-parse")?;
+
+    if !output.status.success() {
+        anyhow::bail!("{} is not a git repository", config.dotfiles_dir.display());
+    }
+
+    let hooks = String::from_utf8(output.stdout)
+        .context("Invalid UTF-8 in git output")?
+        .trim()
+        .to_string();
+    Ok(config.dotfiles_dir.join(hooks))
+}
+
+pub fn install_hooks(config: &Config, hooks_path: Option<&Path>, force: bool) -> Result<()> {
+    let hooks_dir = match hooks_path {
+        Some(dir) => config.dotfiles_dir.join(dir),
+        None => git_hooks_dir(config)?,
+    };
+    let hook = hooks_dir.join("pre-commit");
+
+    if hook.exists() && !force {
+        let existing = fs::read_to_string(&hook).unwrap_or_default();
+        if !existing.contains(HOOK_MARKER) {
+            anyhow::bail!(
+                "{} already exists and wasn't installed by this tool (use --force to overwrite)",
+                hook.display()
+            );
+        }
+    }
+
+    fs::create_dir_all(&hooks_dir)
+        .with_context(|| format!("Failed to create {}", hooks_dir.display()))?;
+    fs::write(&hook, pre_commit_hook_script())
+        .with_context(|| format!("Failed to write {}", hook.display()))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&hook, fs::Permissions::from_mode(0o755))
+            .with_context(|| format!("Failed to make {} executable", hook.display()))?;
+    }
+
+    success(&format!("Installed pre-commit hook at {}", hook.display()));
+
+    if let Some(dir) = hooks_path {
+        let status = Command::new("git")
+            .args(["config", "core.hooksPath"])
+            .arg(dir)
+            .current_dir(&config.dotfiles_dir)
+            .status()
+            .context("Failed to run git config")?;
+        if !status.success() {
+            anyhow::bail!("Failed to set core.hooksPath to {}", dir.display());
+        }
+        success(&format!("Set core.hooksPath to {}", dir.display()));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,37 @@
+//! Ctrl-C during a run: finish the rule in progress, then report what was
+//! collected instead of dying mid-report with the terminal still colored.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Whether Ctrl-C has been pressed since `install`.
+pub fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::Relaxed)
+}
+
+#[cfg(unix)]
+extern "C" fn on_sigint(_signal: libc::c_int) {
+    // A second Ctrl-C means now; only async-signal-safe calls from here
+    if INTERRUPTED.swap(true, Ordering::Relaxed) {
+        let reset = b"\x1b[0m\n";
+        // SAFETY: write and _exit are async-signal-safe
+        unsafe {
+            libc::write(libc::STDOUT_FILENO, reset.as_ptr().cast(), reset.len());
+            libc::_exit(crate::types::ExitCode::INTERRUPTED);
+        }
+    }
+}
+
+/// Catches Ctrl-C so the run can stop between rules. Child processes still
+/// get the signal, so a rule waiting on one finishes promptly.
+#[cfg(unix)]
+pub fn install() {
+    // SAFETY: the handler only touches an atomic, or exits
+    unsafe {
+        libc::signal(libc::SIGINT, on_sigint as *const () as libc::sighandler_t);
+    }
+}
+
+#[cfg(not(unix))]
+pub fn install() {}
@@ -0,0 +1,57 @@
+//! Tools for maintaining this dotfiles repository.
+//!
+//! A composable validation framework using a Rules API, plus the commands
+//! built on it: fixing, drift detection, deployment, and tool checks.
+//! Each rule is a function that returns a validation result, and rules can
+//! be easily composed together.
+//!
+//! The `dotfiles-tools` binary is a thin CLI over this library. Scripts in
+//! `scripts/` can reuse the same primitives by depending on it by path:
+//!
+//! ```toml
+//! dotfiles-tools = { path = "../dotfiles-tools" }
+//! ```
+
+pub mod backup;
+pub mod bootstrap;
+pub mod cache;
+pub mod codes;
+#[cfg(unix)]
+pub mod daemon;
+pub mod deploy;
+pub mod diff;
+pub mod doctor;
+pub mod dotter;
+pub mod drift;
+pub mod fixes;
+pub mod format;
+pub mod gitconfig;
+pub mod history;
+pub mod hooks;
+pub mod interrupt;
+pub mod linters;
+pub mod manage;
+pub mod output;
+#[cfg(unix)]
+pub mod pager;
+pub mod paths;
+pub mod plugins;
+pub mod profiles;
+pub mod repo;
+pub mod rules;
+pub mod schedule;
+pub mod scripting;
+pub mod settings;
+pub mod shell;
+pub mod snippet;
+pub mod stats;
+pub mod summary;
+pub mod sync;
+pub mod template;
+pub mod types;
+pub mod validator;
+pub mod watch;
+pub mod webhook;
+
+#[cfg(test)]
+mod test_support;
@@ -0,0 +1,452 @@
+//! External linters configured in the `[linters]` settings section.
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde_json::Value;
+use tracing::{debug, info_span};
+
+use std::{
+    fs, io,
+    path::Path,
+    process::{Command, Output},
+    sync::{
+        Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+    thread,
+};
+
+use crate::{
+    cache::content_hash,
+    fixes::shell_quote,
+    paths::glob_to_regex,
+    repo::{find_executable, get_content_files, is_binary_file},
+    settings::{JsonFields, LinterConfig, LinterParser},
+    types::{Config, Issue, Severity, ValidationResult},
+};
+
+struct Invocation {
+    linter: usize,
+    files: Vec<String>,
+    args: Vec<String>,
+    // Content hash of the one file a per-file invocation checks, for the cache
+    hash: Option<u64>,
+}
+
+// Linters spell severities many ways; anything unrecognized falls back to
+// the linter's configured severity
+pub fn parse_severity(value: &str) -> Option<Severity> {
+    match value.to_ascii_lowercase().as_str() {
+        "error" | "fatal" | "e" => Some(Severity::Error),
+        "warning" | "warn" | "w" => Some(Severity::Warning),
+        "info" | "information" | "note" | "i" => Some(Severity::Info),
+        "hint" | "style" | "convention" => Some(Severity::Hint),
+        _ => None,
+    }
+}
+
+fn command_args(command: &[String], files: &[String]) -> Vec<String> {
+    if command.iter().any(|a| a == "{file}") {
+        return command
+            .iter()
+            .map(|a| a.replace("{file}", &files[0]))
+            .collect();
+    }
+    if !command.iter().any(|a| a == "{files}") {
+        return command.iter().chain(files).cloned().collect();
+    }
+    command
+        .iter()
+        .flat_map(|a| {
+            if a == "{files}" {
+                files.to_vec()
+            } else {
+                vec![a.clone()]
+            }
+        })
+        .collect()
+}
+
+fn is_installed(config: &Config, program: &str) -> bool {
+    if program.contains('/') {
+        config.dotfiles_dir.join(program).exists() || Path::new(program).exists()
+    } else {
+        find_executable(program).is_some()
+    }
+}
+
+struct Finding {
+    file: Option<String>,
+    line: Option<usize>,
+    column: Option<usize>,
+    message: String,
+    code: Option<String>,
+    severity: Option<Severity>,
+}
+
+impl Finding {
+    fn into_issue(self, linter: &LinterConfig, checked: &[String]) -> Issue {
+        // Linters that check one file at a time often leave the name out
+        let file = self
+            .file
+            .or_else(|| (checked.len() == 1).then(|| checked[0].clone()));
+        let message = match (&file, self.line) {
+            (Some(file), Some(line)) => format!("{}:{}: {}", file, line, self.message),
+            (Some(file), None) => format!("{}: {}", file, self.message),
+            _ => self.message,
+        };
+        let mut issue = Issue::new(self.severity.unwrap_or(linter.severity), message);
+        if let Some(file) = file {
+            issue = issue.with_file(file);
+        }
+        if let Some(code) = self.code {
+            issue = issue.with_code(code);
+        }
+        if let Some(line) = self.line {
+            issue = issue.at(line, self.column.unwrap_or(1));
+        }
+        issue
+    }
+}
+
+fn regex_findings(pattern: &Regex, output: &str) -> Vec<Finding> {
+    output
+        .lines()
+        .filter_map(|line| pattern.captures(line))
+        .map(|caps| {
+            let group = |name: &str| caps.name(name).map(|m| m.as_str().to_string());
+            Finding {
+                file: group("file"),
+                line: group("line").and_then(|l| l.parse().ok()),
+                column: group("column").and_then(|c| c.parse().ok()),
+                message: group("message").unwrap_or_else(|| caps[0].to_string()),
+                code: group("code"),
+                severity: group("severity").and_then(|s| parse_severity(&s)),
+            }
+        })
+        .collect()
+}
+
+fn json_string(item: &Value, pointer: &str) -> Option<String> {
+    match item.pointer(pointer)? {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+fn json_findings(fields: &JsonFields, output: &str) -> Vec<Finding> {
+    let items: Vec<Value> = if fields.items.is_empty() {
+        match serde_json::from_str::<Value>(output) {
+            Ok(Value::Array(items)) => items,
+            Ok(item) => vec![item],
+            // JSON lines: one finding per line
+            Err(_) => output
+                .lines()
+                .filter_map(|l| serde_json::from_str(l).ok())
+                .collect(),
+        }
+    } else {
+        serde_json::from_str::<Value>(output)
+            .ok()
+            .and_then(|v| v.pointer(&fields.items).and_then(Value::as_array).cloned())
+            .unwrap_or_default()
+    };
+
+    items
+        .iter()
+        .filter_map(|item| {
+            Some(Finding {
+                message: json_string(item, &fields.message)?,
+                file: json_string(item, &fields.file),
+                line: json_string(item, &fields.line).and_then(|l| l.parse().ok()),
+                column: json_string(item, &fields.column).and_then(|c| c.parse().ok()),
+                code: json_string(item, &fields.code),
+                severity: json_string(item, &fields.severity).and_then(|s| parse_severity(&s)),
+            })
+        })
+        .collect()
+}
+
+fn invocation_issues(
+    name: &str,
+    linter: &LinterConfig,
+    pattern: Option<&Regex>,
+    invocation: &Invocation,
+    output: io::Result<Output>,
+) -> Vec<Issue> {
+    let output = match output {
+        Ok(output) => output,
+        Err(err) => {
+            return vec![Issue::new(
+                Severity::Error,
+                format!("Failed to run linter {}: {}", name, err),
+            )];
+        }
+    };
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    let findings = match (linter.parser, pattern) {
+        (LinterParser::Regex, Some(pattern)) => {
+            let mut findings = regex_findings(pattern, &stdout);
+            findings.extend(regex_findings(pattern, &stderr));
+            findings
+        }
+        (LinterParser::Json, _) => json_findings(&linter.json, &stdout),
+        _ => Vec::new(),
+    };
+    let mut issues: Vec<_> = findings
+        .into_iter()
+        .map(|f| f.into_issue(linter, &invocation.files))
+        .collect();
+
+    // A failing exit that the parser couldn't explain still gets reported
+    if issues.is_empty() && !output.status.success() {
+        let detail = stdout
+            .lines()
+            .chain(stderr.lines())
+            .map(str::trim)
+            .find(|l| !l.is_empty())
+            .map(|l| format!(": {}", l))
+            .unwrap_or_default();
+        let target = match invocation.files.as_slice() {
+            [file] => file.clone(),
+            files => format!("{} files", files.len()),
+        };
+        let mut issue = Issue::new(
+            linter.severity,
+            format!("{} reported problems in {}{}", name, target, detail),
+        )
+        .with_fix(format!(
+            "Reproduce with: {}",
+            invocation
+                .args
+                .iter()
+                .map(|a| shell_quote(a))
+                .collect::<Vec<_>>()
+                .join(" ")
+        ));
+        if let [file] = invocation.files.as_slice() {
+            issue = issue.with_file(file.clone());
+        }
+        issues.push(issue);
+    }
+    issues
+}
+
+// Linters read files from the working tree, so with --staged they see
+// unstaged edits too
+pub fn run_linters(config: &Config) -> Result<Vec<ValidationResult>> {
+    let settings = &config.settings.linters;
+    if settings.linters.is_empty() {
+        return Ok(Vec::new());
+    }
+    let candidates = get_content_files(config)?;
+
+    let mut results = Vec::new();
+    let mut patterns = Vec::new();
+    let mut invocations = Vec::new();
+    let mut checked = vec![false; settings.linters.len()];
+    let mut cached: Vec<Vec<Issue>> = vec![Vec::new(); settings.linters.len()];
+    for (idx, (name, linter)) in settings.linters.iter().enumerate() {
+        let Some(program) = linter.command.first() else {
+            anyhow::bail!("linters.{}.command is empty", name);
+        };
+        patterns.push(match (&linter.pattern, linter.parser) {
+            (Some(pattern), _) => Some(
+                Regex::new(pattern)
+                    .with_context(|| format!("Invalid pattern for linter {}", name))?,
+            ),
+            (None, LinterParser::Regex) => {
+                anyhow::bail!("linters.{} uses the regex parser but has no pattern", name)
+            }
+            (None, _) => None,
+        });
+        let globs = linter
+            .files
+            .iter()
+            .map(|g| glob_to_regex(g))
+            .collect::<Result<Vec<_>, _>>()
+            .with_context(|| format!("Invalid files glob for linter {}", name))?;
+
+        let files: Vec<String> = candidates
+            .iter()
+            .filter(|f| globs.iter().any(|g| g.is_match(f)) && !is_binary_file(config, f))
+            .cloned()
+            .collect();
+        if files.is_empty() {
+            continue;
+        }
+        if !is_installed(config, program) {
+            debug!(linter = %name, program, "skipping: not installed");
+            let mut result = ValidationResult::skipped(
+                &format!("linter:{}", name),
+                format!("{} not installed", program),
+            );
+            result.rule_name = format!("Linter: {}", name);
+            result.passed = true;
+            results.push((idx, result));
+            continue;
+        }
+
+        checked[idx] = true;
+        if !linter.command.iter().any(|a| a == "{file}") {
+            invocations.push(Invocation {
+                linter: idx,
+                args: command_args(&linter.command, &files),
+                files,
+                hash: None,
+            });
+            continue;
+        }
+        // Per-file runs can reuse results for files that haven't changed
+        for file in files {
+            let hash = fs::read_to_string(config.dotfiles_dir.join(&file))
+                .ok()
+                .map(|content| content_hash(&content));
+            let rule = format!("linter:{}", name);
+            if let Some(issues) = config
+                .cache
+                .as_ref()
+                .zip(hash)
+                .and_then(|(cache, hash)| cache.get(&rule, &file, hash))
+            {
+                cached[idx].extend(issues);
+                continue;
+            }
+            let files = vec![file];
+            invocations.push(Invocation {
+                linter: idx,
+                args: command_args(&linter.command, &files),
+                files,
+                hash,
+            });
+        }
+    }
+
+    let jobs = settings
+        .jobs
+        .or_else(|| thread::available_parallelism().ok().map(|n| n.get()))
+        .unwrap_or(1)
+        .max(1);
+    let next = AtomicUsize::new(0);
+    let outputs: Vec<Mutex<Option<io::Result<Output>>>> =
+        invocations.iter().map(|_| Mutex::new(None)).collect();
+    thread::scope(|scope| {
+        for _ in 0..jobs.min(invocations.len()) {
+            scope.spawn(|| {
+                loop {
+                    let idx = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(invocation) = invocations.get(idx) else {
+                        break;
+                    };
+                    let output = Command::new(&invocation.args[0])
+                        .args(&invocation.args[1..])
+                        .current_dir(&config.dotfiles_dir)
+                        .output();
+                    *outputs[idx].lock().unwrap() = Some(output);
+                }
+            });
+        }
+    });
+
+    let names: Vec<_> = settings.linters.iter().collect();
+    for (idx, (name, linter)) in names.iter().enumerate() {
+        let ran: Vec<_> = invocations
+            .iter()
+            .zip(&outputs)
+            .filter(|(inv, _)| inv.linter == idx)
+            .collect();
+        if !checked[idx] {
+            continue;
+        }
+        let _span = info_span!("linter", name = %name).entered();
+        let mut issues = std::mem::take(&mut cached[idx]);
+        for (invocation, output) in ran {
+            let output = output
+                .lock()
+                .unwrap()
+                .take()
+                .expect("every invocation runs");
+            // A linter that couldn't start says nothing about the file
+            let cacheable = output.is_ok();
+            let found = invocation_issues(name, linter, patterns[idx].as_ref(), invocation, output);
+            if let (Some(cache), Some(hash), true) = (&config.cache, invocation.hash, cacheable) {
+                cache.insert(
+                    &format!("linter:{}", name),
+                    &invocation.files[0],
+                    hash,
+                    &found,
+                );
+            }
+            issues.extend(found);
+        }
+        let passed = issues.iter().all(|i| i.severity != Severity::Error);
+        let mut result = ValidationResult::new(format!("Linter: {}", name), passed, issues);
+        result.rule_id = format!("linter:{}", name);
+        results.push((idx, result));
+    }
+
+    results.sort_by_key(|(idx, _)| *idx);
+    Ok(results.into_iter().map(|(_, r)| r).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{settings::Settings, test_support::FixtureRepo};
+
+    #[test]
+    fn json_findings_follow_the_configured_pointers() {
+        let fields = JsonFields {
+            items: "/problems".to_string(),
+            line: "/range/start".to_string(),
+            ..JsonFields::default()
+        };
+        let output = r#"{"problems":[{"file":"a.yml","range":{"start":4},
+            "message":"too many spaces","code":"E201","severity":"error"}]}"#;
+        let findings = json_findings(&fields, output);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].line, Some(4));
+        assert_eq!(findings[0].code.as_deref(), Some("E201"));
+        assert_eq!(findings[0].severity, Some(Severity::Error));
+    }
+
+    #[test]
+    fn linters_run_on_matching_files_and_skip_missing_tools() {
+        let repo = FixtureRepo::new();
+        repo.write("init.lua", "x = 1\n")
+            .write("notes.md", "# notes\n")
+            .write(
+                ".validate-dotfiles.toml",
+                r#"
+[linters]
+jobs = 2
+
+[linters.fake-lint]
+files = ["*.lua"]
+command = ["sh", "-c", "echo \"$1:3:5: [L001] bad thing\"; exit 1", "sh", "{file}"]
+parser = "regex"
+pattern = '^(?P<file>[^:]+):(?P<line>\d+):(?P<column>\d+): \[(?P<code>\w+)\] (?P<message>.*)$'
+
+[linters.missing]
+files = ["*.md"]
+command = ["definitely-not-a-real-linter"]
+"#,
+            )
+            .commit("fixture");
+        let settings: Settings = crate::settings::load_settings(repo.path()).unwrap();
+        let mut config = repo.config();
+        config.settings = settings;
+
+        let results = run_linters(&config).unwrap();
+        assert_eq!(results.len(), 2);
+        let fake = &results[0];
+        assert_eq!(fake.rule_id, "linter:fake-lint");
+        assert_eq!(fake.issues.len(), 1);
+        assert_eq!(fake.issues[0].location().as_deref(), Some("init.lua:3:5"));
+        assert_eq!(fake.issues[0].code.as_deref(), Some("L001"));
+        assert!(results[1].skipped.is_some() && results[1].passed);
+    }
+}
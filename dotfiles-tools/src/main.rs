@@ -0,0 +1,245 @@
+//! Command-line entry point for dotfiles-tools.
+
+mod cli;
+use anyhow::{Context, Result};
+use clap::{CommandFactory, Parser, error::ErrorKind};
+
+use std::{
+    env,
+    path::{Path, PathBuf},
+    sync::atomic::Ordering,
+};
+
+use dotfiles_tools::{
+    backup, bootstrap, codes, deploy, doctor, drift, hooks, interrupt,
+    manage::{self, MvOptions, RmOptions},
+    output::{ASCII_ENABLED, COLOR_ENABLED, Color, Symbols, init_tracing, locale_is_ascii},
+    profiles,
+    repo::check_environment,
+    schedule,
+    settings::load_settings,
+    stats,
+    sync::{self, SyncBackOptions},
+    types::{Config, ExitCode, GitMissing},
+    validator, watch,
+};
+
+use crate::cli::{Cli, Commands};
+
+fn run(cli: Cli) -> Result<i32> {
+    COLOR_ENABLED.store(cli.color.resolve(), Ordering::Relaxed);
+    ASCII_ENABLED.store(cli.ascii || locale_is_ascii(), Ordering::Relaxed);
+    let _pager = if cli.no_pager || !cli.command.pages_output() {
+        None
+    } else {
+        start_pager()
+    };
+    init_tracing(cli.verbose, cli.log_json);
+
+    // Explaining a code doesn't involve any repository
+    if let Commands::Explain { code } = &cli.command {
+        return codes::explain(code.as_deref());
+    }
+
+    let dirs = if cli.dirs.is_empty() {
+        vec![
+            env::var("DOTFILES_DIR")
+                .map(PathBuf::from)
+                .unwrap_or_else(|_| env::current_dir().expect("Failed to get current directory")),
+        ]
+    } else {
+        cli.dirs
+    };
+    let mut configs = Vec::new();
+    for dir in dirs {
+        let dir = std::path::absolute(&dir)
+            .with_context(|| format!("Invalid dotfiles directory {}", dir.display()))?;
+        let settings = load_settings(&dir)?;
+        configs.push(Config::new(dir, cli.verbose, settings));
+    }
+
+    match cli.command {
+        Commands::Validate(args) => {
+            for config in &mut configs {
+                args.apply(config);
+            }
+            if args.daemon {
+                serve_daemon(single_root(configs)?, args.socket.as_deref())
+            } else if args.watch {
+                watch::watch(configs)
+            } else {
+                interrupt::install();
+                validator::validate_roots(configs)
+            }
+        }
+        Commands::Fix(args) => {
+            for config in &mut configs {
+                args.apply(config);
+                config.apply_mode = true;
+            }
+            if args.watch {
+                watch::watch(configs)
+            } else {
+                interrupt::install();
+                validator::validate_roots(configs)
+            }
+        }
+        Commands::Doctor => {
+            let mut exit_code = ExitCode::CLEAN;
+            for config in &configs {
+                println!(
+                    "\n{}Checking tools referenced in {}...{}\n",
+                    Color::bold(),
+                    config.dotfiles_dir.display(),
+                    Color::reset()
+                );
+                check_environment(config)?;
+                exit_code = exit_code.max(doctor::doctor(config)?);
+            }
+            Ok(exit_code)
+        }
+        Commands::Drift => {
+            let mut exit_code = ExitCode::CLEAN;
+            for config in configs {
+                check_environment(&config)?;
+                exit_code = exit_code.max(drift::drift(config)?);
+            }
+            Ok(exit_code)
+        }
+        Commands::SyncBack { dry_run } => {
+            let config = single_root(configs)?;
+            check_environment(&config)?;
+            sync::sync_back(&config, &SyncBackOptions { dry_run })
+        }
+        Commands::Profiles { profiles } => {
+            let config = single_root(configs)?;
+            check_environment(&config)?;
+            profiles::profiles(config, &profiles)
+        }
+        Commands::Stats => {
+            let config = single_root(configs)?;
+            check_environment(&config)?;
+            stats::stats(&config)
+        }
+        Commands::Adopt(args) => {
+            let config = single_root(configs)?;
+            check_environment(&config)?;
+            let path = args.path.clone();
+            manage::adopt(&config, &path, &args.into_options())
+        }
+        Commands::Add(args) => {
+            let config = single_root(configs)?;
+            check_environment(&config)?;
+            let (source, target) = (args.source.clone(), args.target.clone());
+            manage::add(&config, &source, &target, &args.into_options())
+        }
+        Commands::Mv { old, new, dry_run } => {
+            let config = single_root(configs)?;
+            check_environment(&config)?;
+            manage::mv(&config, &old, &new, &MvOptions { dry_run })
+        }
+        Commands::Rm { path, dry_run } => {
+            let config = single_root(configs)?;
+            check_environment(&config)?;
+            manage::rm(&config, &path, &RmOptions { dry_run })
+        }
+        Commands::CleanLinks { dry_run } => {
+            let config = single_root(configs)?;
+            check_environment(&config)?;
+            drift::clean_links(&config, dry_run)
+        }
+        Commands::Deploy(args) => deploy::deploy(single_root(configs)?, &args.into_options()),
+        Commands::Bootstrap(args) => {
+            bootstrap::bootstrap(single_root(configs)?, &args.into_options())
+        }
+        Commands::Restore { snapshot } => {
+            let config = single_root(configs)?;
+            check_environment(&config)?;
+            backup::restore(&config, snapshot.as_deref())
+        }
+        Commands::Schedule(args) => {
+            schedule::schedule(&single_root(configs)?, &args.into_options())
+        }
+        Commands::Explain { .. } => unreachable!("handled before loading settings"),
+        Commands::InstallHooks { hooks_path, force } => {
+            hooks::install_hooks(&single_root(configs)?, hooks_path.as_deref(), force)?;
+            Ok(ExitCode::CLEAN)
+        }
+    }
+}
+
+// Bootstrapping, deploying, restoring, scheduling, and installing hooks only
+// make sense for one repo at a time
+fn single_root(configs: Vec<Config>) -> Result<Config> {
+    let count = configs.len();
+    match <[Config; 1]>::try_from(configs) {
+        Ok([config]) => Ok(config),
+        Err(_) => anyhow::bail!(
+            "This command takes a single --dir, but {} were given",
+            count
+        ),
+    }
+}
+
+#[cfg(unix)]
+fn serve_daemon(config: Config, socket: Option<&Path>) -> Result<i32> {
+    dotfiles_tools::daemon::serve(config, socket)
+}
+
+#[cfg(not(unix))]
+fn serve_daemon(_config: Config, _socket: Option<&Path>) -> Result<i32> {
+    anyhow::bail!("--daemon needs Unix domain sockets, which this platform lacks")
+}
+
+#[cfg(unix)]
+fn start_pager() -> Option<dotfiles_tools::pager::Pager> {
+    dotfiles_tools::pager::Pager::start()
+}
+
+#[cfg(not(unix))]
+fn start_pager() -> Option<()> {
+    None
+}
+
+fn main() {
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        Err(err) => {
+            let _ = err.print();
+            std::process::exit(if err.use_stderr() {
+                ExitCode::USAGE
+            } else {
+                ExitCode::CLEAN
+            });
+        }
+    };
+    // fix shares validate's flags, but only validate can run as a daemon
+    if let Commands::Fix(args) = &cli.command
+        && args.daemon
+    {
+        let _ = Cli::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                "--daemon only works with validate, not fix",
+            )
+            .print();
+        std::process::exit(ExitCode::USAGE);
+    }
+
+    let exit_code = run(cli).unwrap_or_else(|err| {
+        eprintln!(
+            "{}{} {:#}{}",
+            Color::red(),
+            Symbols::failure(),
+            err,
+            Color::reset()
+        );
+        if err.downcast_ref::<GitMissing>().is_some() {
+            ExitCode::GIT_MISSING
+        } else {
+            ExitCode::INTERNAL
+        }
+    });
+
+    std::process::exit(exit_code);
+}
@@ -0,0 +1,771 @@
+//! Commands that bring files into the repo, create, move and remove them
+//! there, keeping dotter's `files` mappings and the git index in step with
+//! the working tree.
+
+use anyhow::{Context, Result};
+
+use std::{
+    collections::BTreeSet,
+    fs,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    deploy::{DeployOptions, deploy},
+    dotter::{
+        DotterFile, mapped_files, package_config_file, platform_config_files, selected_packages,
+        source_for_target, tilde_path,
+    },
+    output::{info, success, warning},
+    paths::{is_link, normalize_path, relative_path},
+    repo::{expand_home, git_add, git_mv, git_rm, home_dir},
+    rules::references::sourced_paths,
+    types::{Config, ExitCode},
+};
+
+/// A `[package.files]` table line mapping `source` to `target`.
+fn mapping_line(source: &str, target: &str) -> String {
+    format!(
+        "{} = {}",
+        toml::Value::String(source.to_string()),
+        toml::Value::String(target.to_string())
+    )
+}
+
+/// `content` of a dotter config with `source` mapped to `target` in the
+/// package's files table: after its last entry, or in a new table at the end.
+/// Comments and layout elsewhere are left alone.
+fn insert_file_mapping(content: &str, package: &str, source: &str, target: &str) -> String {
+    let header = format!("[{}.files]", package);
+    let mut lines: Vec<String> = content.lines().map(String::from).collect();
+    match lines.iter().position(|l| l.trim() == header) {
+        Some(start) => {
+            let end = lines[start + 1..]
+                .iter()
+                .position(|l| l.trim_start().starts_with('['))
+                .map_or(lines.len(), |i| start + 1 + i);
+            // Comments and blank lines before the next table belong to it
+            let last_entry = (start..end)
+                .rev()
+                .find(|&i| {
+                    let line = lines[i].trim();
+                    !line.is_empty() && !line.starts_with('#')
+                })
+                .unwrap_or(start);
+            lines.insert(last_entry + 1, mapping_line(source, target));
+        }
+        None => {
+            if lines.last().is_some_and(|l| !l.trim().is_empty()) {
+                lines.push(String::new());
+            }
+            lines.push(header);
+            lines.push(mapping_line(source, target));
+        }
+    }
+    lines.join("\n") + "\n"
+}
+
+/// Adds the mapping to the package's dotter config, refusing to write a
+/// config that no longer parses. Returns the config's repo-relative path.
+fn add_mapping(config: &Config, package: &str, source: &str, target: &str) -> Result<String> {
+    let path = package_config_file(config, package)?;
+    let content = fs::read_to_string(&path).unwrap_or_default();
+    let updated = insert_file_mapping(&content, package, source, target);
+    toml::from_str::<toml::Table>(&updated)
+        .with_context(|| format!("Adding {} would break {}", source, path.display()))?;
+    fs::write(&path, updated).with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path
+        .strip_prefix(&config.dotfiles_dir)
+        .unwrap_or(&path)
+        .display()
+        .to_string())
+}
+
+// Packages are named after the app: the directory under ~/.config, or the
+// dotfile's name without its dot, extension and `rc`
+fn default_package(relative: &str) -> String {
+    let name = match relative.strip_prefix(".config/") {
+        Some(rest) => rest.split('/').next().unwrap_or(rest),
+        None => relative.split('/').next().unwrap_or(relative),
+    };
+    let name = name.trim_start_matches('.');
+    let name = name.split_once('.').map_or(name, |(stem, _)| stem);
+    name.strip_suffix("rc")
+        .filter(|stem| !stem.is_empty())
+        .unwrap_or(name)
+        .to_string()
+}
+
+// Sources mirror ~/.config; anything else in $HOME goes under its package's
+// directory in .config
+fn default_source(relative: &str, package: &str) -> String {
+    if relative.starts_with(".config/") {
+        relative.to_string()
+    } else {
+        format!(".config/{}/{}", package, relative)
+    }
+}
+
+pub struct AdoptOptions {
+    pub package: Option<String>,
+    // Repo path to move the file to, instead of the conventional one
+    pub source: Option<String>,
+    pub deploy: bool,
+    pub dry_run: bool,
+}
+
+// The `adopt` command: move a file from $HOME into the repo and map it back
+pub fn adopt(config: &Config, path: &Path, options: &AdoptOptions) -> Result<i32> {
+    let home = home_dir().context("HOME is not set")?;
+    let path =
+        std::path::absolute(path).with_context(|| format!("Invalid path {}", path.display()))?;
+    let relative = path
+        .strip_prefix(&home)
+        .with_context(|| format!("{} is not under {}", path.display(), home.display()))?
+        .display()
+        .to_string();
+    let metadata =
+        fs::symlink_metadata(&path).with_context(|| format!("Cannot read {}", path.display()))?;
+    if is_link(&metadata) || !metadata.is_file() {
+        anyhow::bail!("{} is not a regular file", path.display());
+    }
+
+    let target = format!("~/{}", relative);
+    if let Some(source) = source_for_target(&mapped_files(config)?, &target) {
+        anyhow::bail!("{} is already deployed from {}", target, source);
+    }
+    let package = options
+        .package
+        .clone()
+        .unwrap_or_else(|| default_package(&relative));
+    let source = options
+        .source
+        .clone()
+        .unwrap_or_else(|| default_source(&relative, &package));
+    let destination = config.dotfiles_dir.join(&source);
+    if destination.exists() {
+        anyhow::bail!("{} already exists in the repo", source);
+    }
+
+    if options.dry_run {
+        info(&format!("Would move {} -> {}", target, source));
+        info(&format!(
+            "Would map {} to {} in package '{}' of {}",
+            source,
+            target,
+            package,
+            package_config_file(config, &package)?.display()
+        ));
+        return Ok(ExitCode::CLEAN);
+    }
+
+    if let Some(parent) = destination.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    move_file(&path, &destination)?;
+    let dotter_config = add_mapping(config, &package, &source, &target)?;
+    git_add(config, &[&source, &dotter_config])?;
+    success(&format!(
+        "Adopted {} as {} in package '{}'",
+        target, source, package
+    ));
+
+    if !selected_packages(config)?.contains(&package) {
+        warning(&format!(
+            "Package '{}' isn't selected in .dotter/local.toml, so dotter won't deploy it here",
+            package
+        ));
+        return Ok(ExitCode::WARNINGS);
+    }
+    if options.deploy {
+        return deploy(
+            config.clone(),
+            &DeployOptions {
+                dry_run: false,
+                force: false,
+                validate: false,
+                dotter_args: Vec::new(),
+            },
+        );
+    }
+    info("Run: dotfiles-tools deploy to link it back into place");
+    Ok(ExitCode::CLEAN)
+}
+
+pub struct AddOptions {
+    pub package: Option<String>,
+    pub dry_run: bool,
+}
+
+// The `~/x` form dotter configs use for targets under $HOME
+fn home_relative_target(target: &str) -> String {
+    let target = tilde_path(target);
+    match home_dir().and_then(|home| {
+        Path::new(&target)
+            .strip_prefix(home)
+            .ok()
+            .map(|rest| format!("~/{}", rest.display()))
+    }) {
+        Some(relative) => relative,
+        None => target,
+    }
+}
+
+// The `add` command: create a repo file, map it in dotter, and stage both
+pub fn add(config: &Config, source: &str, target: &str, options: &AddOptions) -> Result<i32> {
+    let source = source.trim_start_matches("./").to_string();
+    let target = home_relative_target(target);
+    let mapped = mapped_files(config)?;
+    if let Some(existing) = mapped.iter().find(|f| f.source == source) {
+        anyhow::bail!("{} is already mapped to {}", source, existing.target);
+    }
+    if let Some(existing) = source_for_target(&mapped, &target) {
+        anyhow::bail!("{} is already deployed from {}", target, existing);
+    }
+    let package = options
+        .package
+        .clone()
+        .unwrap_or_else(|| default_package(target.strip_prefix("~/").unwrap_or(&source)));
+    let path = config.dotfiles_dir.join(&source);
+
+    if options.dry_run {
+        if !path.exists() {
+            info(&format!("Would create {}", source));
+        }
+        info(&format!(
+            "Would map {} to {} in package '{}' of {}",
+            source,
+            target,
+            package,
+            package_config_file(config, &package)?.display()
+        ));
+        return Ok(ExitCode::CLEAN);
+    }
+
+    // An existing file keeps its content; add only maps and stages it
+    if !path.exists() {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        fs::write(&path, "").with_context(|| format!("Failed to create {}", path.display()))?;
+    }
+    let dotter_config = add_mapping(config, &package, &source, &target)?;
+    git_add(config, &[&source, &dotter_config])?;
+    success(&format!(
+        "Added {} -> {} to package '{}'",
+        source, target, package
+    ));
+
+    if !selected_packages(config)?.contains(&package) {
+        warning(&format!(
+            "Package '{}' isn't selected in .dotter/local.toml, so dotter won't deploy it here",
+            package
+        ));
+        return Ok(ExitCode::WARNINGS);
+    }
+    Ok(ExitCode::CLEAN)
+}
+
+// Where `path` ends up when `old` moves to `new`: itself, or something
+// under a moved directory
+fn moved_path(path: &str, old: &str, new: &str) -> Option<String> {
+    if path == old {
+        return Some(new.to_string());
+    }
+    path.strip_prefix(&format!("{}/", old))
+        .map(|rest| format!("{}/{}", new, rest))
+}
+
+// The key of a `key = value` line, unquoted, and the byte range it's
+// written in
+fn mapping_key(line: &str) -> Option<(String, Range<usize>)> {
+    let start = line.len() - line.trim_start().len();
+    let rest = &line[start..];
+    let len = match rest.chars().next()? {
+        '"' => {
+            let bytes = rest.as_bytes();
+            (1..bytes.len()).find(|&i| bytes[i] == b'"' && bytes[i - 1] != b'\\')? + 1
+        }
+        '\'' => rest[1..].find('\'')? + 2,
+        _ => rest.find(|c: char| c.is_whitespace() || c == '=')?,
+    };
+    let written = &rest[..len];
+    let key = match toml::from_str::<toml::Table>(&format!("{} = 0", written)) {
+        Ok(table) => table.keys().next()?.clone(),
+        Err(_) => return None,
+    };
+    Some((key, start..start + len))
+}
+
+/// `content` of a dotter config with every files-table source that is `old`,
+/// or under it, renamed to the same path under `new` or dropped when there's
+/// no `new`; and how many were.
+fn update_file_mappings(content: &str, old: &str, new: Option<&str>) -> (String, usize) {
+    let mut updated = 0;
+    let mut in_files = false;
+    let mut lines = Vec::new();
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            in_files = trimmed.ends_with(".files]");
+        } else if in_files
+            && let Some((key, range)) = mapping_key(line)
+            && let Some(moved) = moved_path(&key, old, new.unwrap_or(old))
+        {
+            updated += 1;
+            if new.is_some() {
+                lines.push(format!(
+                    "{}{}{}",
+                    &line[..range.start],
+                    toml::Value::String(moved),
+                    &line[range.end..]
+                ));
+            }
+            continue;
+        }
+        lines.push(line.to_string());
+    }
+    (lines.join("\n") + "\n", updated)
+}
+
+// How a path written in `file` has to change now that its source moved and
+// `file` itself is at `moved_file`; `None` when it's spelled as a deployed
+// target, or relative and moved along with what it names, which the move
+// leaves alone
+fn rewritten_reference(
+    config: &Config,
+    file: &str,
+    moved_file: &str,
+    written: &str,
+    source: &str,
+    moved: &str,
+) -> Option<String> {
+    // $DOTFILES/x, ~/.dotfiles/x and the like name the repo path itself
+    let repo_root = config.dotfiles_dir.display().to_string();
+    let under_home = home_dir().and_then(|home| {
+        config
+            .dotfiles_dir
+            .strip_prefix(home)
+            .ok()
+            .map(|r| format!("~/{}", r.display()))
+    });
+    if let Some(prefix) = written.strip_suffix(source)
+        && let Some(root) = prefix.strip_suffix('/')
+        && (tilde_path(root) == repo_root
+            || Some(tilde_path(root)) == under_home
+            || root.trim_matches(['$', '{', '}']).starts_with("DOTFILES"))
+    {
+        return Some(format!("{}{}", prefix, moved));
+    }
+    // Relative to the including file, in a config read from the repo
+    let parent = |file: &str| {
+        config
+            .dotfiles_dir
+            .join(Path::new(file).parent().unwrap_or(Path::new("")))
+    };
+    let (dir, moved_dir) = (parent(file), parent(moved_file));
+    let moved = config.dotfiles_dir.join(moved);
+    if normalize_path(&dir.join(written)) != config.dotfiles_dir.join(source)
+        || normalize_path(&moved_dir.join(written)) == moved
+    {
+        return None;
+    }
+    Some(relative_path(&moved_dir, &moved).display().to_string())
+}
+
+pub struct MvOptions {
+    pub dry_run: bool,
+}
+
+// The `mv` command: `git mv`, then repoint dotter and sourced references
+pub fn mv(config: &Config, old: &str, new: &str, options: &MvOptions) -> Result<i32> {
+    let old = old.trim_start_matches("./").trim_end_matches('/');
+    let new = new.trim_start_matches("./").trim_end_matches('/');
+    if !config.dotfiles_dir.join(old).exists() {
+        anyhow::bail!("{} doesn't exist in the repo", old);
+    }
+    if config.dotfiles_dir.join(new).exists() {
+        anyhow::bail!("{} already exists in the repo", new);
+    }
+
+    let mut dotter_configs = Vec::new();
+    for path in platform_config_files(config)? {
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let (updated, renamed) = update_file_mappings(&content, old, Some(new));
+        if renamed > 0 {
+            toml::from_str::<toml::Table>(&updated)
+                .with_context(|| format!("Renaming {} would break {}", old, path.display()))?;
+            dotter_configs.push((path, updated, renamed));
+        }
+    }
+
+    // (file after the move, line, column, as written, rewritten)
+    let mut rewrites = Vec::new();
+    for reference in sourced_paths(config)? {
+        let Some(moved) = moved_path(&reference.source, old, new) else {
+            continue;
+        };
+        let file = moved_path(&reference.file, old, new).unwrap_or(reference.file.clone());
+        let Some(rewritten) = rewritten_reference(
+            config,
+            &reference.file,
+            &file,
+            &reference.written,
+            &reference.source,
+            &moved,
+        ) else {
+            continue;
+        };
+        rewrites.push((
+            file,
+            reference.line,
+            reference.column,
+            reference.written,
+            rewritten,
+        ));
+    }
+
+    if options.dry_run {
+        info(&format!("Would run: git mv {} {}", old, new));
+        for (path, _, renamed) in &dotter_configs {
+            info(&format!(
+                "Would update {} mapping(s) in {}",
+                renamed,
+                path.display()
+            ));
+        }
+        for (file, line, _, written, rewritten) in &rewrites {
+            info(&format!(
+                "Would rewrite {}:{}: {} -> {}",
+                file, line, written, rewritten
+            ));
+        }
+        return Ok(ExitCode::CLEAN);
+    }
+
+    git_mv(config, old, new)?;
+    let mut staged = Vec::new();
+    for (path, updated, renamed) in &dotter_configs {
+        fs::write(path, updated).with_context(|| format!("Failed to write {}", path.display()))?;
+        staged.push(
+            path.strip_prefix(&config.dotfiles_dir)
+                .unwrap_or(path)
+                .display()
+                .to_string(),
+        );
+        info(&format!(
+            "Updated {} mapping(s) in {}",
+            renamed,
+            path.display()
+        ));
+    }
+    for (file, line, column, written, rewritten) in &rewrites {
+        let path = config.dotfiles_dir.join(file);
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let mut lines: Vec<String> = content.split('\n').map(String::from).collect();
+        let Some(text) = lines.get_mut(line - 1) else {
+            continue;
+        };
+        let from = column.saturating_sub(1).min(text.len());
+        let Some(offset) = text
+            .get(from..)
+            .and_then(|rest| rest.find(written.as_str()))
+        else {
+            continue;
+        };
+        text.replace_range(from + offset..from + offset + written.len(), rewritten);
+        fs::write(&path, lines.join("\n"))
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        info(&format!(
+            "Rewrote {}:{}: {} -> {}",
+            file, line, written, rewritten
+        ));
+        staged.push(file.clone());
+    }
+    if !staged.is_empty() {
+        let staged: Vec<_> = staged.iter().map(String::as_str).collect();
+        git_add(config, &staged)?;
+    }
+
+    success(&format!("Moved {} -> {}", old, new));
+    Ok(ExitCode::CLEAN)
+}
+
+pub struct RmOptions {
+    pub dry_run: bool,
+}
+
+// Deployed symlinks that point at `path` or something under it; templates
+// are deployed as copies and stay where they are
+fn links_into(config: &Config, files: &[DotterFile], path: &str) -> Vec<PathBuf> {
+    let mut links = BTreeSet::new();
+    for file in files {
+        if moved_path(&file.source, path, path).is_none() {
+            continue;
+        }
+        let target = expand_home(&file.target);
+        let Ok(link) = fs::read_link(&target) else {
+            continue;
+        };
+        let resolved = normalize_path(&target.parent().unwrap_or(Path::new("/")).join(link));
+        if resolved == config.dotfiles_dir.join(&file.source) {
+            links.insert(target);
+        }
+    }
+    links.into_iter().collect()
+}
+
+// The `rm` command: `git rm`, then drop the mappings and deployed links
+pub fn rm(config: &Config, path: &str, options: &RmOptions) -> Result<i32> {
+    let path = path.trim_start_matches("./").trim_end_matches('/');
+    if !config.dotfiles_dir.join(path).exists() {
+        anyhow::bail!("{} doesn't exist in the repo", path);
+    }
+
+    let mut dotter_configs = Vec::new();
+    for config_path in platform_config_files(config)? {
+        let content = fs::read_to_string(&config_path)
+            .with_context(|| format!("Failed to read {}", config_path.display()))?;
+        let (updated, removed) = update_file_mappings(&content, path, None);
+        if removed > 0 {
+            toml::from_str::<toml::Table>(&updated).with_context(|| {
+                format!("Removing {} would break {}", path, config_path.display())
+            })?;
+            dotter_configs.push((config_path, updated, removed));
+        }
+    }
+    let links = links_into(config, &mapped_files(config)?, path);
+
+    if options.dry_run {
+        info(&format!("Would run: git rm -r {}", path));
+        for (config_path, _, removed) in &dotter_configs {
+            info(&format!(
+                "Would remove {} mapping(s) from {}",
+                removed,
+                config_path.display()
+            ));
+        }
+        for link in &links {
+            info(&format!("Would remove symlink {}", link.display()));
+        }
+        return Ok(ExitCode::CLEAN);
+    }
+
+    git_rm(config, path)?;
+    let mut staged = Vec::new();
+    for (config_path, updated, removed) in &dotter_configs {
+        fs::write(config_path, updated)
+            .with_context(|| format!("Failed to write {}", config_path.display()))?;
+        staged.push(
+            config_path
+                .strip_prefix(&config.dotfiles_dir)
+                .unwrap_or(config_path)
+                .display()
+                .to_string(),
+        );
+        info(&format!(
+            "Removed {} mapping(s) from {}",
+            removed,
+            config_path.display()
+        ));
+    }
+    if !staged.is_empty() {
+        let staged: Vec<_> = staged.iter().map(String::as_str).collect();
+        git_add(config, &staged)?;
+    }
+    for link in &links {
+        fs::remove_file(link).with_context(|| format!("Failed to remove {}", link.display()))?;
+        info(&format!("Removed symlink {}", link.display()));
+    }
+
+    success(&format!("Removed {}", path));
+    Ok(ExitCode::CLEAN)
+}
+
+// Renames, or copies and removes when the repo is on another filesystem
+fn move_file(from: &Path, to: &Path) -> Result<()> {
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    fs::copy(from, to).with_context(|| format!("Failed to copy {}", from.display()))?;
+    fs::remove_file(from).with_context(|| format!("Failed to remove {}", from.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::FixtureRepo;
+
+    #[test]
+    fn mappings_join_their_package_table_or_start_one() {
+        let content = "# Shells\n[zsh.files]\n\".config/shell/.zshrc\" = \"~/.zshrc\"\n\n\
+                       # Editors\n[helix.files]\n\".config/helix/config.toml\" = \"~/.config/helix/config.toml\"\n";
+        assert_eq!(
+            insert_file_mapping(content, "zsh", ".config/shell/.zprofile", "~/.zprofile"),
+            "# Shells\n[zsh.files]\n\".config/shell/.zshrc\" = \"~/.zshrc\"\n\
+             \".config/shell/.zprofile\" = \"~/.zprofile\"\n\n\
+             # Editors\n[helix.files]\n\".config/helix/config.toml\" = \"~/.config/helix/config.toml\"\n"
+        );
+        assert!(
+            insert_file_mapping(
+                content,
+                "kitty",
+                ".config/kitty/kitty.conf",
+                "~/.config/kitty/kitty.conf"
+            )
+            .ends_with(
+                "\n\n[kitty.files]\n\".config/kitty/kitty.conf\" = \"~/.config/kitty/kitty.conf\"\n"
+            )
+        );
+    }
+
+    #[test]
+    fn home_files_get_conventional_packages_and_sources() {
+        let found: Vec<_> = [
+            ".config/kitty/kitty.conf",
+            ".config/starship.toml",
+            ".zshrc",
+            ".tmux.conf",
+        ]
+        .iter()
+        .map(|relative| {
+            let package = default_package(relative);
+            let source = default_source(relative, &package);
+            (package, source)
+        })
+        .collect();
+        assert_eq!(
+            found,
+            [
+                ("kitty".to_string(), ".config/kitty/kitty.conf".to_string()),
+                ("starship".to_string(), ".config/starship.toml".to_string()),
+                ("zsh".to_string(), ".config/zsh/.zshrc".to_string()),
+                ("tmux".to_string(), ".config/tmux/.tmux.conf".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn add_creates_maps_and_stages_the_file() {
+        let repo = FixtureRepo::new();
+        repo.write(
+            ".dotter/global.toml",
+            "[zsh.files]\n\".config/zsh/.zshrc\" = \"~/.zshrc\"\n",
+        )
+        .write(".dotter/local.toml", "packages = [\"zsh\", \"kitty\"]\n")
+        .commit("fixture");
+        let config = repo.config();
+        let options = AddOptions {
+            package: None,
+            dry_run: false,
+        };
+
+        let code = add(
+            &config,
+            ".config/kitty/kitty.conf",
+            "~/.config/kitty/kitty.conf",
+            &options,
+        )
+        .unwrap();
+        assert_eq!(code, ExitCode::CLEAN);
+        assert!(repo.path().join(".config/kitty/kitty.conf").is_file());
+        assert_eq!(
+            fs::read_to_string(repo.path().join(".dotter/global.toml")).unwrap(),
+            "[zsh.files]\n\".config/zsh/.zshrc\" = \"~/.zshrc\"\n\n\
+             [kitty.files]\n\".config/kitty/kitty.conf\" = \"~/.config/kitty/kitty.conf\"\n"
+        );
+        assert_eq!(
+            repo.git(&["diff", "--cached", "--name-only"]),
+            ".config/kitty/kitty.conf\n.dotter/global.toml\n"
+        );
+        assert!(add(&config, ".config/zsh/.zshenv", "~/.zshrc", &options).is_err());
+    }
+
+    #[test]
+    fn mv_repoints_dotter_and_repo_relative_references() {
+        let repo = FixtureRepo::new();
+        repo.write(
+            ".dotter/global.toml",
+            "# Shells\n[zsh.files]\nzsh = \"~/.config/zsh\"\n\"zsh/.zshenv\" = { target = \"~/.zshenv\", type = \"symbolic\" }\n",
+        )
+        .write(
+            "zsh/.zshrc",
+            "source $DOTFILES/zsh/aliases.zsh\nsource ~/.config/zsh/aliases.zsh\n",
+        )
+        .write("zsh/.zshenv", "\n")
+        .write("zsh/aliases.zsh", "\n")
+        .write("git/config", "[include]\n\tpath = local.inc\n")
+        .write("git/local.inc", "\n")
+        .commit("fixture");
+        let config = repo.config();
+        let options = MvOptions { dry_run: false };
+
+        mv(&config, "zsh", "shell", &options).unwrap();
+        mv(&config, "git", "config/git", &options).unwrap();
+        assert_eq!(
+            fs::read_to_string(repo.path().join("config/git/config")).unwrap(),
+            "[include]\n\tpath = local.inc\n"
+        );
+        mv(
+            &config,
+            "config/git/local.inc",
+            "config/git/includes/local.inc",
+            &options,
+        )
+        .unwrap();
+
+        let read = |file: &str| fs::read_to_string(repo.path().join(file)).unwrap();
+        assert_eq!(
+            read(".dotter/global.toml"),
+            "# Shells\n[zsh.files]\n\"shell\" = \"~/.config/zsh\"\n\"shell/.zshenv\" = { target = \"~/.zshenv\", type = \"symbolic\" }\n"
+        );
+        assert_eq!(
+            read("shell/.zshrc"),
+            "source $DOTFILES/shell/aliases.zsh\nsource ~/.config/zsh/aliases.zsh\n"
+        );
+        assert_eq!(
+            read("config/git/config"),
+            "[include]\n\tpath = includes/local.inc\n"
+        );
+        assert_eq!(repo.git(&["diff", "--name-only"]), "");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn rm_drops_mappings_and_links_into_the_removed_path() {
+        let repo = FixtureRepo::new();
+        let root = repo.path().display().to_string();
+        repo.write(
+            ".dotter/global.toml",
+            &format!(
+                "[kitty.files]\n\"kitty/kitty.conf\" = \"{root}/home/kitty.conf\"\n\
+                 \"kitty/theme.conf\" = \"{root}/home/theme.conf\"\n\
+                 [zsh.files]\n\"zsh/.zshrc\" = \"{root}/home/.zshrc\"\n"
+            ),
+        )
+        .write("kitty/kitty.conf", "\n")
+        .write("kitty/theme.conf", "\n")
+        .write("zsh/.zshrc", "\n")
+        .commit("fixture")
+        .symlink("home/kitty.conf", "../kitty/kitty.conf")
+        .symlink("home/theme.conf", "/somewhere/else")
+        .symlink("home/.zshrc", &format!("{root}/zsh/.zshrc"));
+
+        rm(&repo.config(), "kitty/", &RmOptions { dry_run: false }).unwrap();
+
+        assert!(!repo.path().join("kitty").exists());
+        assert_eq!(
+            fs::read_to_string(repo.path().join(".dotter/global.toml")).unwrap(),
+            format!("[kitty.files]\n[zsh.files]\n\"zsh/.zshrc\" = \"{root}/home/.zshrc\"\n")
+        );
+        assert!(fs::symlink_metadata(repo.path().join("home/kitty.conf")).is_err());
+        assert!(fs::symlink_metadata(repo.path().join("home/theme.conf")).is_ok());
+        assert!(fs::symlink_metadata(repo.path().join("home/.zshrc")).is_ok());
+        assert_eq!(repo.git(&["diff", "--name-only"]), "");
+    }
+}
@@ -0,0 +1,388 @@
+//! Terminal output: colors, status lines, diagnostics, and progress bars.
+
+use clap::ValueEnum;
+use indicatif::{ProgressBar, ProgressFinish, ProgressStyle};
+use tracing_subscriber::{filter::LevelFilter, fmt::format::FmtSpan};
+
+use std::{
+    env, fs,
+    io::IsTerminal,
+    process::{Command, Stdio},
+    sync::{
+        OnceLock,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+use crate::{
+    repo::find_executable,
+    types::{Config, OutputMode},
+};
+
+pub static COLOR_ENABLED: AtomicBool = AtomicBool::new(true);
+pub static ASCII_ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Whether the locale can't display the status glyphs: LC_ALL, LC_CTYPE or
+/// LANG (the first one set, as the C library picks) names something other
+/// than UTF-8. An unset locale is left alone, since terminals that never set
+/// one are usually UTF-8 anyway.
+pub fn locale_is_ascii() -> bool {
+    ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .filter_map(|name| env::var(name).ok())
+        .find(|value| !value.is_empty())
+        .is_some_and(|locale| {
+            let locale = locale.to_ascii_lowercase();
+            !locale.contains("utf-8") && !locale.contains("utf8")
+        })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    // NO_COLOR (https://no-color.org) only applies to auto; an explicit
+    // --color always still wins
+    pub fn resolve(self) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                env::var_os("NO_COLOR").is_none_or(|v| v.is_empty())
+                    && std::io::stdout().is_terminal()
+            }
+        }
+    }
+}
+
+pub struct Color;
+
+impl Color {
+    pub fn code(code: &'static str) -> &'static str {
+        if COLOR_ENABLED.load(Ordering::Relaxed) {
+            code
+        } else {
+            ""
+        }
+    }
+
+    pub fn reset() -> &'static str {
+        Self::code("\x1b[0m")
+    }
+
+    pub fn bold() -> &'static str {
+        Self::code("\x1b[1m")
+    }
+
+    pub fn red() -> &'static str {
+        Self::code("\x1b[31m")
+    }
+
+    pub fn green() -> &'static str {
+        Self::code("\x1b[32m")
+    }
+
+    pub fn yellow() -> &'static str {
+        Self::code("\x1b[33m")
+    }
+
+    pub fn cyan() -> &'static str {
+        Self::code("\x1b[36m")
+    }
+
+    pub fn dim() -> &'static str {
+        Self::code("\x1b[2m")
+    }
+}
+
+pub struct Symbols;
+
+impl Symbols {
+    // The glyph, or its plain marker under --ascii
+    fn pick(glyph: &'static str, ascii: &'static str) -> &'static str {
+        if ASCII_ENABLED.load(Ordering::Relaxed) {
+            ascii
+        } else {
+            glyph
+        }
+    }
+
+    pub fn success() -> &'static str {
+        Self::pick("✓", "[OK]")
+    }
+
+    pub fn failure() -> &'static str {
+        Self::pick("✗", "[FAIL]")
+    }
+
+    pub fn warning() -> &'static str {
+        Self::pick("⚠", "[WARN]")
+    }
+
+    pub fn info() -> &'static str {
+        Self::pick("ℹ", "[INFO]")
+    }
+
+    pub fn skipped() -> &'static str {
+        Self::pick("○", "[SKIP]")
+    }
+
+    pub fn hint() -> &'static str {
+        Self::pick("›", ">")
+    }
+}
+
+pub fn log(message: &str, color: &str) {
+    println!("{}{}{}", color, message, Color::reset());
+}
+
+static TERMINAL_WIDTH: OnceLock<Option<usize>> = OnceLock::new();
+
+/// Columns available for output: `None` when stdout isn't a terminal, so
+/// piped output is never rewrapped even with $COLUMNS exported; otherwise
+/// $COLUMNS when it's set, or what `stty size` reports for the terminal.
+pub fn terminal_width() -> Option<usize> {
+    *TERMINAL_WIDTH.get_or_init(|| {
+        if !std::io::stdout().is_terminal() {
+            return None;
+        }
+        if let Some(columns) = env::var("COLUMNS")
+            .ok()
+            .and_then(|c| c.parse().ok())
+            .filter(|&c| c > 0)
+        {
+            return Some(columns);
+        }
+        let tty = fs::File::open("/dev/tty").ok()?;
+        let output = Command::new("stty")
+            .arg("size")
+            .stdin(tty)
+            .stderr(Stdio::null())
+            .output()
+            .ok()?;
+        String::from_utf8_lossy(&output.stdout)
+            .split_whitespace()
+            .nth(1)?
+            .parse()
+            .ok()
+            .filter(|&c| c > 0)
+    })
+}
+
+/// `text` cut to `max` characters by replacing its middle with an ellipsis,
+/// which for a path keeps both the top directory and the file name.
+pub fn truncate_middle(text: &str, max: usize) -> String {
+    let len = text.chars().count();
+    if len <= max {
+        return text.to_string();
+    }
+    let ellipsis = if ASCII_ENABLED.load(Ordering::Relaxed) {
+        "..."
+    } else {
+        "…"
+    };
+    let keep = max.saturating_sub(ellipsis.chars().count());
+    let head = keep / 3;
+    let tail = keep - head;
+    let start: String = text.chars().take(head).collect();
+    let end: String = text.chars().skip(len - tail).collect();
+    format!("{}{}{}", start, ellipsis, end)
+}
+
+/// `text` word-wrapped to `width` columns, keeping its first `indent`
+/// characters as they are and starting continuation lines under them. A
+/// path too long for a line of its own is middle-truncated; any other long
+/// word is left to overflow.
+pub fn wrap(text: &str, width: usize, indent: usize) -> Vec<String> {
+    // Too narrow to leave room for the words themselves
+    if text.chars().count() <= width || indent + 20 > width {
+        return vec![text.to_string()];
+    }
+    let mut line: String = text.chars().take(indent).collect();
+    let rest: String = text.chars().skip(indent).collect();
+    let mut line_len = indent;
+    let mut lines = Vec::new();
+    for word in rest.split_whitespace() {
+        let mut word = word.to_string();
+        let mut word_len = word.chars().count();
+        if line_len > indent && line_len + 1 + word_len > width {
+            lines.push(std::mem::take(&mut line));
+            line = " ".repeat(indent);
+            line_len = indent;
+        }
+        if line_len == indent && indent + word_len > width && word.contains('/') {
+            word = truncate_middle(&word, width - indent);
+            word_len = word.chars().count();
+        }
+        if line_len > indent {
+            line.push(' ');
+            line_len += 1;
+        }
+        line.push_str(&word);
+        line_len += word_len;
+    }
+    lines.push(line);
+    lines
+}
+
+// A symbol and message, wrapped to the terminal with continuation lines
+// under the message text (past any leading "[CODE] ")
+fn status(symbol: &str, message: &str, color: &str) {
+    let line = format!("{} {}", symbol, message);
+    let Some(width) = terminal_width() else {
+        return log(&line, color);
+    };
+    let body = message.trim_start();
+    let mut indent = symbol.chars().count() + 1 + message.len() - body.len();
+    if body.starts_with('[')
+        && let Some(end) = body.find("] ")
+    {
+        indent += end + 2;
+    }
+    for wrapped in wrap(&line, width, indent) {
+        log(&wrapped, color);
+    }
+}
+
+pub fn success(message: &str) {
+    status(Symbols::success(), message, Color::green());
+}
+
+pub fn failure(message: &str) {
+    status(Symbols::failure(), message, Color::red());
+}
+
+pub fn warning(message: &str) {
+    status(Symbols::warning(), message, Color::yellow());
+}
+
+pub fn info(message: &str) {
+    status(Symbols::info(), message, Color::cyan());
+}
+
+pub fn hint(message: &str) {
+    status(Symbols::hint(), message, Color::dim());
+}
+
+pub fn skipped(message: &str) {
+    status(Symbols::skipped(), message, Color::dim());
+}
+
+// Diagnostics go through tracing on stderr so they never interleave with the
+// report on stdout
+pub fn init_tracing(verbosity: u8, json: bool) {
+    let level = match verbosity {
+        0 => LevelFilter::WARN,
+        1 => LevelFilter::DEBUG,
+        _ => LevelFilter::TRACE,
+    };
+
+    // At trace level every span reports its own timing when it closes, which
+    // is the quickest way to find a slow rule or file
+    let span_events = if verbosity >= 2 {
+        FmtSpan::CLOSE
+    } else {
+        FmtSpan::NONE
+    };
+
+    let builder = tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_span_events(span_events)
+        .with_writer(std::io::stderr)
+        .with_target(false);
+
+    if json {
+        builder.json().init();
+    } else {
+        builder
+            .with_ansi(COLOR_ENABLED.load(Ordering::Relaxed))
+            .compact()
+            .init();
+    }
+}
+
+// Progress goes to stderr and only when someone is watching: never for
+// non-TTY output, condensed output modes, or alongside diagnostic logs
+pub fn progress_bar(config: &Config, label: &str, len: usize) -> ProgressBar {
+    if config.output != OutputMode::Normal
+        || config.verbosity > 0
+        || !std::io::stderr().is_terminal()
+    {
+        return ProgressBar::hidden();
+    }
+
+    let style = ProgressStyle::with_template("{prefix:>20} [{bar:30}] {pos}/{len} {wide_msg}")
+        .unwrap()
+        .progress_chars("=> ");
+    ProgressBar::new(len as u64)
+        .with_style(style)
+        .with_prefix(label.to_string())
+        .with_finish(ProgressFinish::AndClear)
+}
+
+// AppleScript string literals only need quotes and backslashes escaped
+fn applescript_string(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Best-effort desktop notification; nothing happens if none of the
+/// notifiers are available.
+pub fn notify(title: &str, message: &str) {
+    let mut command = if find_executable("terminal-notifier").is_some() {
+        let mut command = Command::new("terminal-notifier");
+        command.args(["-title", title, "-message", message]);
+        command
+    } else if find_executable("osascript").is_some() {
+        let mut command = Command::new("osascript");
+        command.arg("-e").arg(format!(
+            "display notification {} with title {}",
+            applescript_string(message),
+            applescript_string(title)
+        ));
+        command
+    } else if find_executable("notify-send").is_some() {
+        let mut command = Command::new("notify-send");
+        command.args([title, message]);
+        command
+    } else {
+        return;
+    };
+    let _ = command.output();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn applescript_strings_are_escaped() {
+        assert_eq!(applescript_string(r#"a "b" \c"#), r#""a \"b\" \\c""#);
+    }
+
+    #[test]
+    fn long_lines_wrap_under_the_message() {
+        let line = "✗   [REF001] zsh/.zshrc sources a file that doesn't exist in the repository";
+        assert_eq!(
+            wrap(line, 40, 13),
+            [
+                "✗   [REF001] zsh/.zshrc sources a file",
+                "             that doesn't exist in the",
+                "             repository",
+            ]
+        );
+        assert_eq!(wrap("✓ short", 40, 2), ["✓ short"]);
+    }
+
+    #[test]
+    fn long_paths_are_truncated_in_the_middle() {
+        let line = "⚠ .config/nvim/lua/plugins/lsp/servers/rust_analyzer.lua:12:3 is unused";
+        let wrapped = wrap(line, 36, 2);
+        assert_eq!(wrapped[0], "⚠ .config/nvi…rust_analyzer.lua:12:3");
+        assert_eq!(wrapped[1], "  is unused");
+        assert_eq!(truncate_middle("abc", 3), "abc");
+    }
+}
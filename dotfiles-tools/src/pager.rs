@@ -0,0 +1,84 @@
+//! Paging long reports through `$PAGER`, the way git does.
+
+use std::{
+    env,
+    io::{IsTerminal, Write},
+    os::fd::AsRawFd,
+    process::{Child, Command, Stdio},
+};
+
+use crate::output::terminal_width;
+
+const DEFAULT_PAGER: &str = "less";
+
+/// A running pager that stdout (and stderr, when it's on the terminal too)
+/// is redirected into; dropping it puts them back and waits for the pager.
+pub struct Pager {
+    child: Child,
+    // (redirected fd, duplicate of what it was)
+    saved: Vec<(i32, i32)>,
+}
+
+impl Pager {
+    /// Starts `$PAGER`, or `less`, with LESS=FRX unless LESS is set: quit if
+    /// the report fits on one screen, pass colors through, and leave it on
+    /// screen afterwards. `None` when stdout isn't a terminal, TERM is dumb,
+    /// PAGER is empty or `cat`, or the pager can't be started.
+    pub fn start() -> Option<Pager> {
+        if !std::io::stdout().is_terminal() || env::var("TERM").is_ok_and(|t| t == "dumb") {
+            return None;
+        }
+        let command = env::var("PAGER").unwrap_or_else(|_| DEFAULT_PAGER.to_string());
+        if matches!(command.trim(), "" | "cat") {
+            return None;
+        }
+        // Measure the terminal while stdout is still attached to it
+        terminal_width();
+
+        let mut pager = Command::new("sh");
+        pager.arg("-c").arg(&command).stdin(Stdio::piped());
+        if env::var_os("LESS").is_none() {
+            pager.env("LESS", "FRX");
+        }
+        let mut child = pager.spawn().ok()?;
+        let input = child.stdin.take()?;
+
+        let mut fds = vec![libc::STDOUT_FILENO];
+        if std::io::stderr().is_terminal() {
+            fds.push(libc::STDERR_FILENO);
+        }
+        let mut saved = Vec::new();
+        for fd in fds {
+            // SAFETY: dup and dup2 only operate on descriptors this process
+            // owns; `input` stays open until both redirections are made
+            unsafe {
+                let copy = libc::dup(fd);
+                if copy >= 0 && libc::dup2(input.as_raw_fd(), fd) >= 0 {
+                    saved.push((fd, copy));
+                }
+            }
+        }
+        // Quitting the pager early should end the report quietly, not
+        // panic on the next write
+        // SAFETY: restoring the default disposition of a signal
+        unsafe {
+            libc::signal(libc::SIGPIPE, libc::SIG_DFL);
+        }
+        Some(Pager { child, saved })
+    }
+}
+
+impl Drop for Pager {
+    fn drop(&mut self) {
+        let _ = std::io::stdout().flush();
+        for &(fd, copy) in &self.saved {
+            // SAFETY: `copy` was duplicated from `fd` in `start`; putting it
+            // back closes this process's end of the pager's pipe
+            unsafe {
+                libc::dup2(copy, fd);
+                libc::close(copy);
+            }
+        }
+        let _ = self.child.wait();
+    }
+}
@@ -0,0 +1,196 @@
+//! Symlink analysis and lexical path helpers.
+
+use regex::Regex;
+
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+// Beyond this many hops a chain is almost certainly a mistake, and it's well
+// under the limits where the OS itself gives up (40 on Linux, 32 on macOS)
+pub const MAX_SYMLINK_HOPS: usize = 8;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SymlinkStatus {
+    NotSymlink,
+    Resolved,
+    Dangling(PathBuf),
+    Cycle(PathBuf),
+    TooDeep,
+    EscapesRepo(PathBuf),
+}
+
+// Symlinks, plus directory junctions on Windows: both are reparse points
+// that fs::read_link can follow, but only symlinks report is_symlink()
+pub fn is_link(metadata: &fs::Metadata) -> bool {
+    #[cfg(windows)]
+    {
+        use std::os::windows::fs::MetadataExt;
+        const FILE_ATTRIBUTE_REPARSE_POINT: u32 = 0x400;
+        metadata.file_type().is_symlink()
+            || metadata.file_attributes() & FILE_ATTRIBUTE_REPARSE_POINT != 0
+    }
+    #[cfg(not(windows))]
+    {
+        metadata.file_type().is_symlink()
+    }
+}
+
+pub fn analyze_symlink(path: &Path, repo_root: &Path) -> SymlinkStatus {
+    let mut current = path.to_path_buf();
+    let mut visited = HashSet::from([normalize_path(path)]);
+    let mut hops = 0;
+
+    loop {
+        let Ok(metadata) = fs::symlink_metadata(&current) else {
+            if hops == 0 {
+                return SymlinkStatus::NotSymlink;
+            }
+            return SymlinkStatus::Dangling(current);
+        };
+
+        if !is_link(&metadata) {
+            break;
+        }
+
+        let Ok(target) = fs::read_link(&current) else {
+            return SymlinkStatus::Dangling(current);
+        };
+
+        let next = match current.parent() {
+            Some(parent) if target.is_relative() => normalize_path(&parent.join(&target)),
+            _ => normalize_path(&target),
+        };
+
+        if !visited.insert(next.clone()) {
+            return SymlinkStatus::Cycle(next);
+        }
+
+        hops += 1;
+        if hops > MAX_SYMLINK_HOPS {
+            return SymlinkStatus::TooDeep;
+        }
+
+        current = next;
+    }
+
+    if hops == 0 {
+        return SymlinkStatus::NotSymlink;
+    }
+
+    // Compare canonical forms so symlinked parent directories (e.g. /tmp on
+    // macOS) don't produce false escapes
+    let final_target = fs::canonicalize(&current).unwrap_or(current);
+    let root = fs::canonicalize(repo_root).unwrap_or_else(|_| repo_root.to_path_buf());
+    if !final_target.starts_with(&root) {
+        return SymlinkStatus::EscapesRepo(final_target);
+    }
+
+    SymlinkStatus::Resolved
+}
+
+// Lexically resolves `.` and `..` without touching the filesystem, since the
+// path may point at something that doesn't exist
+pub fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    normalized.push(component);
+                }
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+// Both paths are expected to be absolute and normalized
+pub fn relative_path(from_dir: &Path, to: &Path) -> PathBuf {
+    let from: Vec<_> = from_dir.components().collect();
+    let to: Vec<_> = to.components().collect();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+    // Paths on different Windows drives have no relative form
+    if common == 0 {
+        return to.iter().collect();
+    }
+
+    let mut relative = PathBuf::new();
+    for _ in common..from.len() {
+        relative.push("..");
+    }
+    for component in &to[common..] {
+        relative.push(component);
+    }
+    relative
+}
+
+// gitignore-style globs over repo-relative paths: `*` and `?` stay within a
+// segment, `**` crosses segments, `{a,b}` alternates, and a pattern without a
+// `/` matches the file name at any depth
+pub fn glob_to_regex(pattern: &str) -> Result<Regex, regex::Error> {
+    let anchored = pattern.contains('/');
+    let mut regex = String::from(if anchored { "^" } else { "^(?:.*/)?" });
+    let mut chars = pattern.trim_start_matches('/').chars().peekable();
+    let mut alternations = 0;
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.next_if_eq(&'/').is_some() {
+                    regex.push_str("(?:.*/)?");
+                } else {
+                    regex.push_str(".*");
+                }
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            '{' => {
+                alternations += 1;
+                regex.push_str("(?:");
+            }
+            '}' if alternations > 0 => {
+                alternations -= 1;
+                regex.push(')');
+            }
+            ',' if alternations > 0 => regex.push('|'),
+            c => regex.push_str(&regex::escape(c.encode_utf8(&mut [0; 4]))),
+        }
+    }
+    regex.push('$');
+    Regex::new(&regex)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relative_paths_walk_up_and_down() {
+        assert_eq!(
+            relative_path(Path::new("/repo/a/b"), Path::new("/repo/c/d.toml")),
+            PathBuf::from("../../c/d.toml")
+        );
+        assert_eq!(
+            normalize_path(Path::new("/repo/a/./b/../c")),
+            PathBuf::from("/repo/a/c")
+        );
+    }
+
+    #[test]
+    fn globs_match_like_gitignore() {
+        let lua = glob_to_regex("*.{lua,luau}").unwrap();
+        assert!(lua.is_match("init.lua"));
+        assert!(lua.is_match(".config/nvim/lua/plugins.luau"));
+        assert!(!lua.is_match("init.lua.bak"));
+
+        let nested = glob_to_regex(".config/**/*.yml").unwrap();
+        assert!(nested.is_match(".config/a.yml"));
+        assert!(nested.is_match(".config/x/y/a.yml"));
+        assert!(!nested.is_match("other/.config/a.yml"));
+    }
+}
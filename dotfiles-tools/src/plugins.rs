@@ -0,0 +1,183 @@
+//! External rule plugins that report issues as JSON.
+
+use serde::Deserialize;
+
+use std::{
+    collections::HashSet,
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use crate::{
+    repo::{expand_home, find_executable},
+    types::{Config, Issue, Severity, ValidationResult},
+};
+
+pub const PLUGIN_PREFIX: &str = "validate-dotfiles-rule-";
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum PluginOutput {
+    Report {
+        name: Option<String>,
+        issues: Vec<PluginIssue>,
+    },
+    Issues(Vec<PluginIssue>),
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PluginIssue {
+    pub severity: Severity,
+    pub message: String,
+    pub file: Option<String>,
+    pub fix: Option<String>,
+    pub code: Option<String>,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+}
+
+pub fn is_executable(path: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::metadata(path)
+            .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+            .unwrap_or(false)
+    }
+    #[cfg(not(unix))]
+    {
+        path.is_file()
+    }
+}
+
+// Plugins from the settings file come first, then anything on PATH named
+// validate-dotfiles-rule-*; the first PATH entry wins for duplicate names
+pub fn discover_plugins(config: &Config) -> Vec<PathBuf> {
+    let mut plugins: Vec<PathBuf> = config
+        .settings
+        .plugins
+        .commands
+        .iter()
+        .map(|command| {
+            let path = expand_home(command);
+            if command.contains('/') && path.is_relative() {
+                config.dotfiles_dir.join(path)
+            } else {
+                find_executable(command).unwrap_or(path)
+            }
+        })
+        .collect();
+
+    if !config.settings.plugins.discover {
+        return plugins;
+    }
+
+    let mut seen = HashSet::new();
+    let path_dirs = env::var_os("PATH").unwrap_or_default();
+    for dir in env::split_paths(&path_dirs) {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        let mut found: Vec<_> = entries
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| {
+                p.file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| n.starts_with(PLUGIN_PREFIX))
+                    && is_executable(p)
+            })
+            .collect();
+        found.sort();
+        for path in found {
+            if seen.insert(path.file_name().map(|n| n.to_owned())) && !plugins.contains(&path) {
+                plugins.push(path);
+            }
+        }
+    }
+
+    plugins
+}
+
+pub fn run_plugin(config: &Config, plugin: &Path) -> ValidationResult {
+    let name = plugin
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default();
+    let short_name = name.strip_prefix(PLUGIN_PREFIX).unwrap_or(name);
+    let mut result = ValidationResult::new(format!("Plugin: {}", short_name), true, Vec::new());
+    result.rule_id = format!("plugin:{}", short_name);
+
+    let output = match Command::new(plugin)
+        .arg(&config.dotfiles_dir)
+        .current_dir(&config.dotfiles_dir)
+        .output()
+    {
+        Ok(output) => output,
+        Err(err) => {
+            result.passed = false;
+            result.issues.push(
+                Issue::new(
+                    Severity::Error,
+                    format!("Failed to run plugin {}: {}", plugin.display(), err),
+                )
+                .with_fix("Check the plugin path in .validate-dotfiles.toml"),
+            );
+            return result;
+        }
+    };
+
+    // Plugins report findings through JSON, so a non-zero exit with valid
+    // output just means "found problems"
+    let issues = match serde_json::from_slice::<PluginOutput>(&output.stdout) {
+        Ok(PluginOutput::Report { name, issues }) => {
+            if let Some(name) = name {
+                result.rule_name = name;
+            }
+            issues
+        }
+        Ok(PluginOutput::Issues(issues)) => issues,
+        Err(err) => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let detail = stderr.lines().next().unwrap_or_default();
+            result.passed = false;
+            result.issues.push(Issue::new(
+                Severity::Error,
+                format!(
+                    "Plugin {} produced invalid output ({}){}",
+                    short_name,
+                    err,
+                    if detail.is_empty() {
+                        String::new()
+                    } else {
+                        format!(": {}", detail)
+                    }
+                ),
+            ));
+            return result;
+        }
+    };
+
+    result.issues = issues
+        .into_iter()
+        .map(|i| {
+            let mut issue = Issue::new(i.severity, i.message);
+            if let Some(file) = i.file {
+                issue = issue.with_file(file);
+            }
+            if let Some(fix) = i.fix {
+                issue = issue.with_fix(fix);
+            }
+            if let Some(code) = i.code {
+                issue = issue.with_code(code);
+            }
+            if let Some(line) = i.line {
+                issue = issue.at(line, i.column.unwrap_or(1));
+            }
+            issue
+        })
+        .collect();
+    result.passed = result.issues.iter().all(|i| i.severity != Severity::Error);
+    result
+}
@@ -0,0 +1,277 @@
+//! Deployment profiles: package combinations declared in the settings file,
+//! each checked as if it were a machine's local.toml.
+
+use anyhow::Result;
+
+use std::{collections::BTreeMap, fs};
+
+use crate::{
+    dotter::{
+        DeployKind, defined_packages_and_helpers, selected_files, selected_variables, tilde_path,
+    },
+    output::{Color, Symbols},
+    settings::ProfileSettings,
+    snippet::line_col,
+    template::{is_defined, required_variables},
+    types::{Config, Issue, Severity, ValidationResult},
+    validator::Validator,
+};
+
+/// Whether one profile could be deployed: its packages and includes exist,
+/// no two files claim the same target, every source exists, and every
+/// variable its templates need is defined.
+pub fn check_profile(
+    config: &Config,
+    name: &str,
+    profile: &ProfileSettings,
+) -> Result<ValidationResult> {
+    let mut issues = Vec::new();
+    let missing_includes: Vec<_> = profile
+        .includes
+        .iter()
+        .filter(|include| !config.dotfiles_dir.join(include).is_file())
+        .collect();
+    for include in &missing_includes {
+        issues.push(
+            Issue::new(
+                Severity::Error,
+                format!(
+                    "Profile '{}' includes {}, which doesn't exist",
+                    name, include
+                ),
+            )
+            .with_code("PROF002"),
+        );
+    }
+    if !missing_includes.is_empty() {
+        return Ok(profile_result(name, issues));
+    }
+
+    let (defined, helpers) = defined_packages_and_helpers(config, &profile.includes)?;
+    for package in profile.packages.iter().filter(|p| !defined.contains(*p)) {
+        issues.push(
+            Issue::new(
+                Severity::Error,
+                format!("Profile '{}' selects unknown package '{}'", name, package),
+            )
+            .with_code("PROF004"),
+        );
+    }
+
+    let files = selected_files(config, &profile.packages, &profile.includes)?;
+    let mut by_target: BTreeMap<String, Vec<&str>> = BTreeMap::new();
+    for file in &files {
+        by_target
+            .entry(tilde_path(&file.target).trim_end_matches('/').to_string())
+            .or_default()
+            .push(&file.source);
+    }
+    for (target, sources) in by_target.iter().filter(|(_, s)| s.len() > 1) {
+        issues.push(
+            Issue::new(
+                Severity::Error,
+                format!(
+                    "Profile '{}' deploys {} to {}",
+                    name,
+                    sources.join(" and "),
+                    target
+                ),
+            )
+            .with_code("PROF001"),
+        );
+    }
+
+    let mut variables = selected_variables(config, &profile.packages, &profile.includes)?;
+    variables.extend(profile.variables.clone());
+    for file in &files {
+        let path = config.dotfiles_dir.join(&file.source);
+        if !path.exists() {
+            issues.push(
+                Issue::new(
+                    Severity::Error,
+                    format!(
+                        "Profile '{}' deploys {}, which doesn't exist",
+                        name, file.source
+                    ),
+                )
+                .with_code("PROF002")
+                .with_file(file.source.clone()),
+            );
+            continue;
+        }
+        if file.kind != DeployKind::Template {
+            continue;
+        }
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        for (variable, offset) in required_variables(&content, &helpers, None) {
+            if is_defined(&variables, &variable) {
+                continue;
+            }
+            let (line, col) = line_col(&content, offset);
+            issues.push(
+                Issue::new(
+                    Severity::Error,
+                    format!(
+                        "Profile '{}' doesn't define '{}', which {} uses",
+                        name, variable, file.source
+                    ),
+                )
+                .with_code("PROF003")
+                .with_file(file.source.clone())
+                .with_fix(format!(
+                    "Set it under [{}.variables] or in the profile's variables",
+                    file.package
+                ))
+                .at(line, col),
+            );
+        }
+    }
+
+    Ok(profile_result(name, issues))
+}
+
+fn profile_result(name: &str, issues: Vec<Issue>) -> ValidationResult {
+    let passed = issues.iter().all(|i| i.severity != Severity::Error);
+    let mut result =
+        ValidationResult::new(format!("Profile '{}' is deployable", name), passed, issues);
+    result.rule_id = format!("profile:{}", name);
+    result
+}
+
+// The `profiles` command: check every declared profile (or the ones named)
+// and show which are deployable
+pub fn profiles(config: Config, only: &[String]) -> Result<i32> {
+    let declared = config.settings.profiles.clone();
+    if declared.is_empty() {
+        anyhow::bail!(
+            "No profiles declared; add [profiles.<name>] tables to .validate-dotfiles.toml"
+        );
+    }
+    if let Some(unknown) = only.iter().find(|name| !declared.contains_key(*name)) {
+        anyhow::bail!(
+            "Unknown profile '{}' (declared: {})",
+            unknown,
+            declared.keys().cloned().collect::<Vec<_>>().join(", ")
+        );
+    }
+
+    println!(
+        "\n{}Checking deployment profiles...{}\n",
+        Color::bold(),
+        Color::reset()
+    );
+    let mut results = Vec::new();
+    for (name, profile) in &declared {
+        if only.is_empty() || only.contains(name) {
+            results.push((name, profile, check_profile(&config, name, profile)?));
+        }
+    }
+
+    let validator = Validator::new(config);
+    for (_, _, result) in &results {
+        validator.print_result(result);
+    }
+
+    let width = results
+        .iter()
+        .map(|(name, _, _)| name.len())
+        .max()
+        .unwrap_or(0)
+        .max(7);
+    println!(
+        "\n{}{:<width$}  {:>8}  {:>6}  {:>8}  Deployable{}",
+        Color::bold(),
+        "Profile",
+        "Packages",
+        "Errors",
+        "Warnings",
+        Color::reset()
+    );
+    for (name, profile, result) in &results {
+        let count = |severity| {
+            result
+                .issues
+                .iter()
+                .filter(|i| i.severity == severity)
+                .count()
+        };
+        let (mark, color) = if result.passed {
+            (Symbols::success(), Color::green())
+        } else {
+            (Symbols::failure(), Color::red())
+        };
+        println!(
+            "{:<width$}  {:>8}  {:>6}  {:>8}  {}{}{}",
+            name,
+            profile.packages.len(),
+            count(Severity::Error),
+            count(Severity::Warning),
+            color,
+            mark,
+            Color::reset()
+        );
+    }
+
+    let results: Vec<_> = results.into_iter().map(|(_, _, result)| result).collect();
+    Ok(validator.summarize(&results))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::FixtureRepo;
+
+    #[test]
+    fn profiles_are_checked_independently() {
+        let repo = FixtureRepo::new();
+        repo.write(
+            ".dotter/global.toml",
+            "[git.files]\ngitconfig = { target = \"~/.gitconfig\", type = \"template\" }\n\
+             [git.variables]\nname = \"me\"\n\
+             [work.files]\nwork-gitconfig = \"~/.gitconfig\"\n\
+             [work.variables]\nemail = \"me@work.example\"\n\
+             [zsh.files]\nzshrc = \"~/.zshrc\"\nzprofile = \"~/.zprofile\"\n",
+        )
+        .write(
+            "gitconfig",
+            "[user]\n\tname = {{name}}\n\temail = {{email}}\n",
+        )
+        .write("work-gitconfig", "\n")
+        .write("zshrc", "\n")
+        .commit("fixture");
+        let config = repo.config();
+        let profile = |packages: &[&str], variables: &str| ProfileSettings {
+            packages: packages.iter().map(|p| p.to_string()).collect(),
+            includes: Vec::new(),
+            variables: toml::from_str(variables).unwrap(),
+        };
+        let codes = |result: ValidationResult| -> Vec<_> {
+            result.issues.into_iter().filter_map(|i| i.code).collect()
+        };
+
+        let personal = check_profile(
+            &config,
+            "personal",
+            &profile(&["git"], "email = \"me@home\""),
+        )
+        .unwrap();
+        assert!(personal.passed, "{:?}", personal.issues);
+        assert_eq!(
+            codes(check_profile(&config, "bare", &profile(&["git"], "")).unwrap()),
+            ["PROF003"]
+        );
+        assert_eq!(
+            codes(
+                check_profile(
+                    &config,
+                    "work",
+                    &profile(&["git", "work", "zsh", "vim"], "")
+                )
+                .unwrap()
+            ),
+            ["PROF004", "PROF001", "PROF002"]
+        );
+    }
+}
@@ -0,0 +1,400 @@
+//! Access to the repository through git and the filesystem.
+
+use anyhow::{Context, Result};
+use tracing::debug;
+
+use std::{
+    collections::HashSet,
+    env, fs,
+    io::{Read, Write},
+    path::PathBuf,
+    process::{Command, Stdio},
+};
+
+use crate::types::{Config, GitMissing};
+
+pub fn is_ignored_by_git(config: &Config, filepath: &str) -> bool {
+    Command::new("git")
+        .args(["check-ignore", filepath])
+        .current_dir(&config.dotfiles_dir)
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+/// The subset of `paths` that git ignores, from one `git check-ignore` call
+/// instead of one per path.
+pub fn ignored_by_git<'a>(
+    config: &Config,
+    paths: impl IntoIterator<Item = &'a String>,
+) -> HashSet<String> {
+    let input: String = paths.into_iter().map(|p| format!("{}\n", p)).collect();
+    let Ok(mut child) = Command::new("git")
+        .args(["check-ignore", "--stdin"])
+        .current_dir(&config.dotfiles_dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+    else {
+        return HashSet::new();
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(input.as_bytes());
+    }
+    child
+        .wait_with_output()
+        .map(|output| {
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// Fails early with a clear error instead of letting every rule silently find
+// zero tracked files
+pub fn check_environment(config: &Config) -> Result<()> {
+    if find_executable("git").is_none() {
+        return Err(GitMissing.into());
+    }
+
+    fs::read_dir(&config.dotfiles_dir).with_context(|| {
+        format!(
+            "Cannot read dotfiles directory {}",
+            config.dotfiles_dir.display()
+        )
+    })?;
+
+    let output = Command::new("git")
+        .args(["rev-parse", "--git-dir"])
+        .current_dir(&config.dotfiles_dir)
+        .output()
+        .context("Failed to run git rev-parse")?;
+    if !output.status.success() {
+        anyhow::bail!("{} is not a git repository", config.dotfiles_dir.display());
+    }
+
+    Ok(())
+}
+
+pub fn find_executable(name: &str) -> Option<PathBuf> {
+    let path = env::var_os("PATH")?;
+    // Windows resolves bare names through PATHEXT (.exe, .cmd, ...)
+    let extensions: Vec<String> = if cfg!(windows) {
+        env::var("PATHEXT")
+            .unwrap_or_else(|_| ".EXE;.CMD;.BAT".to_string())
+            .split(';')
+            .map(str::to_string)
+            .chain([String::new()])
+            .collect()
+    } else {
+        vec![String::new()]
+    };
+    env::split_paths(&path)
+        .flat_map(|dir| {
+            extensions
+                .iter()
+                .map(move |ext| dir.join(format!("{}{}", name, ext)))
+        })
+        .find(|candidate| candidate.is_file())
+}
+
+pub fn get_tracked_files(config: &Config) -> Result<Vec<String>> {
+    let output = Command::new("git")
+        .args(["ls-files"])
+        .current_dir(&config.dotfiles_dir)
+        .output()
+        .context("Failed to run git ls-files")?;
+
+    if !output.status.success() {
+        return Ok(Vec::new());
+    }
+
+    let files = String::from_utf8(output.stdout)
+        .context("Invalid UTF-8 in git output")?
+        .lines()
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+        .collect();
+
+    Ok(files)
+}
+
+/// Stages `files` (repo-relative) for the next commit.
+pub fn git_add(config: &Config, files: &[&str]) -> Result<()> {
+    let status = Command::new("git")
+        .arg("add")
+        .arg("--")
+        .args(files)
+        .current_dir(&config.dotfiles_dir)
+        .status()
+        .context("Failed to run git add")?;
+    if !status.success() {
+        anyhow::bail!("git add failed ({})", status);
+    }
+    Ok(())
+}
+
+/// Moves a tracked file or directory with `git mv`, creating the
+/// destination's parent directory first.
+pub fn git_mv(config: &Config, from: &str, to: &str) -> Result<()> {
+    if let Some(parent) = config.dotfiles_dir.join(to).parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    let output = Command::new("git")
+        .args(["mv", "--", from, to])
+        .current_dir(&config.dotfiles_dir)
+        .output()
+        .context("Failed to run git mv")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "git mv failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+/// Removes a tracked file or directory from the index and the working tree.
+pub fn git_rm(config: &Config, path: &str) -> Result<()> {
+    let output = Command::new("git")
+        .args(["rm", "-r", "--quiet", "--", path])
+        .current_dir(&config.dotfiles_dir)
+        .output()
+        .context("Failed to run git rm")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "git rm failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+/// Every file in the working tree, tracked or not and ignored or not, apart
+/// from git's own.
+pub fn get_working_tree_files(config: &Config) -> Result<Vec<String>> {
+    let output = Command::new("git")
+        .args(["ls-files", "--cached", "--others"])
+        .current_dir(&config.dotfiles_dir)
+        .output()
+        .context("Failed to run git ls-files")?;
+
+    if !output.status.success() {
+        return Ok(Vec::new());
+    }
+
+    let files = String::from_utf8(output.stdout)
+        .context("Invalid UTF-8 in git output")?
+        .lines()
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+
+    Ok(files)
+}
+
+/// Files and directories in the working tree that git neither tracks nor
+/// ignores; an untracked directory is listed once, with a trailing `/`.
+pub fn get_untracked_files(config: &Config) -> Result<Vec<String>> {
+    let output = Command::new("git")
+        .args(["ls-files", "--others", "--exclude-standard", "--directory"])
+        .current_dir(&config.dotfiles_dir)
+        .output()
+        .context("Failed to run git ls-files")?;
+
+    if !output.status.success() {
+        return Ok(Vec::new());
+    }
+
+    let files = String::from_utf8(output.stdout)
+        .context("Invalid UTF-8 in git output")?
+        .lines()
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+        .collect();
+
+    Ok(files)
+}
+
+// Files whose contents the content rules (syntax, conflict markers) should
+// inspect: everything tracked, just what's staged in --staged mode, or what
+// changed relative to a ref in --changed-since mode
+pub fn get_content_files(config: &Config) -> Result<Vec<String>> {
+    if let Some(files) = &config.only_files {
+        return Ok(files.clone());
+    }
+    let args: Vec<&str> = if config.staged {
+        vec!["diff", "--cached", "--name-only", "--diff-filter=d"]
+    } else if let Some(base) = &config.changed_since {
+        vec!["diff", "--name-only", "--diff-filter=d", base, "--"]
+    } else {
+        return get_tracked_files(config);
+    };
+
+    let output = Command::new("git")
+        .args(&args)
+        .current_dir(&config.dotfiles_dir)
+        .output()
+        .context("Failed to run git diff")?;
+
+    if !output.status.success() {
+        anyhow::bail!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    let files = String::from_utf8(output.stdout)
+        .context("Invalid UTF-8 in git output")?
+        .lines()
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+        .collect();
+
+    Ok(files)
+}
+
+/// Files larger than this are never read by content rules; the large-files
+/// rule reports them instead, so one stray blob can't balloon memory.
+pub const MAX_CONTENT_SIZE: u64 = 2 * 1024 * 1024;
+
+// In --staged mode content comes from the index rather than the working tree,
+// so partially staged files are checked exactly as they'll be committed.
+// Reads stop just past MAX_CONTENT_SIZE, and an oversized file reads as None
+pub fn read_file_bytes(config: &Config, file: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::new();
+    if !config.staged {
+        fs::File::open(config.dotfiles_dir.join(file))
+            .ok()?
+            .take(MAX_CONTENT_SIZE + 1)
+            .read_to_end(&mut bytes)
+            .ok()?;
+    } else {
+        let mut child = Command::new("git")
+            .args(["show", &format!(":{}", file)])
+            .current_dir(&config.dotfiles_dir)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .ok()?;
+        child
+            .stdout
+            .take()?
+            .take(MAX_CONTENT_SIZE + 1)
+            .read_to_end(&mut bytes)
+            .ok()?;
+        if bytes.len() as u64 > MAX_CONTENT_SIZE {
+            let _ = child.kill();
+        }
+        if !child.wait().ok()?.success() {
+            return None;
+        }
+    }
+
+    if bytes.len() as u64 > MAX_CONTENT_SIZE {
+        debug!("skipping {}: larger than {} bytes", file, MAX_CONTENT_SIZE);
+        return None;
+    }
+    Some(bytes)
+}
+
+/// A file's size in bytes, taken from the index in --staged mode.
+pub fn file_size(config: &Config, file: &str) -> Option<u64> {
+    if !config.staged {
+        return fs::metadata(config.dotfiles_dir.join(file))
+            .ok()
+            .map(|m| m.len());
+    }
+    let output = Command::new("git")
+        .args(["cat-file", "-s", &format!(":{}", file)])
+        .current_dir(&config.dotfiles_dir)
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().parse().ok())
+        .flatten()
+}
+
+/// A file's content as text; `None` for unreadable and binary files and
+/// ones that aren't valid UTF-8, which content rules all skip.
+pub fn read_file_content(config: &Config, file: &str) -> Option<String> {
+    let bytes = read_file_bytes(config, file)?;
+    if looks_binary(&bytes) {
+        return None;
+    }
+    String::from_utf8(bytes).ok()
+}
+
+// How much of a file the binary sniffer looks at, as in git
+const SNIFF_LEN: usize = 8000;
+
+// Leading bytes of binary formats that turn up in dotfiles repos (icons,
+// wallpapers, fonts, archives, compiled helpers), for the ones that don't
+// reliably have a NUL byte early on
+const BINARY_MAGIC: &[&[u8]] = &[
+    b"\x89PNG",
+    b"\xFF\xD8\xFF",
+    b"GIF8",
+    b"%PDF-",
+    b"RIFF",
+    b"icns",
+    b"wOFF",
+    b"wOF2",
+    b"OTTO",
+    b"PK\x03\x04",
+    b"\x1F\x8B",
+    b"BZh",
+    b"\xFD7zXZ",
+    b"\x28\xB5\x2F\xFD",
+    b"\x7FELF",
+    b"\xCF\xFA\xED\xFE",
+    b"SQLite format 3",
+];
+
+/// Whether content is binary rather than text: it starts with a known binary
+/// signature, or (git's heuristic) has a NUL byte in its first 8000 bytes.
+pub fn looks_binary(bytes: &[u8]) -> bool {
+    BINARY_MAGIC.iter().any(|magic| bytes.starts_with(magic))
+        || bytes.iter().take(SNIFF_LEN).any(|b| *b == 0)
+}
+
+/// Whether a file is binary, judged by sniffing only its first few
+/// kilobytes. Unreadable files count as text, for the caller to deal with.
+pub fn is_binary_file(config: &Config, file: &str) -> bool {
+    if config.staged {
+        return read_file_bytes(config, file).is_some_and(|bytes| looks_binary(&bytes));
+    }
+    let mut head = Vec::new();
+    fs::File::open(config.dotfiles_dir.join(file))
+        .and_then(|f| f.take(SNIFF_LEN as u64).read_to_end(&mut head))
+        .is_ok_and(|_| looks_binary(&head))
+}
+
+// Falls back to USERPROFILE on Windows, where HOME is usually only set by
+// Git Bash and MSYS shells
+pub fn home_dir() -> Option<PathBuf> {
+    env::var_os("HOME")
+        .filter(|home| !home.is_empty())
+        .or_else(|| env::var_os("USERPROFILE").filter(|_| cfg!(windows)))
+        .map(PathBuf::from)
+}
+
+pub fn expand_home(path: &str) -> PathBuf {
+    let rest = path
+        .strip_prefix("~/")
+        .or_else(|| path.strip_prefix("~\\").filter(|_| cfg!(windows)));
+    match (rest, home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ if path == "~" => home_dir().unwrap_or_else(|| PathBuf::from(path)),
+        _ => PathBuf::from(path),
+    }
+}
@@ -0,0 +1,240 @@
+//! Rule for AeroSpace's aerospace.toml, beyond what toml-syntax checks.
+
+use anyhow::Result;
+
+use std::{collections::BTreeSet, path::Path};
+
+use crate::{
+    rules::check_files,
+    snippet::line_of,
+    types::{Config, Issue, Severity, ValidationResult},
+};
+
+const TOP_LEVEL_KEYS: &[&str] = &[
+    "config-version",
+    "start-at-login",
+    "after-login-command",
+    "after-startup-command",
+    "exec-on-workspace-change",
+    "on-focus-changed",
+    "on-focused-monitor-changed",
+    "on-mode-changed",
+    "enable-normalization-flatten-containers",
+    "enable-normalization-opposite-orientation-for-nested-containers",
+    "accordion-padding",
+    "default-root-container-layout",
+    "default-root-container-orientation",
+    "automatically-unhide-macos-hidden-apps",
+    "persistent-workspaces",
+    "key-mapping",
+    "gaps",
+    "exec",
+    "mode",
+    "workspace-to-monitor-force-assignment",
+    "on-window-detected",
+];
+
+// Commands that take a workspace name as their first non-flag argument
+const WORKSPACE_COMMANDS: &[&str] = &["workspace", "move-node-to-workspace", "summon-workspace"];
+
+// Relative targets rather than names
+const RELATIVE_WORKSPACES: &[&str] = &["next", "prev"];
+
+// Every command in a binding or callback, which can be a string or a list
+fn commands(value: &toml::Value) -> Vec<&str> {
+    match value {
+        toml::Value::String(command) => vec![command.as_str()],
+        toml::Value::Array(items) => items.iter().filter_map(|v| v.as_str()).collect(),
+        _ => Vec::new(),
+    }
+}
+
+// The first argument that isn't a flag, for `command [--flag] arg`
+fn argument<'a>(command: &'a str, name: &str) -> Option<&'a str> {
+    let mut words = command.split_whitespace();
+    (words.next()? == name).then_some(())?;
+    words.find(|w| !w.starts_with('-'))
+}
+
+fn issue(
+    file: &str,
+    content: &str,
+    severity: Severity,
+    code: &str,
+    message: String,
+    needle: &str,
+) -> Issue {
+    let issue = Issue::new(severity, message)
+        .with_code(code)
+        .with_file(file.to_string());
+    match line_of(content, needle) {
+        Some(line) => issue.at(line, 1),
+        None => issue,
+    }
+}
+
+pub fn aerospace_file_issues(file: &str, content: &str) -> Vec<Issue> {
+    // Broken TOML is toml-syntax's to report
+    let Ok(doc) = toml::from_str::<toml::Table>(content) else {
+        return Vec::new();
+    };
+    let mut issues = Vec::new();
+
+    for key in doc.keys() {
+        if !TOP_LEVEL_KEYS.contains(&key.as_str()) {
+            issues.push(issue(
+                file,
+                content,
+                Severity::Warning,
+                "AERO001",
+                format!("Unknown AeroSpace setting '{}'", key),
+                key,
+            ));
+        }
+    }
+
+    let modes = doc.get("mode").and_then(|m| m.as_table());
+    let defined: BTreeSet<&str> = modes
+        .map(|m| m.keys().map(String::as_str).collect())
+        .unwrap_or_default();
+    if !defined.contains("main") {
+        issues.push(issue(
+            file,
+            content,
+            Severity::Error,
+            "AERO002",
+            format!(
+                "{} has no [mode.main.binding] table; AeroSpace needs one",
+                file
+            ),
+            "[mode",
+        ));
+    }
+
+    // Workspaces are created on demand, so names can only be checked against a
+    // config that declares its workspaces up front
+    let mut configured: BTreeSet<String> = doc
+        .get("persistent-workspaces")
+        .and_then(|w| w.as_array())
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|v| v.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default();
+    if let Some(assignments) = doc
+        .get("workspace-to-monitor-force-assignment")
+        .and_then(|w| w.as_table())
+    {
+        configured.extend(assignments.keys().cloned());
+    }
+
+    let bindings = modes.into_iter().flatten().flat_map(|(mode, table)| {
+        table
+            .get("binding")
+            .and_then(|b| b.as_table())
+            .into_iter()
+            .flatten()
+            .map(move |(key, value)| (mode, key, value))
+    });
+    let mut reported = BTreeSet::new();
+    for (mode, key, value) in bindings {
+        for command in commands(value) {
+            if let Some(target) = argument(command, "mode")
+                && !defined.contains(target)
+            {
+                issues.push(issue(
+                    file,
+                    content,
+                    Severity::Error,
+                    "AERO002",
+                    format!(
+                        "Binding {} in mode '{}' switches to mode '{}', which isn't defined",
+                        key, mode, target
+                    ),
+                    command,
+                ));
+            }
+            let workspace = WORKSPACE_COMMANDS
+                .iter()
+                .find_map(|name| argument(command, name));
+            if let Some(workspace) = workspace
+                && !configured.is_empty()
+                && !configured.contains(workspace)
+                && !RELATIVE_WORKSPACES.contains(&workspace)
+                && reported.insert(workspace)
+            {
+                issues.push(issue(
+                    file,
+                    content,
+                    Severity::Warning,
+                    "AERO003",
+                    format!(
+                        "Workspace '{}' is used by binding {} but isn't in persistent-workspaces \
+                         or workspace-to-monitor-force-assignment",
+                        workspace, key
+                    ),
+                    command,
+                ));
+            }
+        }
+    }
+
+    issues
+}
+
+fn is_aerospace_config(file: &str) -> bool {
+    matches!(
+        Path::new(file).file_name().and_then(|n| n.to_str()),
+        Some("aerospace.toml" | ".aerospace.toml")
+    )
+}
+
+pub fn aerospace_configs_valid(config: &Config) -> Result<ValidationResult> {
+    let (count, issues) =
+        check_files(config, "aerospace", is_aerospace_config, |file, content| {
+            Ok(aerospace_file_issues(file, content))
+        })?;
+    let passed = issues.iter().all(|i| i.severity != Severity::Error);
+    Ok(ValidationResult::new(
+        format!("All {} AeroSpace configs are valid", count),
+        passed,
+        issues,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn modes_and_workspaces_are_resolved() {
+        let content = r#"start-at-login = true
+gap = 4
+persistent-workspaces = ["1", "2", "web"]
+
+[mode.main.binding]
+alt-1 = "workspace 1"
+alt-3 = "workspace 3"
+alt-shift-3 = "move-node-to-workspace --focus-follows-window 3"
+alt-tab = "workspace next"
+alt-r = "mode resize"
+alt-s = ["mode service", "exec-and-forget echo"]
+
+[mode.service.binding]
+esc = ["reload-config", "mode main"]
+"#;
+        let issues = aerospace_file_issues("aerospace.toml", content);
+        let found: Vec<_> = issues
+            .iter()
+            .map(|i| (i.code.as_deref().unwrap(), i.line.unwrap()))
+            .collect();
+        assert_eq!(
+            found,
+            [("AERO001", 2), ("AERO003", 7), ("AERO002", 10)],
+            "{:?}",
+            issues
+        );
+    }
+}
@@ -0,0 +1,358 @@
+//! Rule for Cargo's .cargo/config.toml: known tables, deprecated keys,
+//! aliases and registries that collide with Cargo's own, and linkers.
+
+use anyhow::Result;
+use serde::Deserialize;
+
+use std::{collections::BTreeMap, path::Path};
+
+use crate::{
+    doctor::{collect_cargo_references, tool_is_available},
+    repo::{get_content_files, read_file_content},
+    rules::check_files,
+    snippet::line_of,
+    types::{Config, Issue, Severity, ValidationResult},
+};
+
+const TOP_LEVEL_TABLES: &[&str] = &[
+    "alias",
+    "build",
+    "cache",
+    "cargo-new",
+    "credential-alias",
+    "doc",
+    "env",
+    "future-incompat-report",
+    "http",
+    "install",
+    "net",
+    "patch",
+    "paths",
+    "profile",
+    "registries",
+    "registry",
+    "resolver",
+    "source",
+    "target",
+    "term",
+    "unstable",
+];
+
+// Commands an alias of the same name can never override; Cargo warns and
+// runs the built-in instead. The short built-in aliases (b, c, r, ...) are
+// fair game.
+const BUILTIN_COMMANDS: &[&str] = &[
+    "add",
+    "bench",
+    "build",
+    "check",
+    "clean",
+    "config",
+    "doc",
+    "fetch",
+    "fix",
+    "generate-lockfile",
+    "help",
+    "info",
+    "init",
+    "install",
+    "locate-project",
+    "login",
+    "logout",
+    "metadata",
+    "new",
+    "owner",
+    "package",
+    "pkgid",
+    "publish",
+    "remove",
+    "report",
+    "run",
+    "rustc",
+    "rustdoc",
+    "search",
+    "test",
+    "tree",
+    "uninstall",
+    "update",
+    "vendor",
+    "verify-project",
+    "version",
+    "yank",
+];
+
+/// The parts of a Cargo config this rule and `doctor` look at. Everything
+/// else lands in `other`, keyed by top-level table.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct CargoConfig {
+    pub alias: BTreeMap<String, toml::Value>,
+    pub build: BuildConfig,
+    pub cargo_new: CargoNewConfig,
+    pub registry: RegistryConfig,
+    pub registries: BTreeMap<String, RegistryConfig>,
+    /// Keyed by target triple or `cfg(...)` expression
+    pub target: BTreeMap<String, TargetConfig>,
+    #[serde(flatten)]
+    pub other: toml::Table,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct BuildConfig {
+    pub pipelining: Option<toml::Value>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct CargoNewConfig {
+    pub name: Option<toml::Value>,
+    pub email: Option<toml::Value>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct RegistryConfig {
+    pub index: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct TargetConfig {
+    pub linker: Option<String>,
+    pub ar: Option<toml::Value>,
+}
+
+impl CargoConfig {
+    // Keys Cargo has stopped reading, with what replaced them
+    fn deprecated_keys(&self) -> Vec<(String, &'static str)> {
+        let mut keys = Vec::new();
+        if self.build.pipelining.is_some() {
+            keys.push((
+                "build.pipelining".to_string(),
+                "pipelining is always on; remove it",
+            ));
+        }
+        if self.cargo_new.name.is_some() {
+            keys.push((
+                "cargo-new.name".to_string(),
+                "cargo new no longer fills in authors; remove it",
+            ));
+        }
+        if self.cargo_new.email.is_some() {
+            keys.push((
+                "cargo-new.email".to_string(),
+                "cargo new no longer fills in authors; remove it",
+            ));
+        }
+        if self.registry.index.is_some() {
+            keys.push((
+                "registry.index".to_string(),
+                "use a [registries] entry, or [source] replacement for crates.io",
+            ));
+        }
+        for (target, config) in &self.target {
+            if config.ar.is_some() {
+                keys.push((
+                    format!("target.{}.ar", target),
+                    "Cargo never used it; remove it",
+                ));
+            }
+        }
+        keys
+    }
+}
+
+pub fn is_cargo_config(file: &str) -> bool {
+    let path = Path::new(file);
+    let in_cargo_dir = path
+        .parent()
+        .and_then(|p| p.file_name())
+        .is_some_and(|n| n == ".cargo" || n == "cargo");
+    in_cargo_dir
+        && path
+            .file_name()
+            .is_some_and(|n| n == "config.toml" || n == "config")
+}
+
+fn issue(file: &str, content: &str, code: &str, message: String, needle: &str) -> Issue {
+    let issue = Issue::new(Severity::Warning, message)
+        .with_code(code)
+        .with_file(file.to_string());
+    match line_of(content, needle) {
+        Some(line) => issue.at(line, 1),
+        None => issue,
+    }
+}
+
+pub fn cargo_file_issues(file: &str, content: &str) -> Vec<Issue> {
+    // Broken TOML is toml-syntax's to report
+    let Ok(cargo) = toml::from_str::<CargoConfig>(content) else {
+        return Vec::new();
+    };
+    let mut issues = Vec::new();
+
+    if !file.ends_with(".toml") {
+        issues.push(
+            Issue::new(
+                Severity::Warning,
+                format!("{} uses the deprecated extensionless name", file),
+            )
+            .with_code("CARGO002")
+            .with_file(file.to_string())
+            .with_fix(format!("Rename it to {}.toml", file)),
+        );
+    }
+
+    for table in cargo.other.keys() {
+        if !TOP_LEVEL_TABLES.contains(&table.as_str()) {
+            issues.push(issue(
+                file,
+                content,
+                "CARGO001",
+                format!("Unknown Cargo config table '{}'", table),
+                table,
+            ));
+        }
+    }
+
+    for (key, replacement) in cargo.deprecated_keys() {
+        let last = key.rsplit('.').next().unwrap_or(&key);
+        issues.push(
+            issue(
+                file,
+                content,
+                "CARGO002",
+                format!("Cargo config key {} is deprecated", key),
+                last,
+            )
+            .with_fix(replacement.to_string()),
+        );
+    }
+
+    for name in cargo.alias.keys() {
+        if BUILTIN_COMMANDS.contains(&name.as_str()) {
+            issues.push(issue(
+                file,
+                content,
+                "CARGO003",
+                format!(
+                    "Alias '{}' is shadowed by the built-in cargo {} and never runs",
+                    name, name
+                ),
+                name,
+            ));
+        }
+    }
+    if cargo
+        .registries
+        .get("crates-io")
+        .is_some_and(|r| r.index.is_some())
+    {
+        issues.push(issue(
+            file,
+            content,
+            "CARGO003",
+            "[registries.crates-io] can't change the crates.io index; use [source] replacement"
+                .to_string(),
+            "crates-io",
+        ));
+    }
+
+    issues
+}
+
+// Linkers missing from this machine. These depend on more than the file's
+// content, so they're kept out of the per-file cache
+fn linker_issues(file: &str, content: &str) -> Vec<Issue> {
+    let mut refs = Vec::new();
+    collect_cargo_references(file, content, &mut refs);
+    refs.iter()
+        .filter(|r| !tool_is_available(r))
+        .map(|reference| {
+            let issue = Issue::new(
+                Severity::Warning,
+                format!("Linker {} doesn't exist on this machine", reference.command),
+            )
+            .with_code("CARGO004")
+            .with_file(file.to_string())
+            .with_fix("Install it, or drop the [target] table on machines without it".to_string());
+            match reference.line {
+                Some(line) => issue.at(line, 1),
+                None => issue,
+            }
+        })
+        .collect()
+}
+
+pub fn cargo_configs_valid(config: &Config) -> Result<ValidationResult> {
+    let (count, mut issues) =
+        check_files(config, "cargo-config", is_cargo_config, |file, content| {
+            Ok(cargo_file_issues(file, content))
+        })?;
+    for file in get_content_files(config)?
+        .iter()
+        .filter(|f| is_cargo_config(f))
+    {
+        if let Some(content) = read_file_content(config, file) {
+            issues.extend(linker_issues(file, &content));
+        }
+    }
+    let passed = issues.iter().all(|i| i.severity != Severity::Error);
+    Ok(ValidationResult::new(
+        format!("All {} Cargo configs are valid", count),
+        passed,
+        issues,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tables_deprecations_collisions_and_linkers_are_reported() {
+        let content = r#"[alias]
+b = "build --release"
+test = "nextest run"
+
+[registry]
+index = "https://example.com/index"
+
+[registries.crates-io]
+index = "https://example.com/mirror"
+
+[target.x86_64-unknown-linux-gnu]
+linker = "/definitely/not/a/linker"
+
+[target.aarch64-apple-darwin]
+linker = "sh"
+
+[bulid]
+jobs = 4
+"#;
+        let mut issues = cargo_file_issues(".cargo/config.toml", content);
+        issues.extend(linker_issues(".cargo/config.toml", content));
+        let found: Vec<_> = issues
+            .iter()
+            .map(|i| (i.code.as_deref().unwrap(), i.line.unwrap()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("CARGO001", 17),
+                ("CARGO002", 6),
+                ("CARGO003", 3),
+                ("CARGO003", 8),
+                ("CARGO004", 12),
+            ],
+            "{:?}",
+            issues
+        );
+
+        let issues = cargo_file_issues("cargo/config", "[build]\njobs = 2\n");
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].code.as_deref(), Some("CARGO002"));
+        assert!(!is_cargo_config("nvim/config.toml"));
+    }
+}
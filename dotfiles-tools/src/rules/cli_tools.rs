@@ -0,0 +1,358 @@
+//! Rules for ripgrep's config, fd's ignore files, and bat's config, where a
+//! typo doesn't fail loudly so much as quietly change what the tool does.
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use tracing::debug;
+
+use std::{collections::BTreeSet, path::Path, process::Command, sync::LazyLock};
+
+use crate::{
+    repo::{find_executable, get_content_files, read_file_content},
+    rules::check_files,
+    shell::shell_words,
+    snippet::line_of,
+    types::{Config, Issue, Severity, ValidationResult},
+};
+
+// bat's special theme values, which pick a theme by terminal background
+const BAT_THEME_KEYWORDS: &[&str] = &["auto", "auto:always", "auto:system", "dark", "light"];
+
+fn file_name(file: &str) -> &str {
+    Path::new(file)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default()
+}
+
+fn parent_name(file: &str) -> &str {
+    Path::new(file)
+        .parent()
+        .and_then(|p| p.file_name())
+        .and_then(|n| n.to_str())
+        .unwrap_or_default()
+}
+
+pub fn is_ripgrep_config(file: &str) -> bool {
+    matches!(file_name(file), ".ripgreprc" | "ripgreprc")
+        || (parent_name(file) == "ripgrep" && file_name(file) == "config")
+}
+
+pub fn is_fd_ignore(file: &str) -> bool {
+    file_name(file) == ".fdignore" || (parent_name(file) == "fd" && file_name(file) == "ignore")
+}
+
+pub fn is_bat_config(file: &str) -> bool {
+    parent_name(file) == "bat" && file_name(file) == "config"
+}
+
+/// The long and short flags the installed rg accepts, from `rg --help`, or
+/// None when it can't say. Negations like --no-hidden are only mentioned in
+/// the descriptions, which is why every `--flag` in the text counts.
+fn ripgrep_flags() -> Result<Option<BTreeSet<String>>> {
+    static RE_LONG: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"--[a-z0-9][a-z0-9-]*").unwrap());
+    static RE_SHORT: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"(?m)^\s+(-[^-\s])(?: [A-Z]+)?, --").unwrap());
+
+    // A broken config would otherwise stop rg from printing its help
+    let output = Command::new("rg")
+        .args(["--no-config", "--help"])
+        .output()
+        .context("Failed to run rg --help")?;
+    if !output.status.success() {
+        debug!("skipping flag check: rg --help failed");
+        return Ok(None);
+    }
+    let help = String::from_utf8_lossy(&output.stdout);
+    Ok(Some(
+        RE_LONG
+            .find_iter(&help)
+            .map(|m| m.as_str().to_string())
+            .chain(RE_SHORT.captures_iter(&help).map(|c| c[1].to_string()))
+            .collect(),
+    ))
+}
+
+// ripgrep reads each line of its config as exactly one argument, so a flag
+// and its value need `=` (or a line each), and `--a --b` is one bogus flag.
+// `known` is None when rg isn't installed to ask.
+pub fn ripgrep_config_issues(
+    file: &str,
+    content: &str,
+    known: Option<&BTreeSet<String>>,
+) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    for (idx, line) in content.lines().enumerate() {
+        let line = line.trim_end();
+        // Lines that don't start with a dash are values for the flag above
+        if line.starts_with('#') || !line.starts_with('-') || line == "--" {
+            continue;
+        }
+        let name = line.split_once('=').map_or(line, |(name, _)| name);
+        if name.contains(char::is_whitespace) {
+            let flag = name.split_whitespace().next().unwrap_or(name);
+            issues.push(
+                Issue::new(
+                    Severity::Error,
+                    format!(
+                        "'{}' is read as a single argument; ripgrep wants one flag per line",
+                        line
+                    ),
+                )
+                .with_code("RG001")
+                .with_file(file.to_string())
+                .with_fix(format!("Split it across lines, or write {}=<value>", flag))
+                .at(idx + 1, 1),
+            );
+            continue;
+        }
+        // A cluster like -uu or a value like -g!*.lock only says something
+        // about its first letter
+        let flag = if name.starts_with("--") {
+            name
+        } else {
+            &name[..name.char_indices().nth(2).map_or(name.len(), |(i, _)| i)]
+        };
+        if let Some(known) = known
+            && !known.contains(flag)
+        {
+            issues.push(
+                Issue::new(
+                    Severity::Error,
+                    format!("rg doesn't accept the flag {}", flag),
+                )
+                .with_code("RG002")
+                .with_file(file.to_string())
+                .at(idx + 1, 1),
+            );
+        }
+    }
+    issues
+}
+
+pub fn ripgrep_configs_valid(config: &Config) -> Result<ValidationResult> {
+    let candidates = get_content_files(config)?;
+    let files: Vec<_> = candidates.iter().filter(|f| is_ripgrep_config(f)).collect();
+    // Accepted flags change between releases, so they come from the
+    // installed rg rather than a list that goes stale
+    let known = if files.is_empty() || find_executable("rg").is_none() {
+        debug!("skipping flag check: no ripgrep config or rg not on PATH");
+        None
+    } else {
+        ripgrep_flags()?
+    };
+
+    let mut issues = Vec::new();
+    for file in &files {
+        if let Some(content) = read_file_content(config, file) {
+            issues.extend(ripgrep_config_issues(file, &content, known.as_ref()));
+        }
+    }
+    Ok(ValidationResult::new(
+        format!("All {} ripgrep configs are valid", files.len()),
+        issues.is_empty(),
+        issues,
+    ))
+}
+
+// What's wrong with a gitignore-style glob, in the words of the globset
+// crate fd parses it with
+pub fn glob_error(pattern: &str) -> Option<&'static str> {
+    let mut chars = pattern.chars().peekable();
+    let mut in_alternates = false;
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.next().is_none() => return Some("dangling escape"),
+            '[' => {
+                // `]` right after the opening (or its negation) is a literal
+                if matches!(chars.peek(), Some('!' | '^')) {
+                    chars.next();
+                }
+                let mut class = Vec::new();
+                if chars.peek() == Some(&']') {
+                    class.push(chars.next()?);
+                }
+                loop {
+                    match chars.next() {
+                        Some(']') => break,
+                        Some(c) => class.push(c),
+                        None => return Some("unclosed character class"),
+                    }
+                }
+                let invalid_range = class.windows(3).any(|w| w[1] == '-' && w[0] > w[2]);
+                if invalid_range {
+                    return Some("invalid character range");
+                }
+            }
+            '{' if in_alternates => return Some("nested alternate groups"),
+            '{' => in_alternates = true,
+            '}' => in_alternates = false,
+            _ => {}
+        }
+    }
+    in_alternates.then_some("unclosed alternate group")
+}
+
+pub fn fd_ignore_issues(file: &str, content: &str) -> Vec<Issue> {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.starts_with('#'))
+        .filter_map(|(idx, line)| {
+            let error = glob_error(line.trim_end())?;
+            Some(
+                Issue::new(
+                    Severity::Warning,
+                    format!("fd can't parse the glob '{}': {}", line.trim_end(), error),
+                )
+                .with_code("FD001")
+                .with_file(file.to_string())
+                .at(idx + 1, 1),
+            )
+        })
+        .collect()
+}
+
+pub fn fd_ignores_valid(config: &Config) -> Result<ValidationResult> {
+    let (count, issues) = check_files(config, "fd-ignore", is_fd_ignore, |file, content| {
+        Ok(fd_ignore_issues(file, content))
+    })?;
+    Ok(ValidationResult::new(
+        format!("All {} fd ignore files are valid", count),
+        issues.is_empty(),
+        issues,
+    ))
+}
+
+// Every theme a bat config selects, from --theme, --theme-dark and
+// --theme-light in either the `--flag=value` or `--flag value` form
+pub fn bat_themes(content: &str) -> Vec<(String, String)> {
+    let mut themes = Vec::new();
+    for line in content.lines().filter(|l| !l.trim_start().starts_with('#')) {
+        let words = shell_words(line);
+        let mut words = words.iter();
+        while let Some(word) = words.next() {
+            let (flag, value) = match word.split_once('=') {
+                Some((flag, value)) => (flag, Some(value.to_string())),
+                None => (word.as_str(), None),
+            };
+            if matches!(flag, "--theme" | "--theme-dark" | "--theme-light")
+                && let Some(value) = value.or_else(|| words.next().cloned())
+            {
+                themes.push((flag.to_string(), value));
+            }
+        }
+    }
+    themes
+}
+
+pub fn bat_configs_valid(config: &Config) -> Result<ValidationResult> {
+    let candidates = get_content_files(config)?;
+    let files: Vec<_> = candidates.iter().filter(|f| is_bat_config(f)).collect();
+    let rule_name = format!("Themes in {} bat configs exist", files.len());
+    // Debian and Ubuntu ship bat as batcat
+    let bat = find_executable("bat").or_else(|| find_executable("batcat"));
+    let Some(bat) = bat.filter(|_| !files.is_empty()) else {
+        debug!("skipping: no bat config or bat not on PATH");
+        return Ok(ValidationResult::new(rule_name, true, Vec::new()));
+    };
+
+    let output = Command::new(bat)
+        .args(["--list-themes", "--color=never"])
+        .output()
+        .context("Failed to run bat --list-themes")?;
+    let installed: BTreeSet<String> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|l| l.trim().to_string())
+        .filter(|l| !l.is_empty())
+        .collect();
+
+    let mut issues = Vec::new();
+    for file in &files {
+        let Some(content) = read_file_content(config, file) else {
+            continue;
+        };
+        // Custom themes tracked next to the config may not be built into
+        // bat's cache yet
+        let themes_dir = Path::new(file.as_str()).with_file_name("themes");
+        for (flag, theme) in bat_themes(&content) {
+            let tracked = candidates.iter().any(|f| {
+                Path::new(f).parent() == Some(themes_dir.as_path())
+                    && Path::new(f)
+                        .file_stem()
+                        .is_some_and(|s| s == theme.as_str())
+            });
+            if installed.contains(&theme)
+                || tracked
+                || (flag == "--theme" && BAT_THEME_KEYWORDS.contains(&theme.as_str()))
+            {
+                continue;
+            }
+            let issue = Issue::new(
+                Severity::Warning,
+                format!("bat has no theme '{}' ({})", theme, flag),
+            )
+            .with_code("BAT001")
+            .with_file(file.to_string())
+            .with_fix("Pick a name from `bat --list-themes`".to_string());
+            issues.push(match line_of(&content, &theme) {
+                Some(line) => issue.at(line, 1),
+                None => issue,
+            });
+        }
+    }
+    Ok(ValidationResult::new(rule_name, issues.is_empty(), issues))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ripgrep_flags_are_one_per_line_and_known() {
+        let content = "# defaults\n--smart-case\n--glob=!*.lock\n--glob\n!.git/\n--hidden --follow\n--max-columns 150\n-S\n--smrat-case\n";
+        let known: BTreeSet<String> = ["--smart-case", "--glob", "--hidden", "--max-columns", "-S"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let found: Vec<_> = ripgrep_config_issues(".ripgreprc", content, Some(&known))
+            .iter()
+            .map(|i| (i.code.clone().unwrap(), i.line.unwrap()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("RG001".to_string(), 6),
+                ("RG001".to_string(), 7),
+                ("RG002".to_string(), 9)
+            ]
+        );
+        assert!(is_ripgrep_config("ripgrep/config"));
+    }
+
+    #[test]
+    fn fd_globs_and_bat_themes_are_parsed() {
+        assert_eq!(glob_error("*.{rs,toml}"), None);
+        assert_eq!(glob_error("[]a]"), None);
+        assert_eq!(glob_error("\\[literal"), None);
+        assert_eq!(glob_error("[a-z"), Some("unclosed character class"));
+        assert_eq!(glob_error("[z-a].txt"), Some("invalid character range"));
+        assert_eq!(glob_error("*.{rs,{a,b}}"), Some("nested alternate groups"));
+        assert_eq!(glob_error("*.{rs"), Some("unclosed alternate group"));
+        assert_eq!(glob_error("trailing\\"), Some("dangling escape"));
+
+        let issues = fd_ignore_issues("fd/ignore", "# comment\nnode_modules/\n*.{log\n");
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].line, Some(3));
+
+        let content = "--style=numbers\n# --theme=Commented\n--theme=\"Monokai Extended\"\n--theme-dark Nord\n";
+        assert_eq!(
+            bat_themes(content),
+            [
+                ("--theme".to_string(), "Monokai Extended".to_string()),
+                ("--theme-dark".to_string(), "Nord".to_string())
+            ]
+        );
+    }
+}
@@ -0,0 +1,191 @@
+//! Rule for direnv's .envrc files: bash syntax, secrets exported in the
+//! clear, and whether a deployed .envrc has a direnv to load it.
+
+use anyhow::Result;
+use regex::Regex;
+
+use std::{path::Path, sync::LazyLock};
+
+use crate::{
+    dotter::mapped_files,
+    repo::{get_content_files, get_tracked_files, read_file_content},
+    rules::{shell::shell_syntax_issues, tools::collect_tool_pins},
+    types::{Config, Issue, Severity, ValidationResult},
+};
+
+// Values that pull the secret from somewhere else at load time, or point at
+// a file holding it
+const INDIRECT_PREFIXES: &[&str] = &["$", "`", "op://", "~", "/"];
+
+pub fn is_envrc(file: &str) -> bool {
+    Path::new(file).file_name().is_some_and(|n| n == ".envrc")
+}
+
+// `export NAME=value` lines whose name or value looks like a credential,
+// unless the value is read from a command, variable or 1Password
+pub fn secret_exports(content: &str) -> Vec<(usize, String)> {
+    static RE_EXPORT: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"^\s*export\s+([A-Za-z_][A-Za-z0-9_]*)=(\S.*)$").unwrap());
+    static RE_SECRET_NAME: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r"(?i)(^|_)(TOKEN|SECRET|PASSWORD|PASSWD|API_?KEY|PRIVATE_KEY|ACCESS_KEY)$")
+            .unwrap()
+    });
+    static RE_SECRET_VALUE: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r"^['\x22]?(gh[pousr]_|github_pat_|glpat-|sk-|xox[abpr]-|AKIA[0-9A-Z]{16})")
+            .unwrap()
+    });
+
+    content
+        .lines()
+        .enumerate()
+        .filter_map(|(idx, line)| {
+            let caps = RE_EXPORT.captures(line)?;
+            let value = caps[2].trim();
+            let unquoted = value.trim_matches(['"', '\'']);
+            let indirect =
+                unquoted.is_empty() || INDIRECT_PREFIXES.iter().any(|p| unquoted.starts_with(p));
+            let secret =
+                RE_SECRET_VALUE.is_match(value) || (RE_SECRET_NAME.is_match(&caps[1]) && !indirect);
+            secret.then(|| (idx + 1, caps[1].to_string()))
+        })
+        .collect()
+}
+
+// Whether a Brewfile or a tool version pin installs direnv
+fn direnv_is_listed(config: &Config) -> Result<bool> {
+    static RE_BREW: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r#"^\s*brew\s+["'](?:[\w-]+/[\w-]+/)?direnv["']"#).unwrap());
+
+    let (pins, _) = collect_tool_pins(config)?;
+    if pins.iter().any(|p| p.tool == "direnv") {
+        return Ok(true);
+    }
+    let tracked = get_tracked_files(config)?;
+    Ok(tracked
+        .iter()
+        .filter(|f| Path::new(f).file_name().is_some_and(|n| n == "Brewfile"))
+        .filter_map(|f| read_file_content(config, f))
+        .any(|content| content.lines().any(|l| RE_BREW.is_match(l))))
+}
+
+pub fn envrc_files_valid(config: &Config) -> Result<ValidationResult> {
+    let candidates = get_content_files(config)?;
+    let files: Vec<_> = candidates.iter().filter(|f| is_envrc(f)).collect();
+
+    let mut issues = Vec::new();
+    for file in &files {
+        let Some(content) = read_file_content(config, file) else {
+            continue;
+        };
+        // direnv always evaluates .envrc with bash
+        issues.extend(shell_syntax_issues(file, &content, "bash", "ENVRC001")?);
+        for (line, name) in secret_exports(&content) {
+            issues.push(
+                Issue::new(
+                    Severity::Error,
+                    format!("{} exports what looks like a secret in {}", file, name),
+                )
+                .with_code("ENVRC002")
+                .with_file(file.to_string())
+                .with_fix(format!(
+                    "Read {} from a secret store, e.g. export {}=\"$(op read op://...)\"",
+                    name, name
+                ))
+                .at(line, 1),
+            );
+        }
+    }
+
+    // A deployed .envrc does nothing on a machine set up without direnv
+    let mapped = if files.is_empty() {
+        Vec::new()
+    } else {
+        mapped_files(config)?
+    };
+    let deployed: Vec<_> = files
+        .iter()
+        .filter(|f| {
+            mapped
+                .iter()
+                .any(|m| **f == &m.source || f.starts_with(&format!("{}/", m.source)))
+        })
+        .collect();
+    if let Some(first) = deployed.first()
+        && !direnv_is_listed(config)?
+    {
+        issues.push(
+            Issue::new(
+                Severity::Warning,
+                format!(
+                    "{} .envrc file(s) are deployed, but direnv isn't in a Brewfile or tool \
+                     version file",
+                    deployed.len()
+                ),
+            )
+            .with_code("ENVRC003")
+            .with_file(first.to_string())
+            .with_fix("Add brew \"direnv\" to the Brewfile".to_string()),
+        );
+    }
+
+    let passed = issues.iter().all(|i| i.severity != Severity::Error);
+    Ok(ValidationResult::new(
+        format!("All {} .envrc files are valid", files.len()),
+        passed,
+        issues,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::FixtureRepo;
+
+    #[test]
+    fn secrets_and_missing_direnv_are_reported() {
+        let repo = FixtureRepo::new();
+        repo.write(
+            ".dotter/global.toml",
+            "[direnv.files]\n\"projects/.envrc\" = \"~/projects/.envrc\"\n",
+        )
+        .write(
+            "projects/.envrc",
+            "export GITHUB_TOKEN=ghp_abcdef\n\
+             export API_KEY=\"$(op read op://Private/api/key)\"\n\
+             export DB_PASSWORD=hunter2\n\
+             export EDITOR=hx\n\
+             export TOKENIZERS_PARALLELISM=false\n\
+             export NPM_TOKEN=~/.config/npm/token\n\
+             export OPENAI=sk-abc123\n",
+        )
+        .write("Brewfile", "brew \"ripgrep\"\n")
+        .commit("fixture");
+
+        let result = envrc_files_valid(&repo.config()).unwrap();
+        let found: Vec<_> = result
+            .issues
+            .iter()
+            .map(|i| (i.code.as_deref().unwrap(), i.line))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("ENVRC002", Some(1)),
+                ("ENVRC002", Some(3)),
+                ("ENVRC002", Some(7)),
+                ("ENVRC003", None)
+            ]
+        );
+        assert!(!result.passed);
+
+        repo.write("Brewfile", "brew \"ripgrep\"\nbrew \"direnv\"\n")
+            .commit("add direnv");
+        let result = envrc_files_valid(&repo.config()).unwrap();
+        assert!(
+            result
+                .issues
+                .iter()
+                .all(|i| i.code.as_deref() != Some("ENVRC003"))
+        );
+    }
+}
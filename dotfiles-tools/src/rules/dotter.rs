@@ -0,0 +1,715 @@
+//! Rules for dotter's own configuration.
+
+use anyhow::{Context, Result};
+use indicatif::ProgressIterator;
+use tracing::debug;
+
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs,
+    path::Path,
+};
+
+use crate::{
+    dotter::{
+        DeployKind, defined_packages_and_helpers, local_profile, mapped_files,
+        platform_config_files, selected_files, selected_variables, tilde_path,
+    },
+    fixes::shell_quote,
+    output::progress_bar,
+    paths::{glob_to_regex, is_link},
+    repo::{expand_home, get_tracked_files, get_working_tree_files, home_dir, ignored_by_git},
+    snippet::line_col,
+    template::{is_defined, required_variables},
+    types::{Config, Issue, Severity, ValidationResult},
+};
+
+pub fn dotter_configs_exist(config: &Config) -> ValidationResult {
+    let global_toml = config.dotfiles_dir.join(".dotter/global.toml");
+    let mut issues = Vec::new();
+
+    if !global_toml.exists() {
+        issues.push(
+            Issue::new(Severity::Error, "Dotter global.toml not found")
+                .with_code("DOT001")
+                .with_file(global_toml.display().to_string()),
+        );
+    }
+
+    ValidationResult::new(
+        "Dotter configuration files exist",
+        issues.is_empty(),
+        issues,
+    )
+}
+
+#[derive(Debug, Default)]
+struct SourceDirContents {
+    // Files and working symlinks, repo-relative
+    files: Vec<String>,
+    broken_links: Vec<String>,
+    // Directories with nothing in them, with a trailing '/'
+    empty_dirs: Vec<String>,
+}
+
+// Walks a directory source; submodules (tracked as a single gitlink) and
+// symlinked directories are entries of their own rather than walked into
+fn walk_source_dir(
+    config: &Config,
+    tracked: &HashSet<String>,
+    dir: &str,
+    contents: &mut SourceDirContents,
+) {
+    let Ok(entries) = fs::read_dir(config.dotfiles_dir.join(dir)) else {
+        return;
+    };
+    let mut is_empty = true;
+    for entry in entries.flatten() {
+        is_empty = false;
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name == ".git" {
+            continue;
+        }
+        let relative = format!("{}/{}", dir, name);
+        let Ok(metadata) = fs::symlink_metadata(entry.path()) else {
+            continue;
+        };
+        if is_link(&metadata) && fs::metadata(entry.path()).is_err() {
+            contents.broken_links.push(relative);
+        } else if metadata.is_dir() && !is_link(&metadata) && !tracked.contains(&relative) {
+            walk_source_dir(config, tracked, &relative, contents);
+        } else {
+            contents.files.push(relative);
+        }
+    }
+    if is_empty {
+        contents.empty_dirs.push(format!("{}/", dir));
+    }
+}
+
+pub fn dotter_files_tracked(config: &Config) -> Result<ValidationResult> {
+    let mut all_files = HashSet::new();
+
+    // Parse TOML files to extract referenced files
+    for toml_path in platform_config_files(config)? {
+        let content = fs::read_to_string(&toml_path)
+            .with_context(|| format!("Failed to read {}", toml_path.display()))?;
+
+        let doc: toml::Value = toml::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", toml_path.display()))?;
+
+        if let Some(table) = doc.as_table() {
+            for (_, value) in table {
+                if let Some(files) = value.get("files")
+                    && let Some(files_table) = files.as_table()
+                {
+                    for (source, _) in files_table {
+                        all_files.insert(source.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    debug!(
+        count = all_files.len(),
+        "found files referenced in dotter configs"
+    );
+
+    // A glob source stands for every file it matches, each of which needs
+    // the same checks as a literal source
+    let mut issues = Vec::new();
+    let (patterns, literals): (Vec<_>, Vec<_>) = all_files
+        .into_iter()
+        .partition(|s| s.contains(['*', '?', '[', '{']));
+    let mut all_files: HashSet<String> = literals.into_iter().collect();
+    if !patterns.is_empty() {
+        let present = get_working_tree_files(config)?;
+        for pattern in &patterns {
+            // Sources are relative to the repo root, never matched at any depth
+            let regex = glob_to_regex(&format!("/{}", pattern))
+                .with_context(|| format!("Invalid glob in dotter config: {}", pattern))?;
+            let matched: Vec<_> = present.iter().filter(|f| regex.is_match(f)).collect();
+            if matched.is_empty() {
+                issues.push(
+                    Issue::new(
+                        Severity::Error,
+                        format!("Glob matches no files: {}", pattern),
+                    )
+                    .with_code("DOT009")
+                    .with_file(pattern.clone()),
+                );
+            }
+            all_files.extend(matched.into_iter().cloned());
+        }
+    }
+
+    // One git call each for tracked and ignored files, rather than two per
+    // source; a directory source counts as tracked if anything under it is
+    let tracked: HashSet<String> = get_tracked_files(config)?.into_iter().collect();
+
+    // A directory source deploys everything under it, so each file gets the
+    // checks a literal source does; git also can't carry an empty directory
+    // or a dangling link to another machine
+    let directories: Vec<_> = all_files
+        .iter()
+        .filter(|s| {
+            !tracked.contains(s.trim_end_matches('/'))
+                && fs::symlink_metadata(config.dotfiles_dir.join(s))
+                    .is_ok_and(|m| m.is_dir() && !is_link(&m))
+        })
+        .cloned()
+        .collect();
+    for dir in directories {
+        all_files.remove(&dir);
+        let mut contents = SourceDirContents::default();
+        walk_source_dir(config, &tracked, dir.trim_end_matches('/'), &mut contents);
+        for link in contents.broken_links {
+            issues.push(
+                Issue::new(
+                    Severity::Error,
+                    format!("Broken symlink in directory source {}: {}", dir, link),
+                )
+                .with_code("DOT010")
+                .with_file(link),
+            );
+        }
+        for empty in contents.empty_dirs {
+            issues.push(
+                Issue::new(
+                    Severity::Warning,
+                    format!("Empty directory in directory source {}: {}", dir, empty),
+                )
+                .with_code("DOT011")
+                .with_file(empty.clone())
+                .with_fix(format!(
+                    "Remove it, or add a placeholder: touch {}.gitkeep",
+                    empty
+                )),
+            );
+        }
+        all_files.extend(contents.files);
+    }
+
+    let is_tracked = |source: &str| {
+        let source = source.trim_end_matches('/');
+        tracked.contains(source)
+            || tracked.iter().any(|f| {
+                f.strip_prefix(source)
+                    .is_some_and(|rest| rest.starts_with('/'))
+            })
+    };
+    let untracked: Vec<_> = all_files.iter().filter(|s| !is_tracked(s)).collect();
+    let ignored = ignored_by_git(config, untracked.iter().copied());
+
+    let progress = progress_bar(config, "dotter files", all_files.len());
+    for source in all_files.iter().progress_with(progress) {
+        let filepath = config.dotfiles_dir.join(source);
+
+        if !filepath.exists() {
+            issues.push(
+                Issue::new(Severity::Error, format!("File missing: {}", source))
+                    .with_code("DOT002")
+                    .with_file(source.clone()),
+            );
+            continue;
+        }
+
+        if !is_tracked(source) {
+            if ignored.contains(source) {
+                issues.push(
+                    Issue::new(Severity::Error, format!("File ignored by git: {}", source))
+                        .with_code("DOT003")
+                        .with_file(source.clone())
+                        .with_fix(format!("Add to .gitignore: !{}", source)),
+                );
+            } else {
+                issues.push(
+                    Issue::new(Severity::Warning, format!("File not tracked: {}", source))
+                        .with_code("DOT004")
+                        .with_file(source.clone())
+                        .with_fix(format!("Run: git add {}", source)),
+                );
+            }
+        }
+    }
+
+    let passed = issues.iter().all(|i| i.severity != Severity::Error);
+    Ok(ValidationResult::new(
+        "Dotter files exist and are tracked",
+        passed,
+        issues,
+    ))
+}
+
+// dotter's per-machine files; a trailing '/' marks a directory
+const MACHINE_LOCAL: &[&str] = &[".dotter/local.toml", ".dotter/cache.toml", ".dotter/cache/"];
+
+pub fn dotter_local_files_ignored(config: &Config) -> Result<ValidationResult> {
+    let tracked = get_tracked_files(config)?;
+    let paths: Vec<String> = MACHINE_LOCAL.iter().map(|p| p.to_string()).collect();
+    let ignored = ignored_by_git(config, &paths);
+
+    let mut issues = Vec::new();
+    for path in &paths {
+        let tracked_here: Vec<_> = tracked
+            .iter()
+            .filter(|f| *f == path || (path.ends_with('/') && f.starts_with(path.as_str())))
+            .collect();
+        if !tracked_here.is_empty() {
+            issues.push(
+                Issue::new(
+                    Severity::Error,
+                    format!(
+                        "Machine-local dotter state is tracked: {}; untrack it and add it to \
+                         .gitignore",
+                        path
+                    ),
+                )
+                .with_code("DOT005")
+                .with_file(path.clone())
+                .with_fix(format!("Run: git rm -r --cached -- {}", shell_quote(path))),
+            );
+        } else if !ignored.contains(path) {
+            issues.push(
+                Issue::new(Severity::Warning, format!("Not in .gitignore: {}", path))
+                    .with_code("DOT006")
+                    .with_file(".gitignore".to_string())
+                    .with_fix(format!("Add to .gitignore: {}", path)),
+            );
+        }
+    }
+
+    let passed = issues.iter().all(|i| i.severity != Severity::Error);
+    Ok(ValidationResult::new(
+        "Machine-local dotter files are ignored",
+        passed,
+        issues,
+    ))
+}
+
+// Whether writing to `path` needs another user's permissions: its nearest
+// existing ancestor belongs to someone other than the owner of $HOME and
+// isn't world-writable
+#[cfg(unix)]
+fn needs_elevation(path: &Path, home: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    let Ok(home_owner) = fs::metadata(home).map(|m| m.uid()) else {
+        return false;
+    };
+    path.ancestors()
+        .find_map(|dir| fs::metadata(dir).ok())
+        .is_some_and(|m| m.uid() != home_owner && m.mode() & 0o002 == 0)
+}
+
+#[cfg(not(unix))]
+fn needs_elevation(_path: &Path, _home: &Path) -> bool {
+    false
+}
+
+pub fn deploy_targets_safe(config: &Config) -> Result<ValidationResult> {
+    let Some(home) = home_dir() else {
+        debug!("skipping: no home directory");
+        return Ok(ValidationResult::new(
+            "Deploy targets stay in the home directory",
+            true,
+            Vec::new(),
+        ));
+    };
+    let allowed = config
+        .settings
+        .deploy
+        .allowed_targets
+        .iter()
+        .map(|pattern| {
+            let pattern = expand_home(&tilde_path(pattern)).display().to_string();
+            glob_to_regex(&pattern)
+                .with_context(|| format!("Invalid allowed-targets pattern '{}'", pattern))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut issues = Vec::new();
+    let mut seen = HashSet::new();
+    for file in mapped_files(config)? {
+        let target = tilde_path(&file.target);
+        if !seen.insert(target.clone()) {
+            continue;
+        }
+        let path = expand_home(&target);
+        // A relative target depends on where dotter runs, so it can't be judged
+        let display = path.display().to_string();
+        if !path.is_absolute()
+            || allowed
+                .iter()
+                .any(|re| re.is_match(display.trim_start_matches('/')))
+        {
+            continue;
+        }
+        // A hardcoded home directory from another machine isn't a system path
+        let foreign_home = ["/Users", "/home"].iter().find_map(|root| {
+            let user = path.strip_prefix(root).ok()?.components().next()?;
+            Some(Path::new(root).join(user))
+        });
+        if foreign_home.is_some_and(|dir| !dir.exists()) {
+            continue;
+        }
+        let (code, problem) = if !path.starts_with(&home) {
+            ("DOT007", "is outside the home directory")
+        } else if needs_elevation(&path, &home) {
+            ("DOT008", "needs elevated permissions to write")
+        } else {
+            continue;
+        };
+        issues.push(
+            Issue::new(
+                Severity::Error,
+                format!("Target {} for {} {}", target, file.source, problem),
+            )
+            .with_code(code)
+            .with_file(file.source.clone())
+            .with_fix(format!(
+                "Deploy under ~ instead, or allow it in .validate-dotfiles.toml: \
+                 [deploy] allowed-targets = [\"{}\"]",
+                target
+            )),
+        );
+    }
+
+    Ok(ValidationResult::new(
+        "Deploy targets stay in the home directory",
+        issues.is_empty(),
+        issues,
+    ))
+}
+
+// A template variable some hosts don't define: where the template first uses
+// it, and which hosts
+struct UndefinedVariable<'a> {
+    at: (usize, usize),
+    hosts: Vec<&'a str>,
+}
+
+pub fn host_templates_render(config: &Config) -> Result<ValidationResult> {
+    let hosts = &config.settings.hosts;
+    if hosts.is_empty() {
+        debug!("skipping: no [hosts] declared in settings");
+        return Ok(ValidationResult::new(
+            "Templates render on every declared host",
+            true,
+            Vec::new(),
+        ));
+    }
+
+    let mut issues = Vec::new();
+    let mut undefined: BTreeMap<(String, String), UndefinedVariable> = BTreeMap::new();
+    let mut checked = 0;
+    for (hostname, host) in hosts {
+        let selection = match &host.profile {
+            Some(name) => match config.settings.profiles.get(name) {
+                Some(profile) => profile.clone(),
+                None => {
+                    issues.push(
+                        Issue::new(
+                            Severity::Error,
+                            format!("Host '{}' uses undeclared profile '{}'", hostname, name),
+                        )
+                        .with_code("HOST002"),
+                    );
+                    continue;
+                }
+            },
+            None => match local_profile(config)? {
+                Some(local) => local,
+                None => {
+                    debug!(
+                        "skipping host {}: no profile and no .dotter/local.toml",
+                        hostname
+                    );
+                    continue;
+                }
+            },
+        };
+        checked += 1;
+
+        let (_, helpers) = defined_packages_and_helpers(config, &selection.includes)?;
+        let mut variables = selected_variables(config, &selection.packages, &selection.includes)?;
+        variables.extend(selection.variables);
+        variables.extend(host.variables.clone());
+        for file in selected_files(config, &selection.packages, &selection.includes)? {
+            if file.kind != DeployKind::Template {
+                continue;
+            }
+            let Ok(content) = fs::read_to_string(config.dotfiles_dir.join(&file.source)) else {
+                continue;
+            };
+            for (variable, offset) in required_variables(&content, &helpers, Some(hostname)) {
+                if is_defined(&variables, &variable) {
+                    continue;
+                }
+                let entry = undefined
+                    .entry((file.source.clone(), variable))
+                    .or_insert_with(|| UndefinedVariable {
+                        at: line_col(&content, offset),
+                        hosts: Vec::new(),
+                    });
+                if !entry.hosts.contains(&hostname.as_str()) {
+                    entry.hosts.push(hostname);
+                }
+            }
+        }
+    }
+
+    for ((source, variable), UndefinedVariable { at, hosts: failing }) in undefined {
+        let message = if failing.len() == checked {
+            format!(
+                "{} fails to render on every host: '{}' is undefined",
+                source, variable
+            )
+        } else {
+            format!(
+                "{} fails to render only on {}: '{}' is undefined there",
+                source,
+                failing.join(", "),
+                variable
+            )
+        };
+        issues.push(
+            Issue::new(Severity::Error, message)
+                .with_code("HOST001")
+                .with_file(source)
+                .with_fix(format!(
+                    "Define '{}' for those hosts, or guard it with {{{{#if {}}}}}",
+                    variable, variable
+                ))
+                .at(at.0, at.1),
+        );
+    }
+
+    Ok(ValidationResult::new(
+        format!("Templates render on {} declared hosts", hosts.len()),
+        issues.is_empty(),
+        issues,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::FixtureRepo;
+
+    #[test]
+    fn missing_and_untracked_sources_are_reported() {
+        let repo = FixtureRepo::new();
+        repo.write(
+            ".dotter/global.toml",
+            "[zsh.files]\n\"zshrc\" = \"~/.zshrc\"\n\"gone\" = \"~/.gone\"\n\"new\" = \"~/.new\"\n",
+        )
+        .write("zshrc", "\n")
+        .commit("fixture");
+        repo.write("new", "\n");
+
+        let result = dotter_files_tracked(&repo.config()).unwrap();
+        let mut messages: Vec<_> = result.issues.iter().map(|i| i.message.as_str()).collect();
+        messages.sort();
+        assert_eq!(messages, ["File missing: gone", "File not tracked: new"]);
+        assert!(!result.passed);
+    }
+
+    #[test]
+    fn platform_configs_are_discovered_and_filterable() {
+        let repo = FixtureRepo::new();
+        repo.write(
+            ".dotter/global.toml",
+            "[zsh.files]\n\"zshrc\" = \"~/.zshrc\"\n",
+        )
+        .write(".dotter/local.toml", "packages = [\"zsh\"]\n")
+        .write(".dotter/macos.toml", "[mac.files]\n\"mac\" = \"~/.mac\"\n")
+        .write(
+            ".dotter/linux.toml",
+            "[linux.files]\n\"linux\" = \"~/.linux\"\n",
+        )
+        .write("zshrc", "\n")
+        .commit("fixture");
+
+        let mut config = repo.config();
+        let missing = |config: &Config| -> Vec<String> {
+            let result = dotter_files_tracked(config).unwrap();
+            let mut messages: Vec<_> = result.issues.into_iter().map(|i| i.message).collect();
+            messages.sort();
+            messages
+        };
+        assert_eq!(
+            missing(&config),
+            ["File missing: linux", "File missing: mac"]
+        );
+
+        config.platform = Some("linux".to_string());
+        assert_eq!(missing(&config), ["File missing: linux"]);
+
+        config.platform = Some("bsd".to_string());
+        assert!(dotter_files_tracked(&config).is_err());
+    }
+
+    #[test]
+    fn machine_local_files_must_be_ignored_and_untracked() {
+        let repo = FixtureRepo::new();
+        repo.write(".gitignore", ".dotter/cache.toml\n.dotter/cache/\n")
+            .write(".dotter/global.toml", "\n")
+            .write(".dotter/local.toml", "packages = [\"zsh\"]\n")
+            .commit("fixture");
+
+        let result = dotter_local_files_ignored(&repo.config()).unwrap();
+        let found: Vec<_> = result
+            .issues
+            .iter()
+            .map(|i| i.code.as_deref().unwrap())
+            .collect();
+        assert_eq!(found, ["DOT005"], "{:?}", result.issues);
+        assert_eq!(
+            result.issues[0].fix_suggestion.as_deref(),
+            Some("Run: git rm -r --cached -- .dotter/local.toml")
+        );
+        assert!(!result.passed);
+
+        repo.write(".gitignore", "local.toml\n");
+        let result = dotter_local_files_ignored(&repo.config()).unwrap();
+        let found: Vec<_> = result
+            .issues
+            .iter()
+            .map(|i| (i.code.as_deref().unwrap(), i.file.as_deref().unwrap()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("DOT005", ".dotter/local.toml"),
+                ("DOT006", ".gitignore"),
+                ("DOT006", ".gitignore"),
+            ]
+        );
+    }
+
+    #[test]
+    fn targets_outside_home_need_allowing() {
+        let repo = FixtureRepo::new();
+        repo.write(
+            ".dotter/global.toml",
+            "[system.files]\n\"hosts\" = \"/etc/hosts\"\n\"brew-up\" = \"/usr/local/bin/brew-up\"\n\
+             \"nu\" = \"/Users/nobody-here/Library/nu\"\n\
+             \n[zsh.files]\n\"zshrc\" = \"~/.zshrc\"\n",
+        )
+        .commit("fixture");
+
+        let mut config = repo.config();
+        let sources = |config: &Config| -> Vec<String> {
+            let result = deploy_targets_safe(config).unwrap();
+            let mut sources: Vec<_> = result.issues.into_iter().filter_map(|i| i.file).collect();
+            sources.sort();
+            sources
+        };
+        assert_eq!(sources(&config), ["brew-up", "hosts"]);
+
+        config.settings.deploy.allowed_targets = vec!["/usr/local/bin/*".to_string()];
+        assert_eq!(sources(&config), ["hosts"]);
+    }
+
+    #[test]
+    fn glob_sources_are_checked_per_match() {
+        let repo = FixtureRepo::new();
+        repo.write(".gitignore", "*.secret.fish\n")
+            .write(
+                ".dotter/global.toml",
+                "[fish.files]\n\"fish/conf.d/*.fish\" = \"~/.config/fish/conf.d\"\n\
+                 \"fish/themes/*.theme\" = \"~/.config/fish/themes\"\n",
+            )
+            .write("fish/conf.d/abbr.fish", "\n")
+            .commit("fixture")
+            .write("fish/conf.d/path.fish", "\n")
+            .write("fish/conf.d/tokens.secret.fish", "\n");
+
+        let result = dotter_files_tracked(&repo.config()).unwrap();
+        let mut found: Vec<_> = result
+            .issues
+            .iter()
+            .map(|i| (i.code.as_deref().unwrap(), i.file.as_deref().unwrap()))
+            .collect();
+        found.sort();
+        assert_eq!(
+            found,
+            [
+                ("DOT003", "fish/conf.d/tokens.secret.fish"),
+                ("DOT004", "fish/conf.d/path.fish"),
+                ("DOT009", "fish/themes/*.theme"),
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn directory_sources_are_checked_file_by_file() {
+        let repo = FixtureRepo::new();
+        repo.write(".gitignore", "lazy-lock.json\n")
+            .write(
+                ".dotter/global.toml",
+                "[nvim.files]\nnvim = \"~/.config/nvim\"\n",
+            )
+            .write("nvim/init.lua", "\n")
+            .commit("fixture")
+            .write("nvim/lua/new.lua", "\n")
+            .write("nvim/lazy-lock.json", "{}\n")
+            .symlink("nvim/old.lua", "gone.lua");
+        fs::create_dir_all(repo.path().join("nvim/after/ftplugin")).unwrap();
+
+        let result = dotter_files_tracked(&repo.config()).unwrap();
+        let mut found: Vec<_> = result
+            .issues
+            .iter()
+            .map(|i| (i.code.as_deref().unwrap(), i.file.as_deref().unwrap()))
+            .collect();
+        found.sort();
+        assert_eq!(
+            found,
+            [
+                ("DOT003", "nvim/lazy-lock.json"),
+                ("DOT004", "nvim/lua/new.lua"),
+                ("DOT010", "nvim/old.lua"),
+                ("DOT011", "nvim/after/ftplugin/"),
+            ]
+        );
+    }
+
+    #[test]
+    fn hostname_branches_failing_on_some_hosts_are_reported() {
+        let repo = FixtureRepo::new();
+        repo.write(
+            ".dotter/global.toml",
+            "[git.files]\ngitconfig = { target = \"~/.gitconfig\", type = \"template\" }\n\
+             [git.variables]\nname = \"me\"\n",
+        )
+        .write(".dotter/local.toml", "packages = [\"git\"]\n")
+        .write(
+            "gitconfig",
+            "[user]\n\tname = {{name}}\n\
+             {{#if (eq dotter.hostname \"work\")}}\temail = {{work_email}}\n{{/if}}\
+             \tsigningkey = {{key}}\n",
+        )
+        .commit("fixture");
+        let mut config = repo.config();
+        config.settings.hosts = toml::from_str(
+            "[work]\n[mbp]\nvariables = { key = \"ABC\" }\n[nas]\nprofile = \"server\"\n",
+        )
+        .unwrap();
+
+        let result = host_templates_render(&config).unwrap();
+        let messages: Vec<_> = result.issues.iter().map(|i| i.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            [
+                "Host 'nas' uses undeclared profile 'server'",
+                "gitconfig fails to render only on work: 'key' is undefined there",
+                "gitconfig fails to render only on work: 'work_email' is undefined there",
+            ]
+        );
+        assert_eq!(result.issues[2].line, Some(3));
+    }
+}
@@ -0,0 +1,347 @@
+//! Formatting rules for TOML and JSON, checked without external formatters,
+//! and indentation consistency for the indentation-sensitive formats.
+
+use anyhow::Result;
+use indicatif::ProgressIterator;
+use tracing::trace_span;
+
+use std::path::Path;
+
+use crate::{
+    cache::file_issues,
+    format::{first_difference, format_json, format_toml},
+    output::progress_bar,
+    repo::{get_content_files, read_file_content},
+    rules::check_files,
+    settings::Indent,
+    types::{Config, Issue, Severity, ValidationResult},
+};
+
+/// The formatted version of `file`, when it's a TOML or JSON file this
+/// module can format without changing what it means.
+pub fn formatted_content(config: &Config, file: &str, content: &str) -> Option<String> {
+    if file.ends_with(".toml") {
+        let formatted = format_toml(content);
+        // Never suggest a rewrite that would parse differently
+        let same = toml::from_str::<toml::Value>(content).ok()?
+            == toml::from_str::<toml::Value>(&formatted).ok()?;
+        same.then_some(formatted)
+    } else if file.ends_with(".json") {
+        // Files with comments or other JSONC extensions are left alone
+        serde_json::from_str::<serde_json::Value>(content).ok()?;
+        format_json(content, config.settings.formatting.sort_json_keys)
+    } else {
+        None
+    }
+}
+
+fn formatting_issue(
+    config: &Config,
+    file: &str,
+    content: &str,
+    rule: &str,
+    code: &str,
+) -> Option<Issue> {
+    let formatted = formatted_content(config, file, content)?;
+    if formatted == content {
+        return None;
+    }
+    let line = first_difference(content, &formatted);
+    Some(
+        Issue::new(
+            Severity::Warning,
+            format!(
+                "{} is not formatted (first difference on line {})",
+                file, line
+            ),
+        )
+        .with_code(code)
+        .with_file(file.to_string())
+        .at(line, 1)
+        .with_fix(format!("Run: dotfiles-tools fix --enable {}", rule)),
+    )
+}
+
+fn check_formatting(
+    config: &Config,
+    extension: &str,
+    rule: &str,
+    code: &str,
+) -> Result<ValidationResult> {
+    let candidates = get_content_files(config)?;
+    let files: Vec<_> = candidates
+        .iter()
+        .filter(|f| f.ends_with(extension))
+        .collect();
+    let mut issues = Vec::new();
+
+    let progress = progress_bar(config, rule, files.len());
+    for file in files.iter().progress_with(progress) {
+        let _span = trace_span!("file", path = %file).entered();
+        let Some(content) = read_file_content(config, file) else {
+            continue;
+        };
+        issues.extend(file_issues(config, rule, file, &content, || {
+            Ok(formatting_issue(config, file, &content, rule, code)
+                .into_iter()
+                .collect())
+        })?);
+    }
+
+    Ok(ValidationResult::new(
+        format!(
+            "All {} {} files are formatted",
+            files.len(),
+            &extension[1..]
+        ),
+        true,
+        issues,
+    ))
+}
+
+pub fn toml_files_formatted(config: &Config) -> Result<ValidationResult> {
+    check_formatting(config, ".toml", "toml-format", "FMT001")
+}
+
+pub fn json_files_formatted(config: &Config) -> Result<ValidationResult> {
+    check_formatting(config, ".json", "json-format", "FMT002")
+}
+
+// Checked by the indentation rule even without a [formatting.indent] entry
+const INDENTED_EXTENSIONS: &[&str] = &["toml", "yaml", "yml", "lua"];
+
+fn extension(file: &str) -> &str {
+    Path::new(file)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default()
+}
+
+// Line numbers and leading whitespace of the lines whose indentation is
+// structure. Multi-line TOML strings, Lua long brackets and YAML block
+// scalars hold text indented however it needs to be, so they're left out
+fn structural_indents<'a>(ext: &str, content: &'a str) -> Vec<(usize, &'a str)> {
+    let mut indents = Vec::new();
+    let mut in_string = false;
+    // Indentation of the key that opened a YAML block scalar
+    let mut block_scalar: Option<usize> = None;
+    for (index, line) in content.lines().enumerate() {
+        let body = line.trim_start_matches([' ', '\t']);
+        let indent = &line[..line.len() - body.len()];
+        let starts_in_string = in_string;
+        match ext {
+            "toml" => {
+                let quotes = line.matches("\"\"\"").count() + line.matches("'''").count();
+                in_string ^= quotes % 2 == 1;
+            }
+            "lua" => {
+                if in_string {
+                    in_string = !line.contains("]]");
+                } else if let Some(open) = line.find("[[") {
+                    in_string = !line[open + 2..].contains("]]");
+                }
+            }
+            _ => {}
+        }
+        if starts_in_string || body.is_empty() {
+            continue;
+        }
+        if matches!(ext, "yaml" | "yml") {
+            if let Some(key_indent) = block_scalar {
+                if indent.len() > key_indent {
+                    continue;
+                }
+                block_scalar = None;
+            }
+            let end = body.trim_end();
+            let indicator =
+                end.trim_end_matches(|c: char| c == '-' || c == '+' || c.is_ascii_digit());
+            if indicator.ends_with(": |")
+                || indicator.ends_with(": >")
+                || indicator == "|"
+                || indicator == ">"
+                || indicator.ends_with("- |")
+                || indicator.ends_with("- >")
+            {
+                block_scalar = Some(indent.len());
+            }
+        }
+        indents.push((index + 1, indent));
+    }
+    indents
+}
+
+fn indentation_issues(file: &str, content: &str, expected: Option<Indent>) -> Vec<Issue> {
+    let ext = extension(file);
+    let structural = structural_indents(ext, content);
+    let (tabbed, spaced): (Vec<_>, Vec<_>) = structural
+        .iter()
+        .filter(|(_, indent)| !indent.is_empty())
+        .partition(|(_, indent)| indent.contains('\t'));
+    let mut issues = Vec::new();
+
+    // The lines out of place, what they use, and what the file should use.
+    // YAML doesn't allow tabs in indentation at all; otherwise an
+    // unconfigured file should follow its own majority
+    let yaml = matches!(ext, "yaml" | "yml");
+    let stray = match expected {
+        _ if yaml => Some((&tabbed, "tabs", "spaces")),
+        Some(Indent::Spaces(_)) => Some((&tabbed, "tabs", "spaces")),
+        Some(Indent::Tabs(_)) => Some((&spaced, "spaces", "tabs")),
+        None if tabbed.is_empty() || spaced.is_empty() => None,
+        None if tabbed.len() <= spaced.len() => Some((&tabbed, "tabs", "spaces")),
+        None => Some((&spaced, "spaces", "tabs")),
+    };
+    if let Some((lines, used, wanted)) = stray
+        && let Some((line, _)) = lines.first()
+    {
+        issues.push(
+            Issue::new(
+                Severity::Warning,
+                format!(
+                    "{} indents {} line(s) with {} instead of {} (first on line {})",
+                    file,
+                    lines.len(),
+                    used,
+                    wanted,
+                    line
+                ),
+            )
+            .with_code("FMT003")
+            .with_file(file.to_string())
+            .at(*line, 1),
+        );
+    }
+    if stray.is_some_and(|(_, _, wanted)| wanted == "tabs") {
+        return issues;
+    }
+
+    // Without a configured width, go by the most common step in
+    // indentation between consecutive lines
+    let width = match expected {
+        Some(Indent::Spaces(width)) => width,
+        _ => {
+            let mut steps = std::collections::BTreeMap::new();
+            let levels: Vec<_> = structural
+                .iter()
+                .filter(|(_, indent)| !indent.contains('\t'))
+                .collect();
+            for pair in levels.windows(2) {
+                let (a, b) = (pair[0].1.len(), pair[1].1.len());
+                if b > a {
+                    *steps.entry(b - a).or_insert(0) += 1;
+                }
+            }
+            let Some((&width, _)) = steps
+                .iter()
+                .max_by_key(|(step, count)| (**count, std::cmp::Reverse(**step)))
+            else {
+                return issues;
+            };
+            width
+        }
+    };
+    if width == 0 {
+        return issues;
+    }
+    let uneven: Vec<_> = spaced
+        .iter()
+        .filter(|(_, indent)| indent.len() % width != 0)
+        .collect();
+    if let Some((line, _)) = uneven.first() {
+        issues.push(
+            Issue::new(
+                Severity::Warning,
+                format!(
+                    "{} has {} line(s) indented by a non-multiple of {} spaces (first on line {})",
+                    file,
+                    uneven.len(),
+                    width,
+                    line
+                ),
+            )
+            .with_code("FMT004")
+            .with_file(file.to_string())
+            .at(*line, 1),
+        );
+    }
+    issues
+}
+
+pub fn indentation_consistent(config: &Config) -> Result<ValidationResult> {
+    let configured = &config.settings.formatting.indent;
+    let (checked, issues) = check_files(
+        config,
+        "indentation",
+        |file| {
+            let ext = extension(file);
+            INDENTED_EXTENSIONS.contains(&ext) || configured.contains_key(ext)
+        },
+        |file, content| {
+            Ok(indentation_issues(
+                file,
+                content,
+                configured.get(extension(file)).copied(),
+            ))
+        },
+    )?;
+
+    Ok(ValidationResult::new(
+        format!("Indentation is consistent in {} files", checked),
+        true,
+        issues,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::FixtureRepo;
+
+    #[test]
+    fn unformatted_files_are_reported_at_the_first_difference() {
+        let repo = FixtureRepo::new();
+        repo.write("good.toml", "a = 1\n")
+            .write("bad.toml", "a = 1\nb=2\n")
+            .write("bad.json", "{\"a\": 1}")
+            .write("commented.json", "{\n  // note\n  \"a\": 1\n}\n")
+            .commit("fixture");
+
+        let result = toml_files_formatted(&repo.config()).unwrap();
+        assert_eq!(result.issues.len(), 1);
+        assert_eq!(result.issues[0].location().as_deref(), Some("bad.toml:2:1"));
+
+        let result = json_files_formatted(&repo.config()).unwrap();
+        let files: Vec<_> = result
+            .issues
+            .iter()
+            .filter_map(|i| i.file.as_deref())
+            .collect();
+        assert_eq!(files, ["bad.json"]);
+    }
+
+    #[test]
+    fn mixed_and_uneven_indentation_is_reported() {
+        let yaml = "a:\n  b: 1\n\tc: 2\n  d:\n     e: 3\n  script: |\n     echo  hi\n";
+        let codes = |issues: Vec<Issue>| -> Vec<_> {
+            issues
+                .into_iter()
+                .map(|i| (i.code.unwrap(), i.line.unwrap()))
+                .collect()
+        };
+        assert_eq!(
+            codes(indentation_issues("ci.yaml", yaml, None)),
+            [("FMT003".to_string(), 3), ("FMT004".to_string(), 5)]
+        );
+
+        let lua = "local t = {\n\tx = 1,\n\ty = [[\n  text\n]],\n}\n";
+        assert!(indentation_issues("init.lua", lua, None).is_empty());
+        assert_eq!(
+            codes(indentation_issues("init.lua", lua, Some(Indent::Spaces(2)))),
+            [("FMT003".to_string(), 2)]
+        );
+
+        let toml = "[a]\n    b = \"\"\"\n  text\n\"\"\"\n    c = 1\n";
+        assert!(indentation_issues("x.toml", toml, None).is_empty());
+    }
+}
@@ -0,0 +1,736 @@
+//! Rules for tracked git configs.
+
+use anyhow::Result;
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{
+    doctor::{collect_gitconfig_references, tool_is_available},
+    dotter::{DotterFile, mapped_files},
+    gitconfig::{
+        GitConfigEntry, IncludeTarget, builtin_tool_program, is_gitconfig, parse_gitconfig,
+        resolve_config_path,
+    },
+    repo::{get_content_files, read_file_content},
+    types::{Config, Issue, Severity, ValidationResult},
+};
+
+/// Every tracked git config, parsed, keyed by repo path.
+pub fn tracked_gitconfigs(config: &Config) -> Result<BTreeMap<String, Vec<GitConfigEntry>>> {
+    let mut configs = BTreeMap::new();
+    for file in get_content_files(config)?
+        .into_iter()
+        .filter(|f| is_gitconfig(f))
+    {
+        if let Some(content) = read_file_content(config, &file) {
+            configs.insert(file, parse_gitconfig(&content));
+        }
+    }
+    Ok(configs)
+}
+
+pub fn is_include(entry: &GitConfigEntry) -> bool {
+    entry.key == "path" && matches!(entry.section.as_str(), "include" | "includeif")
+}
+
+// The directory pattern of an `includeIf "gitdir:..."` condition
+fn gitdir_condition(entry: &GitConfigEntry) -> Option<&str> {
+    let condition = entry.subsection.as_deref()?;
+    condition
+        .strip_prefix("gitdir:")
+        .or_else(|| condition.strip_prefix("gitdir/i:"))
+}
+
+// Git refuses to let an alias replace one of its own commands
+const BUILTIN_COMMANDS: &[&str] = &[
+    "add",
+    "am",
+    "annotate",
+    "apply",
+    "archive",
+    "bisect",
+    "blame",
+    "branch",
+    "bundle",
+    "cat-file",
+    "checkout",
+    "cherry",
+    "cherry-pick",
+    "clean",
+    "clone",
+    "commit",
+    "config",
+    "count-objects",
+    "describe",
+    "diff",
+    "difftool",
+    "fetch",
+    "for-each-ref",
+    "format-patch",
+    "fsck",
+    "gc",
+    "grep",
+    "hash-object",
+    "help",
+    "init",
+    "log",
+    "ls-files",
+    "ls-remote",
+    "ls-tree",
+    "maintenance",
+    "merge",
+    "merge-base",
+    "mergetool",
+    "mv",
+    "name-rev",
+    "notes",
+    "prune",
+    "pull",
+    "push",
+    "range-diff",
+    "rebase",
+    "reflog",
+    "remote",
+    "repack",
+    "replace",
+    "request-pull",
+    "reset",
+    "restore",
+    "rev-list",
+    "rev-parse",
+    "revert",
+    "rm",
+    "shortlog",
+    "show",
+    "show-branch",
+    "sparse-checkout",
+    "stash",
+    "status",
+    "submodule",
+    "switch",
+    "symbolic-ref",
+    "tag",
+    "update-index",
+    "update-ref",
+    "var",
+    "version",
+    "whatchanged",
+    "worktree",
+];
+
+struct Includes<'a> {
+    configs: &'a BTreeMap<String, Vec<GitConfigEntry>>,
+    config: &'a Config,
+    files: Vec<DotterFile>,
+}
+
+impl<'a> Includes<'a> {
+    fn new(config: &'a Config, configs: &'a BTreeMap<String, Vec<GitConfigEntry>>) -> Result<Self> {
+        let files = if configs.is_empty() {
+            Vec::new()
+        } else {
+            mapped_files(config)?
+        };
+        Ok(Self {
+            configs,
+            config,
+            files,
+        })
+    }
+
+    fn resolve(&self, file: &str, entry: &GitConfigEntry) -> IncludeTarget {
+        resolve_config_path(self.config, &self.files, file, &entry.value)
+    }
+
+    // Whether a config sets user.email itself or through an unconditional
+    // include; files outside the repo can't be checked and count as setting it
+    fn sets_email(&self, file: &str, depth: usize) -> bool {
+        let Some(entries) = self.configs.get(file) else {
+            return true;
+        };
+        entries.iter().any(|e| e.is("user", "email"))
+            || (depth < 8
+                && entries
+                    .iter()
+                    .filter(|e| e.section == "include" && e.key == "path")
+                    .any(|e| match self.resolve(file, e) {
+                        IncludeTarget::Repo(source) => self.sets_email(&source, depth + 1),
+                        IncludeTarget::Machine(_) => true,
+                    }))
+    }
+
+    // Tracked configs that no other tracked config includes
+    fn roots(&self) -> Vec<&'a String> {
+        let included: BTreeSet<_> = self
+            .configs
+            .iter()
+            .flat_map(|(file, entries)| {
+                entries
+                    .iter()
+                    .filter(|e| is_include(e))
+                    .filter_map(move |e| match self.resolve(file, e) {
+                        IncludeTarget::Repo(source) => Some(source),
+                        IncludeTarget::Machine(_) => None,
+                    })
+            })
+            .collect();
+        self.configs
+            .keys()
+            .filter(|f| !included.contains(*f))
+            .collect()
+    }
+
+    // A config followed by everything it includes, in the order git reads them
+    fn closure(&self, file: &str, out: &mut Vec<String>) {
+        if out.iter().any(|f| f == file) {
+            return;
+        }
+        let Some(entries) = self.configs.get(file) else {
+            return;
+        };
+        out.push(file.to_string());
+        for entry in entries.iter().filter(|e| is_include(e)) {
+            if let IncludeTarget::Repo(source) = self.resolve(file, entry) {
+                self.closure(&source, out);
+            }
+        }
+    }
+}
+
+pub fn gitconfig_includes_valid(config: &Config) -> Result<ValidationResult> {
+    let configs = tracked_gitconfigs(config)?;
+    let includes = Includes::new(config, &configs)?;
+
+    let mut issues = Vec::new();
+    for (file, entries) in &configs {
+        for entry in entries.iter().filter(|e| is_include(e)) {
+            let target = includes.resolve(file, entry);
+            if !target.exists(config) {
+                issues.push(
+                    Issue::new(
+                        Severity::Warning,
+                        format!(
+                            "{} includes {}, which doesn't exist; git skips it silently",
+                            file,
+                            target.display()
+                        ),
+                    )
+                    .with_code("GIT001")
+                    .with_file(file.clone())
+                    .at(entry.line, 1),
+                );
+            }
+        }
+    }
+
+    // A work/personal split usually moves user.email into per-directory
+    // includes; repos matching none of them then commit without one
+    for file in includes.roots() {
+        let entries = &configs[file];
+        let conditional: Vec<_> = entries
+            .iter()
+            .filter(|e| is_include(e) && gitdir_condition(e).is_some())
+            .collect();
+        if conditional.is_empty() || includes.sets_email(file, 0) {
+            continue;
+        }
+        let patterns: Vec<_> = conditional
+            .iter()
+            .filter_map(|e| gitdir_condition(e))
+            .collect();
+        issues.push(
+            Issue::new(
+                Severity::Warning,
+                format!(
+                    "{} sets no user.email outside {}",
+                    file,
+                    patterns.join(", ")
+                ),
+            )
+            .with_code("GIT002")
+            .with_file(file.clone())
+            .with_fix("Set a default user.email before the includeIf sections")
+            .at(conditional[0].line, 1),
+        );
+        for entry in conditional {
+            if let IncludeTarget::Repo(source) = includes.resolve(file, entry)
+                && configs.contains_key(&source)
+                && !includes.sets_email(&source, 0)
+            {
+                issues.push(
+                    Issue::new(
+                        Severity::Warning,
+                        format!(
+                            "Repos under {} get no user.email: {} doesn't set one",
+                            gitdir_condition(entry).unwrap_or_default(),
+                            source
+                        ),
+                    )
+                    .with_code("GIT002")
+                    .with_file(file.clone())
+                    .at(entry.line, 1),
+                );
+            }
+        }
+    }
+
+    let passed = issues.iter().all(|i| i.severity != Severity::Error);
+    Ok(ValidationResult::new(
+        format!("Includes in {} git configs resolve", configs.len()),
+        passed,
+        issues,
+    ))
+}
+
+pub fn gitconfig_aliases_valid(config: &Config) -> Result<ValidationResult> {
+    let configs = tracked_gitconfigs(config)?;
+    let includes = Includes::new(config, &configs)?;
+    let mut issues = Vec::new();
+
+    for (file, entries) in &configs {
+        for entry in entries.iter().filter(|e| e.section == "alias") {
+            if BUILTIN_COMMANDS.contains(&entry.key.as_str()) {
+                issues.push(
+                    Issue::new(
+                        Severity::Warning,
+                        format!(
+                            "Alias '{}' shadows git's own command, so git never runs it",
+                            entry.key
+                        ),
+                    )
+                    .with_code("GIT003")
+                    .with_file(file.clone())
+                    .at(entry.line, 1),
+                );
+            }
+        }
+
+        let mut refs = Vec::new();
+        collect_gitconfig_references(file, entries, &mut refs);
+        for reference in refs
+            .iter()
+            .filter(|r| r.kind == "git alias" && !tool_is_available(r))
+        {
+            let issue = Issue::new(
+                Severity::Warning,
+                format!(
+                    "Alias runs {}, which isn't installed or on PATH",
+                    reference.command
+                ),
+            )
+            .with_code("GIT004")
+            .with_file(file.clone());
+            issues.push(match reference.line {
+                Some(line) => issue.at(line, 1),
+                None => issue,
+            });
+        }
+    }
+
+    // Later definitions win, so an alias defined again in an included file
+    // quietly replaces the first one
+    let mut reported = BTreeSet::new();
+    for root in includes.roots() {
+        let mut chain = Vec::new();
+        includes.closure(root, &mut chain);
+        let mut defined: BTreeMap<&str, (&str, usize)> = BTreeMap::new();
+        for file in &chain {
+            for entry in configs[file].iter().filter(|e| e.section == "alias") {
+                match defined.get(entry.key.as_str()) {
+                    Some((first_file, first_line)) => {
+                        if reported.insert((file.clone(), entry.line)) {
+                            issues.push(
+                                Issue::new(
+                                    Severity::Warning,
+                                    format!(
+                                        "Alias '{}' is defined again (first in {}:{})",
+                                        entry.key, first_file, first_line
+                                    ),
+                                )
+                                .with_code("GIT005")
+                                .with_file(file.clone())
+                                .at(entry.line, 1),
+                            );
+                        }
+                    }
+                    None => {
+                        defined.insert(&entry.key, (file, entry.line));
+                    }
+                }
+            }
+        }
+    }
+
+    let passed = issues.iter().all(|i| i.severity != Severity::Error);
+    Ok(ValidationResult::new(
+        format!("Aliases in {} git configs are sane", configs.len()),
+        passed,
+        issues,
+    ))
+}
+
+pub fn gitconfig_tools_available(config: &Config) -> Result<ValidationResult> {
+    let configs = tracked_gitconfigs(config)?;
+    let mut issues = Vec::new();
+
+    // Tool sections can live in any of the configs, typically an include
+    let defined: BTreeSet<(&str, &str)> = configs
+        .values()
+        .flatten()
+        .filter(|e| matches!(e.section.as_str(), "difftool" | "mergetool") && e.key == "cmd")
+        .filter_map(|e| Some((e.section.as_str(), e.subsection.as_deref()?)))
+        .collect();
+
+    for (file, entries) in &configs {
+        let mut refs = Vec::new();
+        collect_gitconfig_references(file, entries, &mut refs);
+        for reference in refs
+            .iter()
+            .filter(|r| r.kind != "git alias" && !tool_is_available(r))
+        {
+            let issue = Issue::new(
+                Severity::Warning,
+                format!(
+                    "{} {} isn't installed or on PATH",
+                    reference.kind, reference.command
+                ),
+            )
+            .with_code("GIT006")
+            .with_file(file.clone())
+            .with_fix(format!(
+                "Install {} or change the setting",
+                reference.command
+            ));
+            issues.push(match reference.line {
+                Some(line) => issue.at(line, 1),
+                None => issue,
+            });
+        }
+
+        for entry in entries.iter().filter(|e| {
+            matches!(e.section.as_str(), "diff" | "merge")
+                && matches!(e.key.as_str(), "tool" | "guitool")
+        }) {
+            let section = if entry.section == "diff" {
+                "difftool"
+            } else {
+                "mergetool"
+            };
+            if builtin_tool_program(&entry.value).is_none()
+                && !defined.contains(&(section, entry.value.as_str()))
+            {
+                issues.push(
+                    Issue::new(
+                        Severity::Warning,
+                        format!(
+                            "{}.{} is {}, which git doesn't know and no [{} \"{}\"] section defines",
+                            entry.section, entry.key, entry.value, section, entry.value
+                        ),
+                    )
+                    .with_code("GIT007")
+                    .with_file(file.clone())
+                    .with_fix(format!(
+                        "Add a [{} \"{}\"] section with a cmd",
+                        section, entry.value
+                    ))
+                    .at(entry.line, 1),
+                );
+            }
+        }
+    }
+
+    let passed = issues.iter().all(|i| i.severity != Severity::Error);
+    Ok(ValidationResult::new(
+        format!(
+            "Pagers and diff tools in {} git configs exist",
+            configs.len()
+        ),
+        passed,
+        issues,
+    ))
+}
+
+// (line, problem) for patterns in an ignore file that don't do what they
+// look like they do; git itself accepts anything
+pub fn gitignore_problems(content: &str) -> Vec<(usize, String)> {
+    let mut problems = Vec::new();
+    let mut excluded_dirs: Vec<String> = Vec::new();
+    for (idx, raw) in content.lines().enumerate() {
+        let line = idx + 1;
+        if raw.trim().is_empty() || raw.starts_with('#') {
+            continue;
+        }
+        let escaped_space = raw.trim_end().ends_with('\\') && raw.len() > raw.trim_end().len();
+        if raw.ends_with(char::is_whitespace) && !escaped_space {
+            problems.push((
+                line,
+                "Trailing whitespace is dropped by git; escape it with '\\ ' if it's meant"
+                    .to_string(),
+            ));
+        }
+        let pattern = raw.trim_end();
+        if pattern.ends_with('\\') && !pattern.ends_with("\\\\") && !escaped_space {
+            problems.push((
+                line,
+                "Pattern ends with a lone backslash and never matches".to_string(),
+            ));
+        }
+        if pattern.contains('[') && !pattern.contains(']') {
+            problems.push((line, "Unclosed '[' character class".to_string()));
+        }
+        let misplaced_globstar = pattern
+            .split('/')
+            .any(|segment| segment.contains("**") && segment != "**");
+        if misplaced_globstar {
+            problems.push((
+                line,
+                "'**' only spans directories as a whole path segment; here it acts like '*'"
+                    .to_string(),
+            ));
+        }
+
+        match pattern.strip_prefix('!') {
+            Some(negated) => {
+                let negated = negated.trim_start_matches('/');
+                if let Some(dir) = excluded_dirs
+                    .iter()
+                    .find(|d| negated.starts_with(&format!("{}/", d)))
+                {
+                    problems.push((
+                        line,
+                        format!(
+                            "Git can't re-include a file inside an excluded directory ({}/); \
+                             exclude {}/* instead",
+                            dir, dir
+                        ),
+                    ));
+                }
+            }
+            None => {
+                if let Some(dir) = pattern.strip_suffix('/')
+                    && !dir.contains(['*', '?', '['])
+                {
+                    excluded_dirs.push(dir.trim_start_matches('/').to_string());
+                }
+            }
+        }
+    }
+    problems
+}
+
+pub fn gitconfig_excludes_valid(config: &Config) -> Result<ValidationResult> {
+    let configs = tracked_gitconfigs(config)?;
+    let includes = Includes::new(config, &configs)?;
+    let mut issues = Vec::new();
+
+    let mut checked = BTreeSet::new();
+    for (file, entries) in &configs {
+        for entry in entries.iter().filter(|e| e.is("core", "excludesfile")) {
+            let target = includes.resolve(file, entry);
+            let source = match &target {
+                IncludeTarget::Repo(source) if target.exists(config) => source,
+                _ => {
+                    let problem = if target.exists(config) {
+                        "isn't in the repo or deployed by dotter"
+                    } else {
+                        "doesn't exist, so global ignores are silently off"
+                    };
+                    issues.push(
+                        Issue::new(
+                            Severity::Warning,
+                            format!("core.excludesFile {} {}", target.display(), problem),
+                        )
+                        .with_code("GIT008")
+                        .with_file(file.clone())
+                        .with_fix(
+                            "Track the ignore file and deploy it to the path excludesFile names",
+                        )
+                        .at(entry.line, 1),
+                    );
+                    continue;
+                }
+            };
+            if !checked.insert(source.clone()) {
+                continue;
+            }
+            let Some(content) = read_file_content(config, source) else {
+                continue;
+            };
+            for (line, problem) in gitignore_problems(&content) {
+                issues.push(
+                    Issue::new(Severity::Warning, problem)
+                        .with_code("GIT009")
+                        .with_file(source.clone())
+                        .at(line, 1),
+                );
+            }
+        }
+    }
+
+    let passed = issues.iter().all(|i| i.severity != Severity::Error);
+    Ok(ValidationResult::new(
+        format!(
+            "Global gitignores in {} git configs are deployed",
+            configs.len()
+        ),
+        passed,
+        issues,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::FixtureRepo;
+
+    #[test]
+    fn includes_resolve_through_dotter_and_email_gaps_are_found() {
+        let repo = FixtureRepo::new();
+        repo.write(
+            ".dotter/global.toml",
+            "[git.files]\ngitconfig = \"~/.gitconfig\"\ngit = \"~/.config/git\"\n",
+        )
+        .write(
+            "gitconfig",
+            "[user]\n\tname = Me\n\
+             [includeIf \"gitdir:~/work/\"]\n\tpath = ~/.config/git/work.gitconfig\n\
+             [includeIf \"gitdir:~/oss/\"]\n\tpath = .config/git/oss.gitconfig\n\
+             [includeIf \"gitdir:~/old/\"]\n\tpath = ~/.config/git/gone.gitconfig\n",
+        )
+        .write("git/work.gitconfig", "[user]\n\temail = me@work.example\n")
+        .write("git/oss.gitconfig", "[core]\n\teditor = hx\n")
+        .commit("fixture");
+
+        let result = gitconfig_includes_valid(&repo.config()).unwrap();
+        let found: Vec<_> = result
+            .issues
+            .iter()
+            .map(|i| (i.code.as_deref().unwrap(), i.line.unwrap()))
+            .collect();
+        assert_eq!(
+            found,
+            [("GIT001", 8), ("GIT002", 4), ("GIT002", 6)],
+            "{:?}",
+            result.issues
+        );
+        assert!(result.issues[1].message.contains("~/work/, ~/oss/, ~/old/"));
+        assert!(result.issues[2].message.contains("git/oss.gitconfig"));
+    }
+
+    #[test]
+    fn aliases_are_checked_for_shadowing_tools_and_redefinition() {
+        let repo = FixtureRepo::new();
+        repo.write(
+            ".gitconfig",
+            "[include]\n\tpath = .gitconfig-aliases\n\
+             [alias]\n\tst = status -sb\n\tlog = log --oneline\n\
+             \tsync = !definitely-not-installed-tool --all\n\
+             \tpr = \"!f() { gh pr view $1; }; f\"\n\tls = !ls -la\n",
+        )
+        .write(".gitconfig-aliases", "[alias]\n\tst = status\n")
+        .commit("fixture");
+
+        let result = gitconfig_aliases_valid(&repo.config()).unwrap();
+        let found: Vec<_> = result
+            .issues
+            .iter()
+            .map(|i| {
+                (
+                    i.code.as_deref().unwrap(),
+                    i.file.as_deref().unwrap(),
+                    i.line.unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("GIT003", ".gitconfig", 5),
+                ("GIT004", ".gitconfig", 6),
+                ("GIT005", ".gitconfig-aliases", 2),
+            ],
+            "{:?}",
+            result.issues
+        );
+    }
+
+    #[test]
+    fn pagers_and_diff_tools_must_resolve() {
+        let repo = FixtureRepo::new();
+        repo.write(
+            ".gitconfig",
+            "[core]\n\tpager = definitely-not-a-pager --dark\n\
+             [pager]\n\tbranch = false\n\
+             [interactive]\n\tdiffFilter = sh -c cat\n\
+             [diff]\n\ttool = difftastic\n\
+             [merge]\n\ttool = vimdiff2\n\tguitool = fancymerge\n",
+        )
+        .write(
+            ".config/git/tools.gitconfig",
+            "[difftool \"difftastic\"]\n\tcmd = sh -c 'difft \"$LOCAL\" \"$REMOTE\"'\n",
+        )
+        .commit("fixture");
+
+        let result = gitconfig_tools_available(&repo.config()).unwrap();
+        let found: Vec<_> = result
+            .issues
+            .iter()
+            .map(|i| (i.code.as_deref().unwrap(), i.line.unwrap()))
+            // vimdiff needs vim, which may or may not be installed here
+            .filter(|(_, line)| *line != 10)
+            .collect();
+        assert_eq!(
+            found,
+            [("GIT006", 2), ("GIT007", 11)],
+            "{:?}",
+            result.issues
+        );
+    }
+
+    #[test]
+    fn excludes_file_must_be_deployed_and_sensible() {
+        let repo = FixtureRepo::new();
+        repo.write(
+            ".dotter/global.toml",
+            "[git.files]\n\"git/ignore\" = \"~/.config/git/ignore\"\n",
+        )
+        .write(
+            "git/config",
+            "[core]\n\texcludesFile = ~/.config/git/ignore\n",
+        )
+        .write(
+            "git/work.config",
+            "[core]\n\texcludesfile = ~/.gitignore_global\n",
+        )
+        .write(
+            "git/ignore",
+            "# global\n.DS_Store\nnode_modules/\n!node_modules/keep\nfoo**bar\n*.log \n[abc\n",
+        )
+        .commit("fixture");
+
+        let result = gitconfig_excludes_valid(&repo.config()).unwrap();
+        let found: Vec<_> = result
+            .issues
+            .iter()
+            .map(|i| {
+                (
+                    i.code.as_deref().unwrap(),
+                    i.file.as_deref().unwrap(),
+                    i.line.unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("GIT009", "git/ignore", 4),
+                ("GIT009", "git/ignore", 5),
+                ("GIT009", "git/ignore", 6),
+                ("GIT009", "git/ignore", 7),
+                ("GIT008", "git/work.config", 2),
+            ],
+            "{:?}",
+            result.issues
+        );
+    }
+}
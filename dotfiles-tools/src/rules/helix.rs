@@ -0,0 +1,456 @@
+//! Rule for Helix's config.toml and languages.toml: known keys, the tools
+//! languages run, and the theme.
+
+use anyhow::Result;
+use tracing::debug;
+
+use std::{
+    env,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    doctor::{ToolReference, collect_helix_references, tool_is_available},
+    repo::{expand_home, find_executable, get_content_files, read_file_content},
+    types::{Config, Issue, Severity, ValidationResult},
+};
+
+const CONFIG_KEYS: &[&str] = &["theme", "editor", "keys"];
+
+const EDITOR_KEYS: &[&str] = &[
+    "scrolloff",
+    "mouse",
+    "default-yank-register",
+    "middle-click-paste",
+    "scroll-lines",
+    "shell",
+    "line-number",
+    "cursorline",
+    "cursorcolumn",
+    "continue-comments",
+    "gutters",
+    "auto-completion",
+    "path-completion",
+    "word-completion",
+    "auto-format",
+    "auto-save",
+    "idle-timeout",
+    "completion-timeout",
+    "preview-completion-insert",
+    "completion-trigger-len",
+    "completion-replace",
+    "auto-info",
+    "true-color",
+    "undercurl",
+    "rulers",
+    "bufferline",
+    "color-modes",
+    "text-width",
+    "workspace-lsp-roots",
+    "default-line-ending",
+    "insert-final-newline",
+    "trim-final-newlines",
+    "trim-trailing-whitespace",
+    "atomic-save",
+    "popup-border",
+    "indent-heuristic",
+    "jump-label-alphabet",
+    "end-of-line-diagnostics",
+    "clipboard-provider",
+    "editor-config",
+    "rainbow-brackets",
+    "auto-pairs",
+    "statusline",
+    "lsp",
+    "cursor-shape",
+    "file-picker",
+    "search",
+    "whitespace",
+    "indent-guides",
+    "soft-wrap",
+    "smart-tab",
+    "inline-diagnostics",
+    "buffer-picker",
+];
+
+// [editor.<section>] tables whose keys are a fixed set
+const EDITOR_SECTIONS: &[(&str, &[&str])] = &[
+    (
+        "statusline",
+        &[
+            "left",
+            "center",
+            "right",
+            "separator",
+            "mode",
+            "diagnostics",
+            "workspace-diagnostics",
+        ],
+    ),
+    (
+        "lsp",
+        &[
+            "enable",
+            "display-messages",
+            "display-progress-messages",
+            "auto-signature-help",
+            "display-inlay-hints",
+            "display-color-swatches",
+            "display-signature-help-docs",
+            "snippets",
+            "goto-reference-include-declaration",
+            "inlay-hints-length-limit",
+        ],
+    ),
+    ("cursor-shape", &["normal", "insert", "select"]),
+    (
+        "file-picker",
+        &[
+            "hidden",
+            "follow-symlinks",
+            "deduplicate-links",
+            "parents",
+            "ignore",
+            "git-ignore",
+            "git-global",
+            "git-exclude",
+            "max-depth",
+        ],
+    ),
+    ("search", &["smart-case", "wrap-around"]),
+    ("whitespace", &["render", "characters"]),
+    ("indent-guides", &["render", "character", "skip-levels"]),
+    (
+        "soft-wrap",
+        &[
+            "enable",
+            "max-wrap",
+            "max-indent-retain",
+            "wrap-indicator",
+            "wrap-at-text-width",
+        ],
+    ),
+    ("smart-tab", &["enable", "supersede-menu"]),
+    (
+        "inline-diagnostics",
+        &[
+            "cursor-line",
+            "other-lines",
+            "prefix-len",
+            "max-wrap",
+            "max-diagnostics",
+        ],
+    ),
+];
+
+const KEY_MODES: &[&str] = &["normal", "insert", "select"];
+
+const LANGUAGES_KEYS: &[&str] = &["use-grammars", "language", "language-server", "grammar"];
+
+const LANGUAGE_KEYS: &[&str] = &[
+    "name",
+    "language-id",
+    "scope",
+    "injection-regex",
+    "file-types",
+    "shebangs",
+    "roots",
+    "auto-format",
+    "diagnostic-severity",
+    "comment-token",
+    "comment-tokens",
+    "block-comment-tokens",
+    "indent",
+    "grammar",
+    "formatter",
+    "soft-wrap",
+    "text-width",
+    "rulers",
+    "workspace-lsp-roots",
+    "persistent-diagnostic-sources",
+    "language-servers",
+    "debugger",
+    "auto-pairs",
+    "path-completion",
+    "word-completion",
+    "rainbow-brackets",
+];
+
+const LANGUAGE_SERVER_KEYS: &[&str] = &[
+    "command",
+    "args",
+    "config",
+    "timeout",
+    "environment",
+    "required-root-patterns",
+];
+
+// Compiled into Helix rather than shipped as runtime files
+const BUILTIN_THEMES: &[&str] = &["default", "base16_default"];
+
+// Where packaged builds keep their runtime when HELIX_RUNTIME isn't set
+const PACKAGED_RUNTIMES: &[&str] = &[
+    "/usr/lib/helix/runtime",
+    "/usr/share/helix/runtime",
+    "/usr/local/lib/helix/runtime",
+    "/opt/homebrew/opt/helix/libexec/runtime",
+    "/usr/local/opt/helix/libexec/runtime",
+];
+
+// The line a `key = ...` or `[...key]` header is on
+fn key_line(content: &str, key: &str) -> Option<usize> {
+    content
+        .lines()
+        .position(|line| {
+            let line = line.trim_start().trim_start_matches('[');
+            let line = line
+                .strip_prefix(&format!("\"{}\"", key))
+                .or_else(|| line.strip_prefix(key));
+            line.is_some_and(|rest| rest.trim_start().starts_with(['=', '.', ']']))
+        })
+        .map(|idx| idx + 1)
+}
+
+fn unknown_keys(table: &toml::Table, known: &[&str], prefix: &str, out: &mut Vec<String>) {
+    for key in table.keys() {
+        if !known.contains(&key.as_str()) {
+            out.push(format!("{}{}", prefix, key));
+        }
+    }
+}
+
+fn config_unknown_keys(doc: &toml::Table) -> Vec<String> {
+    let mut unknown = Vec::new();
+    unknown_keys(doc, CONFIG_KEYS, "", &mut unknown);
+    if let Some(editor) = doc.get("editor").and_then(|e| e.as_table()) {
+        unknown_keys(editor, EDITOR_KEYS, "editor.", &mut unknown);
+        for (section, known) in EDITOR_SECTIONS {
+            if let Some(table) = editor.get(*section).and_then(|s| s.as_table()) {
+                unknown_keys(table, known, &format!("editor.{}.", section), &mut unknown);
+            }
+        }
+    }
+    if let Some(keys) = doc.get("keys").and_then(|k| k.as_table()) {
+        unknown_keys(keys, KEY_MODES, "keys.", &mut unknown);
+    }
+    unknown
+}
+
+fn languages_unknown_keys(doc: &toml::Table) -> Vec<String> {
+    let mut unknown = Vec::new();
+    unknown_keys(doc, LANGUAGES_KEYS, "", &mut unknown);
+    for language in doc
+        .get("language")
+        .and_then(|l| l.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|l| l.as_table())
+    {
+        let name = language.get("name").and_then(|n| n.as_str()).unwrap_or("?");
+        unknown_keys(
+            language,
+            LANGUAGE_KEYS,
+            &format!("language '{}': ", name),
+            &mut unknown,
+        );
+    }
+    if let Some(servers) = doc.get("language-server").and_then(|s| s.as_table()) {
+        for (name, server) in servers {
+            if let Some(server) = server.as_table() {
+                unknown_keys(
+                    server,
+                    LANGUAGE_SERVER_KEYS,
+                    &format!("language-server.{}.", name),
+                    &mut unknown,
+                );
+            }
+        }
+    }
+    unknown
+}
+
+// `theme = "x"`, or `theme = { light = "x", dark = "y" }` to follow the system
+fn theme_names(doc: &toml::Table) -> Vec<&str> {
+    match doc.get("theme") {
+        Some(toml::Value::String(name)) => vec![name.as_str()],
+        Some(toml::Value::Table(variants)) => {
+            variants.values().filter_map(|v| v.as_str()).collect()
+        }
+        _ => Vec::new(),
+    }
+}
+
+// Theme directories of the installed runtime, in Helix's lookup order
+fn runtime_theme_dirs() -> Vec<PathBuf> {
+    let mut runtimes: Vec<PathBuf> = env::var_os("HELIX_RUNTIME")
+        .map(PathBuf::from)
+        .into_iter()
+        .collect();
+    runtimes.push(expand_home("~/.config/helix/runtime"));
+    if let Some(hx) = find_executable("hx").and_then(|p| p.canonicalize().ok())
+        && let Some(dir) = hx.parent()
+    {
+        runtimes.push(dir.join("runtime"));
+    }
+    runtimes.extend(PACKAGED_RUNTIMES.iter().map(PathBuf::from));
+    runtimes
+        .into_iter()
+        .map(|r| r.join("themes"))
+        .filter(|d| d.is_dir())
+        .collect()
+}
+
+fn tool_issue(file: &str, reference: &ToolReference) -> Issue {
+    let issue = Issue::new(
+        Severity::Warning,
+        format!(
+            "{} {} isn't installed or on PATH",
+            reference.kind, reference.command
+        ),
+    )
+    .with_code("HELIX002")
+    .with_file(file.to_string())
+    .with_fix(format!(
+        "Install {} or remove it from {}",
+        reference.command, file
+    ));
+    match reference.line {
+        Some(line) => issue.at(line, 1),
+        None => issue,
+    }
+}
+
+fn is_helix_file(file: &str, name: &str) -> bool {
+    (file.starts_with("helix/") || file.contains("/helix/"))
+        && Path::new(file).file_name().is_some_and(|n| n == name)
+}
+
+pub fn helix_configs_valid(config: &Config) -> Result<ValidationResult> {
+    let candidates = get_content_files(config)?;
+    let files: Vec<_> = candidates
+        .iter()
+        .filter(|f| is_helix_file(f, "config.toml") || is_helix_file(f, "languages.toml"))
+        .collect();
+    let runtime_themes = if files.is_empty() {
+        Vec::new()
+    } else {
+        runtime_theme_dirs()
+    };
+
+    let mut issues = Vec::new();
+    for file in &files {
+        let Some(content) = read_file_content(config, file) else {
+            continue;
+        };
+        // Broken TOML is toml-syntax's to report
+        let Ok(doc) = toml::from_str::<toml::Table>(&content) else {
+            continue;
+        };
+        let is_languages = is_helix_file(file, "languages.toml");
+
+        let unknown = if is_languages {
+            languages_unknown_keys(&doc)
+        } else {
+            config_unknown_keys(&doc)
+        };
+        for key in unknown {
+            let last = key.rsplit(['.', ' ']).next().unwrap_or(&key);
+            let issue = Issue::new(Severity::Warning, format!("Unknown Helix key {}", key))
+                .with_code("HELIX001")
+                .with_file(file.to_string());
+            issues.push(match key_line(&content, last) {
+                Some(line) => issue.at(line, 1),
+                None => issue,
+            });
+        }
+
+        if is_languages {
+            let mut refs = Vec::new();
+            collect_helix_references(file, &content, &mut refs);
+            for reference in refs.iter().filter(|r| !tool_is_available(r)) {
+                issues.push(tool_issue(file, reference));
+            }
+            continue;
+        }
+
+        // Themes shipped in the repo sit next to config.toml
+        let repo_themes = config
+            .dotfiles_dir
+            .join(Path::new(file.as_str()).parent().unwrap_or(Path::new("")))
+            .join("themes");
+        let mut theme_dirs = vec![repo_themes, expand_home("~/.config/helix/themes")];
+        theme_dirs.extend(runtime_themes.iter().cloned());
+        for theme in theme_names(&doc) {
+            if BUILTIN_THEMES.contains(&theme)
+                || theme_dirs
+                    .iter()
+                    .any(|d| d.join(format!("{}.toml", theme)).is_file())
+            {
+                continue;
+            }
+            // Without a runtime, every stock theme would look missing
+            if runtime_themes.is_empty() {
+                debug!(theme, "skipping theme check: no Helix runtime found");
+                continue;
+            }
+            let issue = Issue::new(
+                Severity::Warning,
+                format!("Helix theme '{}' isn't in the runtime or the repo", theme),
+            )
+            .with_code("HELIX003")
+            .with_file(file.to_string());
+            issues.push(match key_line(&content, "theme") {
+                Some(line) => issue.at(line, 1),
+                None => issue,
+            });
+        }
+    }
+
+    let passed = issues.iter().all(|i| i.severity != Severity::Error);
+    Ok(ValidationResult::new(
+        format!("All {} Helix configs are valid", files.len()),
+        passed,
+        issues,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::FixtureRepo;
+
+    #[test]
+    fn unknown_keys_tools_and_themes_are_reported() {
+        let repo = FixtureRepo::new();
+        repo.write(
+            ".config/helix/config.toml",
+            "theme = \"custom\"\n\n[editor]\nline-number = \"relative\"\nauto-fomat = true\n\n[editor.cursor-shape]\ninsert = \"bar\"\nvisual = \"block\"\n\n[keys.normal]\nC-s = \":w\"\n",
+        )
+        .write(".config/helix/themes/custom.toml", "inherits = \"default\"\n")
+        .write(
+            ".config/helix/languages.toml",
+            "[[language]]\nname = \"rust\"\nauto-format = true\nformatter = { command = 'definitely-not-a-formatter' }\n\n[[language]]\nname = \"toml\"\nformatter = { command = 'definitely-not-a-formatter', args = [\"-\"] }\n\n[language-server.taplo]\ncommand = \"definitely-not-a-formatter\"\n\n[language-server.rust-analyzer]\ncommand = \"sh\"\nsettings = {}\n",
+        )
+        .commit("fixture");
+
+        let result = helix_configs_valid(&repo.config()).unwrap();
+        let found: Vec<_> = result
+            .issues
+            .iter()
+            .map(|i| (i.code.as_deref().unwrap(), i.line.unwrap()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("HELIX001", 5),
+                ("HELIX001", 9),
+                ("HELIX001", 15),
+                ("HELIX002", 4),
+                ("HELIX002", 11)
+            ],
+            "{:?}",
+            result.issues
+        );
+        assert!(result.passed);
+    }
+}
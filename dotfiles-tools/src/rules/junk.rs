@@ -0,0 +1,257 @@
+//! Rules for working-tree files git isn't handling as it should: machine
+//! state and large blobs committed by accident, and edits that were never
+//! committed at all.
+
+use anyhow::Result;
+
+use std::path::Path;
+
+use crate::{
+    fixes::shell_quote,
+    repo::{
+        MAX_CONTENT_SIZE, file_size, get_content_files, get_tracked_files, get_untracked_files,
+    },
+    stats::format_size,
+    types::{Config, Issue, Severity, ValidationResult},
+};
+
+struct Junk<'a> {
+    what: &'static str,
+    // The .gitignore pattern that keeps it out
+    ignore: &'a str,
+}
+
+// What kind of junk a tracked file is, judged by its name alone
+fn junk_kind(file: &str) -> Option<Junk<'_>> {
+    let name = Path::new(file)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default();
+    let (what, ignore) = match name {
+        ".DS_Store" => ("Finder metadata", ".DS_Store"),
+        "fish_variables" => ("fish universal variables", "fish_variables"),
+        ".viminfo" | ".lesshst" | ".wget-hsts" => ("session state", name),
+        _ if file == ".dotter/cache.toml" => ("dotter's deploy cache", ".dotter/cache.toml"),
+        _ if name.ends_with("history") || name == ".histfile" => ("shell or REPL history", name),
+        _ if name.ends_with(".log") => ("log file", "*.log"),
+        _ if name.ends_with(".zwc") => ("compiled zsh", "*.zwc"),
+        _ if name.ends_with(".swp") || name.ends_with(".swo") => ("vim swap file", "*.sw[po]"),
+        _ if name.ends_with('~') => ("editor backup", "*~"),
+        _ if name.starts_with(".#") || (name.starts_with('#') && name.ends_with('#')) => {
+            ("emacs lock or autosave file", "#*#")
+        }
+        _ => return None,
+    };
+    Some(Junk { what, ignore })
+}
+
+pub fn no_tracked_junk(config: &Config) -> Result<ValidationResult> {
+    let tracked = get_tracked_files(config)?;
+
+    let mut issues = Vec::new();
+    for file in &tracked {
+        let Some(junk) = junk_kind(file) else {
+            continue;
+        };
+        issues.push(
+            Issue::new(
+                Severity::Warning,
+                format!("{} is a {} and shouldn't be tracked", file, junk.what),
+            )
+            .with_code("JUNK001")
+            .with_file(file.clone())
+            .with_fix(format!(
+                "Run: git rm --cached -- {} && echo {} >> .gitignore",
+                shell_quote(file),
+                shell_quote(junk.ignore)
+            )),
+        );
+    }
+
+    let passed = issues.iter().all(|i| i.severity != Severity::Error);
+    Ok(ValidationResult::new(
+        format!(
+            "No caches or histories among {} tracked files",
+            tracked.len()
+        ),
+        passed,
+        issues,
+    ))
+}
+
+pub fn no_untracked_files(config: &Config) -> Result<ValidationResult> {
+    let untracked = get_untracked_files(config)?;
+
+    let mut issues = Vec::new();
+    for file in &untracked {
+        let (message, fix) = match junk_kind(file.trim_end_matches('/')) {
+            Some(junk) => (
+                format!("{} is neither tracked nor ignored", file),
+                format!("Add to .gitignore: {}", junk.ignore),
+            ),
+            None => (
+                format!(
+                    "{} is neither tracked nor ignored; track it or add it to .gitignore",
+                    file
+                ),
+                format!("Run: git add -- {}", shell_quote(file)),
+            ),
+        };
+        issues.push(
+            Issue::new(Severity::Warning, message)
+                .with_code("JUNK002")
+                .with_file(file.clone())
+                .with_fix(fix),
+        );
+    }
+
+    Ok(ValidationResult::new(
+        "Every file in the working tree is tracked or ignored",
+        true,
+        issues,
+    ))
+}
+
+// Content rules skip these, so this is the only rule that sees them
+pub fn no_large_files(config: &Config) -> Result<ValidationResult> {
+    let files = get_content_files(config)?;
+
+    let mut issues = Vec::new();
+    for file in &files {
+        let Some(size) = file_size(config, file).filter(|&s| s > MAX_CONTENT_SIZE) else {
+            continue;
+        };
+        issues.push(
+            Issue::new(
+                Severity::Warning,
+                format!(
+                    "{} is {}, too large for content rules to check",
+                    file,
+                    format_size(size)
+                ),
+            )
+            .with_code("JUNK003")
+            .with_file(file.clone())
+            .with_fix(format!(
+                "If it was committed by accident, run: git rm --cached {}",
+                file
+            )),
+        );
+    }
+
+    Ok(ValidationResult::new(
+        format!(
+            "No files over {} among {} checked",
+            format_size(MAX_CONTENT_SIZE),
+            files.len()
+        ),
+        true,
+        issues,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::FixtureRepo;
+
+    #[test]
+    fn junk_is_recognised_by_name() {
+        let repo = FixtureRepo::new();
+        repo.write("fish/config.fish", "\n")
+            .write("fish/fish_variables", "\n")
+            .write("zsh/.zsh_history", "\n")
+            .write("zsh/.zshrc.zwc", "\n")
+            .write(".DS_Store", "\n")
+            .write("nvim/.init.lua.swp", "\n")
+            .write("logs/brew.log", "\n")
+            .write(".dotter/cache.toml", "\n")
+            .write(".dotter/global.toml", "\n")
+            .write("scripts/catalog.sh", "\n")
+            .commit("fixture");
+
+        let result = no_tracked_junk(&repo.config()).unwrap();
+        let mut files: Vec<_> = result
+            .issues
+            .iter()
+            .map(|i| i.file.as_deref().unwrap())
+            .collect();
+        files.sort();
+        assert_eq!(
+            files,
+            [
+                ".DS_Store",
+                ".dotter/cache.toml",
+                "fish/fish_variables",
+                "logs/brew.log",
+                "nvim/.init.lua.swp",
+                "zsh/.zsh_history",
+                "zsh/.zshrc.zwc",
+            ]
+        );
+        assert!(result.passed);
+
+        let log = result
+            .issues
+            .iter()
+            .find(|i| i.file.as_deref() == Some("logs/brew.log"))
+            .unwrap();
+        assert_eq!(
+            log.fix_suggestion.as_deref(),
+            Some("Run: git rm --cached -- logs/brew.log && echo '*.log' >> .gitignore")
+        );
+    }
+
+    #[test]
+    fn untracked_files_outside_gitignore_are_listed() {
+        let repo = FixtureRepo::new();
+        repo.write(".gitignore", "*.local\n")
+            .write("zsh/.zshrc", "\n")
+            .commit("fixture")
+            .write("zsh/.zshrc.local", "\n")
+            .write("zsh/aliases.zsh", "\n")
+            .write("kitty/kitty.conf", "\n")
+            .write("kitty/theme.conf", "\n")
+            .write(".DS_Store", "\n");
+
+        let result = no_untracked_files(&repo.config()).unwrap();
+        let found: Vec<_> = result
+            .issues
+            .iter()
+            .map(|i| {
+                (
+                    i.file.as_deref().unwrap(),
+                    i.fix_suggestion.as_deref().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (".DS_Store", "Add to .gitignore: .DS_Store"),
+                ("kitty/", "Run: git add -- kitty/"),
+                ("zsh/aliases.zsh", "Run: git add -- zsh/aliases.zsh"),
+            ]
+        );
+    }
+
+    #[test]
+    fn oversized_files_are_reported_and_not_read() {
+        let repo = FixtureRepo::new();
+        let blob = "x".repeat(MAX_CONTENT_SIZE as usize + 1);
+        repo.write("small.toml", "a = 1\n")
+            .write("dump.json", &blob)
+            .commit("fixture");
+        let config = repo.config();
+
+        let result = no_large_files(&config).unwrap();
+        let files: Vec<_> = result
+            .issues
+            .iter()
+            .map(|i| i.file.as_deref().unwrap())
+            .collect();
+        assert_eq!(files, ["dump.json"]);
+        assert!(crate::repo::read_file_bytes(&config, "dump.json").is_none());
+        assert!(crate::repo::read_file_bytes(&config, "small.toml").is_some());
+    }
+}
@@ -0,0 +1,343 @@
+//! Rule for Karabiner-Elements' karabiner.json.
+
+use anyhow::Result;
+use serde::Deserialize;
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{
+    rules::check_files,
+    snippet::line_of,
+    types::{Config, Issue, Severity, ValidationResult},
+};
+
+// Only the parts of the schema the checks below need; Karabiner ignores
+// fields it doesn't know, so this does too
+#[derive(Debug, Deserialize)]
+struct KarabinerConfig {
+    profiles: Vec<Profile>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Profile {
+    name: String,
+    #[serde(default)]
+    selected: bool,
+    #[serde(default)]
+    complex_modifications: ComplexModifications,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ComplexModifications {
+    #[serde(default)]
+    rules: Vec<ComplexRule>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ComplexRule {
+    #[serde(default)]
+    description: String,
+    manipulators: Vec<Manipulator>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Manipulator {
+    #[serde(rename = "type")]
+    kind: String,
+    from: Option<FromEvent>,
+    #[serde(default)]
+    conditions: Vec<serde_json::Value>,
+    to: Option<Vec<serde_json::Value>>,
+    to_if_alone: Option<Vec<serde_json::Value>>,
+    to_if_held_down: Option<Vec<serde_json::Value>>,
+    to_after_key_up: Option<Vec<serde_json::Value>>,
+    to_delayed_action: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FromEvent {
+    key_code: Option<String>,
+    consumer_key_code: Option<String>,
+    pointing_button: Option<String>,
+    any: Option<String>,
+    #[serde(default)]
+    simultaneous: Vec<FromEvent>,
+    #[serde(default)]
+    modifiers: FromModifiers,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct FromModifiers {
+    #[serde(default)]
+    mandatory: Vec<String>,
+}
+
+impl FromEvent {
+    // The key or button itself, without modifiers
+    fn key(&self) -> Option<String> {
+        if let Some(key) = self
+            .key_code
+            .as_ref()
+            .or(self.consumer_key_code.as_ref())
+            .or(self.pointing_button.as_ref())
+        {
+            return Some(key.clone());
+        }
+        if let Some(any) = &self.any {
+            return Some(format!("any {}", any));
+        }
+        let keys: Option<Vec<_>> = self.simultaneous.iter().map(FromEvent::key).collect();
+        keys.filter(|k| !k.is_empty())
+            .map(|k| format!("simultaneous {}", k.join("+")))
+    }
+
+    // e.g. "left_command+left_shift+h"; modifier order doesn't matter to Karabiner
+    fn combination(&self) -> Option<String> {
+        let key = self.key()?;
+        let mut modifiers = self.modifiers.mandatory.clone();
+        modifiers.sort();
+        modifiers.push(key);
+        Some(modifiers.join("+"))
+    }
+}
+
+impl Manipulator {
+    fn has_output(&self) -> bool {
+        [
+            &self.to,
+            &self.to_if_alone,
+            &self.to_if_held_down,
+            &self.to_after_key_up,
+        ]
+        .iter()
+        .any(|events| events.as_ref().is_some_and(|e| !e.is_empty()))
+            || self.to_delayed_action.is_some()
+    }
+}
+
+fn positioned(issue: Issue, content: &str, needle: &str) -> Issue {
+    match line_of(content, needle) {
+        Some(line) => issue.at(line, 1),
+        None => issue,
+    }
+}
+
+pub fn karabiner_file_issues(file: &str, content: &str) -> Vec<Issue> {
+    // Malformed JSON is json-syntax's to report
+    if serde_json::from_str::<serde_json::Value>(content).is_err() {
+        return Vec::new();
+    }
+    let config: KarabinerConfig = match serde_json::from_str(content) {
+        Ok(config) => config,
+        Err(err) => {
+            return vec![
+                Issue::new(
+                    Severity::Error,
+                    format!("{} doesn't match Karabiner's schema: {}", file, err),
+                )
+                .with_code("KAR001")
+                .with_file(file.to_string())
+                .at(err.line(), err.column()),
+            ];
+        }
+    };
+
+    let mut issues = Vec::new();
+    let issue = |severity, code: &str, message: String, needle: &str| {
+        positioned(
+            Issue::new(severity, message)
+                .with_code(code)
+                .with_file(file.to_string()),
+            content,
+            needle,
+        )
+    };
+
+    if config.profiles.is_empty() {
+        issues.push(issue(
+            Severity::Error,
+            "KAR001",
+            format!("{} has no profiles", file),
+            "\"profiles\"",
+        ));
+    }
+    let selected = config.profiles.iter().filter(|p| p.selected).count();
+    if !config.profiles.is_empty() && selected != 1 {
+        issues.push(issue(
+            Severity::Warning,
+            "KAR002",
+            format!(
+                "{} profiles are selected in {}; Karabiner expects exactly one",
+                selected, file
+            ),
+            "\"profiles\"",
+        ));
+    }
+    let mut names = HashMap::new();
+    for profile in &config.profiles {
+        *names.entry(profile.name.as_str()).or_insert(0) += 1;
+    }
+    for (name, count) in names.into_iter().filter(|(_, n)| *n > 1) {
+        issues.push(issue(
+            Severity::Warning,
+            "KAR002",
+            format!("Profile name '{}' is used by {} profiles", name, count),
+            &format!("\"{}\"", name),
+        ));
+    }
+
+    for profile in &config.profiles {
+        // Same keys under different conditions (apps, variables) don't clash
+        let mut bound: BTreeMap<(String, String), &str> = BTreeMap::new();
+        for rule in &profile.complex_modifications.rules {
+            let needle = format!("\"{}\"", rule.description);
+            for manipulator in &rule.manipulators {
+                if !matches!(
+                    manipulator.kind.as_str(),
+                    "basic" | "mouse_motion_to_scroll"
+                ) {
+                    issues.push(issue(
+                        Severity::Error,
+                        "KAR001",
+                        format!(
+                            "Rule '{}' has a manipulator of unknown type '{}'",
+                            rule.description, manipulator.kind
+                        ),
+                        &needle,
+                    ));
+                    continue;
+                }
+                if manipulator.kind != "basic" {
+                    continue;
+                }
+                let Some(combination) = manipulator.from.as_ref().and_then(FromEvent::combination)
+                else {
+                    issues.push(issue(
+                        Severity::Error,
+                        "KAR001",
+                        format!(
+                            "Rule '{}' has a manipulator with no key in `from`",
+                            rule.description
+                        ),
+                        &needle,
+                    ));
+                    continue;
+                };
+                if !manipulator.has_output() {
+                    issues.push(issue(
+                        Severity::Error,
+                        "KAR001",
+                        format!(
+                            "Rule '{}' maps {} to nothing (no `to` events)",
+                            rule.description, combination
+                        ),
+                        &needle,
+                    ));
+                }
+                let conditions = serde_json::to_string(&manipulator.conditions).unwrap_or_default();
+                match bound.get(&(combination.clone(), conditions.clone())) {
+                    Some(first) if *first != rule.description => issues.push(issue(
+                        Severity::Warning,
+                        "KAR003",
+                        format!(
+                            "{} is mapped by both '{}' and '{}' in profile '{}'; only the first applies",
+                            combination, first, rule.description, profile.name
+                        ),
+                        &needle,
+                    )),
+                    Some(_) => {}
+                    None => {
+                        bound.insert((combination, conditions), &rule.description);
+                    }
+                }
+            }
+        }
+    }
+
+    issues
+}
+
+pub fn karabiner_configs_valid(config: &Config) -> Result<ValidationResult> {
+    let (count, issues) = check_files(
+        config,
+        "karabiner",
+        |f| f == "karabiner.json" || f.ends_with("/karabiner.json"),
+        |file, content| Ok(karabiner_file_issues(file, content)),
+    )?;
+    let passed = issues.iter().all(|i| i.severity != Severity::Error);
+    Ok(ValidationResult::new(
+        format!("All {} Karabiner configs are valid", count),
+        passed,
+        issues,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"{
+  "profiles": [
+    {
+      "name": "Default",
+      "selected": true,
+      "complex_modifications": {
+        "rules": [
+          {
+            "description": "Caps lock to control",
+            "manipulators": [
+              {
+                "type": "basic",
+                "from": { "key_code": "caps_lock", "modifiers": { "mandatory": ["left_shift", "left_command"] } },
+                "to": [{ "key_code": "left_control" }]
+              }
+            ]
+          },
+          {
+            "description": "Caps lock to escape",
+            "manipulators": [
+              {
+                "type": "basic",
+                "from": { "key_code": "caps_lock", "modifiers": { "mandatory": ["left_command", "left_shift"] } },
+                "to": [{ "key_code": "escape" }]
+              },
+              {
+                "type": "basic",
+                "from": { "key_code": "caps_lock" },
+                "to": [{ "key_code": "escape" }],
+                "conditions": [{ "type": "frontmost_application_if", "bundle_identifiers": ["^com\\.apple\\.Terminal$"] }]
+              },
+              { "type": "basic", "from": { "key_code": "f1" } }
+            ]
+          }
+        ]
+      }
+    }
+  ]
+}
+"#;
+
+    #[test]
+    fn duplicate_combinations_and_missing_outputs_are_reported() {
+        let issues = karabiner_file_issues("karabiner.json", CONFIG);
+        let codes: Vec<_> = issues.iter().filter_map(|i| i.code.as_deref()).collect();
+        assert_eq!(codes, ["KAR003", "KAR001"], "{:?}", issues);
+        assert!(
+            issues[0]
+                .message
+                .contains("left_command+left_shift+caps_lock")
+        );
+        assert_eq!(issues[0].line, Some(19));
+    }
+
+    #[test]
+    fn schema_mismatches_point_at_the_value() {
+        let issues = karabiner_file_issues(
+            "karabiner.json",
+            "{\n  \"profiles\": [{ \"name\": 3 }]\n}\n",
+        );
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].code.as_deref(), Some("KAR001"));
+        assert_eq!(issues[0].line, Some(2));
+    }
+}
@@ -0,0 +1,279 @@
+//! Rule for lazygit's config.yml: known keys, and keys bound twice in the
+//! same view.
+
+use anyhow::Result;
+use serde_yaml_ng::Value;
+
+use std::{collections::BTreeMap, path::Path};
+
+use crate::{
+    rules::check_files,
+    types::{Config, Issue, Severity, ValidationResult},
+};
+
+const TOP_LEVEL_KEYS: &[&str] = &[
+    "gui",
+    "git",
+    "update",
+    "refresher",
+    "confirmOnQuit",
+    "quitOnTopLevelReturn",
+    "os",
+    "disableStartupPopups",
+    "customCommands",
+    "services",
+    "notARepository",
+    "promptToReturnFromSubprocess",
+    "keybinding",
+];
+
+// Views under `keybinding:`; bindings in different views never collide
+const KEYBINDING_VIEWS: &[&str] = &[
+    "universal",
+    "status",
+    "files",
+    "branches",
+    "worktrees",
+    "commits",
+    "amendAttribute",
+    "stash",
+    "commitFiles",
+    "main",
+    "submodules",
+    "commitMessage",
+];
+
+// Unbinds an action rather than binding a key
+const DISABLED: &str = "<disabled>";
+
+// The line of a nested `a: b: c:` key, found by walking down the indentation
+fn key_line(content: &str, path: &[&str]) -> Option<usize> {
+    let lines: Vec<&str> = content.lines().collect();
+    let (mut start, mut min_indent, mut found) = (0, 0, None);
+    for segment in path {
+        let idx = (start..lines.len()).find(|&i| {
+            let body = lines[i].trim_start();
+            let indent = lines[i].len() - body.len();
+            indent >= min_indent
+                && [
+                    *segment,
+                    &format!("\"{}\"", segment),
+                    &format!("'{}'", segment),
+                ]
+                .iter()
+                .any(|key| {
+                    body.strip_prefix(key)
+                        .is_some_and(|rest| rest.starts_with(':'))
+                })
+        })?;
+        min_indent = lines[idx].len() - lines[idx].trim_start().len() + 1;
+        start = idx + 1;
+        found = Some(idx + 1);
+    }
+    found
+}
+
+fn key_name(value: &Value) -> Option<String> {
+    match value {
+        Value::String(key) => Some(key.clone()),
+        Value::Number(key) => Some(key.to_string()),
+        _ => None,
+    }
+}
+
+fn issue(file: &str, content: &str, code: &str, message: String, path: &[&str]) -> Issue {
+    let issue = Issue::new(Severity::Warning, message)
+        .with_code(code)
+        .with_file(file.to_string());
+    match key_line(content, path) {
+        Some(line) => issue.at(line, 1),
+        None => issue,
+    }
+}
+
+pub fn lazygit_file_issues(file: &str, content: &str) -> Vec<Issue> {
+    let doc = match serde_yaml_ng::from_str::<Value>(content) {
+        Ok(doc) => doc,
+        Err(err) => {
+            // The position is reported separately
+            let message = err.to_string();
+            let message = message
+                .split_once(" at line ")
+                .map_or(message.as_str(), |(m, _)| m);
+            let issue = Issue::new(
+                Severity::Error,
+                format!("Invalid YAML in {}: {}", file, message),
+            )
+            .with_code("LAZYGIT001")
+            .with_file(file.to_string());
+            return vec![match err.location() {
+                Some(at) => issue.at(at.line(), at.column()),
+                None => issue,
+            }];
+        }
+    };
+    let Some(root) = doc.as_mapping() else {
+        return Vec::new();
+    };
+    let mut issues = Vec::new();
+
+    for key in root.keys().filter_map(|k| k.as_str()) {
+        if !TOP_LEVEL_KEYS.contains(&key) {
+            issues.push(issue(
+                file,
+                content,
+                "LAZYGIT002",
+                format!("Unknown lazygit setting '{}'", key),
+                &[key],
+            ));
+        }
+    }
+
+    let views = root.get("keybinding").and_then(|k| k.as_mapping());
+    for (view, bindings) in views.into_iter().flatten() {
+        let Some(view) = view.as_str() else {
+            continue;
+        };
+        if !KEYBINDING_VIEWS.contains(&view) {
+            issues.push(issue(
+                file,
+                content,
+                "LAZYGIT002",
+                format!("Unknown lazygit keybinding view '{}'", view),
+                &["keybinding", view],
+            ));
+            continue;
+        }
+        let mut bound: BTreeMap<String, &str> = BTreeMap::new();
+        for (action, key) in bindings.as_mapping().into_iter().flatten() {
+            let (Some(action), Some(key)) = (action.as_str(), key_name(key)) else {
+                continue;
+            };
+            if key == DISABLED {
+                continue;
+            }
+            if let Some(first) = bound.get(&key) {
+                issues.push(issue(
+                    file,
+                    content,
+                    "LAZYGIT003",
+                    format!(
+                        "{} is bound to both {} and {} in the {} view",
+                        key, first, action, view
+                    ),
+                    &["keybinding", view, action],
+                ));
+            } else {
+                bound.insert(key, action);
+            }
+        }
+    }
+
+    // Custom commands can list several contexts, comma-separated
+    let mut custom: BTreeMap<(String, String), usize> = BTreeMap::new();
+    let commands = root.get("customCommands").and_then(|c| c.as_sequence());
+    for (idx, command) in commands.into_iter().flatten().enumerate() {
+        let Some(key) = command.get("key").and_then(key_name) else {
+            continue;
+        };
+        let contexts = command
+            .get("context")
+            .and_then(|c| c.as_str())
+            .unwrap_or("global");
+        for context in contexts.split(',').map(str::trim) {
+            if let Some(first) = custom.insert((context.to_string(), key.clone()), idx) {
+                issues.push(issue(
+                    file,
+                    content,
+                    "LAZYGIT003",
+                    format!(
+                        "Custom commands {} and {} both bind {} in the {} context",
+                        first + 1,
+                        idx + 1,
+                        key,
+                        context
+                    ),
+                    &["customCommands"],
+                ));
+            }
+        }
+    }
+
+    issues
+}
+
+fn is_lazygit_config(file: &str) -> bool {
+    let path = Path::new(file);
+    path.parent()
+        .and_then(|p| p.file_name())
+        .is_some_and(|n| n == "lazygit")
+        && path
+            .file_name()
+            .is_some_and(|n| n == "config.yml" || n == "config.yaml")
+}
+
+pub fn lazygit_configs_valid(config: &Config) -> Result<ValidationResult> {
+    let (count, issues) = check_files(config, "lazygit", is_lazygit_config, |file, content| {
+        Ok(lazygit_file_issues(file, content))
+    })?;
+    let passed = issues.iter().all(|i| i.severity != Severity::Error);
+    Ok(ValidationResult::new(
+        format!("All {} lazygit configs are valid", count),
+        passed,
+        issues,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_keys_and_conflicting_bindings_are_reported() {
+        let content = "gui:
+  theme:
+    activeBorderColor: [green, bold]
+gitt:
+  paging:
+    pager: delta
+keybinding:
+  universal:
+    quit: q
+    return: <esc>
+  files:
+    commitChanges: c
+    stashAllChanges: c
+    amendLastCommit: <disabled>
+    ignoreFile: <disabled>
+  branchs:
+    pushTag: P
+customCommands:
+  - key: C
+    context: files, commits
+    command: git cz
+  - key: C
+    context: commits
+    command: git commit --fixup HEAD
+";
+        let issues = lazygit_file_issues("lazygit/config.yml", content);
+        let found: Vec<_> = issues
+            .iter()
+            .map(|i| (i.code.as_deref().unwrap(), i.line.unwrap()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("LAZYGIT002", 4),
+                ("LAZYGIT003", 13),
+                ("LAZYGIT002", 16),
+                ("LAZYGIT003", 18)
+            ],
+            "{:?}",
+            issues
+        );
+
+        let issues = lazygit_file_issues("lazygit/config.yml", "gui:\n  theme: [\n");
+        assert_eq!(issues[0].code.as_deref(), Some("LAZYGIT001"));
+        assert!(issues[0].line.is_some());
+    }
+}
@@ -0,0 +1,251 @@
+//! Built-in validation rules, in the order they run.
+
+pub mod aerospace;
+pub mod cargo;
+pub mod cli_tools;
+pub mod direnv;
+pub mod dotter;
+pub mod formatting;
+pub mod git;
+pub mod helix;
+pub mod junk;
+pub mod karabiner;
+pub mod lazygit;
+pub mod references;
+pub mod rust_script;
+pub mod secrets;
+pub mod shell;
+pub mod shellcheck;
+pub mod skhd;
+pub mod symlinks;
+pub mod syntax;
+pub mod tmux;
+pub mod tools;
+pub mod xdg;
+pub mod yabai;
+pub mod zed;
+
+use anyhow::Result;
+use indicatif::ProgressIterator;
+use tracing::trace_span;
+
+use crate::{
+    cache::file_issues,
+    output::progress_bar,
+    repo::{get_content_files, read_file_content},
+    types::{Config, Issue, Rule},
+};
+
+// Everything --tags/--exclude-tags accept. "plugin", "linter", and "script"
+// select plugins, [linters], and Rhai script rules, which can't declare tags
+// of their own
+pub const TAGS: &[&str] = &[
+    "syntax", "format", "git", "deploy", "secrets", "shell", "tools", "apps", "slow", "plugin",
+    "linter", "script",
+];
+
+/// Runs `check` over each content file that `matches` selects, reusing cached
+/// issues for files that haven't changed. Returns how many files matched,
+/// along with everything they reported.
+pub fn check_files(
+    config: &Config,
+    rule: &str,
+    matches: impl Fn(&str) -> bool,
+    check: impl Fn(&str, &str) -> Result<Vec<Issue>>,
+) -> Result<(usize, Vec<Issue>)> {
+    let candidates = get_content_files(config)?;
+    let files: Vec<_> = candidates.iter().filter(|f| matches(f)).collect();
+    let mut issues = Vec::new();
+
+    let progress = progress_bar(config, rule, files.len());
+    for file in files.iter().progress_with(progress) {
+        let _span = trace_span!("file", path = %file).entered();
+        let Some(content) = read_file_content(config, file) else {
+            continue;
+        };
+        issues.extend(file_issues(config, rule, file, &content, || {
+            check(file, &content)
+        })?);
+    }
+    Ok((files.len(), issues))
+}
+
+pub fn all() -> Vec<Rule> {
+    vec![
+        Rule::new("dotter-configs", |c| Ok(dotter::dotter_configs_exist(c))).tags(&["deploy"]),
+        Rule::new("dotter-files", dotter::dotter_files_tracked)
+            .requires(&["dotter-configs"])
+            .tags(&["deploy", "git"]),
+        Rule::new("deploy-targets", dotter::deploy_targets_safe)
+            .requires(&["dotter-configs"])
+            .tags(&["deploy"]),
+        Rule::new("dotter-local", dotter::dotter_local_files_ignored).tags(&["deploy", "git"]),
+        Rule::new("symlinks", symlinks::no_broken_symlinks).tags(&["deploy"]),
+        Rule::new("relative-symlinks", symlinks::symlinks_are_relative).tags(&["git"]),
+        Rule::new("stale-links", symlinks::no_stale_deployed_links).tags(&["deploy"]),
+        Rule::new("host-templates", dotter::host_templates_render)
+            .requires(&["dotter-configs"])
+            .tags(&["deploy"]),
+        Rule::new("toml-syntax", syntax::toml_files_valid)
+            .tags(&["syntax"])
+            .per_file(),
+        Rule::new("json-syntax", syntax::json_files_valid)
+            .tags(&["syntax"])
+            .per_file(),
+        Rule::new("nushell-syntax", syntax::nushell_files_valid)
+            .tags(&["syntax", "shell"])
+            .per_file(),
+        Rule::new("conflict-markers", syntax::no_merge_conflict_markers)
+            .tags(&["syntax", "git"])
+            .per_file(),
+        Rule::new("utf8", syntax::text_files_are_utf8).tags(&["syntax"]),
+        Rule::new("confusable-chars", syntax::no_confusable_characters)
+            .tags(&["syntax", "shell"])
+            .per_file(),
+        Rule::new("tool-versions", tools::tool_versions_consistent).tags(&["tools"]),
+        Rule::new("shell-definitions", shell::no_conflicting_shell_definitions).tags(&["shell"]),
+        Rule::new("path-entries", shell::path_modifications_sane).tags(&["shell"]),
+        Rule::new("fish-autoload", shell::fish_autoload_files_valid)
+            .tags(&["shell"])
+            .per_file(),
+        Rule::new(
+            "rust-script-headers",
+            rust_script::rust_script_headers_valid,
+        )
+        .tags(&["syntax"])
+        .per_file(),
+        Rule::new("karabiner", karabiner::karabiner_configs_valid)
+            .tags(&["apps"])
+            .per_file(),
+        Rule::new("yabai", yabai::yabai_configs_valid)
+            .tags(&["apps", "shell"])
+            .per_file(),
+        Rule::new("skhd", skhd::skhd_configs_valid)
+            .tags(&["apps"])
+            .per_file(),
+        Rule::new("aerospace", aerospace::aerospace_configs_valid)
+            .tags(&["apps"])
+            .per_file(),
+        Rule::new("helix", helix::helix_configs_valid)
+            .tags(&["apps"])
+            .per_file(),
+        Rule::new("cargo-config", cargo::cargo_configs_valid)
+            .tags(&["apps"])
+            .per_file(),
+        Rule::new("lazygit", lazygit::lazygit_configs_valid)
+            .tags(&["apps", "git"])
+            .per_file(),
+        Rule::new("ripgrep", cli_tools::ripgrep_configs_valid).tags(&["apps"]),
+        Rule::new("fd-ignore", cli_tools::fd_ignores_valid)
+            .tags(&["apps"])
+            .per_file(),
+        Rule::new("bat", cli_tools::bat_configs_valid).tags(&["apps"]),
+        Rule::new("zed-keymap", zed::zed_keymaps_valid)
+            .tags(&["apps"])
+            .per_file(),
+        Rule::new("tmux", tmux::tmux_configs_valid).tags(&["apps"]),
+        Rule::new("envrc", direnv::envrc_files_valid).tags(&["shell", "secrets", "deploy"]),
+        Rule::new("sourced-files", references::sourced_files_valid).tags(&["shell", "git"]),
+        Rule::new("bin-references", references::script_references_valid)
+            .tags(&["shell", "git"])
+            .per_file(),
+        Rule::new("gitconfig-includes", git::gitconfig_includes_valid).tags(&["git"]),
+        Rule::new("git-aliases", git::gitconfig_aliases_valid).tags(&["git"]),
+        Rule::new("git-tools", git::gitconfig_tools_available).tags(&["git"]),
+        Rule::new("git-excludes", git::gitconfig_excludes_valid).tags(&["git", "deploy"]),
+        Rule::new("credential-files", secrets::no_tracked_credentials).tags(&["secrets", "git"]),
+        Rule::new("junk-files", junk::no_tracked_junk).tags(&["git"]),
+        Rule::new("untracked-files", junk::no_untracked_files).tags(&["git"]),
+        Rule::new("large-files", junk::no_large_files).tags(&["git"]),
+        Rule::new("brew-bundle", tools::brew_bundle_in_sync)
+            .opt_in()
+            .tags(&["tools", "slow"]),
+        Rule::new("tool-versions-installed", tools::tool_versions_installed)
+            .opt_in()
+            .requires(&["tool-versions"])
+            .tags(&["tools", "slow"]),
+        Rule::new("vscode-extensions", tools::vscode_extensions_in_sync)
+            .opt_in()
+            .tags(&["tools", "apps", "slow"]),
+        Rule::new("op-references", secrets::op_references_resolve)
+            .opt_in()
+            .tags(&["secrets", "slow"]),
+        Rule::new("rust-script-check", rust_script::rust_scripts_compile)
+            .opt_in()
+            .requires(&["rust-script-headers"])
+            .tags(&["tools", "slow"])
+            .per_file(),
+        Rule::new("toml-format", formatting::toml_files_formatted)
+            .opt_in()
+            .tags(&["format"])
+            .per_file(),
+        Rule::new("json-format", formatting::json_files_formatted)
+            .opt_in()
+            .tags(&["format"])
+            .per_file(),
+        Rule::new("indentation", formatting::indentation_consistent)
+            .opt_in()
+            .tags(&["format"])
+            .per_file(),
+        Rule::new("shellcheck", shellcheck::scripts_pass_shellcheck)
+            .opt_in()
+            .tags(&["shell", "tools", "slow"])
+            .per_file(),
+        Rule::new("xdg-paths", xdg::configs_use_xdg_paths)
+            .opt_in()
+            .requires(&["dotter-configs"])
+            .tags(&["deploy"]),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_support::FixtureRepo, validator::Validator};
+
+    #[test]
+    fn prerequisites_run_earlier() {
+        let rules = all();
+        for (idx, rule) in rules.iter().enumerate() {
+            for required in rule.requires {
+                assert!(
+                    rules[..idx].iter().any(|r| r.id == *required),
+                    "{} requires {}, which doesn't run before it",
+                    rule.id,
+                    required
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn tags_are_known() {
+        for rule in all() {
+            assert!(!rule.tags.is_empty(), "{} has no tags", rule.id);
+            for tag in rule.tags {
+                assert!(TAGS.contains(tag), "{} has unknown tag {}", rule.id, tag);
+            }
+        }
+    }
+
+    #[test]
+    fn tags_select_rules_and_opt_in_ones_with_them() {
+        let repo = FixtureRepo::new();
+        repo.write("config.toml", "a = 1\n").commit("fixture");
+        let mut config = repo.config();
+        config.tags = vec!["tools".to_string()];
+        config.exclude_tags = vec!["slow".to_string()];
+        let ran: Vec<_> = Validator::new(config.clone())
+            .run_rules()
+            .unwrap()
+            .into_iter()
+            .map(|r| r.rule_id)
+            .collect();
+        assert_eq!(ran, ["tool-versions"]);
+
+        // Naming a tag is an explicit request, so it enables opt-in rules
+        config.exclude_tags.clear();
+        let ran = Validator::new(config).run_rules().unwrap();
+        assert!(ran.iter().any(|r| r.rule_id == "brew-bundle"));
+    }
+}
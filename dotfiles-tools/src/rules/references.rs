@@ -0,0 +1,504 @@
+//! Rules for references between the repo's files: scripts run from shell
+//! configs, hotkey daemons and LaunchAgents, and files that configs source,
+//! include or require.
+
+use anyhow::Result;
+use regex::Regex;
+
+use std::{
+    collections::{BTreeSet, HashSet},
+    path::Path,
+    sync::LazyLock,
+};
+
+use crate::{
+    dotter::{DotterFile, mapped_files, source_for_target, tilde_path},
+    gitconfig::{IncludeTarget, is_gitconfig, parse_gitconfig, resolve_config_path},
+    repo::{get_content_files, get_tracked_files, home_dir, read_file_content},
+    rules::{
+        git::is_include,
+        tmux::{is_tmux_config, parse_tmux_conf},
+    },
+    shell::is_shell_config,
+    types::{Config, Issue, Severity, ValidationResult},
+};
+
+// Top-level repo directories whose contents are meant to be run
+const SCRIPT_DIRS: &[&str] = &["bin", "scripts"];
+
+fn is_referencing_file(file: &str) -> bool {
+    let name = Path::new(file)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default();
+    is_shell_config(file)
+        || matches!(name, "skhdrc" | ".skhdrc" | "yabairc" | ".yabairc")
+        || (file.contains("LaunchAgents/") && name.ends_with(".plist"))
+}
+
+fn in_script_dir(path: &str) -> bool {
+    SCRIPT_DIRS
+        .iter()
+        .any(|d| path == *d || path.starts_with(&format!("{}/", d)))
+}
+
+// Ways a config can spell a path inside the repo: through a deployed dotter
+// target, or into the checkout itself
+struct Resolver {
+    files: Vec<DotterFile>,
+    // Prefixes that name the repo root, ending in '/'
+    roots: Vec<String>,
+}
+
+impl Resolver {
+    fn new(config: &Config) -> Result<Self> {
+        let files = mapped_files(config)?;
+        let mut roots = vec![format!("{}/", config.dotfiles_dir.display())];
+        if let Some(relative) = home_dir().and_then(|home| {
+            config
+                .dotfiles_dir
+                .strip_prefix(home)
+                .ok()
+                .map(|r| r.display().to_string())
+        }) {
+            roots.push(format!("~/{}/", relative));
+        }
+        Ok(Self { files, roots })
+    }
+
+    // The repo path a reference points at, when it points into a script dir
+    fn resolve(&self, reference: &str) -> Option<String> {
+        static RE_DOTFILES_VAR: LazyLock<Regex> =
+            LazyLock::new(|| Regex::new(r"^\$\{?DOTFILES\w*\}?/").unwrap());
+
+        let reference = tilde_path(reference);
+        let reference = reference.trim_end_matches('/');
+        let in_repo = RE_DOTFILES_VAR
+            .find(reference)
+            .map(|m| &reference[m.end()..])
+            .or_else(|| {
+                self.roots
+                    .iter()
+                    .find_map(|root| reference.strip_prefix(root.as_str()))
+            })
+            .map(str::to_string)
+            .or_else(|| source_for_target(&self.files, reference))?;
+        in_script_dir(&in_repo).then_some(in_repo)
+    }
+}
+
+pub fn script_references_valid(config: &Config) -> Result<ValidationResult> {
+    static RE_PATH: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r#"[~$/][^\s'"`;|&()<>:,]*"#).unwrap());
+
+    let candidates = get_content_files(config)?;
+    let files: Vec<_> = candidates
+        .iter()
+        .filter(|f| is_referencing_file(f))
+        .collect();
+    let tracked: HashSet<String> = if files.is_empty() {
+        HashSet::new()
+    } else {
+        get_tracked_files(config)?.into_iter().collect()
+    };
+    let resolver = Resolver::new(config)?;
+    // A directory reference (a PATH entry, say) is fine if anything under it
+    // is tracked
+    let is_tracked = |path: &str| {
+        tracked.contains(path) || tracked.iter().any(|t| t.starts_with(&format!("{}/", path)))
+    };
+
+    let mut issues = Vec::new();
+    for file in &files {
+        let Some(content) = read_file_content(config, file) else {
+            continue;
+        };
+        let mut seen = BTreeSet::new();
+        for (idx, line) in content.lines().enumerate() {
+            if line.trim_start().starts_with('#') {
+                continue;
+            }
+            for reference in RE_PATH.find_iter(line) {
+                let Some(path) = resolver.resolve(reference.as_str()) else {
+                    continue;
+                };
+                if is_tracked(&path) || !seen.insert(path.clone()) {
+                    continue;
+                }
+                let issue = if config.dotfiles_dir.join(&path).exists() {
+                    Issue::new(
+                        Severity::Warning,
+                        format!("{} references {}, which isn't tracked", file, path),
+                    )
+                    .with_code("REF002")
+                    .with_fix(format!("Run: git add {}", path))
+                } else {
+                    Issue::new(
+                        Severity::Error,
+                        format!("{} references {}, which doesn't exist", file, path),
+                    )
+                    .with_code("REF001")
+                };
+                issues.push(
+                    issue
+                        .with_file(file.to_string())
+                        .at(idx + 1, reference.start() + 1),
+                );
+            }
+        }
+    }
+
+    let passed = issues.iter().all(|i| i.severity != Severity::Error);
+    Ok(ValidationResult::new(
+        format!("Script references in {} configs resolve", files.len()),
+        passed,
+        issues,
+    ))
+}
+
+fn is_vim_config(file: &str) -> bool {
+    let name = Path::new(file)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default();
+    name.ends_with(".vim") || matches!(name, "vimrc" | ".vimrc" | "gvimrc" | ".gvimrc")
+}
+
+#[derive(Debug, PartialEq)]
+enum Sourced {
+    // A path as written, from a file that sources it at runtime
+    Path(String),
+    // A path that a dedicated rule already checks exists (gitconfig includes,
+    // tmux source-file), so only whether it's tracked is checked here
+    Checked(String),
+    // A dotted Lua module name
+    Module(String),
+}
+
+// (line, column, what) for everything a config pulls in from another file
+fn sourced_references(file: &str, content: &str) -> Vec<(usize, usize, Sourced)> {
+    static RE_SOURCE: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r#"(?:^|[\s;&|({])(?:source|\.)\s+['"]?([^\s'";|&)]+)"#).unwrap()
+    });
+    static RE_REQUIRE: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r#"\brequire\s*\(?\s*['"]([\w.-]+)['"]"#).unwrap());
+
+    if is_gitconfig(file) {
+        return parse_gitconfig(content)
+            .into_iter()
+            .filter(is_include)
+            .map(|e| (e.line, 1, Sourced::Checked(e.value)))
+            .collect();
+    }
+    if is_tmux_config(file) {
+        return parse_tmux_conf(content)
+            .sources
+            .into_iter()
+            .map(|(line, path)| (line, 1, Sourced::Checked(path)))
+            .collect();
+    }
+
+    let is_lua = file.ends_with(".lua");
+    if !(is_lua || is_shell_config(file) || is_vim_config(file)) {
+        return Vec::new();
+    }
+    let mut found = Vec::new();
+    for (idx, line) in content.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with('#') || trimmed.starts_with("--") || trimmed.starts_with('"') {
+            continue;
+        }
+        if is_lua {
+            for caps in RE_REQUIRE.captures_iter(line) {
+                let module = caps.get(1).unwrap();
+                found.push((
+                    idx + 1,
+                    module.start() + 1,
+                    Sourced::Module(module.as_str().to_string()),
+                ));
+            }
+        } else {
+            for caps in RE_SOURCE.captures_iter(line) {
+                let path = caps.get(1).unwrap();
+                found.push((
+                    idx + 1,
+                    path.start() + 1,
+                    Sourced::Path(path.as_str().to_string()),
+                ));
+            }
+        }
+    }
+    found
+}
+
+// The repo files a Lua module could be, when the module belongs to the
+// config itself; modules from plugins resolve to nothing
+fn lua_module_candidates(file: &str, module: &str, tracked: &HashSet<String>) -> Vec<String> {
+    // Neovim looks modules up under the config's lua/ directory
+    let root = match file.rfind("lua/") {
+        Some(pos) if pos == 0 || file[..pos].ends_with('/') => file[..pos].to_string(),
+        _ => match Path::new(file).parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => format!("{}/", dir.display()),
+            _ => String::new(),
+        },
+    };
+    let path = module.replace('.', "/");
+    let candidates = vec![
+        format!("{}lua/{}.lua", root, path),
+        format!("{}lua/{}/init.lua", root, path),
+    ];
+    // Only modules in a namespace the config already has are its own
+    let namespace = path.split('/').next().unwrap_or_default();
+    let owned = [
+        format!("{}lua/{}/", root, namespace),
+        format!("{}lua/{}.lua", root, namespace),
+    ];
+    let is_owned = tracked
+        .iter()
+        .any(|t| *t == owned[1] || (path.contains('/') && t.starts_with(&owned[0])));
+    if is_owned { candidates } else { Vec::new() }
+}
+
+// The repo file a sourced path points at; `None` for Lua modules, paths
+// outside the repo and its deployments, and ones only known at runtime
+fn sourced_repo_path(
+    config: &Config,
+    dotter_files: &[DotterFile],
+    file: &str,
+    reference: &Sourced,
+) -> Option<String> {
+    static RE_DOTFILES_VAR: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"^\$\{?DOTFILES\w*\}?/").unwrap());
+
+    let (Sourced::Path(path) | Sourced::Checked(path)) = reference else {
+        return None;
+    };
+    let path = tilde_path(path);
+    if let Some(m) = RE_DOTFILES_VAR.find(&path) {
+        return Some(path[m.end()..].to_string());
+    }
+    if path.contains('$')
+        || path.contains(['*', '?', '['])
+        || path.contains("#{")
+        // A shell sources relative paths from wherever it runs
+        || (matches!(reference, Sourced::Path(_))
+            && !path.starts_with("~/")
+            && !path.starts_with('/'))
+    {
+        return None;
+    }
+    match resolve_config_path(config, dotter_files, file, &path) {
+        IncludeTarget::Repo(source) => Some(source),
+        IncludeTarget::Machine(_) => None,
+    }
+}
+
+/// A path one repo file sources or includes from another, as written.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourcedPath {
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+    pub written: String,
+    // The repo file it resolves to
+    pub source: String,
+}
+
+/// Every path the repo's configs source or include that resolves to a file
+/// in the repo, directly or through a dotter target.
+pub fn sourced_paths(config: &Config) -> Result<Vec<SourcedPath>> {
+    let dotter_files = mapped_files(config)?;
+    let mut found = Vec::new();
+    for file in get_content_files(config)? {
+        let Some(content) = read_file_content(config, &file) else {
+            continue;
+        };
+        for (line, column, reference) in sourced_references(&file, &content) {
+            let Some(source) = sourced_repo_path(config, &dotter_files, &file, &reference) else {
+                continue;
+            };
+            let (Sourced::Path(written) | Sourced::Checked(written)) = reference else {
+                continue;
+            };
+            found.push(SourcedPath {
+                file: file.clone(),
+                line,
+                column,
+                written,
+                source,
+            });
+        }
+    }
+    Ok(found)
+}
+
+pub fn sourced_files_valid(config: &Config) -> Result<ValidationResult> {
+    let candidates = get_content_files(config)?;
+    let tracked: HashSet<String> = get_tracked_files(config)?.into_iter().collect();
+    let dotter_files = mapped_files(config)?;
+
+    let mut files = 0;
+    let mut issues = Vec::new();
+    for file in &candidates {
+        let Some(content) = read_file_content(config, file) else {
+            continue;
+        };
+        let references = sourced_references(file, &content);
+        files += usize::from(!references.is_empty());
+        for (line, col, reference) in references {
+            let (sources, existence_checked) = match &reference {
+                Sourced::Module(module) => (lua_module_candidates(file, module, &tracked), false),
+                Sourced::Path(_) | Sourced::Checked(_) => {
+                    let Some(source) = sourced_repo_path(config, &dotter_files, file, &reference)
+                    else {
+                        continue;
+                    };
+                    (vec![source], matches!(reference, Sourced::Checked(_)))
+                }
+            };
+            if sources.is_empty() || sources.iter().any(|s| tracked.contains(s)) {
+                continue;
+            }
+            let what = match &reference {
+                Sourced::Module(module) => format!("module {}", module),
+                _ => sources[0].clone(),
+            };
+            let issue = match sources
+                .iter()
+                .find(|s| config.dotfiles_dir.join(s).exists())
+            {
+                Some(source) => Issue::new(
+                    Severity::Warning,
+                    format!("{} pulls in {}, which isn't tracked", file, what),
+                )
+                .with_code("REF004")
+                .with_fix(format!("Run: git add {}", source)),
+                None if existence_checked => continue,
+                None => Issue::new(
+                    Severity::Error,
+                    format!("{} pulls in {}, which doesn't exist", file, what),
+                )
+                .with_code("REF003"),
+            };
+            issues.push(issue.with_file(file.clone()).at(line, col));
+        }
+    }
+
+    let passed = issues.iter().all(|i| i.severity != Severity::Error);
+    Ok(ValidationResult::new(
+        format!("Sourced and required files in {} configs resolve", files),
+        passed,
+        issues,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::FixtureRepo;
+
+    #[test]
+    fn references_resolve_through_the_repo_and_dotter_targets() {
+        let repo = FixtureRepo::new();
+        let root = repo.path().display().to_string();
+        repo.write(
+            ".dotter/global.toml",
+            "[scripts.files]\nscripts = \"~/.local/share/scripts\"\n",
+        )
+        .write("bin/backup", "#!/bin/sh\n")
+        .write(
+            ".zshrc",
+            "export PATH=\"$DOTFILES/bin:$PATH\"\n\
+             alias bk=\"$DOTFILES/bin/backup\"\n\
+             alias gone=\"${DOTFILES_DIR}/bin/gone\"\n\
+             # $DOTFILES/bin/commented-out\n\
+             alias sync=\"$HOME/.local/share/scripts/sync.sh\"\n",
+        )
+        .write(
+            "Library/LaunchAgents/com.example.sync.plist",
+            &format!(
+                "<array>\n  <string>{}/scripts/nightly.sh</string>\n</array>\n",
+                root
+            ),
+        )
+        .commit("fixture")
+        .write("scripts/sync.sh", "#!/bin/sh\n");
+
+        let result = script_references_valid(&repo.config()).unwrap();
+        let found: Vec<_> = result
+            .issues
+            .iter()
+            .map(|i| {
+                (
+                    i.code.as_deref().unwrap(),
+                    i.file.as_deref().unwrap(),
+                    i.line.unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("REF001", ".zshrc", 3),
+                ("REF002", ".zshrc", 5),
+                ("REF001", "Library/LaunchAgents/com.example.sync.plist", 2),
+            ],
+            "{:?}",
+            result.issues
+        );
+        assert!(!result.passed);
+    }
+
+    #[test]
+    fn sourced_files_resolve_to_tracked_files() {
+        let repo = FixtureRepo::new();
+        repo.write(
+            ".dotter/global.toml",
+            "[zsh.files]\nzsh = \"~/.config/zsh\"\n\n[nvim.files]\nnvim = \"~/.config/nvim\"\n",
+        )
+        .write(
+            "zsh/.zshrc",
+            "source ~/.config/zsh/aliases.zsh\n\
+             [[ -f ~/.zshrc.local ]] && . ~/.zshrc.local\n\
+             source $DOTFILES/zsh/gone.zsh\n\
+             source ~/.config/zsh/new.zsh\n\
+             source \"$ZDOTDIR/prompt.zsh\"\n",
+        )
+        .write("zsh/aliases.zsh", "alias ll='ls -l'\n")
+        .write(
+            "nvim/init.lua",
+            "require('config.options')\n\
+             require(\"config.keymaps\")\n\
+             require('telescope').setup({})\n",
+        )
+        .write("nvim/lua/config/options.lua", "vim.o.number = true\n")
+        .write("git/config", "[include]\n\tpath = local.inc\n")
+        .commit("fixture")
+        .write("zsh/new.zsh", "\n")
+        .write("git/local.inc", "\n");
+
+        let result = sourced_files_valid(&repo.config()).unwrap();
+        let found: Vec<_> = result
+            .issues
+            .iter()
+            .map(|i| {
+                (
+                    i.code.as_deref().unwrap(),
+                    i.file.as_deref().unwrap(),
+                    i.line.unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("REF004", "git/config", 2),
+                ("REF003", "nvim/init.lua", 2),
+                ("REF003", "zsh/.zshrc", 3),
+                ("REF004", "zsh/.zshrc", 4),
+            ],
+            "{:?}",
+            result.issues
+        );
+    }
+}
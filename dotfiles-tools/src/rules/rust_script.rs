@@ -0,0 +1,229 @@
+//! Rules for rust-script files and their embedded cargo manifests.
+
+use anyhow::{Context, Result};
+use tracing::debug;
+
+use std::{env, fs, path::Path, process::Command};
+
+use crate::{
+    repo::{find_executable, get_content_files, read_file_content},
+    types::{Config, Issue, Severity, ValidationResult},
+};
+
+pub fn is_rust_script(file: &str) -> bool {
+    file.starts_with("scripts/") && file.ends_with(".rs")
+}
+
+// The ```cargo block rust-script reads from the leading //! doc comment
+pub fn extract_cargo_manifest(content: &str) -> Option<String> {
+    let mut manifest = None;
+    for line in content.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("#!") && manifest.is_none() {
+            continue;
+        }
+        let Some(doc) = trimmed.strip_prefix("//!") else {
+            if trimmed.is_empty() {
+                continue;
+            }
+            break;
+        };
+        let doc = doc.strip_prefix(' ').unwrap_or(doc);
+        match manifest.as_mut() {
+            None if doc.trim() == "```cargo" => manifest = Some(String::new()),
+            None => {}
+            Some(body) if doc.trim() == "```" => return Some(std::mem::take(body)),
+            Some(body) => {
+                body.push_str(doc);
+                body.push('\n');
+            }
+        }
+    }
+    None
+}
+
+pub fn dependency_has_version(spec: &toml::Value) -> bool {
+    match spec {
+        toml::Value::String(version) => !version.trim().is_empty() && version.trim() != "*",
+        // Path and git dependencies are pinned by location instead
+        toml::Value::Table(table) => {
+            table
+                .get("version")
+                .and_then(|v| v.as_str())
+                .is_some_and(|v| !v.trim().is_empty() && v.trim() != "*")
+                || table.contains_key("path")
+                || table.contains_key("git")
+        }
+        _ => false,
+    }
+}
+
+pub fn rust_script_headers_valid(config: &Config) -> Result<ValidationResult> {
+    let scripts: Vec<_> = get_content_files(config)?
+        .into_iter()
+        .filter(|f| is_rust_script(f))
+        .collect();
+    let mut issues = Vec::new();
+
+    for file in &scripts {
+        let Some(content) = read_file_content(config, file) else {
+            continue;
+        };
+        let Some(manifest) = extract_cargo_manifest(&content) else {
+            continue;
+        };
+        let parsed = match toml::from_str::<toml::Table>(&manifest) {
+            Ok(parsed) => parsed,
+            Err(err) => {
+                issues.push(
+                    Issue::new(
+                        Severity::Error,
+                        format!("Invalid cargo manifest in {}: {}", file, err.message()),
+                    )
+                    .with_code("RS001")
+                    .with_file(file.clone()),
+                );
+                continue;
+            }
+        };
+
+        for section in ["dependencies", "dev-dependencies", "build-dependencies"] {
+            let Some(deps) = parsed.get(section).and_then(|d| d.as_table()) else {
+                continue;
+            };
+            for (name, spec) in deps {
+                if !dependency_has_version(spec) {
+                    issues.push(
+                        Issue::new(
+                            Severity::Error,
+                            format!("{} has no version requirement in {}", name, file),
+                        )
+                        .with_code("RS002")
+                        .with_file(file.clone())
+                        .with_fix(format!("Pin it, e.g. {} = \"1\"", name)),
+                    );
+                }
+            }
+        }
+    }
+
+    Ok(ValidationResult::new(
+        format!("All {} rust-script manifests are valid", scripts.len()),
+        issues.is_empty(),
+        issues,
+    ))
+}
+
+// Builds each script as a throwaway cargo project so broken manifests and
+// compile errors surface here instead of on first run. Shares one target dir
+// across runs so only the first check pays for compiling dependencies.
+pub fn rust_scripts_compile(config: &Config) -> Result<ValidationResult> {
+    let rule_name = "All rust-scripts compile";
+    if find_executable("cargo").is_none() {
+        debug!("skipping: cargo not on PATH");
+        return Ok(ValidationResult::new(rule_name, true, Vec::new()));
+    }
+
+    let scripts: Vec<_> = get_content_files(config)?
+        .into_iter()
+        .filter(|f| is_rust_script(f))
+        .collect();
+    let work_dir = env::temp_dir().join("validate-dotfiles-rust-scripts");
+    let mut issues = Vec::new();
+
+    for file in &scripts {
+        let Some(content) = read_file_content(config, file) else {
+            continue;
+        };
+        let Some(manifest) = extract_cargo_manifest(&content) else {
+            continue;
+        };
+        // A manifest that doesn't parse is already reported by rust-script-headers
+        let Ok(mut manifest) = toml::from_str::<toml::Table>(&manifest) else {
+            continue;
+        };
+
+        let name = Path::new(file)
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("script")
+            .to_string();
+        let project = work_dir.join(&name);
+        fs::create_dir_all(project.join("src"))
+            .with_context(|| format!("Failed to create {}", project.display()))?;
+        let mut package = toml::Table::new();
+        package.insert("name".into(), name.clone().into());
+        package.insert("version".into(), "0.0.0".into());
+        package.insert("edition".into(), "2024".into());
+        manifest
+            .entry("package")
+            .or_insert_with(|| toml::Value::Table(package));
+        fs::write(project.join("Cargo.toml"), toml::to_string(&manifest)?)?;
+        let source = content.strip_prefix("#!").map_or(content.as_str(), |rest| {
+            rest.split_once('\n').map_or("", |(_, body)| body)
+        });
+        fs::write(project.join("src").join("main.rs"), source)?;
+
+        let output = Command::new("cargo")
+            .args(["check", "--quiet", "--message-format=short"])
+            .env("CARGO_TARGET_DIR", work_dir.join("target"))
+            .current_dir(&project)
+            .output()
+            .context("Failed to run cargo check")?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let first_error = stderr
+                .lines()
+                .find(|l| l.contains("error"))
+                .unwrap_or("cargo check failed")
+                .trim()
+                .to_string();
+            issues.push(
+                Issue::new(
+                    Severity::Error,
+                    format!("{} does not compile: {}", file, first_error),
+                )
+                .with_code("RS003")
+                .with_file(file.clone())
+                .with_fix(format!("Run: rust-script --check {}", file)),
+            );
+        }
+    }
+
+    Ok(ValidationResult::new(rule_name, issues.is_empty(), issues))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::FixtureRepo;
+
+    #[test]
+    fn manifest_is_extracted_from_doc_comment() {
+        let content = "#!/usr/bin/env rust-script\n//! Docs\n//!\n//! ```cargo\n//! [dependencies]\n//! anyhow = \"1\"\n//! ```\n\nfn main() {}\n";
+        assert_eq!(
+            extract_cargo_manifest(content).as_deref(),
+            Some("[dependencies]\nanyhow = \"1\"\n")
+        );
+        assert_eq!(extract_cargo_manifest("fn main() {}\n"), None);
+    }
+
+    #[test]
+    fn unversioned_dependencies_are_reported() {
+        let repo = FixtureRepo::new();
+        repo.write(
+            "scripts/tool.rs",
+            "//! ```cargo\n//! [dependencies]\n//! a = \"*\"\n//! b = { path = \"../b\" }\n//! c = { features = [\"x\"] }\n//! ```\nfn main() {}\n",
+        )
+        .commit("fixture");
+
+        let result = rust_script_headers_valid(&repo.config()).unwrap();
+        let mut names: Vec<_> = result
+            .issues
+            .iter()
+            .map(|i| i.message.split(' ').next().unwrap())
+            .collect();
+        names.sort();
+        assert_eq!(names, ["a", "c"]);
+    }
+}
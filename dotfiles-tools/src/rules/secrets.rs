@@ -0,0 +1,289 @@
+//! Rules for credential files that must never be committed, and for the
+//! secret references that templates use instead.
+
+use anyhow::Result;
+use regex::Regex;
+use tracing::debug;
+
+use std::{
+    collections::HashMap,
+    path::Path,
+    process::{Command, Stdio},
+    sync::LazyLock,
+};
+
+use crate::{
+    fixes::shell_quote,
+    repo::{find_executable, get_content_files, get_tracked_files, read_file_content},
+    snippet::line_col,
+    types::{Config, Issue, Severity, ValidationResult},
+};
+
+// Suffixes of the private half of an ssh key pair
+const PRIVATE_KEY_SUFFIXES: &[&str] = &["_rsa", "_dsa", "_ecdsa", "_ed25519"];
+
+// Extensions of key and certificate bundles
+const KEY_EXTENSIONS: &[&str] = &["pem", "key", "p12", "pfx", "keystore"];
+
+// Example env files are meant to be committed
+const ENV_TEMPLATES: &[&str] = &["example", "sample", "template", "dist"];
+
+// Whether gh's hosts.yml has an oauth_token field under any host or user. A
+// comment mentioning one doesn't count, but a file too broken to parse does.
+fn holds_gh_token(content: &str) -> bool {
+    fn has_token(value: &serde_yaml_ng::Value) -> bool {
+        value.as_mapping().is_some_and(|map| {
+            map.iter()
+                .any(|(key, value)| key.as_str() == Some("oauth_token") || has_token(value))
+        })
+    }
+    match serde_yaml_ng::from_str(content) {
+        Ok(doc) => has_token(&doc),
+        Err(_) => content.contains("oauth_token"),
+    }
+}
+
+// What kind of credential file a tracked file is, by path, with its article;
+// gh's hosts.yml only counts once it holds a token, since it's otherwise plain
+// config
+fn credential_kind(config: &Config, file: &str) -> Option<&'static str> {
+    let path = Path::new(file);
+    let name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default();
+    let parent = path
+        .parent()
+        .and_then(|p| p.file_name())
+        .and_then(|n| n.to_str())
+        .unwrap_or_default();
+
+    if name == ".env"
+        || name
+            .strip_prefix(".env.")
+            .is_some_and(|rest| !ENV_TEMPLATES.contains(&rest))
+    {
+        Some("an environment file")
+    } else if PRIVATE_KEY_SUFFIXES.iter().any(|s| name.ends_with(s)) {
+        Some("an ssh private key")
+    } else if KEY_EXTENSIONS.contains(&extension) {
+        Some("a private key or certificate bundle")
+    } else if matches!(name, ".netrc" | "_netrc" | ".pgpass" | ".git-credentials")
+        || (parent == ".aws" && name == "credentials")
+    {
+        Some("a credentials file")
+    } else if parent == "gh"
+        && name == "hosts.yml"
+        && read_file_content(config, file).is_some_and(|c| holds_gh_token(&c))
+    {
+        Some("a gh token file")
+    } else {
+        None
+    }
+}
+
+pub fn no_tracked_credentials(config: &Config) -> Result<ValidationResult> {
+    let tracked = get_tracked_files(config)?;
+
+    let mut issues = Vec::new();
+    for file in &tracked {
+        let Some(kind) = credential_kind(config, file) else {
+            continue;
+        };
+        issues.push(
+            Issue::new(
+                Severity::Error,
+                format!(
+                    "{} is {} and must not be tracked; untrack it, add it to .gitignore and \
+                     rotate anything it held",
+                    file, kind
+                ),
+            )
+            .with_code("SEC001")
+            .with_file(file.clone())
+            .with_fix(format!("Run: git rm --cached -- {}", shell_quote(file))),
+        );
+    }
+
+    Ok(ValidationResult::new(
+        "No credential files are tracked",
+        issues.is_empty(),
+        issues,
+    ))
+}
+
+// Query parameters `op read` accepts after `?`
+const OP_QUERY_KEYS: &[&str] = &["attribute", "ssh-format"];
+
+// What's wrong with an `op://vault/item/[section/]field` reference, if anything
+fn op_reference_problem(reference: &str) -> Option<String> {
+    let rest = reference.strip_prefix("op://")?;
+    let (path, query) = match rest.split_once('?') {
+        Some((path, query)) => (path, Some(query)),
+        None => (rest, None),
+    };
+    let segments: Vec<_> = path.split('/').collect();
+    if !(3..=4).contains(&segments.len()) {
+        return Some(format!(
+            "has {} path segments; expected vault/item/field or vault/item/section/field",
+            segments.len()
+        ));
+    }
+    if segments.iter().any(|s| s.is_empty()) {
+        return Some("has an empty vault, item, section or field".to_string());
+    }
+    for param in query.into_iter().flat_map(|q| q.split('&')) {
+        let key = param.split_once('=').map_or(param, |(key, _)| key);
+        if !OP_QUERY_KEYS.contains(&key) {
+            return Some(format!("has an unknown query parameter '{}'", key));
+        }
+    }
+    None
+}
+
+// Whether `op` is installed and has an account it can read with
+fn op_signed_in() -> bool {
+    find_executable("op").is_some()
+        && Command::new("op")
+            .arg("whoami")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|s| s.success())
+}
+
+// Reads the secret without keeping it; only whether it resolves matters
+fn op_reference_resolves(reference: &str) -> bool {
+    Command::new("op")
+        .args(["read", "--no-newline", reference])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|s| s.success())
+}
+
+pub fn op_references_resolve(config: &Config) -> Result<ValidationResult> {
+    static RE_OP_REF: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r#"op://[^\s"'`<>(){}\[\],;|]+"#).unwrap());
+
+    let signed_in = op_signed_in();
+    if !signed_in {
+        debug!("op not on PATH or not signed in; checking reference syntax only");
+    }
+
+    let mut resolved: HashMap<String, bool> = HashMap::new();
+    let mut files = 0;
+    let mut issues = Vec::new();
+    for file in get_content_files(config)? {
+        let Some(content) = read_file_content(config, &file) else {
+            continue;
+        };
+        let mut found = false;
+        for reference in RE_OP_REF.find_iter(&content) {
+            found = true;
+            let (line, col) = line_col(&content, reference.start());
+            let text = reference.as_str();
+            if let Some(problem) = op_reference_problem(text) {
+                issues.push(
+                    Issue::new(Severity::Error, format!("{} {}", text, problem))
+                        .with_code("OP001")
+                        .with_file(file.clone())
+                        .at(line, col),
+                );
+            } else if signed_in
+                && !*resolved
+                    .entry(text.to_string())
+                    .or_insert_with(|| op_reference_resolves(text))
+            {
+                issues.push(
+                    Issue::new(Severity::Error, format!("{} doesn't resolve", text))
+                        .with_code("OP002")
+                        .with_file(file.clone())
+                        .with_fix(format!("Check it with: op read '{}'", text))
+                        .at(line, col),
+                );
+            }
+        }
+        files += usize::from(found);
+    }
+
+    Ok(ValidationResult::new(
+        format!("1Password references in {} files are valid", files),
+        issues.is_empty(),
+        issues,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::FixtureRepo;
+
+    #[test]
+    fn credential_files_are_caught_by_name() {
+        let repo = FixtureRepo::new();
+        repo.write(".env", "")
+            .write(".env.example", "TOKEN=\n")
+            .write("ssh/id_ed25519", "")
+            .write("ssh/id_ed25519.pub", "ssh-ed25519 AAAA\n")
+            .write("certs/work.pem", "")
+            .write(".aws/credentials", "")
+            .write(".aws/config", "[default]\n")
+            .write(
+                "gh/hosts.yml",
+                "github.com:\n    users:\n        me:\n            oauth_token: gho_x\n",
+            )
+            .write(
+                "work/gh/hosts.yml",
+                "# oauth_token lives in the keyring\ngithub.com:\n    user: me\n",
+            )
+            .write("glab/hosts.yml", "gitlab.com:\n    token: x\n")
+            .commit("fixture");
+
+        let result = no_tracked_credentials(&repo.config()).unwrap();
+        let mut files: Vec<_> = result
+            .issues
+            .iter()
+            .map(|i| i.file.as_deref().unwrap())
+            .collect();
+        files.sort();
+        assert_eq!(
+            files,
+            [
+                ".aws/credentials",
+                ".env",
+                "certs/work.pem",
+                "gh/hosts.yml",
+                "ssh/id_ed25519",
+            ]
+        );
+        assert!(!result.passed);
+
+        let env = result
+            .issues
+            .iter()
+            .find(|i| i.file.as_deref() == Some(".env"))
+            .unwrap();
+        assert!(env.message.starts_with(".env is an environment file"));
+        assert_eq!(
+            env.fix_suggestion.as_deref(),
+            Some("Run: git rm --cached -- .env")
+        );
+    }
+
+    #[test]
+    fn op_reference_syntax() {
+        assert_eq!(op_reference_problem("op://Private/GitHub/token"), None);
+        assert_eq!(
+            op_reference_problem("op://Private/GitHub/api/token?attribute=otp"),
+            None
+        );
+        assert!(op_reference_problem("op://Private/token").is_some());
+        assert!(op_reference_problem("op://Private//token").is_some());
+        assert!(op_reference_problem("op://Private/GitHub/token?format=raw").is_some());
+    }
+}
@@ -0,0 +1,463 @@
+//! Rules for shell definitions, PATH construction, and fish's autoloaded
+//! functions and completions.
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use tracing::debug;
+
+use std::{
+    collections::BTreeMap,
+    io::Write,
+    path::Path,
+    process::{Command, Stdio},
+    sync::LazyLock,
+};
+
+use crate::{
+    repo::{find_executable, get_tracked_files, read_file_content},
+    rules::check_files,
+    shell::{
+        PathEntry, ShellDefinition, expand_path_entry, in_exclusive_branches, is_shell_config,
+        parse_path_entries, parse_shell_definitions, shell_words,
+    },
+    types::{Config, Issue, Severity, ValidationResult},
+};
+
+pub fn no_conflicting_shell_definitions(config: &Config) -> Result<ValidationResult> {
+    let tracked = get_tracked_files(config)?;
+    let mut definitions = Vec::new();
+
+    for file in tracked.iter().filter(|f| is_shell_config(f)) {
+        if let Some(content) = read_file_content(config, file) {
+            definitions.extend(parse_shell_definitions(file, &content));
+        }
+    }
+
+    // Only definitions for the same shell can collide; an alias in both zsh
+    // and fish is just the same convenience ported twice
+    let mut grouped: BTreeMap<(&str, &str, &str), Vec<&ShellDefinition>> = BTreeMap::new();
+    for definition in &definitions {
+        grouped
+            .entry((definition.shell, definition.namespace, &definition.name))
+            .or_default()
+            .push(definition);
+    }
+
+    let mut issues = Vec::new();
+    for ((shell, namespace, name), defs) in grouped {
+        // Incremental updates like PATH="$HOME/bin:$PATH" build on the previous
+        // value rather than replacing it
+        let self_references = [format!("${}", name), format!("${{{}}}", name)];
+        let replacing: Vec<_> = defs
+            .iter()
+            .filter(|d| !self_references.iter().any(|r| d.value.contains(r.as_str())))
+            .collect();
+        let conflicting = replacing.iter().enumerate().any(|(i, a)| {
+            replacing[i + 1..]
+                .iter()
+                .any(|b| a.value != b.value && !a.mutually_exclusive(b))
+        });
+        if !conflicting {
+            continue;
+        }
+
+        let kind = if namespace == "env" {
+            "Environment variable"
+        } else {
+            "Alias/abbreviation"
+        };
+        let locations: Vec<_> = defs
+            .iter()
+            .map(|d| format!("{}:{} = {}", d.file, d.line, d.value))
+            .collect();
+        issues.push(
+            Issue::new(
+                Severity::Warning,
+                format!(
+                    "{} '{}' defined with conflicting values ({}): {}",
+                    kind,
+                    name,
+                    shell,
+                    locations.join("; ")
+                ),
+            )
+            .with_code("SH001")
+            .with_file(defs[0].file.clone()),
+        );
+    }
+
+    let passed = issues.iter().all(|i| i.severity != Severity::Error);
+    Ok(ValidationResult::new(
+        "No conflicting shell aliases or environment variables",
+        passed,
+        issues,
+    ))
+}
+
+pub fn path_modifications_sane(config: &Config) -> Result<ValidationResult> {
+    let tracked = get_tracked_files(config)?;
+    let mut entries = Vec::new();
+    for file in tracked.iter().filter(|f| is_shell_config(f)) {
+        if let Some(content) = read_file_content(config, file) {
+            entries.extend(parse_path_entries(file, &content));
+        }
+    }
+
+    let mut issues = Vec::new();
+
+    for entry in &entries {
+        let location = format!("{}:{}", entry.file, entry.line);
+        let is_absolute = entry.dir.starts_with('/')
+            || entry.dir.starts_with('~')
+            || entry.dir.starts_with('$')
+            || Path::new(&entry.dir).is_absolute();
+        if !is_absolute {
+            issues.push(
+                Issue::new(
+                    Severity::Warning,
+                    format!("Relative PATH entry '{}' at {}", entry.dir, location),
+                )
+                .with_code("SH002")
+                .with_file(entry.file.clone())
+                .with_fix(
+                    "Relative PATH entries resolve against the current directory; use an \
+                     absolute path",
+                ),
+            );
+            continue;
+        }
+
+        if let Some(path) = expand_path_entry(&entry.dir)
+            && !path.is_dir()
+        {
+            issues.push(
+                Issue::new(
+                    Severity::Warning,
+                    format!(
+                        "PATH entry '{}' at {} does not exist on this machine",
+                        entry.dir, location
+                    ),
+                )
+                .with_code("SH003")
+                .with_file(entry.file.clone()),
+            );
+        }
+    }
+
+    let mut by_dir: BTreeMap<(&str, &str), Vec<&PathEntry>> = BTreeMap::new();
+    for entry in &entries {
+        by_dir
+            .entry((entry.shell, entry.dir.trim_end_matches('/')))
+            .or_default()
+            .push(entry);
+    }
+    for ((shell, dir), entries) in by_dir {
+        let duplicated = entries.iter().enumerate().any(|(i, a)| {
+            entries[i + 1..]
+                .iter()
+                .any(|b| !in_exclusive_branches((&a.file, &a.branches), (&b.file, &b.branches)))
+        });
+        if !duplicated {
+            continue;
+        }
+        let locations: Vec<_> = entries
+            .iter()
+            .map(|e| format!("{}:{}", e.file, e.line))
+            .collect();
+        issues.push(
+            Issue::new(
+                Severity::Warning,
+                format!(
+                    "PATH entry '{}' added more than once ({}): {}",
+                    dir,
+                    shell,
+                    locations.join(", ")
+                ),
+            )
+            .with_code("SH004")
+            .with_file(entries[0].file.clone()),
+        );
+    }
+
+    let passed = issues.iter().all(|i| i.severity != Severity::Error);
+    Ok(ValidationResult::new(
+        format!("All {} PATH modifications are sane", entries.len()),
+        passed,
+        issues,
+    ))
+}
+
+// The fish autoload directory a file is in, if any: "functions" or
+// "completions"
+fn fish_autoload_dir(file: &str) -> Option<&'static str> {
+    let path = Path::new(file);
+    if path.extension().and_then(|e| e.to_str()) != Some("fish") {
+        return None;
+    }
+    let parent = path.parent()?;
+    ["functions", "completions"]
+        .into_iter()
+        .find(|dir| parent.ends_with(Path::new("fish").join(dir)))
+}
+
+// Commands a `complete` statement applies to, from -c/--command
+fn completed_commands(words: &[String]) -> Vec<String> {
+    let mut commands = Vec::new();
+    let mut args = words.iter().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "-c" || arg == "--command" {
+            commands.extend(args.next().cloned());
+        } else if let Some(command) = arg.strip_prefix("--command=") {
+            commands.push(command.to_string());
+        } else if let Some(command) = arg.strip_prefix("-c").filter(|c| !c.is_empty()) {
+            commands.push(command.to_string());
+        }
+    }
+    commands
+}
+
+pub fn fish_autoload_issues(file: &str, content: &str) -> Vec<Issue> {
+    let Some(dir) = fish_autoload_dir(file) else {
+        return Vec::new();
+    };
+    let name = Path::new(file)
+        .file_stem()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default();
+
+    let statements: Vec<(usize, Vec<String>)> = content
+        .lines()
+        .enumerate()
+        .map(|(idx, line)| (idx + 1, shell_words(line)))
+        .filter(|(_, words)| !words.is_empty())
+        .collect();
+
+    let problem = if dir == "functions" {
+        let defined: Vec<_> = statements
+            .iter()
+            .filter(|(_, words)| words[0] == "function")
+            .filter_map(|(line, words)| words.get(1).map(|n| (*line, n.as_str())))
+            .collect();
+        match defined.first() {
+            _ if defined.iter().any(|(_, n)| *n == name) => None,
+            None => Some((
+                1,
+                format!(
+                    "{} defines no function; fish autoloads it as {}",
+                    file, name
+                ),
+                "SH005",
+            )),
+            Some((line, _)) => Some((
+                *line,
+                format!(
+                    "{} defines {} but not {}, so fish never finds it",
+                    file,
+                    defined
+                        .iter()
+                        .map(|(_, n)| *n)
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    name
+                ),
+                "SH005",
+            )),
+        }
+    } else {
+        let completes: Vec<_> = statements
+            .iter()
+            .filter(|(_, words)| words[0] == "complete")
+            .collect();
+        // `tool completion fish | source` loads generated completions
+        let sources_generated = statements
+            .iter()
+            .any(|(_, words)| words.iter().any(|w| w == "source"));
+        let commands: Vec<_> = completes
+            .iter()
+            .flat_map(|(_, words)| completed_commands(words))
+            .collect();
+        match completes.first() {
+            _ if commands.iter().any(|c| c == name) => None,
+            None if sources_generated => None,
+            None => Some((
+                1,
+                format!("{} has no complete statements for {}", file, name),
+                "SH006",
+            )),
+            Some((line, _)) => Some((
+                *line,
+                format!(
+                    "{} completes {} but fish only loads it for {}",
+                    file,
+                    if commands.is_empty() {
+                        "no command".to_string()
+                    } else {
+                        commands.join(", ")
+                    },
+                    name
+                ),
+                "SH006",
+            )),
+        }
+    };
+
+    problem
+        .map(|(line, message, code)| {
+            Issue::new(Severity::Warning, message)
+                .with_code(code)
+                .with_file(file.to_string())
+                .at(line, 1)
+        })
+        .into_iter()
+        .collect()
+}
+
+pub fn fish_autoload_files_valid(config: &Config) -> Result<ValidationResult> {
+    let (count, issues) = check_files(
+        config,
+        "fish-autoload",
+        |f| fish_autoload_dir(f).is_some(),
+        |file, content| Ok(fish_autoload_issues(file, content)),
+    )?;
+    Ok(ValidationResult::new(
+        format!("{} fish functions and completions match their names", count),
+        true,
+        issues,
+    ))
+}
+
+// `<shell> -n` parses without running anything; the script goes over stdin so
+// --staged checks the index version
+pub fn shell_syntax_issues(
+    file: &str,
+    content: &str,
+    shell: &str,
+    code: &str,
+) -> Result<Vec<Issue>> {
+    static RE_LINE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"line (\d+)").unwrap());
+
+    if find_executable(shell).is_none() {
+        debug!(shell, "skipping syntax check: interpreter not on PATH");
+        return Ok(Vec::new());
+    }
+    let mut child = Command::new(shell)
+        .arg("-n")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to run {}", shell))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(content.as_bytes());
+    }
+    let output = child
+        .wait_with_output()
+        .with_context(|| format!("Failed to run {}", shell))?;
+    if output.status.success() {
+        return Ok(Vec::new());
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let message = stderr.lines().next().unwrap_or("syntax error").trim();
+    let issue = Issue::new(
+        Severity::Error,
+        format!("{} has a shell syntax error: {}", file, message),
+    )
+    .with_code(code)
+    .with_file(file.to_string());
+    let line = RE_LINE
+        .captures(&stderr)
+        .and_then(|caps| caps[1].parse().ok());
+    Ok(vec![match line {
+        Some(line) => issue.at(line, 1),
+        None => issue,
+    }])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::FixtureRepo;
+
+    #[test]
+    fn conflicting_aliases_are_reported_outside_exclusive_branches() {
+        let repo = FixtureRepo::new();
+        repo.write(
+            ".zshrc",
+            "if [[ $OSTYPE == darwin* ]]; then\n  alias ls='gls'\nelse\n  alias ls='ls --color'\nfi\nalias ll='ls -l'\n",
+        )
+        .write(".zprofile", "alias ll='ls -la'\n")
+        .commit("fixture");
+
+        let result = no_conflicting_shell_definitions(&repo.config()).unwrap();
+        assert_eq!(result.issues.len(), 1);
+        assert!(result.issues[0].message.contains("'ll'"));
+    }
+
+    #[test]
+    fn duplicate_and_relative_path_entries_are_reported() {
+        let repo = FixtureRepo::new();
+        repo.write(
+            ".zshenv",
+            "export PATH=\"/usr/bin:$PATH\"\nexport PATH=\"/usr/bin:$PATH\"\nexport PATH=\"bin:$PATH\"\n",
+        )
+        .commit("fixture");
+
+        let result = path_modifications_sane(&repo.config()).unwrap();
+        let messages: Vec<_> = result.issues.iter().map(|i| i.message.as_str()).collect();
+        assert!(
+            messages
+                .iter()
+                .any(|m| m.starts_with("Relative PATH entry 'bin'"))
+        );
+        assert!(
+            messages
+                .iter()
+                .any(|m| m.starts_with("PATH entry '/usr/bin' added more than once"))
+        );
+    }
+
+    #[test]
+    fn fish_autoload_files_must_match_their_names() {
+        let check = |file: &str, content: &str| -> Vec<_> {
+            fish_autoload_issues(file, content)
+                .into_iter()
+                .map(|i| (i.code.unwrap(), i.line.unwrap()))
+                .collect()
+        };
+        assert!(check("fish/functions/mkcd.fish", "function mkcd -a dir\nend\n").is_empty());
+        assert_eq!(
+            check(
+                "fish/functions/gco.fish",
+                "# checkout\nfunction git_checkout\nend\n"
+            ),
+            [("SH005".to_string(), 2)]
+        );
+        assert_eq!(
+            check("fish/functions/abbrs.fish", "abbr -a g git\n"),
+            [("SH005".to_string(), 1)]
+        );
+        assert!(
+            check(
+                "fish/completions/just.fish",
+                "complete -c just -f -a '(just --summary)'\n"
+            )
+            .is_empty()
+        );
+        assert!(
+            check(
+                "fish/completions/gh.fish",
+                "gh completion -s fish | source\n"
+            )
+            .is_empty()
+        );
+        assert_eq!(
+            check(
+                "fish/completions/rg.fish",
+                "complete --command=ripgrep -l hidden\n"
+            ),
+            [("SH006".to_string(), 1)]
+        );
+        assert!(check("fish/conf.d/abbrs.fish", "abbr -a g git\n").is_empty());
+    }
+}
@@ -0,0 +1,194 @@
+//! Runs shellcheck over tracked bash and sh scripts.
+
+use anyhow::{Context, Result};
+use indicatif::ProgressIterator;
+use serde::Deserialize;
+use tracing::{debug, trace_span};
+
+use std::{
+    io::Write,
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use crate::{
+    cache::file_issues,
+    output::progress_bar,
+    repo::{find_executable, get_content_files, read_file_content},
+    snippet::offset_of,
+    types::{Config, Issue, Severity, ValidationResult},
+};
+
+#[derive(Debug, Deserialize)]
+struct ShellcheckReport {
+    comments: Vec<ShellcheckComment>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ShellcheckComment {
+    line: usize,
+    column: usize,
+    end_line: usize,
+    end_column: usize,
+    level: String,
+    code: u32,
+    message: String,
+}
+
+/// The dialect to check `file` as, or `None` for anything shellcheck doesn't
+/// understand (zsh, fish, nushell) or that isn't a script at all.
+pub fn shellcheck_dialect(file: &str, content: &str) -> Option<&'static str> {
+    if let Some(shebang) = content.lines().next().and_then(|l| l.strip_prefix("#!")) {
+        // Both `#!/bin/bash` and `#!/usr/bin/env bash`
+        let interpreter = shebang
+            .split_whitespace()
+            .find(|w| !w.ends_with("/env") && !w.starts_with('-'))
+            .and_then(|w| w.rsplit('/').next())
+            .unwrap_or_default();
+        return match interpreter {
+            "bash" => Some("bash"),
+            "sh" => Some("sh"),
+            "dash" => Some("dash"),
+            "ksh" => Some("ksh"),
+            _ => None,
+        };
+    }
+
+    let name = Path::new(file)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default();
+    if name.ends_with(".bash")
+        || matches!(
+            name,
+            ".bashrc" | ".bash_profile" | ".bash_aliases" | ".bash_logout"
+        )
+    {
+        Some("bash")
+    } else if name.ends_with(".sh") || name == ".profile" {
+        Some("sh")
+    } else {
+        None
+    }
+}
+
+fn to_severity(level: &str) -> Severity {
+    match level {
+        "error" => Severity::Error,
+        "warning" => Severity::Warning,
+        "info" => Severity::Info,
+        _ => Severity::Hint,
+    }
+}
+
+pub fn parse_shellcheck_output(file: &str, content: &str, output: &str) -> Result<Vec<Issue>> {
+    let report: ShellcheckReport =
+        serde_json::from_str(output).context("shellcheck produced invalid JSON")?;
+    Ok(report
+        .comments
+        .into_iter()
+        .map(|c| {
+            let code = format!("SC{}", c.code);
+            let issue = Issue::new(
+                to_severity(&c.level),
+                format!("{}:{}: {}", file, c.line, c.message),
+            )
+            .with_fix(format!("See https://www.shellcheck.net/wiki/{}", code))
+            .with_code(code)
+            .with_file(file.to_string());
+            let start = offset_of(content, c.line, c.column);
+            let end = offset_of(content, c.end_line, c.end_column);
+            if end > start {
+                issue.with_span(content, start..end)
+            } else {
+                issue.at(c.line, c.column)
+            }
+        })
+        .collect())
+}
+
+fn run_shellcheck(config: &Config, file: &str, content: &str, dialect: &str) -> Result<Vec<Issue>> {
+    // json1 counts a tab as one column, which is what snippets expect
+    let mut child = Command::new("shellcheck")
+        .args(["--format", "json1", "--shell", dialect, "-"])
+        .current_dir(&config.dotfiles_dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .context("Failed to run shellcheck")?;
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(content.as_bytes());
+    }
+    let output = child
+        .wait_with_output()
+        .context("Failed to run shellcheck")?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // Nothing on stdout means shellcheck itself failed, not the script
+    if stdout.trim().is_empty() {
+        debug!(status = %output.status, "shellcheck produced no report");
+        return Ok(Vec::new());
+    }
+    parse_shellcheck_output(file, content, &stdout)
+}
+
+// Content goes over stdin so --staged checks the index version, with the
+// dialect passed explicitly for rc files that have no shebang
+pub fn scripts_pass_shellcheck(config: &Config) -> Result<ValidationResult> {
+    let rule_name = "Shell scripts pass shellcheck";
+    if find_executable("shellcheck").is_none() {
+        debug!("skipping: shellcheck not on PATH");
+        return Ok(ValidationResult::new(rule_name, true, Vec::new()));
+    }
+
+    let candidates = get_content_files(config)?;
+    let mut issues = Vec::new();
+    let progress = progress_bar(config, "shellcheck", candidates.len());
+    for file in candidates.iter().progress_with(progress) {
+        let _span = trace_span!("file", path = %file).entered();
+        let Some(content) = read_file_content(config, file) else {
+            continue;
+        };
+        let Some(dialect) = shellcheck_dialect(file, &content) else {
+            continue;
+        };
+
+        issues.extend(file_issues(config, "shellcheck", file, &content, || {
+            run_shellcheck(config, file, &content, dialect)
+        })?);
+    }
+
+    let passed = issues.iter().all(|i| i.severity != Severity::Error);
+    Ok(ValidationResult::new(rule_name, passed, issues))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dialect_comes_from_the_shebang_then_the_name() {
+        assert_eq!(
+            shellcheck_dialect("bin/x", "#!/usr/bin/env bash\n"),
+            Some("bash")
+        );
+        assert_eq!(shellcheck_dialect("bin/x", "#!/bin/sh -e\n"), Some("sh"));
+        assert_eq!(shellcheck_dialect("x.sh", "#!/usr/bin/env zsh\n"), None);
+        assert_eq!(shellcheck_dialect(".bashrc", "alias l=ls\n"), Some("bash"));
+        assert_eq!(shellcheck_dialect(".zshrc", "alias l=ls\n"), None);
+    }
+
+    #[test]
+    fn findings_become_coded_positioned_issues() {
+        let content = "#!/bin/sh\necho $1\n";
+        let output = r#"{"comments":[{"file":"-","line":2,"endLine":2,"column":6,"endColumn":8,
+            "level":"info","code":2086,"message":"Double quote to prevent globbing and word splitting.","fix":null}]}"#;
+        let issues = parse_shellcheck_output("bin/greet", content, output).unwrap();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].code.as_deref(), Some("SC2086"));
+        assert_eq!(issues[0].severity, Severity::Info);
+        assert_eq!(issues[0].location().as_deref(), Some("bin/greet:2:6"));
+        assert_eq!(issues[0].span, Some(15..17));
+    }
+}
@@ -0,0 +1,341 @@
+//! Rule for skhd's skhdrc: hotkey syntax, duplicate bindings, and the scripts
+//! bindings run.
+
+use anyhow::Result;
+
+use std::{collections::BTreeMap, path::Path};
+
+use crate::{
+    dotter::mapped_files,
+    repo::{expand_home, find_executable, get_content_files, read_file_content},
+    shell::shell_words,
+    types::{Config, Issue, Severity, ValidationResult},
+};
+
+const MODIFIERS: &[&str] = &[
+    "fn", "cmd", "lcmd", "rcmd", "shift", "lshift", "rshift", "alt", "lalt", "ralt", "ctrl",
+    "lctrl", "rctrl", "hyper", "meh",
+];
+
+const SCRIPT_EXTENSIONS: &[&str] = &[".sh", ".bash", ".zsh", ".fish", ".nu", ".py", ".rb"];
+
+#[derive(Debug, Default)]
+pub struct Skhdrc {
+    // (line, problem) for anything skhd would refuse to load
+    pub problems: Vec<(usize, String)>,
+    // (line, what's bound twice, line of the first binding)
+    pub duplicates: Vec<(usize, String, usize)>,
+    // (line, command) for every command a binding or mode runs
+    pub commands: Vec<(usize, String)>,
+}
+
+// Joins `\`-continued lines, keeping the number of the line each started on
+fn logical_lines(content: &str) -> Vec<(usize, String)> {
+    let mut lines = Vec::new();
+    let mut pending: Option<(usize, String)> = None;
+    for (idx, line) in content.lines().enumerate() {
+        let (start, mut text) = pending.take().unwrap_or((idx + 1, String::new()));
+        match line.strip_suffix('\\') {
+            Some(continued) => {
+                text.push_str(continued);
+                text.push(' ');
+                pending = Some((start, text));
+            }
+            None => {
+                text.push_str(line);
+                lines.push((start, text));
+            }
+        }
+    }
+    lines.extend(pending);
+    lines
+}
+
+pub fn parse_skhdrc(content: &str) -> Skhdrc {
+    let mut parsed = Skhdrc::default();
+    let mut declared = vec!["default".to_string()];
+    let mut used_modes = Vec::new();
+    let mut bound: BTreeMap<String, usize> = BTreeMap::new();
+
+    let lines = logical_lines(content);
+    let mut iter = lines.iter();
+    while let Some((line, text)) = iter.next() {
+        let line = *line;
+        let text = text.trim();
+        if text.is_empty() || text.starts_with('#') || text.starts_with('.') {
+            continue;
+        }
+
+        // :: name [@] [: command]
+        if let Some(declaration) = text.strip_prefix("::") {
+            let (head, command) = declaration.split_once(':').unwrap_or((declaration, ""));
+            let name = head.trim().trim_end_matches('@').trim();
+            if name.is_empty() {
+                parsed
+                    .problems
+                    .push((line, "Mode declaration without a name".to_string()));
+            } else {
+                declared.push(name.to_string());
+            }
+            if !command.trim().is_empty() {
+                parsed.commands.push((line, command.trim().to_string()));
+            }
+            continue;
+        }
+
+        let Some(action_at) = text.find([':', ';', '[']) else {
+            parsed.problems.push((
+                line,
+                format!("Expected ':', ';' or '[' after the hotkey in '{}'", text),
+            ));
+            continue;
+        };
+        let (keysym, action) = text.split_at(action_at);
+        let (modes, keysym) = match keysym.split_once('<') {
+            Some((modes, keysym)) => (
+                modes.split(',').map(|m| m.trim().to_string()).collect(),
+                keysym,
+            ),
+            None => (vec!["default".to_string()], keysym),
+        };
+        used_modes.extend(modes.iter().map(|m| (line, m.clone())));
+        let keysym = keysym.trim().trim_end_matches("->").trim();
+        let (modifiers, key) = match keysym.split_once('-') {
+            Some((modifiers, key)) => (modifiers, key.trim()),
+            None => ("", keysym),
+        };
+        let mut modifiers: Vec<_> = modifiers
+            .split('+')
+            .map(|m| m.trim().to_lowercase())
+            .filter(|m| !m.is_empty())
+            .collect();
+        for modifier in &modifiers {
+            if !MODIFIERS.contains(&modifier.as_str()) {
+                parsed
+                    .problems
+                    .push((line, format!("Unknown modifier '{}'", modifier)));
+            }
+        }
+        if key.is_empty() || key.contains(char::is_whitespace) {
+            parsed
+                .problems
+                .push((line, format!("Invalid key '{}' in '{}'", key, keysym)));
+            continue;
+        }
+
+        let rest = action[1..].trim();
+        match action.chars().next() {
+            Some(':') => parsed.commands.push((line, rest.to_string())),
+            Some(';') => used_modes.push((line, rest.to_string())),
+            // Per-application bindings run until the closing bracket
+            _ => {
+                let mut body = vec![(line, rest.to_string())];
+                if !rest.contains(']') {
+                    for (line, text) in iter.by_ref() {
+                        body.push((*line, text.clone()));
+                        if text.contains(']') {
+                            break;
+                        }
+                    }
+                }
+                for (line, entry) in body {
+                    let entry = entry.split(']').next().unwrap_or_default();
+                    if let Some((_, command)) = entry.split_once(':')
+                        && !command.trim().is_empty()
+                    {
+                        parsed.commands.push((line, command.trim().to_string()));
+                    }
+                }
+                continue;
+            }
+        }
+
+        modifiers.sort();
+        modifiers.dedup();
+        let mut sorted_modes = modes.clone();
+        sorted_modes.sort();
+        let combination = if modifiers.is_empty() {
+            key.to_lowercase()
+        } else {
+            format!("{} - {}", modifiers.join(" + "), key.to_lowercase())
+        };
+        let binding = format!("{} < {}", sorted_modes.join(","), combination);
+        match bound.get(&binding) {
+            Some(first) => parsed.duplicates.push((line, combination, *first)),
+            None => {
+                bound.insert(binding, line);
+            }
+        }
+    }
+
+    for (line, mode) in used_modes {
+        if !declared.contains(&mode) {
+            parsed
+                .problems
+                .push((line, format!("Mode '{}' is used but never declared", mode)));
+        }
+    }
+    parsed.problems.sort();
+    parsed
+}
+
+// `~/x` and `$HOME/x`, as a path under the home directory
+fn home_relative(path: &str) -> Option<&str> {
+    path.strip_prefix("~/")
+        .or_else(|| path.strip_prefix("$HOME/"))
+        .or_else(|| path.strip_prefix("${HOME}/"))
+}
+
+// A script the command runs, when it names one: a path under $HOME, or a
+// bare script name that has to come from PATH
+fn script_reference(command: &str) -> Option<String> {
+    let program = shell_words(command).into_iter().next()?;
+    let is_script = if program.contains('/') {
+        home_relative(&program).is_some()
+    } else {
+        SCRIPT_EXTENSIONS.iter().any(|ext| program.ends_with(ext))
+    };
+    is_script.then_some(program)
+}
+
+pub fn skhd_configs_valid(config: &Config) -> Result<ValidationResult> {
+    let candidates = get_content_files(config)?;
+    let files: Vec<_> = candidates
+        .iter()
+        .filter(|f| {
+            matches!(
+                Path::new(f).file_name().and_then(|n| n.to_str()),
+                Some("skhdrc" | ".skhdrc")
+            )
+        })
+        .collect();
+    // Scripts usually live in the repo and are deployed next to the config
+    let targets: Vec<_> = if files.is_empty() {
+        Vec::new()
+    } else {
+        mapped_files(config)?
+            .into_iter()
+            .map(|f| f.target.replace("$HOME/", "~/"))
+            .collect()
+    };
+    let exists = |script: &str| match home_relative(script) {
+        Some(relative) => {
+            config.dotfiles_dir.join(relative).exists()
+                || expand_home(script).exists()
+                || targets.iter().any(|t| {
+                    let target = format!("~/{}", relative);
+                    *t == target || target.starts_with(&format!("{}/", t.trim_end_matches('/')))
+                })
+        }
+        None => find_executable(script).is_some(),
+    };
+
+    let mut issues = Vec::new();
+    for file in &files {
+        let Some(content) = read_file_content(config, file) else {
+            continue;
+        };
+        let parsed = parse_skhdrc(&content);
+        for (line, problem) in parsed.problems {
+            issues.push(
+                Issue::new(Severity::Error, format!("{}: {}", file, problem))
+                    .with_code("SKHD001")
+                    .with_file(file.to_string())
+                    .at(line, 1),
+            );
+        }
+        for (line, combination, first) in parsed.duplicates {
+            issues.push(
+                Issue::new(
+                    Severity::Warning,
+                    format!(
+                        "'{}' is bound again (first bound on line {})",
+                        combination, first
+                    ),
+                )
+                .with_code("SKHD002")
+                .with_file(file.to_string())
+                .at(line, 1),
+            );
+        }
+        for (line, command) in parsed.commands {
+            let Some(script) = script_reference(&command) else {
+                continue;
+            };
+            if !exists(&script) {
+                issues.push(
+                    Issue::new(
+                        Severity::Warning,
+                        format!(
+                            "Binding runs {}, which isn't in the repo or on PATH",
+                            script
+                        ),
+                    )
+                    .with_code("SKHD003")
+                    .with_file(file.to_string())
+                    .at(line, 1),
+                );
+            }
+        }
+    }
+
+    let passed = issues.iter().all(|i| i.severity != Severity::Error);
+    Ok(ValidationResult::new(
+        format!("All {} skhd configs are valid", files.len()),
+        passed,
+        issues,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::FixtureRepo;
+
+    #[test]
+    fn hotkeys_are_parsed_and_checked() {
+        let repo = FixtureRepo::new();
+        repo.write(
+            ".config/skhd/skhdrc",
+            "# focus\n\
+             :: resize @ : echo resize\n\
+             alt - h : yabai -m window --focus west\n\
+             shift + alt - h : ~/.config/skhd/scripts/swap.sh west\n\
+             alt + shift - h : ~/.config/skhd/scripts/missing.sh\n\
+             cmd - n [\n  \"kitty\" : ~/bin/new-tab.sh\n  * : open -n\n]\n\
+             resize < escape ; default\n\
+             move < alt - j : echo\n\
+             hyperr - k : echo\n\
+             alt - l : yabai -m window \\\n  --focus east\n",
+        )
+        .write(".config/skhd/scripts/swap.sh", "#!/bin/sh\n")
+        .commit("fixture");
+
+        let parsed = parse_skhdrc(
+            &std::fs::read_to_string(repo.path().join(".config/skhd/skhdrc")).unwrap(),
+        );
+        assert_eq!(
+            parsed.problems,
+            [
+                (11, "Mode 'move' is used but never declared".to_string()),
+                (12, "Unknown modifier 'hyperr'".to_string()),
+            ]
+        );
+        assert_eq!(parsed.duplicates, [(5, "alt + shift - h".to_string(), 4)]);
+        assert!(
+            parsed
+                .commands
+                .iter()
+                .any(|(l, c)| *l == 13 && c.contains("--focus east"))
+        );
+
+        let result = skhd_configs_valid(&repo.config()).unwrap();
+        let missing: Vec<_> = result
+            .issues
+            .iter()
+            .filter(|i| i.code.as_deref() == Some("SKHD003"))
+            .map(|i| i.line)
+            .collect();
+        assert_eq!(missing, [Some(5), Some(7)]);
+    }
+}
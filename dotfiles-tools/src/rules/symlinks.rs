@@ -0,0 +1,195 @@
+//! Rules for symlinks committed to the repository, and ones deployed from it.
+
+use anyhow::Result;
+use indicatif::ProgressIterator;
+use tracing::debug;
+
+use std::fs;
+
+use crate::{
+    drift::stale_links,
+    fixes::shell_quote,
+    output::progress_bar,
+    paths::{MAX_SYMLINK_HOPS, SymlinkStatus, analyze_symlink, normalize_path, relative_path},
+    repo::get_tracked_files,
+    types::{Config, Issue, Severity, ValidationResult},
+};
+
+pub fn no_broken_symlinks(config: &Config) -> Result<ValidationResult> {
+    let tracked = get_tracked_files(config)?;
+    let mut issues = Vec::new();
+
+    let progress = progress_bar(config, "symlinks", tracked.len());
+    for file in tracked.into_iter().progress_with(progress) {
+        let path = config.dotfiles_dir.join(&file);
+        let issue = match analyze_symlink(&path, &config.dotfiles_dir) {
+            SymlinkStatus::NotSymlink | SymlinkStatus::Resolved => continue,
+            SymlinkStatus::Dangling(target) => Issue::new(
+                Severity::Error,
+                format!("Broken symlink: {} -> {}", file, target.display()),
+            )
+            .with_code("LNK001"),
+            SymlinkStatus::Cycle(at) => Issue::new(
+                Severity::Error,
+                format!("Symlink cycle: {} loops back at {}", file, at.display()),
+            )
+            .with_code("LNK002"),
+            SymlinkStatus::TooDeep => Issue::new(
+                Severity::Warning,
+                format!(
+                    "Symlink chain too deep: {} (more than {} hops)",
+                    file, MAX_SYMLINK_HOPS
+                ),
+            )
+            .with_code("LNK003")
+            .with_fix("Point the link directly at its final target"),
+            SymlinkStatus::EscapesRepo(target) => Issue::new(
+                Severity::Warning,
+                format!(
+                    "Symlink escapes repository: {} -> {}",
+                    file,
+                    target.display()
+                ),
+            )
+            .with_code("LNK004")
+            .with_fix("Commit the target into the repo or deploy it with dotter instead"),
+        };
+        issues.push(issue.with_file(file));
+    }
+
+    let passed = issues.iter().all(|i| i.severity != Severity::Error);
+    Ok(ValidationResult::new("No broken symlinks", passed, issues))
+}
+
+pub fn symlinks_are_relative(config: &Config) -> Result<ValidationResult> {
+    let tracked = get_tracked_files(config)?;
+    let root =
+        fs::canonicalize(&config.dotfiles_dir).unwrap_or_else(|_| config.dotfiles_dir.clone());
+    let mut issues = Vec::new();
+
+    let progress = progress_bar(config, "relative symlinks", tracked.len());
+    for file in tracked.into_iter().progress_with(progress) {
+        let path = config.dotfiles_dir.join(&file);
+        // Junctions are always absolute, so only real symlinks are checked
+        let is_symlink = fs::symlink_metadata(&path)
+            .map(|m| m.file_type().is_symlink())
+            .unwrap_or(false);
+        if !is_symlink {
+            continue;
+        }
+
+        let Ok(target) = fs::read_link(&path) else {
+            continue;
+        };
+        if target.is_relative() {
+            continue;
+        }
+
+        let link_dir = root.join(&file);
+        let link_dir = link_dir.parent().unwrap_or(&root);
+        let suggested = relative_path(link_dir, &normalize_path(&target));
+
+        issues.push(
+            Issue::new(
+                Severity::Error,
+                format!(
+                    "Symlink uses an absolute target: {} -> {}",
+                    file,
+                    target.display()
+                ),
+            )
+            .with_code("LNK005")
+            .with_file(file.clone())
+            // mklink is a cmd.exe builtin, so it's left out of the sh fix script
+            .with_fix(if cfg!(windows) {
+                format!(
+                    "Recreate it from cmd.exe with: mklink {}\"{}\" \"{}\"",
+                    if path.is_dir() { "/D " } else { "" },
+                    file,
+                    suggested.display()
+                )
+            } else {
+                format!(
+                    "Run: ln -sfn {} {}",
+                    shell_quote(&suggested.to_string_lossy()),
+                    shell_quote(&file)
+                )
+            }),
+        );
+    }
+
+    Ok(ValidationResult::new(
+        "Repository symlinks are relative",
+        issues.is_empty(),
+        issues,
+    ))
+}
+
+pub fn no_stale_deployed_links(config: &Config) -> Result<ValidationResult> {
+    if !config.dotfiles_dir.join(".dotter/local.toml").exists() {
+        debug!("skipping: no .dotter/local.toml, so nothing is deployed here");
+        return Ok(ValidationResult::new(
+            "No stale symlinks into the repo",
+            true,
+            Vec::new(),
+        ));
+    }
+
+    let issues: Vec<_> = stale_links(config)?
+        .into_iter()
+        .map(|stale| {
+            Issue::new(
+                Severity::Warning,
+                format!(
+                    "Stale deployed symlink: {} -> {}",
+                    stale.link.display(),
+                    stale.target.display()
+                ),
+            )
+            .with_code("LNK006")
+            .with_fix("Run: dotfiles-tools clean-links")
+        })
+        .collect();
+
+    Ok(ValidationResult::new(
+        "No stale symlinks into the repo",
+        true,
+        issues,
+    ))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::test_support::FixtureRepo;
+
+    #[test]
+    fn dangling_symlinks_are_reported() {
+        let repo = FixtureRepo::new();
+        repo.write("real.conf", "x\n")
+            .symlink("ok.conf", "real.conf")
+            .symlink("broken.conf", "missing.conf")
+            .commit("fixture");
+
+        let result = no_broken_symlinks(&repo.config()).unwrap();
+        assert!(!result.passed);
+        assert_eq!(result.issues.len(), 1);
+        assert_eq!(result.issues[0].file.as_deref(), Some("broken.conf"));
+    }
+
+    #[test]
+    fn absolute_symlinks_into_the_repo_get_a_relative_fix() {
+        let repo = FixtureRepo::new();
+        let target = repo.path().join("dir/real.conf");
+        repo.write("dir/real.conf", "x\n")
+            .symlink("link.conf", target.to_str().unwrap())
+            .commit("fixture");
+
+        let result = symlinks_are_relative(&repo.config()).unwrap();
+        assert_eq!(result.issues.len(), 1);
+        assert_eq!(
+            result.issues[0].fix_suggestion.as_deref(),
+            Some("Run: ln -sfn dir/real.conf link.conf")
+        );
+    }
+}
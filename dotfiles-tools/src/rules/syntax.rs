@@ -0,0 +1,759 @@
+//! Syntax rules for config file formats.
+
+use anyhow::{Context, Result};
+use indicatif::ProgressIterator;
+use regex::Regex;
+use tracing::{debug, trace_span};
+
+use std::{
+    io::Write,
+    ops::Range,
+    path::Path,
+    process::{Command, Stdio},
+    sync::LazyLock,
+};
+
+use crate::{
+    cache::file_issues,
+    fixes::shell_quote,
+    output::progress_bar,
+    repo::{find_executable, get_content_files, looks_binary, read_file_bytes, read_file_content},
+    rules::check_files,
+    snippet::line_col,
+    types::{Config, Issue, Severity, ValidationResult},
+};
+
+// A file this broken has bigger problems than the ones past the first screen
+pub const MAX_PARSE_ERRORS: usize = 20;
+
+#[derive(Debug)]
+pub struct ParseError {
+    pub message: String,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub span: Option<Range<usize>>,
+}
+
+impl ParseError {
+    pub fn into_issue(self, content: &str, code: &str, message: String, file: &str) -> Issue {
+        let issue = Issue::new(Severity::Error, format!("{}: {}", message, self.message))
+            .with_code(code)
+            .with_file(file.to_string());
+        match (self.span, self.line) {
+            (Some(span), _) => issue.with_span(content, span),
+            (None, Some(line)) => issue.at(line, self.column.unwrap_or(1)),
+            (None, None) => issue,
+        }
+    }
+}
+
+fn line_start(content: &str, line: usize) -> usize {
+    content
+        .split_inclusive('\n')
+        .take(line.saturating_sub(1))
+        .map(str::len)
+        .sum()
+}
+
+// Replaces a line with spaces of the same byte length, so offsets into the
+// rest of the file stay valid. Characters in `keep` survive.
+fn blank_line(content: &mut String, line: usize, keep: &[char]) {
+    let start = line_start(content, line);
+    let end = content[start..]
+        .find('\n')
+        .map_or(content.len(), |i| start + i);
+    let blank: String = content[start..end]
+        .chars()
+        .flat_map(|c| {
+            let replacement = if keep.contains(&c) { c } else { ' ' };
+            std::iter::repeat_n(replacement, c.len_utf8())
+        })
+        .collect();
+    content.replace_range(start..end, &blank);
+}
+
+pub fn recover_toml(content: &mut String, error: &ParseError) {
+    if let Some(line) = error.line {
+        blank_line(content, line, &[]);
+    }
+}
+
+// A missing comma is reported at the next value, so dropping that line would
+// just move the same error down; put the comma in the whitespace before it
+// instead. Otherwise brackets stay so the structure around the line holds.
+pub fn recover_json(content: &mut String, error: &ParseError) {
+    let Some(line) = error.line else {
+        return;
+    };
+    if error.message.starts_with("expected `,`") {
+        let offset = line_start(content, line) + error.column.unwrap_or(1).saturating_sub(1);
+        if let Some(space) = content[..offset.min(content.len())].rfind([' ', '\t']) {
+            content.replace_range(space..space + 1, ",");
+            return;
+        }
+    }
+    blank_line(content, line, &['{', '}', '[', ']']);
+}
+
+/// Every error `parse` finds in `content`, not just the first. Parsers stop
+/// at the first error, so `recover` patches over it (usually by blanking the
+/// line) and the file is re-parsed. Errors have to move forward through the
+/// file, which stops recovery from chasing errors it caused itself.
+pub fn collect_parse_errors(
+    content: &str,
+    parse: impl Fn(&str) -> Option<ParseError>,
+    recover: impl Fn(&mut String, &ParseError),
+) -> Vec<ParseError> {
+    let mut working = content.to_string();
+    let mut errors: Vec<ParseError> = Vec::new();
+    while errors.len() < MAX_PARSE_ERRORS {
+        let Some(mut error) = parse(&working) else {
+            break;
+        };
+        if let Some(span) = &error.span {
+            let (line, column) = line_col(content, span.start);
+            error.line = Some(line);
+            error.column = Some(column);
+        }
+        let last_line = errors.last().and_then(|e| e.line).unwrap_or(0);
+        match error.line {
+            Some(line) if line > last_line => {
+                recover(&mut working, &error);
+                errors.push(error);
+            }
+            // No position to recover from, or recovery went backwards
+            _ => {
+                if errors.is_empty() {
+                    errors.push(error);
+                }
+                break;
+            }
+        }
+    }
+    errors
+}
+
+pub fn parse_toml(content: &str) -> Option<ParseError> {
+    let err = toml::from_str::<toml::Value>(content).err()?;
+    Some(ParseError {
+        message: err.message().trim().replace('\n', "; "),
+        line: None,
+        column: None,
+        span: err.span(),
+    })
+}
+
+pub fn parse_json(content: &str) -> Option<ParseError> {
+    let err = serde_json::from_str::<serde_json::Value>(content).err()?;
+    // serde_json appends the position to its message
+    let message = err.to_string();
+    let message = message
+        .rsplit_once(" at line ")
+        .map_or(message.as_str(), |(m, _)| m);
+    Some(ParseError {
+        message: message.to_string(),
+        line: Some(err.line()),
+        column: Some(err.column()),
+        span: None,
+    })
+}
+
+pub fn parse_json5(content: &str) -> Option<ParseError> {
+    let json5::Error::Message { msg, location } =
+        json5::from_str::<serde_json::Value>(content).err()?;
+    // Syntax errors come from pest as a rendered snippet ending in
+    // "= expected ..."; the position is reported separately
+    let message = msg
+        .lines()
+        .last()
+        .map(|line| line.trim().trim_start_matches("= ").to_string())
+        .unwrap_or(msg);
+    Some(ParseError {
+        message,
+        line: location.as_ref().map(|l| l.line),
+        column: location.map(|l| l.column),
+        span: None,
+    })
+}
+
+fn toml_file_issues(file: &str, content: &str) -> Vec<Issue> {
+    collect_parse_errors(content, parse_toml, recover_toml)
+        .into_iter()
+        .map(|error| {
+            error.into_issue(
+                content,
+                "SYN001",
+                format!("Invalid TOML syntax: {}", file),
+                file,
+            )
+        })
+        .collect()
+}
+
+pub fn toml_files_valid(config: &Config) -> Result<ValidationResult> {
+    let candidates = get_content_files(config)?;
+    let toml_files: Vec<_> = candidates.iter().filter(|f| f.ends_with(".toml")).collect();
+    let mut issues = Vec::new();
+
+    let progress = progress_bar(config, "toml syntax", toml_files.len());
+    for file in toml_files.iter().progress_with(progress) {
+        let _span = trace_span!("file", path = %file).entered();
+        let Some(content) = read_file_content(config, file) else {
+            continue;
+        };
+        issues.extend(file_issues(config, "toml-syntax", file, &content, || {
+            Ok(toml_file_issues(file, &content))
+        })?);
+    }
+
+    Ok(ValidationResult::new(
+        format!("All {} TOML files are valid", toml_files.len()),
+        issues.is_empty(),
+        issues,
+    ))
+}
+
+// Comments and trailing commas are blanked out rather than removed, so parse
+// errors still point at the right line and column of the original file
+pub fn strip_json_comments(content: &str) -> String {
+    static RE_LINE_COMMENT: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"(?m)//[^\n]*$").unwrap());
+    static RE_BLOCK_COMMENT: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"(?s)/\*.*?\*/").unwrap());
+    static RE_TRAILING_COMMA: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r",(\s*[}\]])").unwrap());
+
+    let blank = |caps: &regex::Captures| -> String {
+        caps[0]
+            .chars()
+            .map(|c| if c == '\n' { '\n' } else { ' ' })
+            .collect()
+    };
+    let content = RE_LINE_COMMENT.replace_all(content, blank);
+    let content = RE_BLOCK_COMMENT.replace_all(&content, blank);
+    RE_TRAILING_COMMA.replace_all(&content, " $1").to_string()
+}
+
+fn json_file_issues(file: &str, content: &str) -> Vec<Issue> {
+    // JSON5 allows comments, trailing commas and more, and has its own parser
+    if file.ends_with(".json5") {
+        return collect_parse_errors(content, parse_json5, recover_json)
+            .into_iter()
+            .map(|error| {
+                error.into_issue(
+                    content,
+                    "SYN002",
+                    format!("Invalid JSON5 syntax: {}", file),
+                    file,
+                )
+            })
+            .collect();
+    }
+    // Strip comments from JSON files that have them
+    let has_comments = content.contains("//") || content.contains("/*");
+    let content = if has_comments {
+        strip_json_comments(content)
+    } else {
+        content.to_string()
+    };
+    collect_parse_errors(&content, parse_json, recover_json)
+        .into_iter()
+        .map(|error| {
+            error.into_issue(
+                &content,
+                "SYN002",
+                format!("Invalid JSON syntax: {}", file),
+                file,
+            )
+        })
+        .collect()
+}
+
+pub fn json_files_valid(config: &Config) -> Result<ValidationResult> {
+    let candidates = get_content_files(config)?;
+    let json_files: Vec<_> = candidates
+        .iter()
+        .filter(|f| f.ends_with(".json") || f.ends_with(".jsonc") || f.ends_with(".json5"))
+        .collect();
+    let mut issues = Vec::new();
+
+    let progress = progress_bar(config, "json syntax", json_files.len());
+    for file in json_files.iter().progress_with(progress) {
+        let _span = trace_span!("file", path = %file).entered();
+        // Only report errors for .json files, not .jsonc files
+        if file.ends_with(".jsonc") {
+            continue;
+        }
+        if let Some(content) = read_file_content(config, file) {
+            issues.extend(file_issues(config, "json-syntax", file, &content, || {
+                Ok(json_file_issues(file, &content))
+            })?);
+        }
+    }
+
+    Ok(ValidationResult::new(
+        format!("All {} JSON files are valid", json_files.len()),
+        issues.is_empty(),
+        issues,
+    ))
+}
+
+fn nushell_file_issues(config: &Config, file: &str, content: &str) -> Result<Vec<Issue>> {
+    let mut child = Command::new("nu")
+        .args([
+            "--no-config-file",
+            "--stdin",
+            "--commands",
+            "$in | nu-check --debug",
+        ])
+        .env("NO_COLOR", "1")
+        .current_dir(&config.dotfiles_dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Failed to run nu")?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // nu may exit before reading everything; the exit status still
+        // tells us whether the parse failed
+        let _ = stdin.write_all(content.as_bytes());
+    }
+    let output = child.wait_with_output().context("Failed to run nu")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        // Nushell's diagnostics put the headline after a "×"
+        let message = stderr
+            .lines()
+            .find_map(|l| l.trim().strip_prefix('×'))
+            .or_else(|| stderr.lines().map(str::trim).find(|l| !l.is_empty()))
+            .unwrap_or("parse error")
+            .trim();
+        return Ok(vec![
+            Issue::new(
+                Severity::Error,
+                format!("Nushell parse error in {}: {}", file, message),
+            )
+            .with_code("SYN003")
+            .with_file(file.to_string())
+            // nu-check is a nushell builtin, and it only diagnoses, so this
+            // stays out of the fix script
+            .with_fix(format!(
+                "See the full diagnostic with: nu -c {}",
+                shell_quote(&format!("nu-check --debug '{}'", file))
+            )),
+        ]);
+    }
+    Ok(Vec::new())
+}
+
+// Parses each file with nushell's own nu-check rather than approximating its
+// grammar. Content goes over stdin so --staged checks the index version.
+pub fn nushell_files_valid(config: &Config) -> Result<ValidationResult> {
+    let candidates = get_content_files(config)?;
+    let nu_files: Vec<_> = candidates.iter().filter(|f| f.ends_with(".nu")).collect();
+    let rule_name = format!("All {} nushell files parse", nu_files.len());
+    if find_executable("nu").is_none() {
+        debug!("skipping: nu not on PATH");
+        return Ok(ValidationResult::new(rule_name, true, Vec::new()));
+    }
+
+    let mut issues = Vec::new();
+    let progress = progress_bar(config, "nushell syntax", nu_files.len());
+    for file in nu_files.iter().progress_with(progress) {
+        let _span = trace_span!("file", path = %file).entered();
+        let Some(content) = read_file_content(config, file) else {
+            continue;
+        };
+
+        issues.extend(file_issues(
+            config,
+            "nushell-syntax",
+            file,
+            &content,
+            || nushell_file_issues(config, file, &content),
+        )?);
+    }
+
+    Ok(ValidationResult::new(rule_name, issues.is_empty(), issues))
+}
+
+fn conflict_marker_issues(file: &str, content: &str) -> Vec<Issue> {
+    // A bare `=======` is a common underline in markdown and comment
+    // banners, so it only counts once a `<<<<<<<` has opened a conflict
+    let mut in_conflict = false;
+    let mut marker_lines = Vec::new();
+    for (idx, line) in content.lines().enumerate() {
+        if is_conflict_marker(line, "<<<<<<<") {
+            in_conflict = true;
+            marker_lines.push(idx + 1);
+        } else if is_conflict_marker(line, ">>>>>>>") {
+            in_conflict = false;
+            marker_lines.push(idx + 1);
+        } else if in_conflict
+            && (line.trim_end() == "=======" || is_conflict_marker(line, "|||||||"))
+        {
+            marker_lines.push(idx + 1);
+        }
+    }
+
+    marker_lines
+        .first()
+        .map(|first| {
+            Issue::new(
+                Severity::Error,
+                format!(
+                    "Merge conflict markers in {} (line {}, {} marker(s) total)",
+                    file,
+                    first,
+                    marker_lines.len()
+                ),
+            )
+            .with_code("SYN004")
+            .with_file(file.to_string())
+            .at(*first, 1)
+            .with_fix("Resolve the conflict and remove the markers before deploying")
+        })
+        .into_iter()
+        .collect()
+}
+
+pub fn no_merge_conflict_markers(config: &Config) -> Result<ValidationResult> {
+    let candidates = get_content_files(config)?;
+    let mut issues = Vec::new();
+
+    let progress = progress_bar(config, "conflict markers", candidates.len());
+    for file in candidates.iter().progress_with(progress) {
+        let _span = trace_span!("file", path = %file).entered();
+        // Binary and unreadable files can't hold textual conflict markers
+        let Some(content) = read_file_content(config, file) else {
+            continue;
+        };
+
+        issues.extend(file_issues(
+            config,
+            "conflict-markers",
+            file,
+            &content,
+            || Ok(conflict_marker_issues(file, &content)),
+        )?);
+    }
+
+    Ok(ValidationResult::new(
+        "No merge conflict markers",
+        issues.is_empty(),
+        issues,
+    ))
+}
+
+/// Conflict markers are exactly seven characters, optionally followed by a
+/// space and a label (branch name, commit, etc.).
+pub fn is_conflict_marker(line: &str, marker: &str) -> bool {
+    line.strip_prefix(marker)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with(' '))
+}
+
+// Characters that look like (or like nothing but) ASCII a shell or parser
+// expects, with what to type instead
+const CONFUSABLES: &[(char, &str, &str)] = &[
+    ('\u{00A0}', "non-breaking space", " "),
+    ('\u{00AD}', "soft hyphen", ""),
+    ('\u{2002}', "en space", " "),
+    ('\u{2003}', "em space", " "),
+    ('\u{2007}', "figure space", " "),
+    ('\u{2009}', "thin space", " "),
+    ('\u{200A}', "hair space", " "),
+    ('\u{200B}', "zero-width space", ""),
+    ('\u{200C}', "zero-width non-joiner", ""),
+    ('\u{200D}', "zero-width joiner", ""),
+    ('\u{2013}', "en dash", "-"),
+    ('\u{2014}', "em dash", "--"),
+    ('\u{2018}', "left single quote", "'"),
+    ('\u{2019}', "right single quote", "'"),
+    ('\u{201C}', "left double quote", "\""),
+    ('\u{201D}', "right double quote", "\""),
+    ('\u{202A}', "bidi embedding", ""),
+    ('\u{202B}', "bidi embedding", ""),
+    ('\u{202C}', "bidi pop", ""),
+    ('\u{202D}', "bidi override", ""),
+    ('\u{202E}', "bidi override", ""),
+    ('\u{202F}', "narrow non-breaking space", " "),
+    ('\u{2060}', "word joiner", ""),
+    ('\u{2066}', "bidi isolate", ""),
+    ('\u{2067}', "bidi isolate", ""),
+    ('\u{2068}', "bidi isolate", ""),
+    ('\u{2069}', "bidi isolate", ""),
+    ('\u{2212}', "minus sign", "-"),
+    ('\u{3000}', "ideographic space", " "),
+    ('\u{FEFF}', "byte order mark", ""),
+];
+
+// Prose is where typographic quotes and dashes belong
+fn is_prose(file: &str) -> bool {
+    let extension = Path::new(file)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default();
+    matches!(
+        extension,
+        "md" | "markdown" | "txt" | "rst" | "org" | "adoc" | "html"
+    )
+}
+
+fn confusable_char_issues(file: &str, content: &str) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    for (offset, c) in content.char_indices() {
+        let Some((_, name, replacement)) = CONFUSABLES.iter().find(|(ch, _, _)| *ch == c) else {
+            continue;
+        };
+        let before = content[..offset].chars().next_back();
+        let after = content[offset + c.len_utf8()..].chars().next();
+        let allowed = match c {
+            // Joiners hold emoji sequences (and some scripts) together
+            '\u{200C}' | '\u{200D}' => before.is_some_and(|b| !b.is_ascii()),
+            // A dash only matters where it stands in for a flag's hyphen
+            '\u{2013}' | '\u{2014}' => {
+                !(before.is_none_or(char::is_whitespace)
+                    && after.is_some_and(|a| a.is_ascii_alphanumeric()))
+            }
+            _ => false,
+        };
+        if allowed {
+            continue;
+        }
+        let (line, col) = line_col(content, offset);
+        let fix = if replacement.is_empty() {
+            "Delete it".to_string()
+        } else {
+            format!("Replace it with '{}'", replacement)
+        };
+        issues.push(
+            Issue::new(
+                Severity::Warning,
+                format!("{} (U+{:04X}) in {}", name, c as u32, file),
+            )
+            .with_code("SYN005")
+            .with_file(file.to_string())
+            .with_fix(fix)
+            .at(line, col),
+        );
+        if issues.len() == MAX_PARSE_ERRORS {
+            break;
+        }
+    }
+    issues
+}
+
+pub fn no_confusable_characters(config: &Config) -> Result<ValidationResult> {
+    let (count, issues) = check_files(
+        config,
+        "confusable-chars",
+        |f| !is_prose(f),
+        |file, content| Ok(confusable_char_issues(file, content)),
+    )?;
+    Ok(ValidationResult::new(
+        format!("No invisible or look-alike characters in {} files", count),
+        true,
+        issues,
+    ))
+}
+
+// (line, column, byte offset) of the first byte that isn't valid UTF-8
+fn invalid_utf8_position(bytes: &[u8]) -> Option<(usize, usize, usize)> {
+    let error = std::str::from_utf8(bytes).err()?;
+    let offset = error.valid_up_to();
+    // Everything before the offset is valid, so it can be counted as text
+    let valid = std::str::from_utf8(&bytes[..offset]).unwrap_or_default();
+    let (line, col) = line_col(valid, offset);
+    Some((line, col, offset))
+}
+
+pub fn text_files_are_utf8(config: &Config) -> Result<ValidationResult> {
+    let candidates = get_content_files(config)?;
+    let mut issues = Vec::new();
+
+    let progress = progress_bar(config, "utf-8", candidates.len());
+    for file in candidates.iter().progress_with(progress) {
+        let Some(bytes) = read_file_bytes(config, file) else {
+            continue;
+        };
+        if looks_binary(&bytes) {
+            continue;
+        }
+        let Some((line, col, offset)) = invalid_utf8_position(&bytes) else {
+            continue;
+        };
+        issues.push(
+            Issue::new(
+                Severity::Warning,
+                format!(
+                    "{} isn't valid UTF-8 (byte 0x{:02X} at offset {}); content rules skip it",
+                    file, bytes[offset], offset
+                ),
+            )
+            .with_code("SYN006")
+            .with_file(file.clone())
+            .with_fix(format!(
+                "Convert it, e.g.: iconv -f latin1 -t utf-8 {}",
+                file
+            ))
+            .at(line, col),
+        );
+    }
+
+    Ok(ValidationResult::new(
+        "Text files are valid UTF-8",
+        true,
+        issues,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::FixtureRepo;
+
+    #[test]
+    fn invalid_toml_is_reported() {
+        let repo = FixtureRepo::new();
+        repo.write("good.toml", "a = 1\n")
+            .write("bad.toml", "a = \n")
+            .commit("fixture");
+
+        let result = toml_files_valid(&repo.config()).unwrap();
+        assert!(!result.passed);
+        assert_eq!(result.issues.len(), 1);
+        assert_eq!(result.issues[0].file.as_deref(), Some("bad.toml"));
+        assert_eq!(result.issues[0].line, Some(1));
+    }
+
+    #[test]
+    fn every_independent_error_in_a_file_is_reported() {
+        let content = "a = \nb = 1\nc = = 2\nd = 3\n";
+        let lines: Vec<_> = collect_parse_errors(content, parse_toml, recover_toml)
+            .iter()
+            .map(|e| e.line)
+            .collect();
+        assert_eq!(lines, [Some(1), Some(3)]);
+
+        let content = "{\n  \"a\": 1\n  \"b\": 2,\n  \"c\": 3\n  \"d\": ,\n  \"e\": 4 }\n";
+        let lines: Vec<_> = collect_parse_errors(content, parse_json, recover_json)
+            .iter()
+            .map(|e| e.line)
+            .collect();
+        assert_eq!(lines, [Some(3), Some(5)]);
+    }
+
+    #[test]
+    fn staged_mode_checks_the_index() {
+        let repo = FixtureRepo::new();
+        repo.write("config.toml", "a = \n").add_all();
+        // The working tree is fixed, but the broken version is what's staged
+        repo.write("config.toml", "a = 1\n");
+
+        let mut config = repo.config();
+        assert!(toml_files_valid(&config).unwrap().passed);
+        config.staged = true;
+        assert!(!toml_files_valid(&config).unwrap().passed);
+    }
+
+    #[test]
+    fn json_comments_are_allowed() {
+        let repo = FixtureRepo::new();
+        repo.write("settings.json", "{\n  // comment\n  \"a\": 1,\n}\n")
+            .write("broken.json", "{\n  // comment\n  \"a\": }\n")
+            .commit("fixture");
+
+        let result = json_files_valid(&repo.config()).unwrap();
+        let files: Vec<_> = result
+            .issues
+            .iter()
+            .filter_map(|i| i.file.as_deref())
+            .collect();
+        assert_eq!(files, ["broken.json"]);
+        assert_eq!(result.issues[0].line, Some(3));
+    }
+
+    #[test]
+    fn json5_files_are_parsed_as_json5() {
+        let repo = FixtureRepo::new();
+        repo.write(
+            "ok.json5",
+            "// note\n{\n  unquoted: 'single',\n  hex: 0x1F,\n}\n",
+        )
+        .write("broken.json5", "{\n  a: 1,\n  b: ,\n}\n")
+        .commit("fixture");
+
+        let result = json_files_valid(&repo.config()).unwrap();
+        let files: Vec<_> = result
+            .issues
+            .iter()
+            .filter_map(|i| i.file.as_deref())
+            .collect();
+        assert_eq!(files, ["broken.json5"]);
+        assert_eq!(result.issues[0].line, Some(3));
+        assert!(!result.issues[0].message.contains('\n'));
+    }
+
+    #[test]
+    fn conflict_markers_are_reported() {
+        let repo = FixtureRepo::new();
+        repo.write(
+            "conflicted.conf",
+            "<<<<<<< HEAD\na = 1\n=======\na = 2\n>>>>>>> branch\n",
+        )
+        .write("README.md", "Title\n=======\n")
+        .commit("fixture");
+
+        let result = no_merge_conflict_markers(&repo.config()).unwrap();
+        assert_eq!(result.issues.len(), 1);
+        assert!(result.issues[0].message.contains("3 marker(s)"));
+    }
+
+    #[test]
+    fn confusable_characters_are_reported() {
+        let content = "alias ll=\u{2018}ls -l\u{2019}\n\
+                       export\u{00A0}EDITOR=nvim\n\
+                       rg \u{2013}hidden foo\n\
+                       # 2020\u{2013}2024 \u{2014} fine\n\
+                       PROMPT='\u{1F468}\u{200D}\u{1F4BB} '\n\
+                       x=1\u{200B}\n";
+        let found: Vec<_> = confusable_char_issues(".zshrc", content)
+            .iter()
+            .map(|i| (i.line.unwrap(), i.column.unwrap()))
+            .collect();
+        assert_eq!(found, [(1, 10), (1, 16), (2, 7), (3, 4), (6, 4)]);
+        assert!(is_prose("README.md"));
+    }
+
+    #[test]
+    fn invalid_utf8_is_located() {
+        assert_eq!(invalid_utf8_position("caf\u{e9}\n".as_bytes()), None);
+        assert_eq!(
+            invalid_utf8_position(b"a = 1\nname = \"caf\xe9\"\n"),
+            Some((2, 12, 17))
+        );
+        assert!(looks_binary(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"));
+    }
+
+    #[test]
+    fn binary_files_are_skipped_by_content_rules() {
+        let repo = FixtureRepo::new();
+        repo.write("fonts/icons.ttf", "\0\x01\0\0glyphs\n")
+            .write("notes.txt", "plain\n");
+        // gzip's signature, with no NUL for git's heuristic to find
+        std::fs::write(
+            repo.path().join("wallpaper.gz"),
+            b"\x1F\x8B\x08\xFFcompressed",
+        )
+        .unwrap();
+        repo.commit("fixture");
+        let config = repo.config();
+
+        assert!(read_file_content(&config, "fonts/icons.ttf").is_none());
+        assert!(read_file_content(&config, "wallpaper.gz").is_none());
+        assert!(read_file_content(&config, "notes.txt").is_some());
+        assert!(crate::repo::is_binary_file(&config, "fonts/icons.ttf"));
+        assert!(text_files_are_utf8(&config).unwrap().issues.is_empty());
+    }
+}
@@ -0,0 +1,619 @@
+//! Rule for tmux.conf: command syntax, duplicate key bindings, sourced files,
+//! and TPM plugins.
+
+use anyhow::Result;
+
+use std::{collections::BTreeMap, path::Path};
+
+use crate::{
+    dotter::{mapped_files, source_for_target, tilde_path},
+    gitconfig::{IncludeTarget, resolve_config_path},
+    repo::{get_content_files, read_file_content},
+    types::{Config, Issue, Severity, ValidationResult},
+};
+
+// Every tmux command with its alias, as of tmux 3.4
+const COMMANDS: &[(&str, &str)] = &[
+    ("attach-session", "attach"),
+    ("bind-key", "bind"),
+    ("break-pane", "breakp"),
+    ("capture-pane", "capturep"),
+    ("choose-buffer", ""),
+    ("choose-client", ""),
+    ("choose-tree", ""),
+    ("clear-history", "clearhist"),
+    ("clear-prompt-history", "clearphist"),
+    ("clock-mode", ""),
+    ("command-prompt", ""),
+    ("confirm-before", "confirm"),
+    ("copy-mode", ""),
+    ("customize-mode", ""),
+    ("delete-buffer", "deleteb"),
+    ("detach-client", "detach"),
+    ("display-menu", "menu"),
+    ("display-message", "display"),
+    ("display-panes", "displayp"),
+    ("display-popup", "popup"),
+    ("find-window", "findw"),
+    ("has-session", "has"),
+    ("if-shell", "if"),
+    ("join-pane", "joinp"),
+    ("kill-pane", "killp"),
+    ("kill-server", ""),
+    ("kill-session", ""),
+    ("kill-window", "killw"),
+    ("last-pane", "lastp"),
+    ("last-window", "last"),
+    ("link-window", "linkw"),
+    ("list-buffers", "lsb"),
+    ("list-clients", "lsc"),
+    ("list-commands", "lscm"),
+    ("list-keys", "lsk"),
+    ("list-panes", "lsp"),
+    ("list-sessions", "ls"),
+    ("list-windows", "lsw"),
+    ("load-buffer", "loadb"),
+    ("lock-client", "lockc"),
+    ("lock-server", "lock"),
+    ("lock-session", "locks"),
+    ("move-pane", "movep"),
+    ("move-window", "movew"),
+    ("new-session", "new"),
+    ("new-window", "neww"),
+    ("next-layout", "nextl"),
+    ("next-window", "next"),
+    ("paste-buffer", "pasteb"),
+    ("pipe-pane", "pipep"),
+    ("previous-layout", "prevl"),
+    ("previous-window", "prev"),
+    ("refresh-client", "refresh"),
+    ("rename-session", "rename"),
+    ("rename-window", "renamew"),
+    ("resize-pane", "resizep"),
+    ("resize-window", "resizew"),
+    ("respawn-pane", "respawnp"),
+    ("respawn-window", "respawnw"),
+    ("rotate-window", "rotatew"),
+    ("run-shell", "run"),
+    ("save-buffer", "saveb"),
+    ("select-layout", "selectl"),
+    ("select-pane", "selectp"),
+    ("select-window", "selectw"),
+    ("send-keys", "send"),
+    ("send-prefix", ""),
+    ("server-access", ""),
+    ("set-buffer", "setb"),
+    ("set-environment", "setenv"),
+    ("set-hook", ""),
+    ("set-option", "set"),
+    ("set-window-option", "setw"),
+    ("show-buffer", "showb"),
+    ("show-environment", "showenv"),
+    ("show-hooks", ""),
+    ("show-messages", "showmsgs"),
+    ("show-options", "show"),
+    ("show-prompt-history", "showphist"),
+    ("show-window-options", "showw"),
+    ("source-file", "source"),
+    ("split-window", "splitw"),
+    ("start-server", "start"),
+    ("suspend-client", "suspendc"),
+    ("swap-pane", "swapp"),
+    ("swap-window", "swapw"),
+    ("switch-client", "switchc"),
+    ("unbind-key", "unbind"),
+    ("unlink-window", "unlinkw"),
+    ("wait-for", "wait"),
+];
+
+pub fn is_tmux_config(file: &str) -> bool {
+    let name = Path::new(file)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default();
+    name == "tmux.conf"
+        || name == ".tmux.conf"
+        || (file.contains("tmux/") && (name.ends_with(".conf") || name.ends_with(".tmux")))
+}
+
+// The full name of a command as tmux would look it up: an alias, or a unique
+// prefix of a name
+fn command_name(word: &str) -> Option<&'static str> {
+    if let Some((name, _)) = COMMANDS.iter().find(|(n, a)| *n == word || *a == word) {
+        return Some(name);
+    }
+    let mut candidates = COMMANDS.iter().filter(|(n, _)| n.starts_with(word));
+    match (candidates.next(), candidates.next()) {
+        (Some((name, _)), None) => Some(name),
+        _ => None,
+    }
+}
+
+// Splits a logical line into commands separated by bare `;`. A `{ ... }`
+// block becomes a single word, and an escaped `\;` stays part of the
+// command (it's how bind-key takes several commands)
+fn tmux_commands(text: &str) -> Result<Vec<Vec<String>>, String> {
+    let mut commands = Vec::new();
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' | '"' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some(q) if q == c => break,
+                        Some('\\') if c == '"' => current.extend(chars.next()),
+                        Some(other) => current.push(other),
+                        None => return Err(format!("Unclosed {} quote", c)),
+                    }
+                }
+            }
+            '\\' => {
+                in_word = true;
+                current.extend(chars.next());
+            }
+            '#' if !in_word && chars.peek() != Some(&'{') => break,
+            '{' if !in_word => {
+                let mut depth = 1;
+                current.push('{');
+                while depth > 0 {
+                    let Some(next) = chars.next() else {
+                        return Err("Unclosed '{' block".to_string());
+                    };
+                    depth += match next {
+                        '{' => 1,
+                        '}' => -1,
+                        _ => 0,
+                    };
+                    current.push(next);
+                }
+                in_word = true;
+            }
+            ';' => {
+                if in_word {
+                    words.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+                if !words.is_empty() {
+                    commands.push(std::mem::take(&mut words));
+                }
+            }
+            c if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            c => {
+                current.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if in_word {
+        words.push(current);
+    }
+    if !words.is_empty() {
+        commands.push(words);
+    }
+    Ok(commands)
+}
+
+// Joins `\`-continued lines and multi-line `{ }` blocks, keeping the number
+// of the line each started on
+fn logical_lines(content: &str) -> Vec<(usize, String)> {
+    let mut lines = Vec::new();
+    let mut pending: Option<(usize, String)> = None;
+    for (idx, line) in content.lines().enumerate() {
+        let (start, mut text) = pending.take().unwrap_or((idx + 1, String::new()));
+        if let Some(continued) = line.strip_suffix('\\') {
+            text.push_str(continued);
+            text.push(' ');
+            pending = Some((start, text));
+            continue;
+        }
+        text.push_str(line);
+        if tmux_commands(&text).is_err_and(|e| e.starts_with("Unclosed '{'")) {
+            text.push('\n');
+            pending = Some((start, text));
+        } else {
+            lines.push((start, text));
+        }
+    }
+    lines.extend(pending);
+    lines
+}
+
+// Splits leading flags from the rest of a command's arguments; flags in
+// `with_value` take the following word (or the rest of their own)
+fn split_flags<'a>(args: &'a [String], with_value: &str) -> (Vec<(char, &'a str)>, &'a [String]) {
+    let mut flags = Vec::new();
+    let mut idx = 0;
+    while let Some(arg) = args.get(idx) {
+        let Some(letters) = arg.strip_prefix('-').filter(|l| !l.is_empty()) else {
+            break;
+        };
+        idx += 1;
+        if letters == "-" {
+            break;
+        }
+        for (pos, flag) in letters.char_indices() {
+            if with_value.contains(flag) {
+                let rest = &letters[pos + flag.len_utf8()..];
+                let value = if rest.is_empty() {
+                    idx += 1;
+                    args.get(idx - 1).map_or("", |v| v.as_str())
+                } else {
+                    rest
+                };
+                flags.push((flag, value));
+                break;
+            }
+            flags.push((flag, ""));
+        }
+    }
+    (flags, &args[idx.min(args.len())..])
+}
+
+// tmux accepts ^x for C-x and either case for modifiers
+fn normalize_key(key: &str) -> String {
+    if let Some(rest) = key.strip_prefix('^').filter(|r| !r.is_empty()) {
+        return format!("C-{}", rest);
+    }
+    let mut modifiers = String::new();
+    let mut rest = key;
+    while let Some((modifier, after)) = rest.split_once('-')
+        && modifier.len() == 1
+        && modifier.chars().all(|c| c.is_ascii_alphabetic())
+        && !after.is_empty()
+    {
+        modifiers.push_str(&modifier.to_uppercase());
+        modifiers.push('-');
+        rest = after;
+    }
+    modifiers + rest
+}
+
+#[derive(Debug, Default)]
+pub struct TmuxConf {
+    // (line, problem) for anything tmux would refuse to load
+    pub problems: Vec<(usize, String)>,
+    // (line, "key in table", line of the first binding)
+    pub duplicates: Vec<(usize, String, usize)>,
+    // (line, path) for each file source-file must find
+    pub sources: Vec<(usize, String)>,
+    // (line, plugin) for each TPM @plugin declaration
+    pub plugins: Vec<(usize, String)>,
+    // (line, path) for each run-shell of TPM itself
+    pub tpm_runs: Vec<(usize, String)>,
+}
+
+pub fn parse_tmux_conf(content: &str) -> TmuxConf {
+    let mut parsed = TmuxConf::default();
+    let mut bound: BTreeMap<(String, String), usize> = BTreeMap::new();
+    // Bindings inside %if blocks are alternatives, not duplicates
+    let mut conditional_depth = 0usize;
+
+    for (line, text) in logical_lines(content) {
+        let trimmed = text.trim();
+        if let Some(directive) = trimmed.strip_prefix('%') {
+            match directive.split_whitespace().next().unwrap_or_default() {
+                "if" => conditional_depth += 1,
+                "endif" => conditional_depth = conditional_depth.saturating_sub(1),
+                "elif" | "else" | "hidden" => {}
+                other => parsed
+                    .problems
+                    .push((line, format!("Unknown directive '%{}'", other))),
+            }
+            continue;
+        }
+
+        let commands = match tmux_commands(&text) {
+            Ok(commands) => commands,
+            Err(problem) => {
+                parsed.problems.push((line, problem));
+                continue;
+            }
+        };
+        for words in commands {
+            // NAME=value sets a variable for the rest of the config
+            let is_assignment = words[0].split_once('=').is_some_and(|(name, _)| {
+                !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+            });
+            if is_assignment {
+                continue;
+            }
+            let Some(name) = command_name(&words[0]) else {
+                parsed
+                    .problems
+                    .push((line, format!("Unknown command '{}'", words[0])));
+                continue;
+            };
+            let args = &words[1..];
+            match name {
+                "set-option" | "set-window-option" => {
+                    let (_, rest) = split_flags(args, "t");
+                    match rest {
+                        [] => parsed
+                            .problems
+                            .push((line, format!("{} without an option name", words[0]))),
+                        [option, plugin, ..] if option == "@plugin" => {
+                            parsed.plugins.push((line, plugin.clone()));
+                        }
+                        _ => {}
+                    }
+                }
+                "bind-key" => {
+                    let (flags, rest) = split_flags(args, "TN");
+                    let [key, _, ..] = rest else {
+                        parsed
+                            .problems
+                            .push((line, format!("{} needs a key and a command", words[0])));
+                        continue;
+                    };
+                    let table = flags
+                        .iter()
+                        .find_map(|(f, v)| match f {
+                            'T' => Some(v.to_string()),
+                            'n' => Some("root".to_string()),
+                            _ => None,
+                        })
+                        .unwrap_or_else(|| "prefix".to_string());
+                    let key = normalize_key(key);
+                    if conditional_depth > 0 {
+                        continue;
+                    }
+                    if let Some(first) = bound.insert((table.clone(), key.clone()), line) {
+                        parsed.duplicates.push((
+                            line,
+                            format!("{} in table {}", key, table),
+                            first,
+                        ));
+                    }
+                }
+                "unbind-key" => {
+                    let (flags, rest) = split_flags(args, "T");
+                    let table = flags
+                        .iter()
+                        .find_map(|(f, v)| match f {
+                            'T' => Some(v.to_string()),
+                            'n' => Some("root".to_string()),
+                            _ => None,
+                        })
+                        .unwrap_or_else(|| "prefix".to_string());
+                    match rest.first() {
+                        _ if flags.iter().any(|(f, _)| *f == 'a') => {
+                            bound.retain(|(t, _), _| *t != table);
+                        }
+                        Some(key) => {
+                            bound.remove(&(table, normalize_key(key)));
+                        }
+                        None => parsed
+                            .problems
+                            .push((line, format!("{} needs a key or -a", words[0]))),
+                    }
+                }
+                "source-file" => {
+                    let (flags, rest) = split_flags(args, "t");
+                    // -q is for files that are allowed to be missing
+                    if flags.iter().any(|(f, _)| *f == 'q') {
+                        continue;
+                    }
+                    if rest.is_empty() {
+                        parsed
+                            .problems
+                            .push((line, format!("{} without a path", words[0])));
+                    }
+                    parsed.sources.extend(
+                        rest.iter()
+                            .filter(|p| !p.contains(['$', '*', '?', '[']) && !p.contains("#{"))
+                            .map(|p| (line, p.clone())),
+                    );
+                }
+                "run-shell" => {
+                    let (_, rest) = split_flags(args, "dt");
+                    if let Some(script) = rest.first()
+                        && script.trim_end_matches('/').ends_with("tpm/tpm")
+                    {
+                        parsed.tpm_runs.push((line, script.clone()));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    parsed
+}
+
+pub fn tmux_configs_valid(config: &Config) -> Result<ValidationResult> {
+    let candidates = get_content_files(config)?;
+    let files: Vec<_> = candidates.iter().filter(|f| is_tmux_config(f)).collect();
+    let dotter_files = if files.is_empty() {
+        Vec::new()
+    } else {
+        mapped_files(config)?
+    };
+
+    let mut issues = Vec::new();
+    for file in &files {
+        let Some(content) = read_file_content(config, file) else {
+            continue;
+        };
+        let parsed = parse_tmux_conf(&content);
+        for (line, problem) in parsed.problems {
+            issues.push(
+                Issue::new(Severity::Error, format!("{}: {}", file, problem))
+                    .with_code("TMUX001")
+                    .with_file(file.to_string())
+                    .at(line, 1),
+            );
+        }
+        for (line, binding, first) in parsed.duplicates {
+            issues.push(
+                Issue::new(
+                    Severity::Warning,
+                    format!("{} is already bound on line {}", binding, first),
+                )
+                .with_code("TMUX002")
+                .with_file(file.to_string())
+                .at(line, 1),
+            );
+        }
+        for (line, path) in parsed.sources {
+            let target = resolve_config_path(config, &dotter_files, file, &path);
+            if target.exists(config) {
+                continue;
+            }
+            let fix = match &target {
+                IncludeTarget::Repo(source) => format!("Create {} or fix the path", source),
+                IncludeTarget::Machine(_) => {
+                    "Track the file and deploy it there, or use source-file -q if it's optional"
+                        .to_string()
+                }
+            };
+            issues.push(
+                Issue::new(
+                    Severity::Error,
+                    format!("source-file {} doesn't exist", target.display()),
+                )
+                .with_code("TMUX003")
+                .with_file(file.to_string())
+                .with_fix(fix)
+                .at(line, 1),
+            );
+        }
+
+        // TPM has to be deployed before it can install anything else
+        let Some((first_plugin, _)) = parsed.plugins.first() else {
+            continue;
+        };
+        let problem = match parsed.tpm_runs.first() {
+            None => Some("Plugins are declared but TPM is never run".to_string()),
+            Some((_, script)) => {
+                let tpm_dir = tilde_path(script);
+                let tpm_dir = Path::new(&tpm_dir)
+                    .parent()
+                    .map(|d| d.display().to_string())
+                    .unwrap_or_default();
+                let deployed = source_for_target(&dotter_files, &tpm_dir)
+                    .is_some_and(|source| config.dotfiles_dir.join(source).exists());
+                (!deployed).then(|| {
+                    format!(
+                        "Plugins are declared but nothing in the dotter config deploys {}",
+                        tpm_dir
+                    )
+                })
+            }
+        };
+        if let Some(problem) = problem {
+            issues.push(
+                Issue::new(Severity::Warning, problem)
+                    .with_code("TMUX004")
+                    .with_file(file.to_string())
+                    .with_fix(
+                        "Add TPM as a submodule and map it in dotter, with \
+                         run '~/.tmux/plugins/tpm/tpm' at the end of tmux.conf",
+                    )
+                    .at(*first_plugin, 1),
+            );
+        }
+    }
+
+    let passed = issues.iter().all(|i| i.severity != Severity::Error);
+    Ok(ValidationResult::new(
+        format!("{} tmux configs are valid", files.len()),
+        passed,
+        issues,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::FixtureRepo;
+
+    #[test]
+    fn tmux_conf_syntax_and_bindings() {
+        let parsed = parse_tmux_conf(
+            "set -g prefix C-a\n\
+             setw -g mode-keys vi\n\
+             bind c new-window -c \"#{pane_current_path}\"\n\
+             bind -T copy-mode-vi v send -X begin-selection \\; display 'on'\n\
+             bind C new-window\n\
+             bind ^a last-window\n\
+             bind c-a send-prefix\n\
+             unbind c\n\
+             bind c kill-pane\n\
+             %if #{==:#{host},work}\n\
+             bind C split-window\n\
+             %endif\n\
+             bind -n M-h { select-pane -L\n  display 'left' }\n\
+             bind-kye x kill-pane\n\
+             set -g status-left \"unclosed\n\
+             bind\n\
+             set -g @plugin 'tmux-plugins/tpm'\n\
+             run -b '~/.tmux/plugins/tpm/tpm'\n",
+        );
+        assert_eq!(
+            parsed.problems,
+            [
+                (15, "Unknown command 'bind-kye'".to_string()),
+                (16, "Unclosed \" quote".to_string()),
+                (17, "bind needs a key and a command".to_string()),
+            ]
+        );
+        assert_eq!(
+            parsed.duplicates,
+            [(7, "C-a in table prefix".to_string(), 6)]
+        );
+        assert_eq!(parsed.plugins, [(18, "tmux-plugins/tpm".to_string())]);
+        assert_eq!(
+            parsed.tpm_runs,
+            [(19, "~/.tmux/plugins/tpm/tpm".to_string())]
+        );
+    }
+
+    #[test]
+    fn sourced_files_and_tpm_must_be_deployed() {
+        let repo = FixtureRepo::new();
+        repo.write(
+            ".dotter/global.toml",
+            "[tmux.files]\n\"tmux\" = \"~/.config/tmux\"\n",
+        )
+        .write(
+            "tmux/tmux.conf",
+            "source-file ~/.config/tmux/theme.conf\n\
+             source-file ~/.config/tmux/keys.conf\n\
+             source-file -q ~/.config/tmux/local.conf\n\
+             set -g @plugin 'tmux-plugins/tmux-sensible'\n\
+             run '~/.config/tmux/plugins/tpm/tpm'\n",
+        )
+        .write("tmux/theme.conf", "set -g status-style bg=black\n")
+        .commit("fixture");
+
+        let result = tmux_configs_valid(&repo.config()).unwrap();
+        let found: Vec<_> = result
+            .issues
+            .iter()
+            .map(|i| (i.code.as_deref().unwrap(), i.line.unwrap()))
+            .collect();
+        assert_eq!(
+            found,
+            [("TMUX003", 2), ("TMUX004", 4)],
+            "{:?}",
+            result.issues
+        );
+
+        repo.write("tmux/plugins/tpm/tpm", "#!/usr/bin/env bash\n")
+            .write(
+                "tmux/keys.conf",
+                "bind r source-file ~/.config/tmux/tmux.conf\n",
+            );
+        let result = tmux_configs_valid(&repo.config()).unwrap();
+        assert!(result.issues.is_empty(), "{:?}", result.issues);
+    }
+}
@@ -0,0 +1,465 @@
+//! Rules for tool version pins and package manifests.
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use tracing::debug;
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+    process::Command,
+};
+
+use crate::{
+    fixes::shell_quote,
+    repo::{find_executable, get_tracked_files, read_file_content},
+    rules::syntax::strip_json_comments,
+    types::{Config, Issue, Severity, ValidationResult},
+};
+
+pub fn brew_bundle_in_sync(config: &Config) -> Result<ValidationResult> {
+    let rule_name = "Brewfile matches installed Homebrew packages";
+    let tracked = get_tracked_files(config)?;
+    let brewfiles: Vec<_> = tracked
+        .iter()
+        .filter(|f| Path::new(f).file_name().is_some_and(|n| n == "Brewfile"))
+        .collect();
+
+    if brewfiles.is_empty() || find_executable("brew").is_none() {
+        debug!("skipping: no Brewfile or brew not on PATH");
+        return Ok(ValidationResult::new(rule_name, true, Vec::new()));
+    }
+
+    let re_missing = Regex::new(r"^→ (.+?) needs to be installed or updated").unwrap();
+    let mut issues = Vec::new();
+
+    for brewfile in brewfiles {
+        let check = Command::new("brew")
+            .args(["bundle", "check", "--verbose", "--no-upgrade", "--file"])
+            .arg(brewfile)
+            .current_dir(&config.dotfiles_dir)
+            .output()
+            .context("Failed to run brew bundle check")?;
+
+        for line in String::from_utf8_lossy(&check.stdout).lines() {
+            if let Some(caps) = re_missing.captures(line) {
+                issues.push(
+                    Issue::new(
+                        Severity::Warning,
+                        format!("{} is in {} but not installed", &caps[1], brewfile),
+                    )
+                    .with_code("TOOL006")
+                    .with_file(brewfile.clone())
+                    .with_fix(format!(
+                        "Run: brew bundle install --file {}",
+                        shell_quote(brewfile)
+                    )),
+                );
+            }
+        }
+
+        // Without --force, cleanup only reports what it would uninstall
+        let cleanup = Command::new("brew")
+            .args(["bundle", "cleanup", "--file"])
+            .arg(brewfile)
+            .current_dir(&config.dotfiles_dir)
+            .output()
+            .context("Failed to run brew bundle cleanup")?;
+
+        let mut in_uninstall_list = false;
+        for line in String::from_utf8_lossy(&cleanup.stdout).lines() {
+            if line.starts_with("Would uninstall") {
+                in_uninstall_list = true;
+            } else if line.starts_with("Would") || line.trim().is_empty() {
+                in_uninstall_list = false;
+            } else if in_uninstall_list {
+                issues.push(
+                    Issue::new(
+                        Severity::Warning,
+                        format!("{} is installed but not in {}", line.trim(), brewfile),
+                    )
+                    .with_code("TOOL007")
+                    .with_file(brewfile.clone())
+                    .with_fix(format!("Add it to {} or uninstall it", brewfile)),
+                );
+            }
+        }
+    }
+
+    let passed = issues.iter().all(|i| i.severity != Severity::Error);
+    Ok(ValidationResult::new(rule_name, passed, issues))
+}
+
+#[derive(Debug, Clone)]
+pub struct ToolPin {
+    pub tool: String,
+    pub version: String,
+    pub file: String,
+}
+
+pub fn is_tool_versions_file(file: &str) -> bool {
+    let name = Path::new(file)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default();
+    matches!(
+        name,
+        ".tool-versions" | ".mise.toml" | "mise.toml" | ".rtx.toml"
+    ) || file.ends_with("mise/config.toml")
+}
+
+// Collects every pinned tool version across mise and asdf files, along with
+// issues for entries that are structurally invalid
+pub fn collect_tool_pins(config: &Config) -> Result<(Vec<ToolPin>, Vec<Issue>)> {
+    let tracked = get_tracked_files(config)?;
+    let mut pins = Vec::new();
+    let mut issues = Vec::new();
+
+    for file in tracked.iter().filter(|f| is_tool_versions_file(f)) {
+        let Some(content) = read_file_content(config, file) else {
+            continue;
+        };
+
+        if file.ends_with(".tool-versions") {
+            for (idx, line) in content.lines().enumerate() {
+                let line = line.split('#').next().unwrap_or_default().trim();
+                if line.is_empty() {
+                    continue;
+                }
+                let mut fields = line.split_whitespace();
+                let tool = fields.next().unwrap_or_default();
+                let versions: Vec<_> = fields.collect();
+                if versions.is_empty() {
+                    issues.push(
+                        Issue::new(
+                            Severity::Error,
+                            format!("{}:{}: '{}' has no version", file, idx + 1, tool),
+                        )
+                        .with_code("TOOL001")
+                        .with_file(file.clone()),
+                    );
+                    continue;
+                }
+                // asdf treats extra versions as fallbacks; the first one wins
+                pins.push(ToolPin {
+                    tool: tool.to_string(),
+                    version: versions[0].to_string(),
+                    file: file.clone(),
+                });
+            }
+            continue;
+        }
+
+        // Unparseable TOML is already reported by the TOML syntax rule
+        let Ok(doc) = toml::from_str::<toml::Value>(&content) else {
+            continue;
+        };
+        let Some(tools) = doc.get("tools") else {
+            continue;
+        };
+        let Some(tools) = tools.as_table() else {
+            issues.push(
+                Issue::new(
+                    Severity::Error,
+                    format!("[tools] is not a table in {}", file),
+                )
+                .with_code("TOOL002")
+                .with_file(file.clone()),
+            );
+            continue;
+        };
+
+        for (tool, value) in tools {
+            let version = match value {
+                toml::Value::String(v) => Some(v.clone()),
+                toml::Value::Array(items) => items.first().and_then(|v| match v {
+                    toml::Value::String(v) => Some(v.clone()),
+                    other => other
+                        .get("version")
+                        .and_then(|v| v.as_str())
+                        .map(String::from),
+                }),
+                toml::Value::Table(t) => {
+                    t.get("version").and_then(|v| v.as_str()).map(String::from)
+                }
+                _ => None,
+            };
+
+            match version {
+                Some(version) => pins.push(ToolPin {
+                    tool: tool.clone(),
+                    version,
+                    file: file.clone(),
+                }),
+                None => issues.push(
+                    Issue::new(
+                        Severity::Error,
+                        format!("Tool '{}' in {} has no usable version", tool, file),
+                    )
+                    .with_code("TOOL003")
+                    .with_file(file.clone())
+                    .with_fix("Use a version string, a list of versions, or { version = \"...\" }"),
+                ),
+            }
+        }
+    }
+
+    Ok((pins, issues))
+}
+
+pub fn tool_versions_consistent(config: &Config) -> Result<ValidationResult> {
+    let (pins, mut issues) = collect_tool_pins(config)?;
+
+    let mut by_tool: BTreeMap<&str, Vec<&ToolPin>> = BTreeMap::new();
+    for pin in &pins {
+        by_tool.entry(&pin.tool).or_default().push(pin);
+    }
+
+    for (tool, pins) in by_tool {
+        let versions: BTreeSet<_> = pins.iter().map(|p| p.version.as_str()).collect();
+        if versions.len() < 2 {
+            continue;
+        }
+        let locations: Vec<_> = pins
+            .iter()
+            .map(|p| format!("{} in {}", p.version, p.file))
+            .collect();
+        issues.push(
+            Issue::new(
+                Severity::Warning,
+                format!(
+                    "Conflicting versions for {}: {}",
+                    tool,
+                    locations.join(", ")
+                ),
+            )
+            .with_code("TOOL004")
+            .with_file(pins[0].file.clone())
+            .with_fix("Pin the tool in a single file"),
+        );
+    }
+
+    let passed = issues.iter().all(|i| i.severity != Severity::Error);
+    Ok(ValidationResult::new(
+        format!("All {} pinned tool versions are consistent", pins.len()),
+        passed,
+        issues,
+    ))
+}
+
+pub fn tool_versions_installed(config: &Config) -> Result<ValidationResult> {
+    let rule_name = "Pinned tool versions are installed";
+    if find_executable("mise").is_none() {
+        debug!("skipping: mise not on PATH");
+        return Ok(ValidationResult::new(rule_name, true, Vec::new()));
+    }
+
+    let output = Command::new("mise")
+        .args(["ls", "--installed", "--json"])
+        .current_dir(&config.dotfiles_dir)
+        .output()
+        .context("Failed to run mise ls")?;
+    let installed: serde_json::Value =
+        serde_json::from_slice(&output.stdout).context("Failed to parse mise ls output")?;
+
+    let (pins, _) = collect_tool_pins(config)?;
+    let mut issues = Vec::new();
+
+    for pin in &pins {
+        if matches!(pin.version.as_str(), "latest" | "system" | "lts") {
+            continue;
+        }
+
+        // Fuzzy pins like "20" are satisfied by any 20.x.y install
+        let versions = installed
+            .get(&pin.tool)
+            .and_then(|v| v.as_array())
+            .map(|entries| {
+                entries
+                    .iter()
+                    .filter_map(|e| e.get("version").and_then(|v| v.as_str()))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        let satisfied = versions.iter().any(|v| {
+            *v == pin.version
+                || v.strip_prefix(pin.version.as_str())
+                    .is_some_and(|rest| rest.starts_with('.'))
+        });
+
+        if !satisfied {
+            issues.push(
+                Issue::new(
+                    Severity::Warning,
+                    format!(
+                        "{}@{} (pinned in {}) is not installed",
+                        pin.tool, pin.version, pin.file
+                    ),
+                )
+                .with_code("TOOL005")
+                .with_file(pin.file.clone())
+                .with_fix("Run: mise install"),
+            );
+        }
+    }
+
+    let passed = issues.iter().all(|i| i.severity != Severity::Error);
+    Ok(ValidationResult::new(rule_name, passed, issues))
+}
+
+pub fn is_vscode_extensions_file(file: &str) -> bool {
+    let lower = file.to_lowercase();
+    let in_vscode_dir = lower.contains("vscode/") || lower.contains("code/user/");
+    in_vscode_dir
+        && Path::new(&lower).file_name().is_some_and(|n| {
+            n == "extensions.json" || n == "extensions.txt" || n == "extensions.list"
+        })
+}
+
+// (extension id, line) for every extension a tracked list names. Lists are
+// either JSON (a bare array, or an object with "recommendations" like
+// .vscode/extensions.json) or `code --list-extensions` output saved to a file
+pub fn parse_extension_list(file: &str, content: &str) -> Vec<(String, usize)> {
+    let ids: Vec<String> = if file.ends_with(".json") {
+        let Ok(doc) = serde_json::from_str::<serde_json::Value>(&strip_json_comments(content))
+        else {
+            return Vec::new();
+        };
+        let list = doc.get("recommendations").unwrap_or(&doc);
+        list.as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|v| v.as_str().map(String::from))
+            .collect()
+    } else {
+        content
+            .lines()
+            .map(|l| l.split('#').next().unwrap_or_default().trim())
+            .filter(|l| !l.is_empty())
+            // `--show-versions` output appends @version
+            .map(|l| l.split('@').next().unwrap_or_default().to_string())
+            .collect()
+    };
+    // Extension ids are case-insensitive
+    ids.into_iter()
+        .map(|id| {
+            let line = content
+                .lines()
+                .position(|l| l.contains(&id))
+                .map_or(1, |i| i + 1);
+            (id.to_lowercase(), line)
+        })
+        .collect()
+}
+
+pub fn vscode_extensions_in_sync(config: &Config) -> Result<ValidationResult> {
+    let rule_name = "VS Code extension list matches installed extensions";
+    let tracked = get_tracked_files(config)?;
+    let lists: Vec<_> = tracked
+        .iter()
+        .filter(|f| is_vscode_extensions_file(f))
+        .collect();
+
+    if lists.is_empty() || find_executable("code").is_none() {
+        debug!("skipping: no extension list or code not on PATH");
+        return Ok(ValidationResult::new(rule_name, true, Vec::new()));
+    }
+
+    let output = Command::new("code")
+        .arg("--list-extensions")
+        .output()
+        .context("Failed to run code --list-extensions")?;
+    let installed: BTreeSet<String> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|l| l.trim().to_lowercase())
+        .filter(|l| !l.is_empty())
+        .collect();
+
+    let mut listed = BTreeSet::new();
+    let mut issues = Vec::new();
+    for list in &lists {
+        let Some(content) = read_file_content(config, list) else {
+            continue;
+        };
+        for (id, line) in parse_extension_list(list, &content) {
+            if !installed.contains(&id) {
+                issues.push(
+                    Issue::new(
+                        Severity::Warning,
+                        format!("{} is in {} but not installed", id, list),
+                    )
+                    .with_code("TOOL008")
+                    .with_file(list.to_string())
+                    .with_fix(format!(
+                        "Run: code --install-extension {}",
+                        shell_quote(&id)
+                    ))
+                    .at(line, 1),
+                );
+            }
+            listed.insert(id);
+        }
+    }
+
+    for id in installed.difference(&listed) {
+        issues.push(
+            Issue::new(
+                Severity::Warning,
+                format!("{} is installed but not in {}", id, lists[0]),
+            )
+            .with_code("TOOL009")
+            .with_file(lists[0].to_string())
+            .with_fix(format!(
+                "Add it to {} or run: code --uninstall-extension {}",
+                lists[0], id
+            )),
+        );
+    }
+
+    let passed = issues.iter().all(|i| i.severity != Severity::Error);
+    Ok(ValidationResult::new(rule_name, passed, issues))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::FixtureRepo;
+
+    #[test]
+    fn conflicting_pins_across_files_are_reported() {
+        let repo = FixtureRepo::new();
+        repo.write(".tool-versions", "node 20.1.0\npython 3.12\n")
+            .write(".mise.toml", "[tools]\nnode = \"22\"\npython = \"3.12\"\n")
+            .commit("fixture");
+
+        let result = tool_versions_consistent(&repo.config()).unwrap();
+        assert_eq!(result.issues.len(), 1);
+        assert!(
+            result.issues[0]
+                .message
+                .starts_with("Conflicting versions for node")
+        );
+    }
+
+    #[test]
+    fn extension_lists_parse_from_json_and_plain_text() {
+        let json = "{\n  // editor\n  \"recommendations\": [\n    \"rust-lang.rust-analyzer\",\n    \"vscodevim.Vim\",\n  ]\n}\n";
+        assert_eq!(
+            parse_extension_list("vscode/extensions.json", json),
+            [
+                ("rust-lang.rust-analyzer".to_string(), 4),
+                ("vscodevim.vim".to_string(), 5)
+            ]
+        );
+        let text = "# installed\nms-python.python@2024.1.0\n\neamodio.gitlens\n";
+        let ids: Vec<_> = parse_extension_list("vscode/extensions.txt", text)
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect();
+        assert_eq!(ids, ["ms-python.python", "eamodio.gitlens"]);
+        assert!(is_vscode_extensions_file(
+            "Library/Application Support/Code/User/extensions.txt"
+        ));
+        assert!(!is_vscode_extensions_file("zed/extensions.json"));
+    }
+}
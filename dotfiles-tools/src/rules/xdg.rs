@@ -0,0 +1,160 @@
+//! Rule for configs deployed to legacy dotfile locations.
+
+use anyhow::Result;
+
+use crate::{
+    dotter::mapped_files,
+    repo::home_dir,
+    types::{Config, Issue, Severity, ValidationResult},
+};
+
+struct XdgLocation {
+    tool: &'static str,
+    legacy: &'static str,
+    xdg: &'static str,
+}
+
+// Only tools that read the XDG path natively (without an env var or flag),
+// with the release that added support where it's recent
+const XDG_LOCATIONS: &[XdgLocation] = &[
+    XdgLocation {
+        tool: "alacritty",
+        legacy: ".alacritty.toml",
+        xdg: ".config/alacritty/alacritty.toml",
+    },
+    XdgLocation {
+        tool: "conda",
+        legacy: ".condarc",
+        xdg: ".config/conda/.condarc",
+    },
+    XdgLocation {
+        tool: "fd",
+        legacy: ".fdignore",
+        xdg: ".config/fd/ignore",
+    },
+    XdgLocation {
+        tool: "git",
+        legacy: ".gitconfig",
+        xdg: ".config/git/config",
+    },
+    XdgLocation {
+        tool: "htop",
+        legacy: ".htoprc",
+        xdg: ".config/htop/htoprc",
+    },
+    XdgLocation {
+        tool: "isync (1.5+)",
+        legacy: ".mbsyncrc",
+        xdg: ".config/isyncrc",
+    },
+    XdgLocation {
+        tool: "jj",
+        legacy: ".jjconfig.toml",
+        xdg: ".config/jj/config.toml",
+    },
+    XdgLocation {
+        tool: "mercurial",
+        legacy: ".hgrc",
+        xdg: ".config/hg/hgrc",
+    },
+    XdgLocation {
+        tool: "msmtp",
+        legacy: ".msmtprc",
+        xdg: ".config/msmtp/config",
+    },
+    XdgLocation {
+        tool: "nano",
+        legacy: ".nanorc",
+        xdg: ".config/nano/nanorc",
+    },
+    XdgLocation {
+        tool: "tig",
+        legacy: ".tigrc",
+        xdg: ".config/tig/config",
+    },
+    XdgLocation {
+        tool: "tmux (3.1+)",
+        legacy: ".tmux.conf",
+        xdg: ".config/tmux/tmux.conf",
+    },
+    XdgLocation {
+        tool: "vim (9.1+)",
+        legacy: ".vimrc",
+        xdg: ".config/vim/vimrc",
+    },
+    XdgLocation {
+        tool: "wezterm",
+        legacy: ".wezterm.lua",
+        xdg: ".config/wezterm/wezterm.lua",
+    },
+];
+
+// Dotter targets are written as ~/..., $HOME/..., or an absolute home path
+fn home_relative(target: &str) -> Option<String> {
+    if let Some(rest) = target
+        .strip_prefix("~/")
+        .or_else(|| target.strip_prefix("$HOME/"))
+    {
+        return Some(rest.to_string());
+    }
+    let home = home_dir()?;
+    let rest = std::path::Path::new(target).strip_prefix(home).ok()?;
+    Some(rest.to_string_lossy().into_owned())
+}
+
+pub fn configs_use_xdg_paths(config: &Config) -> Result<ValidationResult> {
+    let mut issues = Vec::new();
+
+    for file in mapped_files(config)? {
+        let Some(target) = home_relative(&file.target) else {
+            continue;
+        };
+        let Some(location) = XDG_LOCATIONS.iter().find(|l| l.legacy == target) else {
+            continue;
+        };
+        issues.push(
+            Issue::new(
+                Severity::Info,
+                format!(
+                    "{} is deployed to ~/{}, but {} reads ~/{}",
+                    file.source, location.legacy, location.tool, location.xdg
+                ),
+            )
+            .with_code("XDG001")
+            .with_file(file.source.clone())
+            .with_fix(format!(
+                "Change the dotter target for {} to ~/{}",
+                file.source, location.xdg
+            )),
+        );
+    }
+
+    Ok(ValidationResult::new(
+        "Deployed configs use XDG locations",
+        true,
+        issues,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::FixtureRepo;
+
+    #[test]
+    fn legacy_targets_with_xdg_support_are_reported() {
+        let repo = FixtureRepo::new();
+        repo.write(
+            ".dotter/global.toml",
+            "[tmux.files]\n\"tmux.conf\" = \"~/.tmux.conf\"\n\n[zsh.files]\n\"zshrc\" = \"~/.zshrc\"\n",
+        )
+        .commit("fixture");
+
+        let result = configs_use_xdg_paths(&repo.config()).unwrap();
+        assert_eq!(result.issues.len(), 1);
+        assert_eq!(
+            result.issues[0].fix_suggestion.as_deref(),
+            Some("Change the dotter target for tmux.conf to ~/.config/tmux/tmux.conf")
+        );
+    }
+}
@@ -0,0 +1,354 @@
+//! Rule for yabai's .yabairc: shell syntax, then the `yabai -m` commands in it.
+
+use anyhow::Result;
+use tracing::debug;
+
+use std::path::Path;
+
+use crate::{
+    rules::{check_files, shell::shell_syntax_issues},
+    shell::shell_words,
+    types::{Config, Issue, Severity, ValidationResult},
+};
+
+// Settings that take a value; a bare setting name is a query and always valid
+const GLOBAL_SETTINGS: &[&str] = &[
+    "debug_output",
+    "external_bar",
+    "menubar_opacity",
+    "mouse_follows_focus",
+    "focus_follows_mouse",
+    "display_arrangement_order",
+    "window_origin_display",
+    "window_placement",
+    "window_insertion_point",
+    "window_zoom_persist",
+    "window_shadow",
+    "window_opacity",
+    "window_opacity_duration",
+    "active_window_opacity",
+    "normal_window_opacity",
+    "window_animation_duration",
+    "window_animation_easing",
+    "insert_feedback_color",
+    "split_ratio",
+    "split_type",
+    "auto_balance",
+    "mouse_modifier",
+    "mouse_action1",
+    "mouse_action2",
+    "mouse_drop_action",
+];
+
+const SPACE_SETTINGS: &[&str] = &[
+    "layout",
+    "top_padding",
+    "bottom_padding",
+    "left_padding",
+    "right_padding",
+    "window_gap",
+];
+
+// Still common in configs copied from older setups; yabai 6 dropped borders
+// and yabai 7 dropped window_topmost
+const REMOVED_SETTINGS: &[&str] = &[
+    "window_border",
+    "window_border_width",
+    "window_border_radius",
+    "window_border_blur",
+    "window_border_hidpi",
+    "active_window_border_color",
+    "normal_window_border_color",
+    "window_topmost",
+];
+
+const RULE_KEYS: &[&str] = &[
+    "label",
+    "app",
+    "title",
+    "role",
+    "subrole",
+    "display",
+    "space",
+    "manage",
+    "sticky",
+    "mouse_follows_focus",
+    "layer",
+    "sub-layer",
+    "opacity",
+    "native-fullscreen",
+    "grid",
+    "scratchpad",
+];
+
+const SIGNAL_EVENTS: &[&str] = &[
+    "application_launched",
+    "application_terminated",
+    "application_front_switched",
+    "application_activated",
+    "application_deactivated",
+    "application_visible",
+    "application_hidden",
+    "window_created",
+    "window_destroyed",
+    "window_focused",
+    "window_moved",
+    "window_resized",
+    "window_minimized",
+    "window_deminimized",
+    "window_title_changed",
+    "space_created",
+    "space_destroyed",
+    "space_changed",
+    "display_added",
+    "display_removed",
+    "display_moved",
+    "display_resized",
+    "display_changed",
+    "mission_control_enter",
+    "mission_control_exit",
+    "dock_did_restart",
+    "dock_did_change_pref",
+    "menu_bar_hidden_changed",
+    "system_woke",
+];
+
+const DOMAINS: &[&str] = &[
+    "config", "display", "space", "window", "query", "rule", "signal",
+];
+
+// None when the value is fine, or what it should have been
+fn setting_value_error(setting: &str, value: &str) -> Option<&'static str> {
+    // Shell variables are only known at runtime
+    if value.starts_with('$') {
+        return None;
+    }
+    let allowed: &[&str] = match setting {
+        "debug_output" | "mouse_follows_focus" | "window_zoom_persist" | "window_opacity" => {
+            &["on", "off"]
+        }
+        "window_shadow" => &["on", "off", "float"],
+        "focus_follows_mouse" => &["autofocus", "autoraise", "off"],
+        "window_placement" => &["first_child", "second_child"],
+        "split_type" => &["vertical", "horizontal", "auto"],
+        "auto_balance" => &["on", "off", "x", "y"],
+        "layout" => &["bsp", "stack", "float"],
+        "top_padding" | "bottom_padding" | "left_padding" | "right_padding" | "window_gap" => {
+            return value
+                .parse::<u32>()
+                .is_err()
+                .then_some("a non-negative integer");
+        }
+        "split_ratio" => {
+            return value
+                .parse::<f64>()
+                .map_or(true, |r| r <= 0.0 || r >= 1.0)
+                .then_some("a number between 0 and 1");
+        }
+        _ => return None,
+    };
+    (!allowed.contains(&value)).then_some(match setting {
+        "window_shadow" => "on, off, or float",
+        "focus_follows_mouse" => "autofocus, autoraise, or off",
+        "window_placement" => "first_child or second_child",
+        "split_type" => "vertical, horizontal, or auto",
+        "auto_balance" => "on, off, x, or y",
+        "layout" => "bsp, stack, or float",
+        _ => "on or off",
+    })
+}
+
+// Problems with one `yabai -m` invocation, given the words after `-m`
+fn command_problems(args: &[String]) -> Vec<String> {
+    let Some(domain) = args.first() else {
+        return vec!["`yabai -m` without a domain".to_string()];
+    };
+    if !DOMAINS.contains(&domain.as_str()) {
+        return vec![format!(
+            "Unknown yabai domain '{}' (expected one of {})",
+            domain,
+            DOMAINS.join(", ")
+        )];
+    }
+    let mut rest = &args[1..];
+    match domain.as_str() {
+        "config" => {
+            let space_scoped = rest.first().is_some_and(|a| a == "--space");
+            if space_scoped {
+                rest = rest.get(2..).unwrap_or_default();
+            }
+            let Some(setting) = rest.first() else {
+                return Vec::new();
+            };
+            if REMOVED_SETTINGS.contains(&setting.as_str()) {
+                return vec![format!(
+                    "yabai setting '{}' no longer exists in current yabai releases",
+                    setting
+                )];
+            }
+            let known = SPACE_SETTINGS.contains(&setting.as_str())
+                || (!space_scoped && GLOBAL_SETTINGS.contains(&setting.as_str()));
+            if !known {
+                return vec![format!("Unknown yabai setting '{}'", setting)];
+            }
+            match rest.get(1) {
+                Some(value) => setting_value_error(setting, value)
+                    .map(|expected| {
+                        format!(
+                            "yabai setting '{}' has value '{}'; expected {}",
+                            setting, value, expected
+                        )
+                    })
+                    .into_iter()
+                    .collect(),
+                None => Vec::new(),
+            }
+        }
+        "rule" | "signal" if rest.first().is_some_and(|a| a == "--add") => {
+            let pairs: Vec<_> = rest[1..]
+                .iter()
+                .filter(|a| !a.starts_with("--"))
+                .map(|a| a.split_once('=').unwrap_or((a, "")))
+                .collect();
+            let mut problems = Vec::new();
+            if domain == "rule" {
+                for (key, _) in &pairs {
+                    // `app!=` and friends negate the match
+                    if !RULE_KEYS.contains(&key.trim_end_matches('!')) {
+                        problems.push(format!("Unknown yabai rule property '{}'", key));
+                    }
+                }
+                return problems;
+            }
+            match pairs.iter().find(|(key, _)| *key == "event") {
+                Some((_, event)) if !SIGNAL_EVENTS.contains(event) => {
+                    problems.push(format!("Unknown yabai signal event '{}'", event))
+                }
+                Some(_) => {}
+                None => problems.push("yabai signal without an event=".to_string()),
+            }
+            if !pairs.iter().any(|(key, _)| *key == "action") {
+                problems.push("yabai signal without an action=".to_string());
+            }
+            problems
+        }
+        _ => Vec::new(),
+    }
+}
+
+pub fn yabai_command_issues(file: &str, content: &str) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    for (idx, line) in content.lines().enumerate() {
+        let words = shell_words(line);
+        // A line can chain several commands with && or ;
+        let starts = words
+            .windows(2)
+            .enumerate()
+            .filter(|(_, w)| w[0] == "yabai" && w[1] == "-m")
+            .map(|(i, _)| i + 2);
+        for start in starts {
+            let args: Vec<_> = words[start..]
+                .iter()
+                .take_while(|w| !matches!(w.as_str(), "&&" | "||" | ";" | "|"))
+                .cloned()
+                .collect();
+            for problem in command_problems(&args) {
+                issues.push(
+                    Issue::new(Severity::Warning, problem)
+                        .with_code("YAB002")
+                        .with_file(file.to_string())
+                        .at(idx + 1, 1),
+                );
+            }
+        }
+    }
+    issues
+}
+
+// Shells whose -n flag parses without executing; anything else could run
+// the config instead of checking it
+const CHECKABLE_SHELLS: &[&str] = &["sh", "bash", "zsh", "dash"];
+
+// The shell the shebang names, or `None` for an interpreter that can't be
+// syntax-checked safely
+fn interpreter(content: &str) -> Option<&'static str> {
+    let name = content
+        .lines()
+        .next()
+        .and_then(|l| l.strip_prefix("#!"))
+        .and_then(|shebang| {
+            shebang
+                .split_whitespace()
+                .find(|w| !w.ends_with("/env") && !w.starts_with('-'))
+        })
+        .and_then(|w| w.rsplit('/').next())
+        .unwrap_or("sh");
+    CHECKABLE_SHELLS.iter().find(|s| **s == name).copied()
+}
+
+fn is_yabairc(file: &str) -> bool {
+    matches!(
+        Path::new(file).file_name().and_then(|n| n.to_str()),
+        Some(".yabairc" | "yabairc")
+    )
+}
+
+pub fn yabai_configs_valid(config: &Config) -> Result<ValidationResult> {
+    let (count, issues) = check_files(config, "yabai", is_yabairc, |file, content| {
+        let mut issues = match interpreter(content) {
+            Some(shell) => shell_syntax_issues(file, content, shell, "YAB001")?,
+            None => {
+                debug!(file, "skipping syntax check: shebang isn't a known shell");
+                Vec::new()
+            }
+        };
+        // Commands in a file that doesn't parse would be misread
+        if issues.is_empty() {
+            issues.extend(yabai_command_issues(file, content));
+        }
+        Ok(issues)
+    })?;
+    let passed = issues.iter().all(|i| i.severity != Severity::Error);
+    Ok(ValidationResult::new(
+        format!("All {} yabai configs are valid", count),
+        passed,
+        issues,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repo::find_executable;
+
+    #[test]
+    fn yabai_commands_are_checked_against_known_settings() {
+        let content = "#!/usr/bin/env sh\n\
+                       yabai -m config layout bsp\n\
+                       yabai -m config --space 2 layout tiles\n\
+                       yabai -m config window_border on\n\
+                       yabai -m config top_padding $gap\n\
+                       yabai -m rule --add app='^System Settings$' manage=off floating=on\n\
+                       yabai -m signal --add event=window_focussed action='echo'\n\
+                       yabai -m config mouse_follows_focus\n";
+        let issues = yabai_command_issues(".yabairc", content);
+        let lines: Vec<_> = issues.iter().filter_map(|i| i.line).collect();
+        assert_eq!(lines, [3, 4, 6, 7], "{:?}", issues);
+        assert!(issues[0].message.contains("bsp, stack, or float"));
+        assert!(issues[2].message.contains("'floating'"));
+    }
+
+    #[test]
+    fn shell_syntax_errors_are_positioned() {
+        assert_eq!(interpreter("#!/usr/bin/env bash\n"), Some("bash"));
+        assert_eq!(interpreter("yabai -m config layout bsp\n"), Some("sh"));
+        assert_eq!(interpreter("#!/usr/bin/perl -w\n"), None);
+
+        let content = "if true; then\n  echo hi\nfi fi\n";
+        let issues = shell_syntax_issues(".yabairc", content, "sh", "YAB001").unwrap();
+        if find_executable("sh").is_some() {
+            assert_eq!(issues.len(), 1);
+            assert_eq!(issues[0].code.as_deref(), Some("YAB001"));
+        }
+    }
+}
@@ -0,0 +1,614 @@
+//! Rule for Zed's keymap.json: context selectors, duplicate bindings, and
+//! misspelled action names.
+
+use anyhow::Result;
+
+use std::{collections::BTreeMap, path::Path};
+
+use crate::{
+    rules::{check_files, syntax::strip_json_comments},
+    snippet::line_col,
+    types::{Config, Issue, Severity, ValidationResult},
+};
+
+// Not every action Zed has, only the ones keymaps commonly bind; names outside
+// the list are only reported when they're a near miss of one in it
+const ACTIONS: &[&str] = &[
+    "agent::NewThread",
+    "agent::ToggleFocus",
+    "assistant::InlineAssist",
+    "assistant::ToggleFocus",
+    "buffer_search::Deploy",
+    "buffer_search::Dismiss",
+    "buffer_search::FocusEditor",
+    "command_palette::Toggle",
+    "diagnostics::Deploy",
+    "editor::AcceptEditPrediction",
+    "editor::AddSelectionAbove",
+    "editor::AddSelectionBelow",
+    "editor::Backspace",
+    "editor::Backtab",
+    "editor::Cancel",
+    "editor::ConfirmCompletion",
+    "editor::ContextMenuFirst",
+    "editor::ContextMenuLast",
+    "editor::ContextMenuNext",
+    "editor::ContextMenuPrevious",
+    "editor::ConvertToLowerCase",
+    "editor::ConvertToUpperCase",
+    "editor::Copy",
+    "editor::Cut",
+    "editor::Delete",
+    "editor::DeleteLine",
+    "editor::DuplicateLineDown",
+    "editor::DuplicateLineUp",
+    "editor::ExpandAllDiffHunks",
+    "editor::FindAllReferences",
+    "editor::Fold",
+    "editor::FoldAll",
+    "editor::Format",
+    "editor::GoToDefinition",
+    "editor::GoToDefinitionSplit",
+    "editor::GoToDiagnostic",
+    "editor::GoToHunk",
+    "editor::GoToImplementation",
+    "editor::GoToPreviousDiagnostic",
+    "editor::GoToPreviousHunk",
+    "editor::GoToTypeDefinition",
+    "editor::Hover",
+    "editor::Indent",
+    "editor::JoinLines",
+    "editor::MoveDown",
+    "editor::MoveLeft",
+    "editor::MoveLineDown",
+    "editor::MoveLineUp",
+    "editor::MoveRight",
+    "editor::MoveToBeginning",
+    "editor::MoveToBeginningOfLine",
+    "editor::MoveToEnd",
+    "editor::MoveToEndOfLine",
+    "editor::MoveToNextWordEnd",
+    "editor::MoveToPreviousWordStart",
+    "editor::MoveUp",
+    "editor::Newline",
+    "editor::NewlineAbove",
+    "editor::NewlineBelow",
+    "editor::NextEditPrediction",
+    "editor::OpenExcerpts",
+    "editor::Outdent",
+    "editor::PageDown",
+    "editor::PageUp",
+    "editor::Paste",
+    "editor::Redo",
+    "editor::Rename",
+    "editor::RevertSelectedHunks",
+    "editor::ScrollCursorBottom",
+    "editor::ScrollCursorCenter",
+    "editor::ScrollCursorTop",
+    "editor::SelectAll",
+    "editor::SelectAllMatches",
+    "editor::SelectDown",
+    "editor::SelectLargerSyntaxNode",
+    "editor::SelectLeft",
+    "editor::SelectLine",
+    "editor::SelectNext",
+    "editor::SelectPrevious",
+    "editor::SelectRight",
+    "editor::SelectSmallerSyntaxNode",
+    "editor::SelectToBeginningOfLine",
+    "editor::SelectToEndOfLine",
+    "editor::SelectUp",
+    "editor::ShowCompletions",
+    "editor::ShowEditPrediction",
+    "editor::ShowSignatureHelp",
+    "editor::SortLinesCaseSensitive",
+    "editor::Tab",
+    "editor::ToggleCodeActions",
+    "editor::ToggleComments",
+    "editor::ToggleFold",
+    "editor::ToggleGitBlame",
+    "editor::ToggleInlayHints",
+    "editor::ToggleLineNumbers",
+    "editor::ToggleSelectedDiffHunks",
+    "editor::ToggleSoftWrap",
+    "editor::Transpose",
+    "editor::Undo",
+    "editor::UnfoldAll",
+    "editor::UnfoldLines",
+    "file_finder::Toggle",
+    "git::Blame",
+    "git::Commit",
+    "git::Diff",
+    "git::Fetch",
+    "git::Pull",
+    "git::Push",
+    "git::StageAll",
+    "git::UnstageAll",
+    "git_panel::ToggleFocus",
+    "go_to_line::Toggle",
+    "language_selector::Toggle",
+    "menu::Cancel",
+    "menu::Confirm",
+    "menu::SelectFirst",
+    "menu::SelectLast",
+    "menu::SelectNext",
+    "menu::SelectPrevious",
+    "outline::Toggle",
+    "outline_panel::ToggleFocus",
+    "pane::ActivateItem",
+    "pane::ActivateNextItem",
+    "pane::ActivatePreviousItem",
+    "pane::CloseActiveItem",
+    "pane::CloseAllItems",
+    "pane::CloseInactiveItems",
+    "pane::DeploySearch",
+    "pane::GoBack",
+    "pane::GoForward",
+    "pane::ReopenClosedItem",
+    "pane::SplitDown",
+    "pane::SplitLeft",
+    "pane::SplitRight",
+    "pane::SplitUp",
+    "pane::TogglePinTab",
+    "project_panel::CollapseAllEntries",
+    "project_panel::Delete",
+    "project_panel::NewDirectory",
+    "project_panel::NewFile",
+    "project_panel::Open",
+    "project_panel::Rename",
+    "project_panel::RevealInFileManager",
+    "project_panel::ToggleFocus",
+    "project_panel::Trash",
+    "project_search::ToggleFocus",
+    "project_symbols::Toggle",
+    "search::ReplaceAll",
+    "search::ReplaceNext",
+    "search::SelectNextMatch",
+    "search::SelectPreviousMatch",
+    "search::ToggleCaseSensitive",
+    "search::ToggleRegex",
+    "search::ToggleReplace",
+    "search::ToggleWholeWord",
+    "tab_switcher::CloseSelectedItem",
+    "tab_switcher::Toggle",
+    "task::Rerun",
+    "task::Spawn",
+    "terminal::Clear",
+    "terminal::Copy",
+    "terminal::Paste",
+    "terminal::SendKeystroke",
+    "terminal::SendText",
+    "terminal_panel::ToggleFocus",
+    "theme_selector::Toggle",
+    "vim::Down",
+    "vim::EndOfDocument",
+    "vim::EndOfLine",
+    "vim::FirstNonWhitespace",
+    "vim::Indent",
+    "vim::InsertAfter",
+    "vim::InsertBefore",
+    "vim::InsertEndOfLine",
+    "vim::InsertFirstNonWhitespace",
+    "vim::InsertLineAbove",
+    "vim::InsertLineBelow",
+    "vim::Left",
+    "vim::LineDown",
+    "vim::LineUp",
+    "vim::Matching",
+    "vim::MoveToNextMatch",
+    "vim::MoveToPreviousMatch",
+    "vim::NextWordEnd",
+    "vim::NextWordStart",
+    "vim::Outdent",
+    "vim::Paste",
+    "vim::PreviousWordStart",
+    "vim::PushChange",
+    "vim::PushDelete",
+    "vim::PushYank",
+    "vim::Redo",
+    "vim::Right",
+    "vim::ScrollDown",
+    "vim::ScrollUp",
+    "vim::Search",
+    "vim::StartOfDocument",
+    "vim::StartOfLine",
+    "vim::SwitchToInsertMode",
+    "vim::SwitchToNormalMode",
+    "vim::SwitchToVisualBlockMode",
+    "vim::SwitchToVisualLineMode",
+    "vim::SwitchToVisualMode",
+    "vim::ToggleComments",
+    "vim::Undo",
+    "vim::Up",
+    "vim::VisualDelete",
+    "vim::VisualYank",
+    "workspace::ActivateNextPane",
+    "workspace::ActivatePaneDown",
+    "workspace::ActivatePaneLeft",
+    "workspace::ActivatePaneRight",
+    "workspace::ActivatePaneUp",
+    "workspace::ActivatePreviousPane",
+    "workspace::CloseAllDocks",
+    "workspace::CloseWindow",
+    "workspace::CopyPath",
+    "workspace::CopyRelativePath",
+    "workspace::NewFile",
+    "workspace::NewSearch",
+    "workspace::NewTerminal",
+    "workspace::NewWindow",
+    "workspace::Open",
+    "workspace::Save",
+    "workspace::SaveAll",
+    "workspace::SaveAs",
+    "workspace::SendKeystrokes",
+    "workspace::ToggleBottomDock",
+    "workspace::ToggleCenteredLayout",
+    "workspace::ToggleLeftDock",
+    "workspace::ToggleRightDock",
+    "workspace::ToggleZoom",
+    "zed::DecreaseBufferFontSize",
+    "zed::Extensions",
+    "zed::Hide",
+    "zed::IncreaseBufferFontSize",
+    "zed::Minimize",
+    "zed::NoAction",
+    "zed::OpenKeymap",
+    "zed::OpenSettings",
+    "zed::Quit",
+    "zed::ResetBufferFontSize",
+    "zed::ToggleFullScreen",
+];
+
+// Typos further than this from every known name are more likely actions the
+// list doesn't have
+const MAX_TYPO_DISTANCE: usize = 2;
+
+#[derive(Debug, PartialEq)]
+enum Token<'a> {
+    Ident(&'a str),
+    Op(&'a str),
+}
+
+fn tokenize(context: &str) -> Result<Vec<Token<'_>>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = context;
+    while let Some(c) = rest.chars().next() {
+        if c.is_whitespace() {
+            rest = &rest[c.len_utf8()..];
+            continue;
+        }
+        if let Some(op) = ["&&", "||", "==", "!="]
+            .iter()
+            .find(|op| rest.starts_with(**op))
+        {
+            tokens.push(Token::Op(op));
+            rest = &rest[2..];
+        } else if "!()>".contains(c) {
+            tokens.push(Token::Op(&rest[..1]));
+            rest = &rest[1..];
+        } else if c.is_alphanumeric() || c == '_' || c == '-' {
+            let end = rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '-' || c == '.'))
+                .unwrap_or(rest.len());
+            tokens.push(Token::Ident(&rest[..end]));
+            rest = &rest[end..];
+        } else {
+            return Err(format!("unexpected '{}'", c));
+        }
+    }
+    Ok(tokens)
+}
+
+// Recursive descent over Zed's predicate grammar, loosest binding first:
+// `||`, `&&`, `>` (descendant), `!`, then identifiers, comparisons and parens
+struct ContextParser<'a> {
+    tokens: Vec<Token<'a>>,
+    pos: usize,
+}
+
+impl ContextParser<'_> {
+    fn eat(&mut self, op: &str) -> bool {
+        let matched = self.tokens.get(self.pos) == Some(&Token::Op(op));
+        if matched {
+            self.pos += 1;
+        }
+        matched
+    }
+
+    fn binary(
+        &mut self,
+        op: &str,
+        next: fn(&mut Self) -> Result<(), String>,
+    ) -> Result<(), String> {
+        next(self)?;
+        while self.eat(op) {
+            next(self)?;
+        }
+        Ok(())
+    }
+
+    fn or(&mut self) -> Result<(), String> {
+        self.binary("||", Self::and)
+    }
+
+    fn and(&mut self) -> Result<(), String> {
+        self.binary("&&", Self::descendant)
+    }
+
+    fn descendant(&mut self) -> Result<(), String> {
+        self.binary(">", Self::unary)
+    }
+
+    fn unary(&mut self) -> Result<(), String> {
+        if self.eat("!") {
+            return self.unary();
+        }
+        if self.eat("(") {
+            self.or()?;
+            return if self.eat(")") {
+                Ok(())
+            } else {
+                Err("unclosed '('".to_string())
+            };
+        }
+        match self.tokens.get(self.pos) {
+            Some(Token::Ident(_)) => self.pos += 1,
+            Some(Token::Op(op)) => return Err(format!("unexpected '{}'", op)),
+            None => return Err("expression ends early".to_string()),
+        }
+        if self.eat("==") || self.eat("!=") {
+            match self.tokens.get(self.pos) {
+                Some(Token::Ident(_)) => self.pos += 1,
+                _ => return Err("comparison without a right-hand side".to_string()),
+            }
+        }
+        Ok(())
+    }
+}
+
+pub fn parse_context(context: &str) -> Result<(), String> {
+    let mut parser = ContextParser {
+        tokens: tokenize(context)?,
+        pos: 0,
+    };
+    // An empty context applies everywhere
+    if parser.tokens.is_empty() {
+        return Ok(());
+    }
+    parser.or()?;
+    match parser.tokens.get(parser.pos) {
+        None => Ok(()),
+        Some(Token::Op(op)) => Err(format!("unexpected '{}'", op)),
+        Some(Token::Ident(ident)) => Err(format!("unexpected '{}'", ident)),
+    }
+}
+
+// `shift-cmd-p` and `cmd-shift-p` are the same keystroke
+fn normalize_keystrokes(keys: &str) -> String {
+    keys.split_whitespace()
+        .map(|keystroke| {
+            let keystroke = keystroke.to_lowercase();
+            let (modifiers, key) = match keystroke.strip_suffix("--") {
+                Some(modifiers) => (modifiers.to_string(), "-".to_string()),
+                None => match keystroke.rsplit_once('-') {
+                    Some((modifiers, key)) if !key.is_empty() => {
+                        (modifiers.to_string(), key.to_string())
+                    }
+                    _ => (String::new(), keystroke.clone()),
+                },
+            };
+            let mut modifiers: Vec<_> = modifiers.split('-').filter(|m| !m.is_empty()).collect();
+            modifiers.sort();
+            modifiers.push(&key);
+            modifiers.join("-")
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous + usize::from(ca != *cb);
+            previous = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(previous + 1);
+        }
+    }
+    row[b.len()]
+}
+
+// The known action an unknown one was probably meant to be
+fn suggest_action(action: &str) -> Option<&'static str> {
+    if ACTIONS.contains(&action) {
+        return None;
+    }
+    let lower = action.to_lowercase();
+    ACTIONS
+        .iter()
+        .map(|known| (edit_distance(&lower, &known.to_lowercase()), *known))
+        .filter(|(distance, _)| *distance <= MAX_TYPO_DISTANCE)
+        .min()
+        .map(|(_, known)| known)
+}
+
+fn action_name(value: &serde_json::Value) -> Option<&str> {
+    match value {
+        serde_json::Value::String(action) => Some(action),
+        // ["action::Name", { arguments }]
+        serde_json::Value::Array(items) => items.first().and_then(|a| a.as_str()),
+        _ => None,
+    }
+}
+
+pub fn zed_keymap_issues(file: &str, content: &str) -> Vec<Issue> {
+    let stripped = strip_json_comments(content);
+    // Malformed JSON is json-syntax's to report
+    let Ok(doc) = serde_json::from_str::<serde_json::Value>(&stripped) else {
+        return Vec::new();
+    };
+    let issue = |severity, code: &str, message: String, offset: Option<usize>| {
+        let issue = Issue::new(severity, message)
+            .with_code(code)
+            .with_file(file.to_string());
+        match offset {
+            Some(offset) => {
+                let (line, column) = line_col(content, offset);
+                issue.at(line, column)
+            }
+            None => issue,
+        }
+    };
+    let Some(blocks) = doc.as_array() else {
+        return vec![issue(
+            Severity::Error,
+            "ZED001",
+            format!("{} should be an array of binding blocks", file),
+            Some(0),
+        )];
+    };
+
+    let mut issues = Vec::new();
+    let mut bound: BTreeMap<(String, String), usize> = BTreeMap::new();
+    // Blocks appear in document order, so each one is searched for after the
+    // end of the one before it
+    let mut cursor = 0;
+    for block in blocks {
+        let find = |needle: &str| stripped[cursor..].find(needle).map(|o| cursor + o);
+        let context = block.get("context").and_then(|c| c.as_str()).unwrap_or("");
+        let context_offset = find(&format!("\"{}\"", context.replace('"', "\\\"")));
+        if let Err(problem) = parse_context(context) {
+            issues.push(issue(
+                Severity::Error,
+                "ZED001",
+                format!("Context \"{}\" doesn't parse: {}", context, problem),
+                context_offset,
+            ));
+        }
+
+        let Some(bindings) = block.get("bindings").and_then(|b| b.as_object()) else {
+            continue;
+        };
+        let mut block_end = cursor;
+        let normalized_context = context.split_whitespace().collect::<Vec<_>>().join(" ");
+        for (keys, action) in bindings {
+            let offset = find(&format!("\"{}\"", keys));
+            block_end = block_end.max(offset.unwrap_or(cursor));
+
+            let combination = normalize_keystrokes(keys);
+            match bound.get(&(normalized_context.clone(), combination.clone())) {
+                Some(first) => issues.push(issue(
+                    Severity::Warning,
+                    "ZED002",
+                    format!(
+                        "{} is bound again in context \"{}\" (first bound on line {})",
+                        keys, context, first
+                    ),
+                    offset,
+                )),
+                None => {
+                    let line = offset.map_or(0, |o| line_col(content, o).0);
+                    bound.insert((normalized_context.clone(), combination), line);
+                }
+            }
+
+            if let Some(name) = action_name(action)
+                && let Some(suggestion) = suggest_action(name)
+            {
+                issues.push(
+                    issue(
+                        Severity::Warning,
+                        "ZED003",
+                        format!(
+                            "Unknown action {} for {}; did you mean {}?",
+                            name, keys, suggestion
+                        ),
+                        offset,
+                    )
+                    .with_fix(format!("Replace {} with {}", name, suggestion)),
+                );
+            }
+        }
+        cursor = block_end;
+    }
+
+    issues.sort_by_key(|i| i.line);
+    issues
+}
+
+fn is_zed_keymap(file: &str) -> bool {
+    (file.starts_with("zed/") || file.contains("/zed/"))
+        && Path::new(file)
+            .file_name()
+            .is_some_and(|n| n == "keymap.json")
+}
+
+pub fn zed_keymaps_valid(config: &Config) -> Result<ValidationResult> {
+    let (count, issues) = check_files(config, "zed-keymap", is_zed_keymap, |file, content| {
+        Ok(zed_keymap_issues(file, content))
+    })?;
+    let passed = issues.iter().all(|i| i.severity != Severity::Error);
+    Ok(ValidationResult::new(
+        format!("All {} Zed keymaps are valid", count),
+        passed,
+        issues,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contexts_parse_with_zed_precedence() {
+        assert!(parse_context("").is_ok());
+        assert!(parse_context("Editor && mode == full").is_ok());
+        assert!(parse_context("Workspace > (Pane || !Terminal) && os != macos").is_ok());
+        assert!(parse_context("Editor &&").is_err());
+        assert!(parse_context("(Editor").is_err());
+        assert!(parse_context("Editor Terminal").is_err());
+        assert!(parse_context("mode = full").is_err());
+    }
+
+    #[test]
+    fn duplicates_and_typos_are_reported() {
+        let content = r#"[
+  // Global
+  {
+    "bindings": {
+      "cmd-shift-p": "command_palette::Toggle",
+      "cmd-s": "workspace::Sav",
+    }
+  },
+  {
+    "context": "Editor && mode == full",
+    "bindings": {
+      "cmd-d": ["editor::SelectNext", { "replace_newest": false }],
+      "shift-cmd-p": "editor::Format",
+      "ctrl-k ctrl-x": "editor::SomethingFromAnExtension"
+    }
+  },
+  {
+    "context": "Editor  &&  mode == full",
+    "bindings": { "cmd-d": null }
+  },
+  { "context": "Editor &&", "bindings": {} }
+]
+"#;
+        let issues = zed_keymap_issues("zed/keymap.json", content);
+        let found: Vec<_> = issues
+            .iter()
+            .map(|i| (i.code.as_deref().unwrap(), i.line.unwrap()))
+            .collect();
+        assert_eq!(
+            found,
+            [("ZED003", 6), ("ZED002", 19), ("ZED001", 21)],
+            "{:?}",
+            issues
+        );
+        assert!(issues[0].message.contains("workspace::Save"));
+    }
+}
@@ -0,0 +1,234 @@
+//! The `schedule` command: a LaunchAgent that validates the repo daily.
+
+use anyhow::{Context, Result};
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use crate::{
+    output::{info, success},
+    repo::home_dir,
+    types::{Config, ExitCode},
+};
+
+pub const AGENT_LABEL: &str = "local.dotfiles-tools.validate";
+
+pub struct ScheduleOptions {
+    pub hour: u8,
+    pub minute: u8,
+    pub print: bool,
+    pub remove: bool,
+}
+
+/// Parses the HH:MM given to `--at`.
+pub fn parse_time(value: &str) -> Result<(u8, u8), String> {
+    let (hour, minute) = value
+        .split_once(':')
+        .ok_or_else(|| format!("expected HH:MM, got '{}'", value))?;
+    let hour: u8 = hour
+        .parse()
+        .map_err(|_| format!("invalid hour '{}'", hour))?;
+    let minute: u8 = minute
+        .parse()
+        .map_err(|_| format!("invalid minute '{}'", minute))?;
+    if hour > 23 || minute > 59 {
+        return Err(format!("{} is not a time of day", value));
+    }
+    Ok((hour, minute))
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+pub fn agent_path() -> Result<PathBuf> {
+    let home = home_dir().context("Couldn't determine the home directory")?;
+    Ok(home
+        .join("Library/LaunchAgents")
+        .join(format!("{}.plist", AGENT_LABEL)))
+}
+
+pub fn log_path() -> Result<PathBuf> {
+    let home = home_dir().context("Couldn't determine the home directory")?;
+    Ok(home.join("Library/Logs/dotfiles-tools/validate.log"))
+}
+
+pub fn agent_plist(
+    executable: &Path,
+    config: &Config,
+    options: &ScheduleOptions,
+    log: &Path,
+    path_var: &str,
+) -> String {
+    let arguments = [
+        executable.display().to_string(),
+        "--dir".to_string(),
+        config.dotfiles_dir.display().to_string(),
+        "--color".to_string(),
+        "never".to_string(),
+        "validate".to_string(),
+        "--summary-only".to_string(),
+        "--notify".to_string(),
+        // Beside the log, for archiving each run's counts
+        "--summary-out".to_string(),
+        log.with_file_name("summary.json").display().to_string(),
+    ];
+    let arguments: String = arguments
+        .iter()
+        .map(|a| format!("        <string>{}</string>\n", xml_escape(a)))
+        .collect();
+    let log = xml_escape(&log.display().to_string());
+
+    // launchd starts agents with a bare PATH, which would hide Homebrew and
+    // cargo-installed tools from the rules that shell out to them
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{label}</string>
+    <key>ProgramArguments</key>
+    <array>
+{arguments}    </array>
+    <key>EnvironmentVariables</key>
+    <dict>
+        <key>PATH</key>
+        <string>{path}</string>
+    </dict>
+    <key>StartCalendarInterval</key>
+    <dict>
+        <key>Hour</key>
+        <integer>{hour}</integer>
+        <key>Minute</key>
+        <integer>{minute}</integer>
+    </dict>
+    <key>StandardOutPath</key>
+    <string>{log}</string>
+    <key>StandardErrorPath</key>
+    <string>{log}</string>
+</dict>
+</plist>
+"#,
+        label = AGENT_LABEL,
+        arguments = arguments,
+        path = xml_escape(path_var),
+        hour = options.hour,
+        minute = options.minute,
+        log = log,
+    )
+}
+
+fn gui_domain() -> Result<String> {
+    let output = Command::new("id")
+        .arg("-u")
+        .output()
+        .context("Failed to run id -u")?;
+    Ok(format!(
+        "gui/{}",
+        String::from_utf8_lossy(&output.stdout).trim()
+    ))
+}
+
+// bootout fails when the agent isn't loaded, which is fine
+fn unload_agent(domain: &str) {
+    let _ = Command::new("launchctl")
+        .args(["bootout", &format!("{}/{}", domain, AGENT_LABEL)])
+        .output();
+}
+
+pub fn schedule(config: &Config, options: &ScheduleOptions) -> Result<i32> {
+    let executable = env::current_exe().context("Couldn't locate the dotfiles-tools binary")?;
+    let log = log_path()?;
+    let plist = agent_plist(
+        &executable,
+        config,
+        options,
+        &log,
+        &env::var("PATH").unwrap_or_default(),
+    );
+
+    if options.print {
+        print!("{}", plist);
+        return Ok(ExitCode::CLEAN);
+    }
+    if !cfg!(target_os = "macos") {
+        anyhow::bail!("schedule installs a launchd agent and only works on macOS (try --print)");
+    }
+
+    let agent = agent_path()?;
+    let domain = gui_domain()?;
+    if options.remove {
+        unload_agent(&domain);
+        if agent.exists() {
+            fs::remove_file(&agent)
+                .with_context(|| format!("Failed to remove {}", agent.display()))?;
+        }
+        success("Removed scheduled validation");
+        return Ok(ExitCode::CLEAN);
+    }
+
+    for dir in [agent.parent(), log.parent()].into_iter().flatten() {
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    fs::write(&agent, plist).with_context(|| format!("Failed to write {}", agent.display()))?;
+
+    unload_agent(&domain);
+    let status = Command::new("launchctl")
+        .args(["bootstrap", &domain])
+        .arg(&agent)
+        .status()
+        .context("Failed to run launchctl")?;
+    if !status.success() {
+        anyhow::bail!("launchctl bootstrap failed ({})", status);
+    }
+
+    success(&format!(
+        "Scheduled daily validation at {:02}:{:02}",
+        options.hour, options.minute
+    ));
+    info(&format!("  Agent: {}", agent.display()));
+    info(&format!("  Log: {}", log.display()));
+    Ok(ExitCode::CLEAN)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::Settings;
+
+    #[test]
+    fn times_are_validated() {
+        assert_eq!(parse_time("09:30"), Ok((9, 30)));
+        assert!(parse_time("24:00").is_err());
+        assert!(parse_time("9").is_err());
+    }
+
+    #[test]
+    fn plist_runs_quiet_validation_with_logging() {
+        let config = Config::new(PathBuf::from("/Users/me/.dotfiles"), 0, Settings::default());
+        let options = ScheduleOptions {
+            hour: 7,
+            minute: 5,
+            print: true,
+            remove: false,
+        };
+        let plist = agent_plist(
+            Path::new("/usr/local/bin/dotfiles-tools"),
+            &config,
+            &options,
+            Path::new("/tmp/validate.log"),
+            "/opt/homebrew/bin:/usr/bin",
+        );
+        assert!(plist.contains("<string>/Users/me/.dotfiles</string>"));
+        assert!(plist.contains("<string>--summary-only</string>"));
+        assert!(plist.contains("<string>/tmp/summary.json</string>"));
+        assert!(plist.contains("<integer>7</integer>"));
+        assert!(plist.contains("<string>/opt/homebrew/bin:/usr/bin</string>"));
+    }
+}
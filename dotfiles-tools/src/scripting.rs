@@ -0,0 +1,99 @@
+//! User-defined rules written in Rhai.
+
+use std::{
+    cell::RefCell,
+    fs,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use crate::{
+    repo::read_file_content,
+    types::{Config, Issue, Severity, ValidationResult},
+};
+
+pub const SCRIPT_RULES_DIR: &str = ".validate-rules";
+
+pub fn discover_script_rules(config: &Config) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(config.dotfiles_dir.join(SCRIPT_RULES_DIR)) else {
+        return Vec::new();
+    };
+    let mut scripts: Vec<_> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "rhai"))
+        .collect();
+    scripts.sort();
+    scripts
+}
+
+// Scripts see the same file list and contents as the built-in content rules,
+// and report through error()/warning()/info()/hint() rather than return values so a rule
+// can bail halfway without losing what it already found
+pub fn run_script_rule(config: &Config, files: &[String], script: &Path) -> ValidationResult {
+    let stem = script
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or_default()
+        .to_string();
+    let issues = Rc::new(RefCell::new(Vec::new()));
+    let name = Rc::new(RefCell::new(format!("Script: {}", stem)));
+
+    let mut engine = rhai::Engine::new();
+    let tracked: rhai::Array = files.iter().cloned().map(rhai::Dynamic::from).collect();
+    engine.register_fn("tracked_files", move || tracked.clone());
+    let repo_root = config.dotfiles_dir.display().to_string();
+    engine.register_fn("repo_root", move || repo_root.clone());
+    let reader = config.clone();
+    engine.register_fn("read_file", move |file: &str| -> rhai::Dynamic {
+        read_file_content(&reader, file).map_or(rhai::Dynamic::UNIT, rhai::Dynamic::from)
+    });
+    let rule_name = name.clone();
+    engine.register_fn("rule_name", move |value: &str| {
+        *rule_name.borrow_mut() = value.to_string();
+    });
+    for (fn_name, severity) in [
+        ("error", Severity::Error),
+        ("warning", Severity::Warning),
+        ("info", Severity::Info),
+        ("hint", Severity::Hint),
+    ] {
+        let sink = issues.clone();
+        engine.register_fn(fn_name, move |message: &str| {
+            sink.borrow_mut().push(Issue::new(severity, message));
+        });
+        let sink = issues.clone();
+        engine.register_fn(fn_name, move |message: &str, file: &str| {
+            sink.borrow_mut()
+                .push(Issue::new(severity, message).with_file(file));
+        });
+        let sink = issues.clone();
+        engine.register_fn(fn_name, move |message: &str, file: &str, fix: &str| {
+            sink.borrow_mut()
+                .push(Issue::new(severity, message).with_file(file).with_fix(fix));
+        });
+    }
+
+    let outcome = engine.run_file(script.to_path_buf());
+    drop(engine);
+
+    let mut issues = Rc::try_unwrap(issues)
+        .map(RefCell::into_inner)
+        .unwrap_or_default();
+    if let Err(err) = outcome {
+        issues.push(
+            Issue::new(Severity::Error, format!("Script rule failed: {}", err)).with_file(
+                script
+                    .strip_prefix(&config.dotfiles_dir)
+                    .unwrap_or(script)
+                    .display()
+                    .to_string(),
+            ),
+        );
+    }
+
+    let passed = issues.iter().all(|i| i.severity != Severity::Error);
+    let mut result = ValidationResult::new(name.borrow().clone(), passed, issues);
+    result.rule_id = format!("script:{}", stem);
+    result
+}
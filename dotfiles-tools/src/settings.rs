@@ -0,0 +1,185 @@
+//! Repo-level settings loaded from `.validate-dotfiles.toml`.
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use std::{collections::BTreeMap, fs, path::Path};
+
+use crate::types::Severity;
+
+pub const SETTINGS_FILE: &str = ".validate-dotfiles.toml";
+
+// Repo-level settings from .validate-dotfiles.toml; every section is optional
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Settings {
+    pub plugins: PluginSettings,
+    pub linters: LinterSettings,
+    pub formatting: FormattingSettings,
+    pub deploy: DeploySettings,
+    // [profiles.<name>]: package combinations deployed on different machines
+    pub profiles: BTreeMap<String, ProfileSettings>,
+    // [hosts.<hostname>]: machines whose `dotter.hostname` branches get checked
+    pub hosts: BTreeMap<String, HostSettings>,
+    pub notify: NotifySettings,
+}
+
+// [notify]: where --notify runs report failures besides the desktop
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NotifySettings {
+    // Slack or Discord incoming webhook, or an ntfy topic URL
+    pub webhook: Option<String>,
+}
+
+// One deployable combination, in the shape of a dotter local.toml
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProfileSettings {
+    pub packages: Vec<String>,
+    // Platform configs layered over global.toml, e.g. ".dotter/macos.toml"
+    pub includes: Vec<String>,
+    // Template variables the machine's local.toml would set
+    pub variables: toml::Table,
+}
+
+// One machine; without a profile it deploys what local.toml selects
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HostSettings {
+    pub profile: Option<String>,
+    // Template variables set only on this machine
+    pub variables: toml::Table,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct DeploySettings {
+    // Targets outside $HOME, or needing root, that dotter may still write;
+    // absolute paths or globs like "/usr/local/bin/*"
+    pub allowed_targets: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct FormattingSettings {
+    // json-format expects object keys in sorted order
+    pub sort_json_keys: bool,
+    // [formatting.indent]: extension -> width in spaces, or "tab", for the
+    // indentation rule; e.g. yaml = 2, lua = "tab"
+    pub indent: BTreeMap<String, Indent>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum Indent {
+    Spaces(usize),
+    Tabs(TabKeyword),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TabKeyword {
+    Tab,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PluginSettings {
+    // Search PATH for validate-dotfiles-rule-* executables
+    pub discover: bool,
+    // Extra plugin executables, relative to the repo root or on PATH
+    pub commands: Vec<String>,
+}
+
+impl Default for PluginSettings {
+    fn default() -> Self {
+        Self {
+            discover: true,
+            commands: Vec::new(),
+        }
+    }
+}
+
+// [linters]: jobs, plus one [linters.<name>] table per external linter
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct LinterSettings {
+    // Most linter processes at once; defaults to the number of CPUs
+    pub jobs: Option<usize>,
+    #[serde(flatten)]
+    pub linters: BTreeMap<String, LinterConfig>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LinterConfig {
+    // Globs over repo-relative paths, e.g. "*.lua" or ".config/**/*.yml"
+    pub files: Vec<String>,
+    // Program and arguments. A `{file}` argument runs it once per file;
+    // otherwise it runs once, with `{files}` (or the end of the command)
+    // replaced by every matching file
+    pub command: Vec<String>,
+    #[serde(default)]
+    pub parser: LinterParser,
+    // Regex parser: named groups file, line, column, message, code, severity
+    pub pattern: Option<String>,
+    #[serde(default)]
+    pub json: JsonFields,
+    // For findings that don't report a severity of their own
+    #[serde(default = "default_linter_severity")]
+    pub severity: Severity,
+}
+
+fn default_linter_severity() -> Severity {
+    Severity::Warning
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LinterParser {
+    // A non-zero exit is one finding per checked file
+    #[default]
+    ExitStatus,
+    Regex,
+    Json,
+}
+
+// JSON pointers: `items` locates the array of findings in the output (empty
+// for a top-level array or one object per line), the rest are relative to
+// each finding
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct JsonFields {
+    pub items: String,
+    pub file: String,
+    pub line: String,
+    pub column: String,
+    pub message: String,
+    pub code: String,
+    pub severity: String,
+}
+
+impl Default for JsonFields {
+    fn default() -> Self {
+        Self {
+            items: String::new(),
+            file: "/file".to_string(),
+            line: "/line".to_string(),
+            column: "/column".to_string(),
+            message: "/message".to_string(),
+            code: "/code".to_string(),
+            severity: "/severity".to_string(),
+        }
+    }
+}
+
+pub fn load_settings(dotfiles_dir: &Path) -> Result<Settings> {
+    let path = dotfiles_dir.join(SETTINGS_FILE);
+    if !path.exists() {
+        return Ok(Settings::default());
+    }
+    let content =
+        fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    toml::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+}
@@ -0,0 +1,426 @@
+//! Lightweight parsing of shell configs across zsh, bash, fish, and nushell.
+
+use regex::Regex;
+
+use std::{
+    env,
+    path::{Path, PathBuf},
+    sync::LazyLock,
+};
+
+use crate::repo::{expand_home, home_dir};
+
+// Tracks if/else nesting while scanning a shell file line by line, so that
+// alternatives in different branches aren't mistaken for conflicts.
+// Nushell's brace-delimited blocks aren't tracked.
+#[derive(Default)]
+pub struct BranchTracker {
+    // (block id, current branch, is an if-block)
+    pub blocks: Vec<(usize, usize, bool)>,
+    pub next_block: usize,
+}
+
+impl BranchTracker {
+    pub fn observe(&mut self, shell: &str, words: &[String]) {
+        let Some(first) = words.first() else {
+            return;
+        };
+        let keyword = first.trim_end_matches(';');
+        let one_liner = words.last().is_some_and(|w| w == "fi" || w == "end");
+        match (shell, keyword) {
+            ("posix" | "fish", "if") if !one_liner => {
+                self.blocks.push((self.next_block, 0, true));
+                self.next_block += 1;
+            }
+            ("fish", "function" | "for" | "while" | "switch" | "begin") if !one_liner => {
+                self.blocks.push((self.next_block, 0, false));
+                self.next_block += 1;
+            }
+            ("posix", "elif" | "else") | ("fish", "else") => {
+                if let Some(block) = self.blocks.last_mut().filter(|b| b.2) {
+                    block.1 += 1;
+                }
+            }
+            ("posix", "fi") | ("fish", "end") => {
+                self.blocks.pop();
+            }
+            _ => {}
+        }
+    }
+
+    // (if-block, branch) pairs enclosing the current line
+    pub fn branches(&self) -> Vec<(usize, usize)> {
+        self.blocks
+            .iter()
+            .filter(|(_, _, is_if)| *is_if)
+            .map(|(block, branch, _)| (*block, *branch))
+            .collect()
+    }
+}
+
+pub fn in_exclusive_branches(a: (&str, &[(usize, usize)]), b: (&str, &[(usize, usize)])) -> bool {
+    a.0 == b.0
+        && a.1
+            .iter()
+            .any(|(block, branch)| b.1.iter().any(|(bl, br)| bl == block && br != branch))
+}
+
+#[derive(Debug, Clone)]
+pub struct ShellDefinition {
+    pub shell: &'static str,
+    pub namespace: &'static str,
+    pub name: String,
+    pub value: String,
+    pub file: String,
+    pub line: usize,
+    pub branches: Vec<(usize, usize)>,
+}
+
+impl ShellDefinition {
+    pub fn mutually_exclusive(&self, other: &ShellDefinition) -> bool {
+        in_exclusive_branches((&self.file, &self.branches), (&other.file, &other.branches))
+    }
+}
+
+pub fn shell_family(file: &str) -> &'static str {
+    if file.ends_with(".fish") {
+        "fish"
+    } else if file.ends_with(".nu") {
+        "nushell"
+    } else {
+        "posix"
+    }
+}
+
+pub fn parse_shell_definitions(file: &str, content: &str) -> Vec<ShellDefinition> {
+    let shell = shell_family(file);
+    let mut definitions = Vec::new();
+    let mut tracker = BranchTracker::default();
+
+    for (idx, line) in content.lines().enumerate() {
+        let words = shell_words(line.trim());
+        tracker.observe(shell, &words);
+        let Some(first) = words.first() else {
+            continue;
+        };
+
+        let first = if shell == "nushell" && first == "export" && words.len() > 1 {
+            &words[1]
+        } else {
+            first
+        };
+        let args: Vec<&String> = words.iter().skip_while(|w| *w != first).skip(1).collect();
+
+        let parsed = match (shell, first.as_str()) {
+            ("nushell", "alias") => {
+                // alias name = expansion
+                let joined: Vec<&str> = args.iter().map(|w| w.as_str()).collect();
+                let joined = joined.join(" ");
+                joined
+                    .split_once('=')
+                    .map(|(n, v)| ("command", n.trim().to_string(), v.trim().to_string()))
+            }
+            ("fish", "alias") if !args.is_empty() => match args[0].split_once('=') {
+                Some((name, value)) => Some(("command", name.to_string(), value.to_string())),
+                None => Some((
+                    "command",
+                    args[0].to_string(),
+                    args[1..]
+                        .iter()
+                        .map(|w| w.as_str())
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                )),
+            },
+            ("posix", "alias") => args
+                .iter()
+                .find(|w| !w.starts_with('-'))
+                .and_then(|w| w.split_once('='))
+                .map(|(n, v)| ("command", n.to_string(), v.to_string())),
+            ("posix", "export") => args
+                .iter()
+                .find(|w| !w.starts_with('-'))
+                .and_then(|w| w.split_once('='))
+                .map(|(n, v)| ("env", n.to_string(), v.to_string())),
+            ("fish", "abbr") => {
+                let positional: Vec<_> = args.iter().filter(|w| !w.starts_with('-')).collect();
+                positional.split_first().map(|(name, rest)| {
+                    (
+                        "command",
+                        name.to_string(),
+                        rest.iter()
+                            .map(|w| w.as_str())
+                            .collect::<Vec<_>>()
+                            .join(" "),
+                    )
+                })
+            }
+            ("fish", "set") => {
+                let (flags, rest): (Vec<&String>, Vec<&String>) =
+                    args.iter().partition(|w| w.starts_with('-'));
+                let exported = flags
+                    .iter()
+                    .any(|f| *f == "--export" || (!f.starts_with("--") && f.contains('x')));
+                let erased = flags
+                    .iter()
+                    .any(|f| *f == "--erase" || (!f.starts_with("--") && f.contains('e')));
+                match rest.split_first() {
+                    Some((name, values)) if exported && !erased => Some((
+                        "env",
+                        name.to_string(),
+                        values
+                            .iter()
+                            .map(|w| w.as_str())
+                            .collect::<Vec<_>>()
+                            .join(" "),
+                    )),
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+
+        if let Some((namespace, name, value)) = parsed
+            && !name.is_empty()
+        {
+            definitions.push(ShellDefinition {
+                shell,
+                namespace,
+                name,
+                value,
+                file: file.to_string(),
+                line: idx + 1,
+                branches: tracker.branches(),
+            });
+        }
+    }
+
+    definitions
+}
+
+#[derive(Debug, Clone)]
+pub struct PathEntry {
+    pub dir: String,
+    pub shell: &'static str,
+    pub file: String,
+    pub line: usize,
+    pub branches: Vec<(usize, usize)>,
+}
+
+pub fn is_path_self_reference(word: &str) -> bool {
+    matches!(
+        word,
+        "$PATH" | "${PATH}" | "$path" | "$fish_user_paths" | "$env.PATH"
+    )
+}
+
+pub fn parse_path_entries(file: &str, content: &str) -> Vec<PathEntry> {
+    static RE_ZSH_ARRAY: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"^\s*path\+?=\((.*)$").unwrap());
+    static RE_NU_PATH_JOIN: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r#"\(\$env\.(\w+)\s*\|\s*path join((?:\s+"[^"]*")+)\s*\)"#).unwrap()
+    });
+    static RE_NU_LITERAL: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r#"^(?:\|\s*(?:prepend|append)\s+)?"([^"]+)"$"#).unwrap());
+    static RE_NU_INLINE: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r#"(?:prepend|append)\s+"([^"]+)""#).unwrap());
+
+    let shell = shell_family(file);
+    let mut entries = Vec::new();
+    let mut tracker = BranchTracker::default();
+    // Multi-line constructs: zsh `path=(...)` arrays and nushell
+    // `$env.PATH = (...)` pipelines
+    let mut in_zsh_array = false;
+    let mut nu_depth = 0i32;
+
+    for (idx, line) in content.lines().enumerate() {
+        let words = shell_words(line.trim());
+        tracker.observe(shell, &words);
+        let mut push = |dir: &str| {
+            if !dir.is_empty() && !is_path_self_reference(dir) {
+                entries.push(PathEntry {
+                    dir: dir.to_string(),
+                    shell,
+                    file: file.to_string(),
+                    line: idx + 1,
+                    branches: tracker.branches(),
+                });
+            }
+        };
+
+        if shell == "nushell" {
+            let code = line.split(" #").next().unwrap_or_default().trim();
+            if nu_depth == 0 && !(code.starts_with("$env.PATH =") || code.starts_with("path add")) {
+                continue;
+            }
+            if let Some(caps) = RE_NU_PATH_JOIN.captures(code) {
+                let parts: Vec<_> = shell_words(&caps[2]);
+                push(&format!("${}/{}", &caps[1], parts.join("/")));
+            } else if let Some(caps) = RE_NU_LITERAL.captures(code) {
+                push(&caps[1]);
+            } else if let Some(rest) = code.strip_prefix("path add") {
+                for word in shell_words(rest) {
+                    push(&word);
+                }
+            } else {
+                for caps in RE_NU_INLINE.captures_iter(code) {
+                    push(&caps[1]);
+                }
+            }
+            nu_depth += code.matches(['(', '[']).count() as i32;
+            nu_depth -= code.matches([')', ']']).count() as i32;
+            continue;
+        }
+
+        if in_zsh_array {
+            // Word splitting drops trailing comments, which often contain
+            // parentheses of their own
+            let closes = words.iter().any(|w| w.contains(')'));
+            for word in &words {
+                push(word.split(')').next().unwrap_or_default());
+            }
+            in_zsh_array = !closes;
+            continue;
+        }
+
+        let Some(first) = words.first() else {
+            continue;
+        };
+
+        match (shell, first.as_str()) {
+            ("fish", "fish_add_path") => {
+                for word in words[1..].iter().filter(|w| !w.starts_with('-')) {
+                    push(word);
+                }
+            }
+            ("fish", "set") => {
+                let rest: Vec<_> = words[1..].iter().filter(|w| !w.starts_with('-')).collect();
+                if let Some((name, values)) = rest.split_first()
+                    && matches!(name.as_str(), "PATH" | "fish_user_paths")
+                {
+                    for value in values {
+                        push(value);
+                    }
+                }
+            }
+            ("posix", _) => {
+                if let Some(caps) = RE_ZSH_ARRAY.captures(line) {
+                    let inner = shell_words(&caps[1]);
+                    let closes = inner.iter().any(|w| w.contains(')'));
+                    for word in &inner {
+                        push(word.split(')').next().unwrap_or_default());
+                    }
+                    in_zsh_array = !closes;
+                    continue;
+                }
+
+                let assignment = if first == "export" {
+                    words.get(1)
+                } else {
+                    Some(first)
+                };
+                if let Some(value) = assignment.and_then(|a| a.strip_prefix("PATH=")) {
+                    for dir in value.split(':') {
+                        push(dir);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    entries
+}
+
+// Expands the variables a PATH entry can reasonably be resolved with
+// statically; anything else is left for the shell
+pub fn expand_path_entry(dir: &str) -> Option<PathBuf> {
+    let home = home_dir()?.display().to_string();
+    let mut expanded = dir.replace("${HOME}", &home).replace("$HOME", &home);
+    if let Ok(user) = env::var("USER") {
+        expanded = expanded.replace("${USER}", &user).replace("$USER", &user);
+    }
+    if expanded.contains('$') {
+        return None;
+    }
+    Some(expand_home(&expanded))
+}
+
+// A small POSIX-ish word splitter: enough to pull apart alias and abbr
+// definitions, not a real shell parser
+pub fn shell_words(line: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut quote = None;
+    let mut in_word = false;
+
+    for ch in line.chars() {
+        match (quote, ch) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => current.push(c),
+            (None, '\'' | '"') => {
+                quote = Some(ch);
+                in_word = true;
+            }
+            (None, '#') if !in_word => break,
+            (None, c) if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            (None, c) => {
+                current.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if in_word {
+        words.push(current);
+    }
+    words
+}
+
+pub fn is_shell_config(file: &str) -> bool {
+    let name = Path::new(file)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default();
+    [".fish", ".zsh", ".bash", ".sh", ".nu"]
+        .iter()
+        .any(|ext| name.ends_with(ext))
+        || matches!(
+            name,
+            ".zshrc"
+                | ".zprofile"
+                | ".zshenv"
+                | ".bashrc"
+                | ".bash_profile"
+                | ".profile"
+                | ".envrc"
+                | ".direnvrc"
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shell_words_handle_quotes_and_comments() {
+        assert_eq!(
+            shell_words("alias gs='git status -sb' # short"),
+            ["alias", "gs=git status -sb"]
+        );
+    }
+
+    #[test]
+    fn nushell_path_prepends_are_parsed() {
+        let content = "$env.PATH = (\n  $env.PATH\n  | prepend [\n    \"/usr/local/bin\"\n    ($env.HOME | path join \".cargo\" \"bin\")\n  ]\n)\n";
+        let dirs: Vec<_> = parse_path_entries("env.nu", content)
+            .into_iter()
+            .map(|e| e.dir)
+            .collect();
+        assert_eq!(dirs, ["/usr/local/bin", "$HOME/.cargo/bin"]);
+    }
+}
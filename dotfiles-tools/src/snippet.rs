@@ -0,0 +1,121 @@
+//! Source snippets that point at where an issue is.
+
+use std::ops::Range;
+
+use crate::output::Color;
+
+/// 1-based line and column (in characters) of a byte offset.
+pub fn line_col(content: &str, offset: usize) -> (usize, usize) {
+    let offset = offset.min(content.len());
+    let before = &content[..content.floor_char_boundary(offset)];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    (line, before[line_start..].chars().count() + 1)
+}
+
+/// 1-based line of the first occurrence of `needle`, for pointing at a key or
+/// value when the parser that read the file doesn't keep positions.
+pub fn line_of(content: &str, needle: &str) -> Option<usize> {
+    content
+        .find(needle)
+        .map(|offset| content[..offset].matches('\n').count() + 1)
+}
+
+/// Byte offset of a 1-based line and column (in characters); the inverse of
+/// [`line_col`]. Positions past the end clamp to the end of the line or file.
+pub fn offset_of(content: &str, line: usize, column: usize) -> usize {
+    let start: usize = content
+        .split_inclusive('\n')
+        .take(line.saturating_sub(1))
+        .map(str::len)
+        .sum();
+    let text = content[start..].split('\n').next().unwrap_or_default();
+    start
+        + text
+            .char_indices()
+            .nth(column.saturating_sub(1))
+            .map_or(text.len(), |(i, _)| i)
+}
+
+/// The offending line with a gutter and a caret underline, e.g.
+///
+/// ```text
+///    |
+///  3 | key = = 1
+///    |       ^
+/// ```
+///
+/// The underline covers `span` when it's on that line, otherwise one
+/// character at `column`.
+pub fn render(
+    content: &str,
+    line: usize,
+    column: usize,
+    span: Option<&Range<usize>>,
+) -> Vec<String> {
+    let Some(text) = content.lines().nth(line.saturating_sub(1)) else {
+        return Vec::new();
+    };
+    let text = text.trim_end_matches('\r');
+
+    let start = column.saturating_sub(1).min(text.chars().count());
+    let width = span
+        .map(|s| {
+            content[s.start.min(content.len())..s.end.min(content.len())]
+                .chars()
+                .count()
+        })
+        .filter(|&w| w > 0)
+        .unwrap_or(1)
+        .min(text.chars().count().saturating_sub(start).max(1));
+
+    // Tabs stay tabs so the carets line up however the terminal renders them
+    let pad: String = text
+        .chars()
+        .take(start)
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect();
+
+    let number = line.to_string();
+    let gutter = " ".repeat(number.len());
+    vec![
+        format!("{}{} |{}", Color::dim(), gutter, Color::reset()),
+        format!("{}{} |{} {}", Color::dim(), number, Color::reset(), text),
+        format!(
+            "{}{} |{} {}{}{}{}",
+            Color::dim(),
+            gutter,
+            Color::reset(),
+            pad,
+            Color::red(),
+            "^".repeat(width),
+            Color::reset()
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::COLOR_ENABLED;
+    use std::sync::atomic::Ordering;
+
+    #[test]
+    fn offsets_map_to_lines_and_columns() {
+        let content = "a = 1\nkey = = 1\n";
+        assert_eq!(line_col(content, 0), (1, 1));
+        assert_eq!(line_col(content, 12), (2, 7));
+        assert_eq!(line_col(content, 999), (3, 1));
+        assert_eq!(offset_of(content, 2, 7), 12);
+        assert_eq!(offset_of(content, 2, 99), 15);
+    }
+
+    #[test]
+    fn snippets_underline_the_span() {
+        COLOR_ENABLED.store(false, Ordering::Relaxed);
+        let content = "a = 1\n\tkey = oops\n";
+        let lines = render(content, 2, 8, Some(&(13..17)));
+        assert_eq!(lines[1], "2 | \tkey = oops");
+        assert_eq!(lines[2], "  | \t      ^^^^");
+    }
+}
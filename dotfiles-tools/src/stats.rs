@@ -0,0 +1,174 @@
+//! Repository statistics: what the repo tracks and what dotter deploys from it.
+
+use anyhow::Result;
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::Path,
+};
+
+use crate::{
+    dotter::{DeployKind, mapped_files},
+    output::Color,
+    repo::get_tracked_files,
+    types::{Config, ExitCode},
+};
+
+const LARGEST_SHOWN: usize = 10;
+const EXTENSIONS_SHOWN: usize = 15;
+
+#[derive(Debug, Default)]
+pub struct RepoStats {
+    pub files: usize,
+    pub total_bytes: u64,
+    // extension -> (files, bytes); files without one are under "(none)"
+    pub by_extension: BTreeMap<String, (usize, u64)>,
+    // package -> distinct sources it maps, across every platform config
+    pub packages: BTreeMap<String, usize>,
+    pub templates: usize,
+    pub symlinks: usize,
+    // (file, bytes), biggest first
+    pub largest: Vec<(String, u64)>,
+}
+
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: &[&str] = &["B", "KiB", "MiB", "GiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+pub fn collect_stats(config: &Config) -> Result<RepoStats> {
+    let mut stats = RepoStats::default();
+
+    // Sizes are of the working tree; tracked files deleted there are skipped
+    for file in get_tracked_files(config)? {
+        let Ok(metadata) = fs::symlink_metadata(config.dotfiles_dir.join(&file)) else {
+            continue;
+        };
+        let extension = Path::new(&file)
+            .extension()
+            .map(|e| e.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "(none)".to_string());
+        let entry = stats.by_extension.entry(extension).or_default();
+        entry.0 += 1;
+        entry.1 += metadata.len();
+        stats.files += 1;
+        stats.total_bytes += metadata.len();
+        stats.largest.push((file, metadata.len()));
+    }
+    stats
+        .largest
+        .sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    stats.largest.truncate(LARGEST_SHOWN);
+
+    // A source mapped by several platform configs still counts once
+    let mut sources: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    let mut kinds = BTreeMap::new();
+    for file in mapped_files(config)? {
+        sources
+            .entry(file.package)
+            .or_default()
+            .insert(file.source.clone());
+        kinds.insert(file.source, file.kind);
+    }
+    stats.packages = sources
+        .into_iter()
+        .map(|(package, files)| (package, files.len()))
+        .collect();
+    stats.templates = kinds
+        .values()
+        .filter(|k| **k == DeployKind::Template)
+        .count();
+    stats.symlinks = kinds.len() - stats.templates;
+
+    Ok(stats)
+}
+
+fn heading(title: &str) {
+    println!("\n{}{}{}", Color::bold(), title, Color::reset());
+}
+
+pub fn stats(config: &Config) -> Result<i32> {
+    let stats = collect_stats(config)?;
+
+    heading(&format!(
+        "Tracked files: {} ({})",
+        stats.files,
+        format_size(stats.total_bytes)
+    ));
+    let mut extensions: Vec<_> = stats.by_extension.iter().collect();
+    extensions.sort_by(|a, b| b.1.0.cmp(&a.1.0).then_with(|| a.0.cmp(b.0)));
+    for (extension, (files, bytes)) in extensions.iter().take(EXTENSIONS_SHOWN) {
+        println!(
+            "  {:<16} {:>6}  {:>10}",
+            extension,
+            files,
+            format_size(*bytes)
+        );
+    }
+    if extensions.len() > EXTENSIONS_SHOWN {
+        println!(
+            "  {}...and {} more{}",
+            Color::dim(),
+            extensions.len() - EXTENSIONS_SHOWN,
+            Color::reset()
+        );
+    }
+
+    heading(&format!("Dotter packages: {}", stats.packages.len()));
+    for (package, files) in &stats.packages {
+        println!("  {:<24} {:>4} file(s)", package, files);
+    }
+    println!(
+        "  {} template(s), {} symlink(s)",
+        stats.templates, stats.symlinks
+    );
+
+    heading("Largest files");
+    for (file, bytes) in &stats.largest {
+        println!("  {:>10}  {}", format_size(*bytes), file);
+    }
+    println!();
+
+    Ok(ExitCode::CLEAN)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::FixtureRepo;
+
+    #[test]
+    fn counts_files_packages_and_deploy_kinds() {
+        let repo = FixtureRepo::new();
+        repo.write(
+            ".dotter/global.toml",
+            "[zsh.files]\nzshrc = \"~/.zshrc\"\n\"prompt.toml\" = \"~/.config/prompt.toml\"\n\n[git.files]\ngitconfig = { target = \"~/.gitconfig\", type = \"template\" }\n",
+        )
+        .write(".dotter/macos.toml", "[zsh.files]\nzshrc = \"~/.zshrc\"\n")
+        .write("zshrc", "alias l=ls\n")
+        .write("prompt.toml", "format = \"$all\"\n")
+        .write("gitconfig", "[user]\n  name = {{name}}\n")
+        .commit("fixture");
+
+        let stats = collect_stats(&repo.config()).unwrap();
+        assert_eq!(stats.files, 5);
+        assert_eq!(stats.by_extension["toml"].0, 3);
+        assert_eq!(stats.by_extension["(none)"].0, 2);
+        assert_eq!(stats.packages["zsh"], 2);
+        assert_eq!(stats.packages["git"], 1);
+        assert_eq!((stats.templates, stats.symlinks), (1, 2));
+        assert_eq!(stats.largest[0].0, ".dotter/global.toml");
+        assert_eq!(format_size(1536), "1.5 KiB");
+    }
+}
@@ -0,0 +1,233 @@
+//! Machine-readable summaries of a validation run: a compact JSON file for
+//! CI steps and scheduled jobs to archive, and Prometheus metrics for
+//! dashboards, neither needing the human report parsed.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::Path,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    history::git_head,
+    types::{Config, Severity, ValidationResult},
+};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize)]
+pub struct SeverityCounts {
+    pub error: usize,
+    pub warning: usize,
+    pub info: usize,
+    pub hint: usize,
+}
+
+impl SeverityCounts {
+    pub fn add(&mut self, severity: Severity) {
+        match severity {
+            Severity::Error => self.error += 1,
+            Severity::Warning => self.warning += 1,
+            Severity::Info => self.info += 1,
+            Severity::Hint => self.hint += 1,
+        }
+    }
+
+    pub fn get(&self, severity: Severity) -> usize {
+        match severity {
+            Severity::Error => self.error,
+            Severity::Warning => self.warning,
+            Severity::Info => self.info,
+            Severity::Hint => self.hint,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct RunSummary {
+    pub timestamp: u64,
+    pub head: Option<String>,
+    pub duration_ms: u128,
+    pub exit_code: i32,
+    pub rules_run: usize,
+    pub issues: SeverityCounts,
+    // Rule id -> its issues by severity; rules without issues are left out
+    pub rules: BTreeMap<String, SeverityCounts>,
+}
+
+/// Issue counts for each rule that found any, keyed by rule id.
+pub fn counts_by_rule(results: &[ValidationResult]) -> BTreeMap<String, SeverityCounts> {
+    let mut rules: BTreeMap<String, SeverityCounts> = BTreeMap::new();
+    for result in results {
+        for issue in &result.issues {
+            rules
+                .entry(result.rule_id.clone())
+                .or_default()
+                .add(issue.severity);
+        }
+    }
+    rules
+}
+
+impl RunSummary {
+    pub fn from_results(
+        config: &Config,
+        results: &[ValidationResult],
+        duration: Duration,
+        exit_code: i32,
+    ) -> Self {
+        let mut issues = SeverityCounts::default();
+        for issue in results.iter().flat_map(|r| &r.issues) {
+            issues.add(issue.severity);
+        }
+        Self {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+            head: git_head(config),
+            duration_ms: duration.as_millis(),
+            exit_code,
+            rules_run: results.len(),
+            issues,
+            rules: counts_by_rule(results),
+        }
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(path, json + "\n").with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+// Label values escape backslashes, quotes and newlines
+fn label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Metrics in Prometheus' text exposition format: issues per rule and
+/// severity (zeros included, so alerts resolve when a rule comes clean),
+/// the run's duration, exit code and time.
+pub fn prometheus_metrics(
+    results: &[ValidationResult],
+    duration: Duration,
+    exit_code: i32,
+) -> String {
+    let mut rules: BTreeMap<&str, SeverityCounts> = BTreeMap::new();
+    for result in results {
+        let counts = rules.entry(&result.rule_id).or_default();
+        for issue in &result.issues {
+            counts.add(issue.severity);
+        }
+    }
+
+    let mut out = String::from(
+        "# HELP dotfiles_validation_issues Issues found by the last validation run.\n\
+         # TYPE dotfiles_validation_issues gauge\n",
+    );
+    for (rule, counts) in &rules {
+        for severity in [
+            Severity::Error,
+            Severity::Warning,
+            Severity::Info,
+            Severity::Hint,
+        ] {
+            out.push_str(&format!(
+                "dotfiles_validation_issues{{rule=\"{}\",severity=\"{}\"}} {}\n",
+                label_value(rule),
+                format!("{:?}", severity).to_lowercase(),
+                counts.get(severity)
+            ));
+        }
+    }
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    for (name, help, value) in [
+        (
+            "duration_seconds",
+            "How long the last validation run took.",
+            format!("{:.3}", duration.as_secs_f64()),
+        ),
+        (
+            "exit_code",
+            "Exit code of the last validation run.",
+            exit_code.to_string(),
+        ),
+        (
+            "rules_run",
+            "Rules the last validation run checked.",
+            rules.len().to_string(),
+        ),
+        (
+            "last_run_timestamp_seconds",
+            "When the last validation run finished.",
+            timestamp.to_string(),
+        ),
+    ] {
+        out.push_str(&format!(
+            "# HELP dotfiles_validation_{name} {help}\n\
+             # TYPE dotfiles_validation_{name} gauge\n\
+             dotfiles_validation_{name} {value}\n"
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Issue;
+
+    #[test]
+    fn issues_are_counted_per_rule_and_severity() {
+        let mut shell = ValidationResult::new(
+            "shell",
+            false,
+            vec![
+                Issue::new(Severity::Error, "a"),
+                Issue::new(Severity::Warning, "b"),
+                Issue::new(Severity::Warning, "c"),
+            ],
+        );
+        shell.rule_id = "shell-syntax".to_string();
+        let mut clean = ValidationResult::new("clean", true, Vec::new());
+        clean.rule_id = "utf8".to_string();
+
+        let rules = counts_by_rule(&[shell, clean]);
+        assert_eq!(rules.keys().collect::<Vec<_>>(), ["shell-syntax"]);
+        assert_eq!(
+            rules["shell-syntax"],
+            SeverityCounts {
+                error: 1,
+                warning: 2,
+                info: 0,
+                hint: 0
+            }
+        );
+    }
+
+    #[test]
+    fn metrics_cover_every_rule_and_severity() {
+        let mut result =
+            ValidationResult::new("json", false, vec![Issue::new(Severity::Error, "bad")]);
+        result.rule_id = "json-syntax".to_string();
+        let metrics = prometheus_metrics(&[result], Duration::from_millis(1500), 2);
+        let lines: Vec<_> = metrics.lines().collect();
+        assert!(
+            lines.contains(&r#"dotfiles_validation_issues{rule="json-syntax",severity="error"} 1"#)
+        );
+        assert!(
+            lines.contains(&r#"dotfiles_validation_issues{rule="json-syntax",severity="hint"} 0"#)
+        );
+        assert!(lines.contains(&"dotfiles_validation_duration_seconds 1.500"));
+        assert!(lines.contains(&"dotfiles_validation_exit_code 2"));
+        assert_eq!(label_value("a\"b\\c"), r#"a\"b\\c"#);
+    }
+}
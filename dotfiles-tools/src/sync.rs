@@ -0,0 +1,181 @@
+//! Copying edits made to deployed files back into the repository.
+
+use anyhow::{Context, Result};
+
+use std::fs;
+
+use crate::{
+    backup::create_snapshot,
+    diff,
+    dotter::{DeployKind, DotterFile, deployed_files},
+    drift::content_diff,
+    output::{Color, info, success, warning},
+    repo::{expand_home, git_add},
+    types::{Config, ExitCode},
+};
+
+pub struct SyncBackOptions {
+    pub dry_run: bool,
+}
+
+// What sync-back does with one deployed file
+#[derive(Debug, PartialEq)]
+enum Plan {
+    // The deployed copy replaces the repo source as is
+    Adopt(Vec<u8>),
+    // A rendered template was edited, and the template's variables mean the
+    // edits have to be moved into it by hand; carries the diff when it's text
+    Manual(Option<String>),
+}
+
+fn plan(config: &Config, file: &DotterFile) -> Option<Plan> {
+    // Symlinks, intact or not, have no local edits to copy back
+    let target = expand_home(&file.target);
+    if !fs::symlink_metadata(&target).ok()?.is_file() {
+        return None;
+    }
+    let deployed = fs::read(&target).ok()?;
+    let template = fs::read(config.dotfiles_dir.join(&file.source)).ok()?;
+
+    match file.kind {
+        DeployKind::Symbolic => (deployed != template).then_some(Plan::Adopt(deployed)),
+        DeployKind::Template => {
+            let cached = config.dotfiles_dir.join(".dotter/cache").join(&file.source);
+            let rendered = fs::read(&cached).ok()?;
+            if deployed == rendered {
+                None
+            } else if template == rendered {
+                // Nothing was substituted, so the source is the render
+                Some(Plan::Adopt(deployed))
+            } else {
+                Some(Plan::Manual(content_diff(&rendered, &deployed, file)))
+            }
+        }
+    }
+}
+
+pub fn sync_back(config: &Config, options: &SyncBackOptions) -> Result<i32> {
+    println!(
+        "\n{}Syncing deployed edits back into the repo...{}\n",
+        Color::bold(),
+        Color::reset()
+    );
+
+    let mut adopted = Vec::new();
+    let mut manual = Vec::new();
+    for file in deployed_files(config)? {
+        match plan(config, &file) {
+            Some(Plan::Adopt(content)) => adopted.push((file, content)),
+            Some(Plan::Manual(diff)) => manual.push((file, diff)),
+            None => {}
+        }
+    }
+
+    if adopted.is_empty() && manual.is_empty() {
+        success("No deployed files were edited");
+        return Ok(ExitCode::CLEAN);
+    }
+
+    if options.dry_run {
+        for (file, _) in &adopted {
+            info(&format!("Would copy {} -> {}", file.target, file.source));
+        }
+    } else if !adopted.is_empty() {
+        let sources: Vec<_> = adopted
+            .iter()
+            .map(|(file, _)| config.dotfiles_dir.join(&file.source))
+            .collect();
+        let snapshot = create_snapshot(config, "sync", &sources)?;
+        // Writing over the source keeps its mode; the deployed copy's may
+        // have drifted too
+        for ((file, content), path) in adopted.iter().zip(&sources) {
+            fs::write(path, content)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            success(&format!("Copied {} -> {}", file.target, file.source));
+        }
+        let staged: Vec<_> = adopted
+            .iter()
+            .map(|(file, _)| file.source.as_str())
+            .collect();
+        git_add(config, &staged)?;
+        info(&format!(
+            "Staged {} file(s); review with: git diff --cached (undo with: dotfiles-tools restore {})",
+            staged.len(),
+            snapshot.id
+        ));
+        info("Run: dotfiles-tools deploy --force to replace the copies with symlinks again");
+    }
+
+    for (file, diff) in &manual {
+        warning(&format!(
+            "{} is rendered from a template; move these edits into {} by hand",
+            file.target, file.source
+        ));
+        for line in diff.iter().flat_map(|d| diff::colorize(d)) {
+            println!("      {}", line);
+        }
+    }
+
+    Ok(if manual.is_empty() {
+        ExitCode::CLEAN
+    } else {
+        ExitCode::WARNINGS
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::FixtureRepo;
+
+    #[test]
+    fn only_templates_without_substitutions_are_adopted() {
+        let repo = FixtureRepo::new();
+        repo.write("git/config", "[user]\n")
+            .write("home/.gitconfig", "[user]\n\tname = me\n")
+            .write("zsh/zshrc", "export A=1\n")
+            .write(".dotter/cache/zsh/zshrc", "export A=1\n")
+            .write("home/.zshrc", "export A=2\n")
+            .write("kitty/kitty.conf", "font_size {{font_size}}\n")
+            .write(".dotter/cache/kitty/kitty.conf", "font_size 13\n")
+            .write("home/kitty.conf", "font_size 14\n")
+            .write("fish/config.fish", "set -x A 1\n")
+            .write("home/config.fish", "set -x A 1\n");
+        let config = repo.config();
+        let deployed = |source: &str, target: &str, kind| DotterFile {
+            package: "test".to_string(),
+            source: source.to_string(),
+            target: repo.path().join(target).display().to_string(),
+            kind,
+        };
+
+        assert_eq!(
+            plan(
+                &config,
+                &deployed("git/config", "home/.gitconfig", DeployKind::Symbolic)
+            ),
+            Some(Plan::Adopt(b"[user]\n\tname = me\n".to_vec()))
+        );
+        assert_eq!(
+            plan(
+                &config,
+                &deployed("zsh/zshrc", "home/.zshrc", DeployKind::Template)
+            ),
+            Some(Plan::Adopt(b"export A=2\n".to_vec()))
+        );
+        let Some(Plan::Manual(Some(diff))) = plan(
+            &config,
+            &deployed("kitty/kitty.conf", "home/kitty.conf", DeployKind::Template),
+        ) else {
+            panic!("edited template should need a manual sync");
+        };
+        assert!(diff.contains("-font_size 13\n+font_size 14\n"));
+        assert_eq!(
+            plan(
+                &config,
+                &deployed("fish/config.fish", "home/config.fish", DeployKind::Symbolic)
+            ),
+            None
+        );
+    }
+}
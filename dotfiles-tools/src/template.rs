@@ -0,0 +1,372 @@
+//! Just enough of dotter's Handlebars templates to tell which variables they
+//! need, without rendering them, on any machine or on one with a given
+//! hostname.
+
+use regex::Regex;
+
+use std::sync::LazyLock;
+
+// Helpers dotter registers, and the Handlebars built-ins; a bare `{{name}}`
+// with one of these names is a helper call, not a variable
+const HELPERS: &[&str] = &[
+    "if",
+    "unless",
+    "each",
+    "with",
+    "lookup",
+    "log",
+    "eq",
+    "ne",
+    "gt",
+    "gte",
+    "lt",
+    "lte",
+    "and",
+    "or",
+    "not",
+    "len",
+    "math",
+    "include_template",
+    "is_executable",
+    "command_success",
+    "command_output",
+];
+
+// Roots that are always defined
+const BUILTIN_ROOTS: &[&str] = &["dotter", "this"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TagKind {
+    // `{{#name args}}`
+    Open(String),
+    // `{{/name}}`
+    Close(String),
+    // `{{else}}` or `{{else if args}}`
+    Else,
+    // `{{expr}}` or `{{{expr}}}`
+    Expression,
+    // Comments and partials
+    Other,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tag {
+    pub kind: TagKind,
+    // What follows the block name, or the whole expression
+    pub expr: String,
+    // Byte offset of the tag in the template
+    pub offset: usize,
+}
+
+/// The template's tags in order, skipping `\{{` escapes.
+pub fn tags(content: &str) -> Vec<Tag> {
+    static RE_TAG: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"\{\{\{?~?((?s:.*?))~?\}?\}\}").unwrap());
+
+    let mut found = Vec::new();
+    for caps in RE_TAG.captures_iter(content) {
+        let whole = caps.get(0).unwrap();
+        if content[..whole.start()].ends_with('\\') {
+            continue;
+        }
+        let inner = caps[1].trim();
+        let (kind, expr) = if let Some(rest) = inner.strip_prefix('#') {
+            let (name, args) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+            (TagKind::Open(name.to_string()), args.trim())
+        } else if let Some(rest) = inner.strip_prefix('/') {
+            (TagKind::Close(rest.trim().to_string()), "")
+        } else if inner == "else" || inner.starts_with("else ") || inner.starts_with('^') {
+            (TagKind::Else, inner.trim_start_matches("else").trim())
+        } else if inner.starts_with('!') || inner.starts_with('>') {
+            (TagKind::Other, inner)
+        } else {
+            (TagKind::Expression, inner)
+        };
+        found.push(Tag {
+            kind,
+            expr: expr.to_string(),
+            offset: whole.start(),
+        });
+    }
+    found
+}
+
+// Splits an expression into words, keeping quoted strings whole and making
+// each parenthesis its own word
+fn words(expr: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut quote = None;
+    for c in expr.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => {
+                current.push(c);
+                quote = None;
+            }
+            (Some(_), c) => current.push(c),
+            (None, '"' | '\'') => {
+                current.push(c);
+                quote = Some(c);
+            }
+            (None, '(' | ')') => {
+                if !current.is_empty() {
+                    words.push(std::mem::take(&mut current));
+                }
+                words.push(c.to_string());
+            }
+            (None, c) if c.is_whitespace() => {
+                if !current.is_empty() {
+                    words.push(std::mem::take(&mut current));
+                }
+            }
+            (None, c) => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+fn is_literal(word: &str) -> bool {
+    word.starts_with(['"', '\'', '@'])
+        || word.parse::<f64>().is_ok()
+        || matches!(word, "true" | "false" | "null" | "undefined")
+}
+
+/// The variable paths an expression reads: every word that isn't a literal
+/// or in a helper's position, with `key=value` hash arguments counting by
+/// their value. `custom_helpers` are the names in dotter's `[helpers]`.
+pub fn expression_paths(expr: &str, custom_helpers: &[String]) -> Vec<String> {
+    let is_helper =
+        |word: &str| HELPERS.contains(&word) || custom_helpers.iter().any(|h| h == word);
+    let words = words(expr);
+    let mut paths = Vec::new();
+    for (idx, word) in words.iter().enumerate() {
+        if word == "(" || word == ")" {
+            continue;
+        }
+        let value = word.split_once('=').map_or(word.as_str(), |(_, v)| v);
+        // The first word of an expression or subexpression names the helper
+        // when anything follows it
+        let leads = idx == 0 || words[idx - 1] == "(";
+        let has_args = words.get(idx + 1).is_some_and(|next| next != ")");
+        if is_literal(value) || (leads && (has_args || is_helper(value))) {
+            continue;
+        }
+        paths.push(value.to_string());
+    }
+    paths
+}
+
+// A condition's operand, as far as it can be known from the hostname alone
+#[derive(Debug, Clone, PartialEq)]
+enum Operand {
+    Text(String),
+    Known(bool),
+    Unknown,
+}
+
+impl Operand {
+    fn truthy(&self) -> Option<bool> {
+        match self {
+            Operand::Text(text) => Some(!text.is_empty()),
+            Operand::Known(value) => Some(*value),
+            Operand::Unknown => None,
+        }
+    }
+}
+
+fn operand(words: &[String], pos: &mut usize, hostname: &str) -> Operand {
+    let Some(word) = words.get(*pos) else {
+        return Operand::Unknown;
+    };
+    *pos += 1;
+    if word != "(" {
+        return match word.as_str() {
+            "dotter.hostname" => Operand::Text(hostname.to_string()),
+            "true" => Operand::Known(true),
+            "false" => Operand::Known(false),
+            _ if word.starts_with(['"', '\'']) && word.len() >= 2 => {
+                Operand::Text(word[1..word.len() - 1].to_string())
+            }
+            _ => Operand::Unknown,
+        };
+    }
+
+    let helper = words.get(*pos).cloned().unwrap_or_default();
+    *pos += 1;
+    let mut args = Vec::new();
+    while words.get(*pos).is_some_and(|w| w != ")") {
+        args.push(operand(words, pos, hostname));
+    }
+    *pos += 1;
+    let known = |values: Vec<Option<bool>>, short: bool| {
+        if values.contains(&Some(short)) {
+            Operand::Known(short)
+        } else if values.iter().all(|v| v.is_some()) {
+            Operand::Known(!short)
+        } else {
+            Operand::Unknown
+        }
+    };
+    match (helper.as_str(), args.as_slice()) {
+        ("eq" | "ne", [Operand::Text(a), Operand::Text(b)]) => {
+            Operand::Known((a == b) == (helper == "eq"))
+        }
+        ("not", [arg]) => arg
+            .truthy()
+            .map_or(Operand::Unknown, |b| Operand::Known(!b)),
+        ("and", _) => known(args.iter().map(Operand::truthy).collect(), false),
+        ("or", _) => known(args.iter().map(Operand::truthy).collect(), true),
+        _ => Operand::Unknown,
+    }
+}
+
+/// What a block condition like `(eq dotter.hostname "mbp")` is on a machine
+/// with this hostname; `None` when it depends on anything besides the
+/// hostname.
+pub fn condition_for_host(expr: &str, hostname: &str) -> Option<bool> {
+    operand(&words(expr), &mut 0, hostname).truthy()
+}
+
+// An open block: whether the current branch renders (`None` when that
+// depends on more than the hostname), and whether an earlier branch
+// certainly did
+struct Frame {
+    name: String,
+    renders: Option<bool>,
+    taken: Option<bool>,
+}
+
+/// Variables the template outputs or iterates over, with the byte offset of
+/// the tag that uses each. Conditions (`#if`, `#unless`, `else if`) may test
+/// variables that aren't defined, and names inside `#each` and `#with` are
+/// relative to the block's context, so neither counts. With a hostname,
+/// branches that can't render on that machine are left out.
+pub fn required_variables(
+    content: &str,
+    custom_helpers: &[String],
+    hostname: Option<&str>,
+) -> Vec<(String, usize)> {
+    let condition = |expr: &str| hostname.and_then(|h| condition_for_host(expr, h));
+    let mut blocks: Vec<Frame> = Vec::new();
+    let mut required = Vec::new();
+    for tag in tags(content) {
+        let in_context_block = blocks.iter().any(|b| b.name == "each" || b.name == "with");
+        let renders = blocks.iter().all(|b| b.renders != Some(false));
+        let paths = match &tag.kind {
+            TagKind::Open(name) => {
+                let state = match name.as_str() {
+                    "if" => condition(&tag.expr),
+                    "unless" => condition(&tag.expr).map(|b| !b),
+                    _ => None,
+                };
+                blocks.push(Frame {
+                    name: name.clone(),
+                    renders: state,
+                    taken: state,
+                });
+                if in_context_block || !renders || matches!(name.as_str(), "if" | "unless") {
+                    continue;
+                }
+                expression_paths(&tag.expr, custom_helpers)
+            }
+            TagKind::Close(name) => {
+                if let Some(pos) = blocks.iter().rposition(|b| b.name == *name) {
+                    blocks.truncate(pos);
+                }
+                continue;
+            }
+            TagKind::Else => {
+                if let Some(frame) = blocks.last_mut() {
+                    let state = match frame.taken {
+                        Some(true) => Some(false),
+                        Some(false) if tag.expr.is_empty() => Some(true),
+                        Some(false) => condition(tag.expr.trim_start_matches("if").trim()),
+                        None => None,
+                    };
+                    frame.renders = state;
+                    frame.taken = match (frame.taken, state) {
+                        (Some(true), _) | (_, Some(true)) => Some(true),
+                        (Some(false), Some(false)) => Some(false),
+                        _ => None,
+                    };
+                }
+                continue;
+            }
+            TagKind::Other => continue,
+            TagKind::Expression if in_context_block || !renders => continue,
+            TagKind::Expression => expression_paths(&tag.expr, custom_helpers),
+        };
+        for path in paths {
+            let root = path.split(['.', '/']).next().unwrap_or_default();
+            if !root.is_empty() && !root.starts_with("..") && !BUILTIN_ROOTS.contains(&root) {
+                required.push((path, tag.offset));
+            }
+        }
+    }
+    required
+}
+
+/// Whether a dotted variable path is defined in `variables`.
+pub fn is_defined(variables: &toml::Table, path: &str) -> bool {
+    let mut parts = path.split(['.', '/']);
+    let Some(mut value) = parts.next().and_then(|root| variables.get(root)) else {
+        return false;
+    };
+    for part in parts {
+        match value.as_table().and_then(|t| t.get(part)) {
+            Some(next) => value = next,
+            None => return false,
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_output_and_iterated_variables_are_required() {
+        let content = "email = {{email}}\n\
+                       {{#if work}}signingkey = {{ signing.key }}{{/if}}\n\
+                       {{#each hosts}}Host {{name}}{{/each}}\n\
+                       shell = {{dotter.os}}\n\
+                       {{#if (eq dotter.hostname \"mbp\")}}x{{else if laptop}}y{{/if}}\n\
+                       font = {{default font_size 13}}\n\
+                       {{command_output \"uname\"}} \\{{literal}} {{! comment }}\n";
+        let required: Vec<_> = required_variables(content, &["default".to_string()], None)
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(required, ["email", "signing.key", "hosts", "font_size"]);
+    }
+
+    #[test]
+    fn nested_variables_are_looked_up_by_path() {
+        let variables: toml::Table = toml::from_str("[signing]\nkey = \"ABC\"\n").unwrap();
+        assert!(is_defined(&variables, "signing.key"));
+        assert!(!is_defined(&variables, "signing.format"));
+        assert!(!is_defined(&variables, "email"));
+    }
+
+    #[test]
+    fn hostname_branches_are_followed_per_machine() {
+        let content = "{{#if (eq dotter.hostname \"work\")}}{{work_email}}\
+                       {{else if (or (eq dotter.hostname \"mbp\") (eq dotter.hostname \"mini\"))}}{{home_email}}\
+                       {{else}}{{fallback}}{{/if}}\
+                       {{#unless (ne dotter.hostname \"mini\")}}{{mini_only}}{{/unless}}\
+                       {{#if laptop}}{{battery}}{{/if}}";
+        let needed = |host| -> Vec<_> {
+            required_variables(content, &[], Some(host))
+                .into_iter()
+                .map(|(name, _)| name)
+                .collect()
+        };
+        assert_eq!(needed("work"), ["work_email", "battery"]);
+        assert_eq!(needed("mini"), ["home_email", "mini_only", "battery"]);
+        assert_eq!(needed("nas"), ["fallback", "battery"]);
+    }
+}
@@ -0,0 +1,81 @@
+//! Throwaway git repositories for exercising rules against real fixtures.
+
+use std::{fs, path::Path, process::Command};
+
+use tempfile::TempDir;
+
+use crate::{settings::Settings, types::Config};
+
+pub struct FixtureRepo {
+    dir: TempDir,
+}
+
+impl FixtureRepo {
+    pub fn new() -> Self {
+        let repo = Self {
+            dir: TempDir::new().expect("create temp dir"),
+        };
+        repo.git(&["init", "--quiet", "--initial-branch=main"]);
+        repo
+    }
+
+    pub fn path(&self) -> &Path {
+        self.dir.path()
+    }
+
+    pub fn config(&self) -> Config {
+        Config::new(self.path().to_path_buf(), 0, Settings::default())
+    }
+
+    pub fn write(&self, file: &str, content: &str) -> &Self {
+        let path = self.path().join(file);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).expect("create fixture dirs");
+        }
+        fs::write(path, content).expect("write fixture file");
+        self
+    }
+
+    #[cfg(unix)]
+    pub fn symlink(&self, link: &str, target: &str) -> &Self {
+        let path = self.path().join(link);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).expect("create fixture dirs");
+        }
+        std::os::unix::fs::symlink(target, path).expect("create fixture symlink");
+        self
+    }
+
+    pub fn add_all(&self) -> &Self {
+        self.git(&["add", "--all"]);
+        self
+    }
+
+    pub fn commit(&self, message: &str) -> &Self {
+        self.add_all();
+        self.git(&["commit", "--quiet", "--no-verify", "-m", message]);
+        self
+    }
+
+    pub fn git(&self, args: &[&str]) -> String {
+        let output = Command::new("git")
+            .args([
+                "-c",
+                "user.name=Fixture",
+                "-c",
+                "user.email=fixture@example.com",
+            ])
+            .args(["-c", "commit.gpgsign=false"])
+            .args(args)
+            .current_dir(self.path())
+            .output()
+            .expect("run git");
+        assert!(
+            output.status.success(),
+            "git {:?} failed: {}",
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    }
+}
@@ -0,0 +1,322 @@
+//! Core types shared by rules, reporters, and commands.
+
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use std::{ops::Range, path::PathBuf, sync::Arc, time::Duration};
+
+use crate::{cache::ResultCache, settings::Settings, snippet::line_col};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+    // Advisory findings (style, conventions) that never fail a run or count
+    // toward --strict and --max-warnings
+    Info,
+    Hint,
+}
+
+impl Severity {
+    pub fn rank(self) -> u8 {
+        match self {
+            Severity::Hint => 0,
+            Severity::Info => 1,
+            Severity::Warning => 2,
+            Severity::Error => 3,
+        }
+    }
+
+    pub fn at_least(self, min: Severity) -> bool {
+        self.rank() >= min.rank()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Issue {
+    pub severity: Severity,
+    pub message: String,
+    pub file: Option<String>,
+    pub fix_suggestion: Option<String>,
+    // Stable identifier like DOT003; see `codes` and the `explain` command
+    pub code: Option<String>,
+    // 1-based position in `file`, and the byte range it covers when known
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub span: Option<Range<usize>>,
+    // Unified diff of what the repo has against what's on disk, for drift
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diff: Option<String>,
+}
+
+impl Issue {
+    pub fn new(severity: Severity, message: impl Into<String>) -> Self {
+        Self {
+            severity,
+            message: message.into(),
+            file: None,
+            fix_suggestion: None,
+            code: None,
+            line: None,
+            column: None,
+            span: None,
+            diff: None,
+        }
+    }
+
+    pub fn with_file(mut self, file: impl Into<String>) -> Self {
+        self.file = Some(file.into());
+        self
+    }
+
+    pub fn with_fix(mut self, fix: impl Into<String>) -> Self {
+        self.fix_suggestion = Some(fix.into());
+        self
+    }
+
+    pub fn with_code(mut self, code: impl Into<String>) -> Self {
+        self.code = Some(code.into());
+        self
+    }
+
+    pub fn with_diff(mut self, diff: Option<String>) -> Self {
+        self.diff = diff;
+        self
+    }
+
+    pub fn at(mut self, line: usize, column: usize) -> Self {
+        self.line = Some(line);
+        self.column = Some(column);
+        self
+    }
+
+    // Positions a parser reported as a byte range into `content`
+    pub fn with_span(self, content: &str, span: Range<usize>) -> Self {
+        let (line, column) = line_col(content, span.start);
+        let mut issue = self.at(line, column);
+        issue.span = Some(span);
+        issue
+    }
+
+    // file:line:column, as far as it's known
+    pub fn location(&self) -> Option<String> {
+        let file = self.file.as_deref()?;
+        Some(match (self.line, self.column) {
+            (Some(line), Some(column)) => format!("{}:{}:{}", file, line, column),
+            (Some(line), None) => format!("{}:{}", file, line),
+            _ => file.to_string(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ValidationResult {
+    pub rule_id: String,
+    pub rule_name: String,
+    pub passed: bool,
+    pub issues: Vec<Issue>,
+    // Why the rule didn't run, when a prerequisite failed
+    pub skipped: Option<String>,
+}
+
+impl ValidationResult {
+    pub fn new(rule_name: impl Into<String>, passed: bool, issues: Vec<Issue>) -> Self {
+        Self {
+            rule_id: String::new(),
+            rule_name: rule_name.into(),
+            passed,
+            issues,
+            skipped: None,
+        }
+    }
+
+    pub fn skipped(rule_id: &str, reason: impl Into<String>) -> Self {
+        Self {
+            rule_id: rule_id.to_string(),
+            rule_name: rule_id.to_string(),
+            passed: false,
+            issues: Vec::new(),
+            skipped: Some(reason.into()),
+        }
+    }
+}
+
+pub type RuleFn = fn(&Config) -> Result<ValidationResult>;
+
+pub struct Rule {
+    pub id: &'static str,
+    pub opt_in: bool,
+    // Rule IDs that must pass first; each has to appear earlier in rules::all()
+    pub requires: &'static [&'static str],
+    // Categories for --tags/--exclude-tags; each must be listed in rules::TAGS
+    pub tags: &'static [&'static str],
+    // Checks each content file on its own, so it can run against one file
+    pub per_file: bool,
+    pub check: RuleFn,
+}
+
+impl Rule {
+    pub fn new(id: &'static str, check: RuleFn) -> Self {
+        Self {
+            id,
+            opt_in: false,
+            requires: &[],
+            tags: &[],
+            per_file: false,
+            check,
+        }
+    }
+
+    // Opt-in rules are slow or depend on the state of the current machine,
+    // so they only run when requested with --enable
+    pub fn opt_in(mut self) -> Self {
+        self.opt_in = true;
+        self
+    }
+
+    // A failed or skipped prerequisite would only make this rule report
+    // confusing follow-on errors, so it's skipped instead
+    pub fn requires(mut self, ids: &'static [&'static str]) -> Self {
+        self.requires = ids;
+        self
+    }
+
+    pub fn tags(mut self, tags: &'static [&'static str]) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    // Per-file rules are the only ones run when checking a single file
+    pub fn per_file(mut self) -> Self {
+        self.per_file = true;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputMode {
+    Normal,
+    SummaryOnly,
+    Quiet,
+}
+
+// How the issue report is written; the summary and exit code don't change
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    Human,
+    // One `file:line:col: severity: message [rule-id]` line per issue, for
+    // editors' errorformat/quickfix parsing
+    Compact,
+    // Issue counts per rule and severity, plus run duration, in the text
+    // exposition format node_exporter's textfile collector reads
+    Prometheus,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum GroupBy {
+    Rule,
+    File,
+}
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub dotfiles_dir: PathBuf,
+    pub fix_mode: bool,
+    pub apply_mode: bool,
+    pub verbosity: u8,
+    pub staged: bool,
+    pub changed_since: Option<String>,
+    pub enabled_rules: Vec<String>,
+    pub tags: Vec<String>,
+    pub exclude_tags: Vec<String>,
+    pub output: OutputMode,
+    pub format: OutputFormat,
+    pub group_by: GroupBy,
+    pub min_severity: Severity,
+    pub strict: bool,
+    pub max_warnings: Option<usize>,
+    pub compare: bool,
+    pub platform: Option<String>,
+    pub notify: bool,
+    pub fail_fast: bool,
+    pub emit_fix_script: Option<PathBuf>,
+    pub summary_out: Option<PathBuf>,
+    // How long a rule or plugin may run before it's abandoned; None waits
+    pub rule_timeout: Option<Duration>,
+    pub incremental: bool,
+    // Loaded for --incremental runs and shared by every rule
+    pub cache: Option<Arc<ResultCache>>,
+    // Restricts a run to the per-file rules over just these files
+    pub only_files: Option<Vec<String>>,
+    pub settings: Settings,
+}
+
+impl Config {
+    // Defaults for commands that don't take the validation flags
+    pub fn new(dotfiles_dir: PathBuf, verbosity: u8, settings: Settings) -> Self {
+        Self {
+            dotfiles_dir,
+            fix_mode: false,
+            apply_mode: false,
+            verbosity,
+            staged: false,
+            changed_since: None,
+            enabled_rules: Vec::new(),
+            tags: Vec::new(),
+            exclude_tags: Vec::new(),
+            output: OutputMode::Normal,
+            format: OutputFormat::Human,
+            group_by: GroupBy::Rule,
+            min_severity: Severity::Hint,
+            strict: false,
+            max_warnings: None,
+            compare: false,
+            platform: None,
+            notify: false,
+            fail_fast: false,
+            emit_fix_script: None,
+            summary_out: None,
+            rule_timeout: Some(DEFAULT_RULE_TIMEOUT),
+            incremental: false,
+            cache: None,
+            only_files: None,
+            settings,
+        }
+    }
+
+    // With --tags, only rules carrying one of them run; --exclude-tags drops
+    // a rule carrying any of them
+    pub fn tags_allow(&self, tags: &[&str]) -> bool {
+        let has = |wanted: &[String]| tags.iter().any(|t| wanted.iter().any(|w| w == t));
+        !has(&self.exclude_tags) && (self.tags.is_empty() || has(&self.tags))
+    }
+}
+
+// Long enough for shellcheck over the whole repo, short enough to notice a hang
+pub const DEFAULT_RULE_TIMEOUT: Duration = Duration::from_secs(30);
+
+pub struct ExitCode;
+
+impl ExitCode {
+    pub const CLEAN: i32 = 0;
+    pub const WARNINGS: i32 = 1;
+    pub const ERRORS: i32 = 2;
+    pub const INTERNAL: i32 = 3;
+    pub const GIT_MISSING: i32 = 4;
+    // 128 + SIGINT, as shells report a process killed by Ctrl-C
+    pub const INTERRUPTED: i32 = 130;
+    pub const USAGE: i32 = 64;
+}
+
+#[derive(Debug)]
+pub struct GitMissing;
+
+impl std::fmt::Display for GitMissing {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "git was not found on PATH")
+    }
+}
+
+impl std::error::Error for GitMissing {}
@@ -0,0 +1,805 @@
+//! Runs rules and reports their results.
+
+use anyhow::{Context, Result};
+use tracing::{debug, info_span};
+
+use std::{
+    collections::BTreeMap,
+    sync::{
+        Arc,
+        mpsc::{self, RecvTimeoutError},
+    },
+    thread,
+    time::Instant,
+};
+
+use crate::{
+    cache::ResultCache,
+    diff,
+    fixes::{apply_formatting, apply_gitignore_negations, fix_script_section, write_fix_script},
+    history::{RunRecord, load_history, print_comparison, save_history},
+    interrupt::interrupted,
+    linters::run_linters,
+    output::{Color, failure, hint, info, notify, skipped, success, warning},
+    plugins::{discover_plugins, run_plugin},
+    repo::{check_environment, get_content_files, is_ignored_by_git, read_file_content},
+    rules,
+    scripting::{discover_script_rules, run_script_rule},
+    snippet,
+    summary::{RunSummary, prometheus_metrics},
+    types::{
+        Config, ExitCode, GroupBy, Issue, OutputFormat, OutputMode, Severity, ValidationResult,
+    },
+    webhook,
+};
+
+pub struct Validator {
+    pub config: Config,
+}
+
+impl Validator {
+    pub fn new(config: Config) -> Self {
+        Self { config }
+    }
+
+    // With --fail-fast, the first error-severity issue ends the run, and
+    // Ctrl-C ends it after whichever rule is running
+    pub fn should_stop(&self, results: &[ValidationResult]) -> bool {
+        interrupted()
+            || self.config.fail_fast
+                && results
+                    .last()
+                    .is_some_and(|r| r.issues.iter().any(|i| i.severity == Severity::Error))
+    }
+
+    // Runs a check on its own thread, giving up on it after --rule-timeout.
+    // A check that hangs, on a git call against a stalled network mount say,
+    // is left behind rather than holding up the whole run
+    fn with_timeout<T: Send + 'static>(
+        &self,
+        check: impl FnOnce(&Config) -> Result<T> + Send + 'static,
+    ) -> Option<Result<T>> {
+        let Some(timeout) = self.config.rule_timeout else {
+            return Some(check(&self.config));
+        };
+        let config = self.config.clone();
+        let span = tracing::Span::current();
+        let (sender, receiver) = mpsc::channel();
+        let handle = thread::spawn(move || {
+            let _span = span.entered();
+            let _ = sender.send(check(&config));
+        });
+        match receiver.recv_timeout(timeout) {
+            Ok(result) => Some(result),
+            Err(RecvTimeoutError::Timeout) => None,
+            // The check panicked; let it take the run down as it would have
+            Err(RecvTimeoutError::Disconnected) => match handle.join() {
+                Err(panic) => std::panic::resume_unwind(panic),
+                Ok(()) => unreachable!("a finished check always sends its result"),
+            },
+        }
+    }
+
+    fn timed_out(&self, id: &str) -> ValidationResult {
+        let seconds = self.config.rule_timeout.unwrap_or_default().as_secs();
+        let mut result = ValidationResult::new(
+            format!("{} (timed out)", id),
+            false,
+            vec![
+                Issue::new(
+                    Severity::Error,
+                    format!("{} didn't finish within {}s and was abandoned", id, seconds),
+                )
+                .with_fix("Look for a hung tool or git call, or raise --rule-timeout"),
+            ],
+        );
+        result.rule_id = id.to_string();
+        result
+    }
+
+    pub fn run_rules(&self) -> Result<Vec<ValidationResult>> {
+        let rules = rules::all();
+
+        for id in &self.config.enabled_rules {
+            if !rules.iter().any(|r| r.id == id) {
+                let known: Vec<_> = rules.iter().map(|r| r.id).collect();
+                anyhow::bail!("Unknown rule '{}' (known rules: {})", id, known.join(", "));
+            }
+        }
+
+        for tag in self.config.tags.iter().chain(&self.config.exclude_tags) {
+            if !rules::TAGS.contains(&tag.as_str()) {
+                anyhow::bail!(
+                    "Unknown tag '{}' (known tags: {})",
+                    tag,
+                    rules::TAGS.join(", ")
+                );
+            }
+        }
+
+        let mut results = Vec::new();
+        for rule in rules {
+            // --enable names a rule outright, so it wins over the tag filters
+            if self.config.only_files.is_some() && !rule.per_file {
+                continue;
+            }
+            let enabled = self.config.enabled_rules.iter().any(|id| id == rule.id);
+            if !enabled && !self.config.tags_allow(rule.tags) {
+                continue;
+            }
+            let tagged = rule
+                .tags
+                .iter()
+                .any(|t| self.config.tags.iter().any(|w| w == t));
+            if rule.opt_in && !enabled && !tagged {
+                continue;
+            }
+            // Prerequisites that didn't run at all (opt-in, not enabled) don't block
+            let blocked: Vec<_> = rule
+                .requires
+                .iter()
+                .filter(|id| {
+                    results
+                        .iter()
+                        .any(|r: &ValidationResult| r.rule_id == **id && !r.passed)
+                })
+                .copied()
+                .collect();
+            if !blocked.is_empty() {
+                debug!(id = rule.id, ?blocked, "rule skipped");
+                results.push(ValidationResult::skipped(
+                    rule.id,
+                    format!("requires {}", blocked.join(", ")),
+                ));
+                continue;
+            }
+            let _span = info_span!("rule", id = rule.id).entered();
+            let started = Instant::now();
+            let mut result = match self.with_timeout(rule.check) {
+                Some(result) => result?,
+                None => self.timed_out(rule.id),
+            };
+            result.rule_id = rule.id.to_string();
+            debug!(
+                issues = result.issues.len(),
+                elapsed_ms = started.elapsed().as_millis() as u64,
+                "rule finished"
+            );
+            results.push(result);
+            if self.should_stop(&results) {
+                return Ok(results);
+            }
+        }
+
+        // Plugins and scripts judge the repo as a whole
+        let whole_repo = self.config.only_files.is_none();
+        let plugins = if whole_repo && self.config.tags_allow(&["plugin"]) {
+            discover_plugins(&self.config)
+        } else {
+            Vec::new()
+        };
+        for plugin in plugins {
+            let _span = info_span!("plugin", path = %plugin.display()).entered();
+            let path = plugin.clone();
+            let result = self.with_timeout(move |config| Ok(run_plugin(config, &path)));
+            results.push(match result {
+                Some(result) => result?,
+                None => {
+                    let name = plugin.file_name().unwrap_or_default().to_string_lossy();
+                    self.timed_out(&format!("plugin:{}", name))
+                }
+            });
+            if self.should_stop(&results) {
+                return Ok(results);
+            }
+        }
+
+        if self.config.tags_allow(&["linter"]) {
+            let _span = info_span!("linters").entered();
+            let linted = match self.with_timeout(run_linters) {
+                Some(linted) => linted?,
+                None => vec![self.timed_out("linters")],
+            };
+            for result in linted {
+                results.push(result);
+                if self.should_stop(&results) {
+                    return Ok(results);
+                }
+            }
+        }
+
+        let scripts = if whole_repo && self.config.tags_allow(&["script"]) {
+            discover_script_rules(&self.config)
+        } else {
+            Vec::new()
+        };
+        if !scripts.is_empty() {
+            let files = get_content_files(&self.config)?;
+            for script in scripts {
+                let _span = info_span!("script", path = %script.display()).entered();
+                results.push(run_script_rule(&self.config, &files, &script));
+                if self.should_stop(&results) {
+                    return Ok(results);
+                }
+            }
+        }
+
+        Ok(results)
+    }
+
+    pub fn print_result(&self, result: &ValidationResult) {
+        if let Some(reason) = &result.skipped {
+            skipped(&format!("{} (skipped: {})", result.rule_name, reason));
+            return;
+        }
+        if result.passed {
+            success(&result.rule_name);
+        } else {
+            failure(&result.rule_name);
+        }
+
+        for issue in &result.issues {
+            let location = issue
+                .location()
+                .map(|l| format!(" ({})", l))
+                .unwrap_or_default();
+            self.print_issue(issue, &location);
+        }
+    }
+
+    pub fn print_issue(&self, issue: &Issue, annotation: &str) {
+        if !issue.severity.at_least(self.config.min_severity) {
+            return;
+        }
+        let code = issue
+            .code
+            .as_ref()
+            .map(|c| format!("[{}] ", c))
+            .unwrap_or_default();
+        let message = format!("  {}{}{}", code, issue.message, annotation);
+        match issue.severity {
+            Severity::Error => failure(&message),
+            Severity::Warning => warning(&message),
+            Severity::Info => info(&message),
+            Severity::Hint => hint(&message),
+        }
+
+        if let (Some(file), Some(line)) = (&issue.file, issue.line)
+            && let Some(content) = read_file_content(&self.config, file)
+        {
+            let column = issue.column.unwrap_or(1);
+            for snippet_line in snippet::render(&content, line, column, issue.span.as_ref()) {
+                println!("      {}", snippet_line);
+            }
+        }
+
+        if let Some(diff) = &issue.diff {
+            for diff_line in diff::colorize(diff) {
+                println!("      {}", diff_line);
+            }
+        }
+
+        if let Some(fix) = &issue.fix_suggestion {
+            info(&format!("    {}", fix));
+        }
+    }
+
+    fn print_grouped(&self, results: &[ValidationResult]) {
+        if self.config.group_by == GroupBy::Rule {
+            for result in results {
+                self.print_result(result);
+            }
+            return;
+        }
+
+        // Issues that aren't about one file sort after all the files
+        let mut by_file: BTreeMap<Option<&str>, Vec<(&str, &Issue)>> = BTreeMap::new();
+        for result in results {
+            let shown = result
+                .issues
+                .iter()
+                .filter(|i| i.severity.at_least(self.config.min_severity));
+            for issue in shown {
+                by_file
+                    .entry(issue.file.as_deref())
+                    .or_default()
+                    .push((&result.rule_id, issue));
+            }
+        }
+        let mut groups: Vec<_> = by_file.into_iter().collect();
+        let unfiled = groups.iter().take_while(|(f, _)| f.is_none()).count();
+        groups.rotate_left(unfiled);
+
+        for (file, issues) in &groups {
+            let heading = file.unwrap_or("(not tied to a file)");
+            let worst = issues
+                .iter()
+                .map(|(_, i)| i.severity)
+                .max_by_key(|s| s.rank())
+                .unwrap_or(Severity::Hint);
+            match worst {
+                Severity::Error => failure(heading),
+                Severity::Warning => warning(heading),
+                Severity::Info => info(heading),
+                Severity::Hint => hint(heading),
+            }
+            for (rule_id, issue) in issues {
+                let position = match (issue.line, issue.column) {
+                    (Some(line), Some(column)) => format!(" at {}:{}", line, column),
+                    (Some(line), None) => format!(" at line {}", line),
+                    _ => String::new(),
+                };
+                self.print_issue(issue, &format!(" [{}]{}", rule_id, position));
+            }
+        }
+        if groups.is_empty() {
+            success(&format!(
+                "No issues in any file ({} rules run)",
+                results.len()
+            ));
+        }
+
+        for result in results {
+            if let Some(reason) = &result.skipped {
+                skipped(&format!("{} (skipped: {})", result.rule_name, reason));
+            }
+        }
+    }
+
+    // Paths are relative to the repo, unless several repos share the output
+    pub fn compact_lines(&self, results: &[ValidationResult], multi_root: bool) -> Vec<String> {
+        let mut lines = Vec::new();
+        for result in results {
+            let shown = result
+                .issues
+                .iter()
+                .filter(|i| i.severity.at_least(self.config.min_severity));
+            for issue in shown {
+                let message = issue.message.replace('\n', " ");
+                let severity = format!("{:?}", issue.severity).to_lowercase();
+                let Some(file) = &issue.file else {
+                    lines.push(format!("{}: {} [{}]", severity, message, result.rule_id));
+                    continue;
+                };
+                let file = if multi_root {
+                    self.config.dotfiles_dir.join(file).display().to_string()
+                } else {
+                    file.clone()
+                };
+                // Editors need a line to jump to, so file-level issues point at
+                // the top; some parsers report column 0 for the start of a line
+                lines.push(format!(
+                    "{}:{}:{}: {}: {} [{}]",
+                    file,
+                    issue.line.unwrap_or(1),
+                    issue.column.unwrap_or(1).max(1),
+                    severity,
+                    message,
+                    result.rule_id
+                ));
+            }
+        }
+        lines
+    }
+
+    pub fn print_results(&self, results: &[ValidationResult]) {
+        self.print_grouped(results);
+
+        let hidden = results
+            .iter()
+            .flat_map(|r| &r.issues)
+            .filter(|i| !i.severity.at_least(self.config.min_severity))
+            .count();
+        if hidden > 0 {
+            info(&format!(
+                "{} lower-severity issue(s) hidden by --min-severity",
+                hidden
+            ));
+        }
+    }
+
+    pub fn apply_fixes(&self, results: &[ValidationResult]) -> Result<()> {
+        self.apply_gitignore_fixes(results)?;
+        self.apply_format_fixes(results)
+    }
+
+    fn apply_format_fixes(&self, results: &[ValidationResult]) -> Result<()> {
+        let unformatted: Vec<_> = results
+            .iter()
+            .flat_map(|r| &r.issues)
+            .filter(|i| matches!(i.code.as_deref(), Some("FMT001" | "FMT002")))
+            .filter_map(|i| i.file.as_ref())
+            .collect();
+        if unformatted.is_empty() {
+            return Ok(());
+        }
+
+        let update = apply_formatting(&self.config, &unformatted)?;
+        if self.config.output != OutputMode::Normal {
+            return Ok(());
+        }
+        if update.rewritten > 0 {
+            success(&format!("Reformatted {} file(s)", update.rewritten));
+        }
+        if let Some(id) = &update.snapshot {
+            info(&format!(
+                "  Previous versions backed up; undo with: dotfiles-tools restore {}",
+                id
+            ));
+        }
+        Ok(())
+    }
+
+    fn apply_gitignore_fixes(&self, results: &[ValidationResult]) -> Result<()> {
+        let ignored_files = negation_candidates(results);
+
+        if ignored_files.is_empty() {
+            return Ok(());
+        }
+
+        let update = apply_gitignore_negations(&self.config, &ignored_files)?;
+        if self.config.output != OutputMode::Normal {
+            return Ok(());
+        }
+        if update.added > 0 {
+            success(&format!("Added {} negation(s) to .gitignore", update.added));
+        }
+        if let Some(id) = &update.snapshot {
+            info(&format!(
+                "  Previous .gitignore backed up; undo with: dotfiles-tools restore {}",
+                id
+            ));
+        }
+
+        // Git can't re-include a file whose parent directory is excluded, so
+        // a negation alone isn't always enough
+        for file in ignored_files {
+            if is_ignored_by_git(&self.config, file) {
+                warning(&format!(
+                    "  Still ignored after adding negation: {} (is a parent directory excluded?)",
+                    file
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn summarize(&self, results: &[ValidationResult]) -> i32 {
+        let output = self.config.output;
+        if output == OutputMode::Normal {
+            println!("\n{}{}{}", Color::bold(), "=".repeat(60), Color::reset());
+        }
+
+        let count = |severity| {
+            results
+                .iter()
+                .flat_map(|r| &r.issues)
+                .filter(|i| i.severity == severity)
+                .count()
+        };
+        let errors = count(Severity::Error);
+        let warnings = count(Severity::Warning);
+        let total_issues = errors + warnings;
+
+        // Info and hints are reported alongside, but never count as issues
+        let advisory: Vec<_> = [
+            (count(Severity::Info), "info"),
+            (count(Severity::Hint), "hint(s)"),
+        ]
+        .into_iter()
+        .filter(|(n, _)| *n > 0)
+        .map(|(n, label)| format!("{} {}", n, label))
+        .collect();
+        let advisory = if advisory.is_empty() {
+            String::new()
+        } else {
+            format!("; {}", advisory.join(", "))
+        };
+
+        // Linters skipped for a missing tool pass; only blocked rules count here
+        let skipped = results
+            .iter()
+            .filter(|r| r.skipped.is_some() && !r.passed)
+            .count();
+        if skipped > 0 && output == OutputMode::Normal {
+            info(&format!(
+                "{} rule(s) skipped because a prerequisite failed",
+                skipped
+            ));
+        }
+
+        if errors > 0 {
+            if output != OutputMode::Quiet {
+                failure(&format!(
+                    "Validation failed: {} issue(s) found ({} errors, {} warnings{})",
+                    total_issues, errors, warnings, advisory
+                ));
+            }
+
+            if self.config.fix_mode && output == OutputMode::Normal {
+                println!("\n{}Fix suggestions:{}\n", Color::bold(), Color::reset());
+
+                let ignored_files = negation_candidates(results);
+
+                if !ignored_files.is_empty() {
+                    info("Add these lines to .gitignore:");
+                    for file in ignored_files {
+                        success(&format!("  !{}", file));
+                    }
+                    println!();
+                }
+
+                let untracked_files: Vec<_> = results
+                    .iter()
+                    .flat_map(|r| &r.issues)
+                    .filter(|i| {
+                        i.fix_suggestion
+                            .as_ref()
+                            .map(|s| s.contains("git add"))
+                            .unwrap_or(false)
+                    })
+                    .filter_map(|i| i.file.as_ref())
+                    .collect();
+
+                if !untracked_files.is_empty() {
+                    info("Run this command to track files:");
+                    let files_str: Vec<String> =
+                        untracked_files.iter().map(|s| s.to_string()).collect();
+                    success(&format!("  git add {}", files_str.join(" ")));
+                    println!();
+                }
+            }
+
+            ExitCode::ERRORS
+        } else if warnings > 0 {
+            if output != OutputMode::Quiet {
+                warning(&format!(
+                    "Validation completed with {} warning(s){}",
+                    warnings, advisory
+                ));
+            }
+            let over_limit = self.config.max_warnings.is_some_and(|max| warnings > max);
+            if over_limit && output != OutputMode::Quiet {
+                failure(&format!(
+                    "Warning count {} exceeds --max-warnings {}",
+                    warnings,
+                    self.config.max_warnings.unwrap_or_default()
+                ));
+            }
+            if self.config.strict || over_limit {
+                ExitCode::WARNINGS
+            } else {
+                ExitCode::CLEAN
+            }
+        } else {
+            if output != OutputMode::Quiet {
+                if advisory.is_empty() {
+                    success("All validations passed!\n");
+                } else {
+                    success(&format!(
+                        "All validations passed ({})\n",
+                        advisory.trim_start_matches("; ")
+                    ));
+                }
+            }
+            ExitCode::CLEAN
+        }
+    }
+}
+
+// The full validation pipeline shared by `validate`, `fix` and `deploy`:
+// run rules, report, apply fixes, record history, summarize
+pub fn validate(config: Config) -> Result<i32> {
+    validate_roots(vec![config])
+}
+
+// Each root is checked, fixed, and recorded on its own, then all results are
+// summarized together so one exit code covers every repo
+pub fn validate_roots(configs: Vec<Config>) -> Result<i32> {
+    let started = Instant::now();
+    let multi_root = configs.len() > 1;
+    let mut all_results = Vec::new();
+    let mut summary_config = None;
+    let mut fix_script = Vec::new();
+
+    for mut config in configs {
+        check_environment(&config)?;
+        if config.incremental {
+            config.cache = Some(Arc::new(ResultCache::load(&config)?));
+        }
+
+        // Compact output is only the issue lines, for editors to parse, and
+        // Prometheus output only the metrics at the end
+        let compact = config.format == OutputFormat::Compact;
+        let show_report =
+            config.output == OutputMode::Normal && config.format == OutputFormat::Human;
+        if show_report {
+            let heading = if multi_root {
+                format!("Validating {}...", config.dotfiles_dir.display())
+            } else {
+                "Validating dotfiles repository...".to_string()
+            };
+            println!("\n{}{}{}\n", Color::bold(), heading, Color::reset());
+        }
+
+        let validator = Validator::new(config);
+        let results = validator.run_rules()?;
+        let stopped = validator.should_stop(&results);
+
+        if compact && validator.config.output == OutputMode::Normal {
+            for line in validator.compact_lines(&results, multi_root) {
+                println!("{}", line);
+            }
+        }
+        if show_report {
+            validator.print_results(&results);
+            if interrupted() {
+                println!();
+                warning("Run interrupted; remaining rules were not run");
+            } else if stopped {
+                info("Stopped at the first error (--fail-fast); remaining rules were not run");
+            }
+        }
+
+        if validator.config.apply_mode && !interrupted() {
+            if show_report {
+                println!();
+            }
+            validator.apply_fixes(&results)?;
+        }
+
+        // Partial runs would make every unchecked file look "resolved" next time
+        let full_run =
+            !stopped && !validator.config.staged && validator.config.changed_since.is_none();
+        if let Some(cache) = &validator.config.cache {
+            cache.save(full_run)?;
+        }
+        if full_run || validator.config.compare {
+            let history = load_history(&validator.config)?;
+            let record = RunRecord::from_results(&validator.config, &results);
+            if validator.config.compare && show_report {
+                print_comparison(history.last(), &record);
+            }
+            if full_run {
+                save_history(&validator.config, history, record)?;
+            }
+        }
+
+        if validator.config.emit_fix_script.is_some() {
+            fix_script.push(fix_script_section(&validator.config, &results));
+        }
+
+        all_results.extend(results);
+        summary_config.get_or_insert(validator.config);
+        if stopped {
+            break;
+        }
+    }
+
+    let mut config = summary_config.context("No dotfiles directories to validate")?;
+    let print_metrics =
+        config.format == OutputFormat::Prometheus && config.output == OutputMode::Normal;
+    if config.format != OutputFormat::Human && config.output == OutputMode::Normal {
+        config.output = OutputMode::Quiet;
+    }
+    if let Some(path) = &config.emit_fix_script {
+        write_fix_script(path, &fix_script)?;
+        if config.output != OutputMode::Quiet {
+            info(&format!("Wrote fix script to {}", path.display()));
+        }
+    }
+    let notify_on_errors = config.notify;
+    let validator = Validator::new(config);
+    let mut exit_code = validator.summarize(&all_results);
+    if interrupted() {
+        exit_code = ExitCode::INTERRUPTED;
+    }
+    if let Some(path) = &validator.config.summary_out {
+        RunSummary::from_results(
+            &validator.config,
+            &all_results,
+            started.elapsed(),
+            exit_code,
+        )
+        .write(path)?;
+    }
+    if print_metrics {
+        print!(
+            "{}",
+            prometheus_metrics(&all_results, started.elapsed(), exit_code)
+        );
+    }
+    if notify_on_errors && exit_code >= ExitCode::ERRORS && !interrupted() {
+        let errors = all_results
+            .iter()
+            .flat_map(|r| &r.issues)
+            .filter(|i| i.severity == Severity::Error)
+            .count();
+        notify(
+            "Dotfiles validation failed",
+            &format!(
+                "{} error(s); run dotfiles-tools validate for details",
+                errors
+            ),
+        );
+        webhook::notify_failure(&validator.config, &all_results, exit_code);
+    }
+    Ok(exit_code)
+}
+
+/// Deployed files that .gitignore hides (DOT003), which `fix` re-includes
+/// with a `!path` negation. Other suggestions that mention .gitignore ask
+/// for the opposite, so they're never picked up here.
+fn negation_candidates(results: &[ValidationResult]) -> Vec<&String> {
+    results
+        .iter()
+        .flat_map(|r| &r.issues)
+        .filter(|i| i.code.as_deref() == Some("DOT003"))
+        .filter_map(|i| i.file.as_ref())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::FixtureRepo;
+
+    #[test]
+    fn compact_lines_follow_errorformat() {
+        let repo = FixtureRepo::new();
+        repo.write("bad.toml", "a = 1\nb = \n").commit("fixture");
+        let mut config = repo.config();
+        config.tags = vec!["syntax".to_string()];
+        let validator = Validator::new(config);
+        let results = validator.run_rules().unwrap();
+        let lines = validator.compact_lines(&results, false);
+        assert!(
+            lines
+                .iter()
+                .any(|l| l.starts_with("bad.toml:2:") && l.ends_with("[toml-syntax]")),
+            "{:?}",
+            lines
+        );
+        assert!(lines.iter().all(|l| l.contains(": error: ")));
+    }
+
+    #[test]
+    fn only_ignored_deployed_files_get_negations() {
+        let results = [ValidationResult::new(
+            "mixed",
+            false,
+            vec![
+                Issue::new(Severity::Warning, "ignored")
+                    .with_code("DOT003")
+                    .with_file("zsh/zshrc".to_string())
+                    .with_fix("Add to .gitignore: !zsh/zshrc"),
+                Issue::new(Severity::Error, "credential")
+                    .with_code("SEC001")
+                    .with_file(".env".to_string())
+                    .with_fix("Add .env to .gitignore"),
+                Issue::new(Severity::Warning, "junk")
+                    .with_code("JUNK001")
+                    .with_file(".DS_Store".to_string())
+                    .with_fix("Add to .gitignore: .DS_Store"),
+            ],
+        )];
+        assert_eq!(negation_candidates(&results), ["zsh/zshrc"]);
+    }
+
+    #[test]
+    fn hung_checks_are_abandoned() {
+        let repo = FixtureRepo::new();
+        let mut config = repo.config();
+        config.rule_timeout = Some(std::time::Duration::from_millis(50));
+        let validator = Validator::new(config);
+        let slow = validator.with_timeout(|_| {
+            thread::sleep(std::time::Duration::from_secs(2));
+            Ok(ValidationResult::new("slow", true, Vec::new()))
+        });
+        assert!(slow.is_none());
+        let quick =
+            validator.with_timeout(|_| Ok(ValidationResult::new("quick", true, Vec::new())));
+        assert!(quick.unwrap().unwrap().passed);
+    }
+}
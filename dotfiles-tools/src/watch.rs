@@ -0,0 +1,138 @@
+//! Watch mode: re-validate whenever the repository changes.
+
+use anyhow::{Context, Result};
+
+use std::{
+    collections::hash_map::DefaultHasher,
+    fs,
+    hash::{Hash, Hasher},
+    process::Command,
+    thread,
+    time::Duration,
+};
+
+use crate::{
+    output::{Color, failure, info, notify},
+    types::{Config, ExitCode, OutputMode},
+    validator::validate,
+};
+
+// Polling keeps this dependency-free; a dotfiles repo is small enough that
+// stat-ing every file once a second is cheap
+pub const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+// Changes to any tracked or untracked-but-not-ignored file, or to the index
+// (staging), change the fingerprint
+pub fn repo_fingerprint(config: &Config) -> Result<u64> {
+    let output = Command::new("git")
+        .args(["ls-files", "--cached", "--others", "--exclude-standard"])
+        .current_dir(&config.dotfiles_dir)
+        .output()
+        .context("Failed to run git ls-files")?;
+    let files = String::from_utf8_lossy(&output.stdout);
+
+    let index = Command::new("git")
+        .args(["rev-parse", "--git-path", "index"])
+        .current_dir(&config.dotfiles_dir)
+        .output()
+        .context("Failed to run git rev-parse")?;
+    let index = config
+        .dotfiles_dir
+        .join(String::from_utf8_lossy(&index.stdout).trim());
+
+    let mut hasher = DefaultHasher::new();
+    for path in files
+        .lines()
+        .map(|f| config.dotfiles_dir.join(f))
+        .chain([index])
+    {
+        path.hash(&mut hasher);
+        if let Ok(metadata) = fs::symlink_metadata(&path) {
+            metadata.len().hash(&mut hasher);
+            metadata.modified().ok().hash(&mut hasher);
+        }
+    }
+    Ok(hasher.finish())
+}
+
+fn run_once(config: &Config) -> i32 {
+    match validate(config.clone()) {
+        Ok(code) => code,
+        Err(err) => {
+            failure(&format!("{:#}", err));
+            ExitCode::INTERNAL
+        }
+    }
+}
+
+pub fn watch(configs: Vec<Config>) -> Result<i32> {
+    let mut fingerprints = vec![None; configs.len()];
+    let mut last_codes: Vec<Option<i32>> = vec![None; configs.len()];
+
+    loop {
+        for (idx, config) in configs.iter().enumerate() {
+            let fingerprint = repo_fingerprint(config)?;
+            if fingerprints[idx] == Some(fingerprint) {
+                continue;
+            }
+
+            let chatty = config.output != OutputMode::Quiet;
+            if chatty && fingerprints[idx].is_some() {
+                println!(
+                    "\n{}Change detected in {}{}",
+                    Color::bold(),
+                    config.dotfiles_dir.display(),
+                    Color::reset()
+                );
+            }
+            let code = run_once(config);
+
+            // Only a pass -> fail transition is worth interrupting for; a repo
+            // that was already failing when the watch started isn't news
+            let was_passing = last_codes[idx].is_some_and(|c| c < ExitCode::ERRORS);
+            if was_passing && code >= ExitCode::ERRORS {
+                let name = config
+                    .dotfiles_dir
+                    .file_name()
+                    .map(|n| n.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| config.dotfiles_dir.display().to_string());
+                notify(
+                    "Dotfiles validation failing",
+                    &format!("{} started failing validation", name),
+                );
+            }
+            last_codes[idx] = Some(code);
+
+            // Validation itself (fixes, history) may touch files; start from
+            // the state after the run so it doesn't retrigger itself
+            fingerprints[idx] = Some(repo_fingerprint(config)?);
+            if chatty {
+                info("Watching for changes (Ctrl-C to stop)...");
+            }
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::FixtureRepo;
+
+    #[test]
+    fn fingerprint_changes_with_edits_and_new_files() {
+        let repo = FixtureRepo::new();
+        repo.write("zshrc", "alias ll='ls -l'\n").commit("fixture");
+        let config = repo.config();
+
+        let initial = repo_fingerprint(&config).unwrap();
+        assert_eq!(initial, repo_fingerprint(&config).unwrap());
+
+        repo.write("zshrc", "alias ll='ls -la'\n");
+        let edited = repo_fingerprint(&config).unwrap();
+        assert_ne!(initial, edited);
+
+        repo.write("new", "\n");
+        assert_ne!(edited, repo_fingerprint(&config).unwrap());
+    }
+}
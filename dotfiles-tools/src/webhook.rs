@@ -0,0 +1,155 @@
+//! Posting a summary of a failed run to a chat or push webhook, for the
+//! `--notify` runs nobody is watching.
+
+use anyhow::{Context, Result};
+use serde_json::{Value, json};
+use tracing::warn;
+
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+use crate::{
+    history::git_head,
+    repo::find_executable,
+    types::{Config, Severity, ValidationResult},
+};
+
+// Enough issues to see what broke without flooding the channel
+const TOP_ISSUES: usize = 5;
+
+/// The JSON posted for a failed run. `text` and `content` carry a readable
+/// message for Slack and Discord respectively; ntfy shows the body as is.
+pub fn failure_payload(config: &Config, results: &[ValidationResult], exit_code: i32) -> Value {
+    let mut issues: Vec<_> = results
+        .iter()
+        .flat_map(|r| r.issues.iter().map(move |i| (r, i)))
+        .filter(|(_, i)| i.severity.at_least(Severity::Warning))
+        .collect();
+    issues.sort_by_key(|(_, i)| std::cmp::Reverse(i.severity.rank()));
+    let count = |severity| {
+        issues
+            .iter()
+            .filter(|(_, i)| i.severity == severity)
+            .count()
+    };
+    let (errors, warnings) = (count(Severity::Error), count(Severity::Warning));
+
+    let head = git_head(config);
+    let repo = config.dotfiles_dir.display().to_string();
+    let mut text = format!(
+        "Dotfiles validation failed in {}{}: {} error(s), {} warning(s)",
+        repo,
+        head.as_deref()
+            .map(|h| format!(" at {}", &h[..h.len().min(12)]))
+            .unwrap_or_default(),
+        errors,
+        warnings
+    );
+    let top: Vec<_> = issues
+        .iter()
+        .take(TOP_ISSUES)
+        .map(|(result, issue)| {
+            text.push_str(&format!(
+                "\n- {}{}",
+                issue
+                    .code
+                    .as_ref()
+                    .map(|c| format!("[{}] ", c))
+                    .unwrap_or_default(),
+                issue.message
+            ));
+            json!({
+                "rule": result.rule_id,
+                "code": issue.code,
+                "severity": issue.severity,
+                "message": issue.message,
+                "file": issue.file,
+                "line": issue.line,
+            })
+        })
+        .collect();
+
+    json!({
+        "text": text,
+        "content": text,
+        "repository": repo,
+        "head": head,
+        "exit_code": exit_code,
+        "errors": errors,
+        "warnings": warnings,
+        "top_issues": top,
+    })
+}
+
+/// Posts `payload` to `url` with curl.
+pub fn post(url: &str, payload: &Value) -> Result<()> {
+    if find_executable("curl").is_none() {
+        anyhow::bail!("curl isn't installed");
+    }
+    let mut child = Command::new("curl")
+        .args(["--fail", "--silent", "--show-error", "--max-time", "10"])
+        .args(["--header", "Content-Type: application/json"])
+        .args(["--data-binary", "@-", url])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Failed to run curl")?;
+    child
+        .stdin
+        .take()
+        .context("curl has no stdin")?
+        .write_all(payload.to_string().as_bytes())?;
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        anyhow::bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(())
+}
+
+/// Reports a failed run to the webhook in `[notify]`, if there is one. A
+/// webhook that can't be reached is logged rather than failing the run.
+pub fn notify_failure(config: &Config, results: &[ValidationResult], exit_code: i32) {
+    let Some(url) = &config.settings.notify.webhook else {
+        return;
+    };
+    if let Err(err) = post(url, &failure_payload(config, results, exit_code)) {
+        warn!("webhook notification failed: {:#}", err);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_support::FixtureRepo, types::Issue};
+
+    #[test]
+    fn payload_leads_with_errors() {
+        let repo = FixtureRepo::new();
+        repo.write("a", "\n").commit("fixture");
+        let mut result = ValidationResult::new(
+            "json",
+            false,
+            vec![
+                Issue::new(Severity::Warning, "loose"),
+                Issue::new(Severity::Hint, "style"),
+                Issue::new(Severity::Error, "broken").with_code("SYN002"),
+            ],
+        );
+        result.rule_id = "json-syntax".to_string();
+
+        let payload = failure_payload(&repo.config(), &[result], 2);
+        assert_eq!(payload["errors"], 1);
+        assert_eq!(payload["warnings"], 1);
+        assert_eq!(payload["top_issues"].as_array().unwrap().len(), 2);
+        assert_eq!(payload["top_issues"][0]["code"], "SYN002");
+        assert!(
+            payload["text"]
+                .as_str()
+                .unwrap()
+                .ends_with("\n- [SYN002] broken\n- loose")
+        );
+    }
+}